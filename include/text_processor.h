@@ -0,0 +1,24 @@
+/* C ABI for affectively-text-processor (build with `--features ffi`) */
+
+#ifndef AFFECTIVELY_TEXT_PROCESSOR_H
+#define AFFECTIVELY_TEXT_PROCESSOR_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Analyze a NUL-terminated UTF-8 string.
+ * Returns a JSON result string owned by the library, or NULL on invalid input.
+ * Release it with text_processor_free_result.
+ */
+char *text_processor_analyze(const char *text);
+
+/* Release a string returned by text_processor_analyze (NULL is ignored). */
+void text_processor_free_result(char *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* AFFECTIVELY_TEXT_PROCESSOR_H */
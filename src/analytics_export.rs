@@ -0,0 +1,206 @@
+//! Exportable analytics document
+//! Analyzes a series of entries (journal entries, messages over weeks) into one self-contained
+//! JSON document with per-entry scores, per-person timelines and trends, stamped with the pattern
+//! set and schema versions so it can be shared with a therapist or imported on another device
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::chat::{risk_profile, timestamp_ms, RiskProfile};
+use super::coded_lexicon::coded_lexicon_version;
+use super::entity_extraction::extract_entities;
+use super::harm_report::harm_report_from;
+use super::incident_timing::{incident_timing, IncidentTiming};
+use super::pattern_matching::pattern_set_version;
+use super::versioning::SchemaVersion;
+use super::analyze_with_entities;
+
+/// Entry to include in the export
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportEntry {
+    /// Caller's id for the entry; the entry's index when omitted
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Chat-style or ISO 8601 timestamp (`2024-03-01T22:15:00Z`)
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    pub text: String,
+}
+
+/// Export request: entries in chronological order
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRequest {
+    pub entries: Vec<ExportEntry>,
+    /// Copy each entry's text into the document; off by default so exports only carry scores
+    #[serde(default)]
+    pub include_text: bool,
+}
+
+/// Scores for one entry
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryScore {
+    pub id: String,
+    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    pub detected: bool,
+    pub score: f64,
+    pub confidence: f64,
+    pub pattern_counts: BTreeMap<String, usize>,
+}
+
+/// A person's attributed matches in one entry
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelinePoint {
+    pub entry_id: String,
+    pub timestamp: Option<String>,
+    pub as_source: usize,
+    pub as_target: usize,
+    pub pattern_counts: BTreeMap<String, usize>,
+}
+
+/// A person's attributed matches across entries, matched by name
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityTimeline {
+    pub name: String,
+    pub relationship: Option<String>,
+    pub total: usize,
+    /// Entries with at least one match attributed to the person, in entry order
+    pub points: Vec<TimelinePoint>,
+}
+
+/// Self-contained analytics document
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyticsExport {
+    pub schema_version: u32,
+    /// Built-in pattern set the scores come from (see `get_pattern_set_version`)
+    pub pattern_set_version: String,
+    pub coded_lexicon_version: Option<String>,
+    pub entries: Vec<EntryScore>,
+    /// Most matches first
+    pub entities: Vec<EntityTimeline>,
+    /// Dominant categories, severity distribution and early/late score trend across entries
+    pub trend: RiskProfile,
+    /// Time-of-day and interval features of flagged entries with timestamps
+    pub timing: IncidentTiming,
+}
+
+/// Analyze the entries into an export document
+pub fn export_analytics(request: &ExportRequest) -> AnalyticsExport {
+    let mut entries = Vec::new();
+    let mut timelines: Vec<EntityTimeline> = Vec::new();
+    let mut matches: Vec<(String, String)> = Vec::new();
+
+    for (index, entry) in request.entries.iter().enumerate() {
+        let id = entry.id.clone().unwrap_or_else(|| index.to_string());
+        let entities = extract_entities(&entry.text).entities;
+        let result = analyze_with_entities(&entry.text);
+
+        let mut pattern_counts = BTreeMap::new();
+        for pattern in &result.patterns {
+            *pattern_counts.entry(pattern.pattern_type.clone()).or_insert(0) += 1;
+            matches.push((pattern.pattern_type.clone(), pattern.severity.clone()));
+        }
+
+        for person in harm_report_from(&entry.text, &entities, &result).people {
+            let slot = match timelines.iter().position(|t| t.name.eq_ignore_ascii_case(&person.name)) {
+                Some(slot) => slot,
+                None => {
+                    timelines.push(EntityTimeline {
+                        name: person.name.clone(),
+                        relationship: person.relationship.clone(),
+                        total: 0,
+                        points: Vec::new(),
+                    });
+                    timelines.len() - 1
+                }
+            };
+            let timeline = &mut timelines[slot];
+            timeline.total += person.total;
+            timeline.relationship = timeline.relationship.take().or(person.relationship);
+            timeline.points.push(TimelinePoint {
+                entry_id: id.clone(),
+                timestamp: entry.timestamp.clone(),
+                as_source: person.as_source,
+                as_target: person.as_target,
+                pattern_counts: person.categories.into_iter().map(|c| (c.pattern_type, c.count)).collect(),
+            });
+        }
+
+        entries.push(EntryScore {
+            id,
+            timestamp: entry.timestamp.clone(),
+            text: request.include_text.then(|| entry.text.clone()),
+            detected: result.detected,
+            score: result.score,
+            confidence: result.confidence,
+            pattern_counts,
+        });
+    }
+    timelines.sort_by_key(|t| std::cmp::Reverse(t.total));
+
+    let scores: Vec<f64> = entries.iter().map(|e| e.score).collect();
+    let trend = risk_profile(matches.iter().map(|(t, s)| (t.as_str(), s.as_str())), &scores);
+    let flagged: Vec<(Option<i64>, bool)> =
+        entries.iter().map(|e| (e.timestamp.as_deref().and_then(timestamp_ms), e.detected)).collect();
+
+    AnalyticsExport {
+        schema_version: SchemaVersion::CURRENT.number(),
+        pattern_set_version: pattern_set_version(),
+        coded_lexicon_version: coded_lexicon_version(),
+        entries,
+        entities: timelines,
+        trend,
+        timing: incident_timing(&flagged),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, text: &str) -> ExportEntry {
+        ExportEntry { id: None, timestamp: Some(timestamp.to_string()), text: text.to_string() }
+    }
+
+    #[test]
+    fn test_export_document() {
+        let request = ExportRequest {
+            entries: vec![
+                entry("2024-03-01T09:00:00Z", "Had a quiet walk with my sister Amy."),
+                entry("2024-03-08T23:10:00Z", "My boss Dave called me an idiot."),
+                entry("2024-03-09T23:40:00Z", "My boss Dave said I'm a failure."),
+            ],
+            include_text: false,
+        };
+        let export = export_analytics(&request);
+        assert_eq!(export.entries.len(), 3);
+        assert_eq!(export.entries[1].id, "1");
+        assert!(export.entries[0].text.is_none());
+        assert_eq!(export.entities[0].name, "Dave");
+        assert_eq!(export.entities[0].points.iter().map(|p| p.entry_id.as_str()).collect::<Vec<_>>(), vec!["1", "2"]);
+        assert_eq!(export.trend.trend, "rising");
+        assert_eq!(export.timing.late_night_share, 1.0);
+        assert_eq!(export.pattern_set_version, pattern_set_version());
+    }
+
+    #[test]
+    fn test_include_text_and_round_trip() {
+        let request: ExportRequest =
+            serde_json::from_str(r#"{"entries":[{"id":"a","text":"You're so selfish"}],"includeText":true}"#).unwrap();
+        let export = export_analytics(&request);
+        assert_eq!(export.entries[0].text.as_deref(), Some("You're so selfish"));
+        let json = serde_json::to_string(&export).unwrap();
+        let parsed: AnalyticsExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.entries[0].id, "a");
+        assert_eq!(parsed.timing.incidents, 0);
+    }
+}
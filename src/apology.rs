@@ -0,0 +1,145 @@
+//! Apology detection
+//! Finds apologies and separates genuine ones from non-apologies that deflect, justify or shift blame
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::offsets::sentence_bounds;
+use super::pattern_matching::{match_patterns, PatternMatch};
+
+lazy_static! {
+    /// Apology cue; alternation order prefers the longest form
+    static ref APOLOGY_CUE: Regex = Regex::new(
+        r"(?i)\b(?:I(?:'m|\s+am)\s+(?:so\s+|really\s+|truly\s+|very\s+)?sorry|I\s+apologi[sz]e|my\s+apologies|my\s+bad|sorry)\b"
+    ).unwrap();
+    /// Sympathy rather than apology ("sorry to hear that")
+    static ref SYMPATHY: Regex = Regex::new(r"(?i)^\s*(?:to\s+hear|for\s+your\s+loss)").unwrap();
+    /// Non-apology forms read from the text after the cue, as (regex, reason)
+    static ref NON_APOLOGY_FORMS: Vec<(Regex, &'static str)> = vec![
+        (
+            Regex::new(r"(?i)^\s*(?:that|if)?\s*you\s+(?:feel|felt|are|were|'re|got|took\s+it)\s+(?:that\s+way|upset|offended|hurt|so\s+sensitive|personally|wrong)").unwrap(),
+            "feelings_deflection",
+        ),
+        (Regex::new(r"(?i)^\s*,?\s*if\b").unwrap(), "conditional"),
+        (Regex::new(r"(?i)^\s*,?\s*but\b").unwrap(), "justification"),
+    ];
+    /// Ownership of the wrong done, which marks a genuine apology
+    static ref OWNERSHIP: Regex = Regex::new(
+        r"(?i)\b(?:I\s+was\s+wrong|(?:my|all\s+my)\s+fault|I\s+shouldn't\s+have|I\s+should\s+not\s+have|I\s+take\s+(?:full\s+)?responsibility|I\s+hurt\s+you|for\s+(?:what\s+I|my|hurting|yelling|lying|ignoring))"
+    ).unwrap();
+}
+
+/// Pattern types whose presence turns an apology into blame shifting
+const BLAME_TYPES: &[&str] = &["displacement", "blame_shifting", "childish_blame", "communication_blame"];
+
+/// Pattern types whose presence turns an apology into minimization
+const MINIMIZATION_TYPES: &[&str] = &["minimization", "minimization_tactic", "gaslighting_minimization"];
+
+/// Detected apology with its quality classification
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Apology {
+    /// Sentence containing the apology
+    pub text: String,
+    pub position: usize,
+    pub end: usize,
+    /// `"genuine"` or `"non_apology"`
+    pub quality: String,
+    /// Why it is a non-apology: blame_shift, minimization, feelings_deflection, conditional, justification
+    pub reasons: Vec<String>,
+    /// Whether the speaker takes ownership ("I was wrong", "my fault")
+    pub takes_ownership: bool,
+    /// High-entropy pattern types found in the same sentence
+    pub related_patterns: Vec<String>,
+    pub confidence: f64,
+}
+
+/// Detect apologies and classify them as genuine or non-apologies
+pub fn detect_apologies(text: &str) -> Vec<Apology> {
+    let matches = match_patterns(text);
+    let mut apologies: Vec<Apology> = Vec::new();
+
+    for cue in APOLOGY_CUE.find_iter(text) {
+        if SYMPATHY.is_match(&text[cue.end()..]) {
+            continue;
+        }
+        let (start, end) = sentence_bounds(text, cue.start());
+        // One apology per sentence ("Sorry, I'm so sorry")
+        if apologies.last().is_some_and(|a| a.end == end) {
+            continue;
+        }
+
+        let in_sentence: Vec<&PatternMatch> = matches.iter().filter(|m| m.position >= start && m.position < end).collect();
+        let has_type = |types: &[&str]| in_sentence.iter().any(|m| types.contains(&m.pattern_type.as_str()));
+
+        let mut reasons: Vec<String> = Vec::new();
+        if has_type(BLAME_TYPES) {
+            reasons.push("blame_shift".to_string());
+        }
+        if has_type(MINIMIZATION_TYPES) {
+            reasons.push("minimization".to_string());
+        }
+        let after = &text[cue.end()..end];
+        for (regex, reason) in NON_APOLOGY_FORMS.iter() {
+            if regex.is_match(after) {
+                reasons.push(reason.to_string());
+            }
+        }
+
+        let takes_ownership = OWNERSHIP.is_match(&text[start..end]);
+        let mut related_patterns: Vec<String> = in_sentence.iter().map(|m| m.pattern_type.clone()).collect();
+        related_patterns.sort_unstable();
+        related_patterns.dedup();
+
+        let (quality, confidence) = match (reasons.is_empty(), takes_ownership) {
+            (true, true) => ("genuine", 0.9),
+            (true, false) => ("genuine", 0.6),
+            (false, _) => ("non_apology", (0.6 + 0.15 * reasons.len() as f64).min(0.95)),
+        };
+
+        apologies.push(Apology {
+            text: text[start..end].trim().to_string(),
+            position: start + (text[start..end].len() - text[start..end].trim_start().len()),
+            end,
+            quality: quality.to_string(),
+            reasons,
+            takes_ownership,
+            related_patterns,
+            confidence,
+        });
+    }
+
+    apologies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_genuine_apology() {
+        let apologies = detect_apologies("I'm sorry I yelled at you. I was wrong.");
+        assert_eq!(apologies.len(), 1);
+        assert_eq!(apologies[0].quality, "genuine");
+        assert!(apologies[0].reasons.is_empty());
+
+        let apologies = detect_apologies("I'm so sorry for hurting you, it was my fault.");
+        assert!(apologies[0].takes_ownership);
+        assert_eq!(apologies[0].confidence, 0.9);
+
+        assert_eq!(detect_apologies("Oh. Sorry, I'm so sorry.").len(), 1);
+    }
+
+    #[test]
+    fn test_non_apologies() {
+        let apologies = detect_apologies("I'm sorry you feel that way. Sorry but you made me do it. Sorry to hear that.");
+        assert_eq!(apologies.len(), 2);
+        assert_eq!(apologies[0].quality, "non_apology");
+        assert_eq!(apologies[0].reasons, vec!["feelings_deflection"]);
+        assert!(apologies[1].reasons.contains(&"blame_shift".to_string()));
+        assert!(apologies[1].reasons.contains(&"justification".to_string()));
+        assert!(apologies[1].related_patterns.contains(&"displacement".to_string()));
+    }
+}
@@ -0,0 +1,123 @@
+//! Match-to-entity attribution
+//! Links each pattern match to the person it comes from or is about: "my boss called me an
+//! idiot" attributes the insult to the boss as its source, "my husband John is so lazy" to
+//! John as its target
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::entity_extraction::ExtractedEntity;
+use super::offsets::sentence_bounds;
+
+lazy_static! {
+    /// Reporting verbs between a person and a match make the person its source
+    static ref REPORTING_VERB: Regex = Regex::new(
+        r"(?i)\b(?:call(?:s|ed)?|sa(?:y|ys|id)|tell(?:s)?|told|yell(?:s|ed)?|scream(?:s|ed)?|shout(?:s|ed)?|text(?:s|ed)?|wr(?:ote|ites?)|accus(?:es|ed)|insult(?:s|ed)?)\b"
+    )
+    .unwrap();
+    static ref THIRD_PERSON_PRONOUN: Regex = Regex::new(r"(?i)\b(?:he|she|they|him|her|them)\b").unwrap();
+}
+
+/// Entity a match is attributed to
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityLink {
+    pub entity_id: String,
+    /// "source" when the person said it, "target" when it is about them
+    pub role: &'static str,
+}
+
+/// Byte spans where each entity is mentioned: its extracted mention plus later whole-word
+/// occurrences of its name, as (start, end, entity index) in text order
+pub fn entity_mentions(text: &str, entities: &[ExtractedEntity]) -> Vec<(usize, usize, usize)> {
+    let mut mentions = Vec::new();
+    for (index, entity) in entities.iter().enumerate() {
+        let end = (entity.position + entity.relationship_context.len()).min(text.len());
+        if text.is_char_boundary(end) {
+            mentions.push((entity.position, end, index));
+        }
+        if entity.name == "unknown" {
+            continue;
+        }
+        if let Ok(name) = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(&entity.name))) {
+            mentions.extend(
+                name.find_iter(text).filter(|m| m.start() >= end).map(|m| (m.start(), m.end(), index)),
+            );
+        }
+    }
+    mentions.sort();
+    mentions.dedup_by_key(|m| m.0);
+    mentions
+}
+
+fn role(between: &str) -> &'static str {
+    if REPORTING_VERB.is_match(between) {
+        "source"
+    } else {
+        "target"
+    }
+}
+
+/// Attribute each match span (start, end) to an entity: the nearest mention before it in its
+/// sentence (source when a reporting verb sits in between, otherwise target), else a mention after
+/// it in the sentence, else, when the sentence refers back with "he"/"she"/"they", the most recent
+/// earlier mention
+pub fn link_spans(text: &str, spans: &[(usize, usize)], entities: &[ExtractedEntity]) -> Vec<Option<EntityLink>> {
+    let mentions = entity_mentions(text, entities);
+    let link = |index: usize, role: &'static str| Some(EntityLink { entity_id: entities[index].id.clone(), role });
+
+    spans
+        .iter()
+        .map(|&(position, match_end)| {
+            let (sentence_start, sentence_end) = sentence_bounds(text, position);
+
+            if let Some(&(_, end, index)) =
+                mentions.iter().rev().find(|&&(start, end, _)| start >= sentence_start && end <= position)
+            {
+                return link(index, role(&text[end..position]));
+            }
+            if let Some(&(start, _, index)) =
+                mentions.iter().find(|&&(start, _, _)| start >= match_end && start < sentence_end)
+            {
+                return link(index, role(&text[start..sentence_end]));
+            }
+            let before = &text[sentence_start..position];
+            if let Some(pronoun) = THIRD_PERSON_PRONOUN.find(before) {
+                if let Some(&(_, _, index)) = mentions.iter().rev().find(|&&(_, end, _)| end <= sentence_start) {
+                    return link(index, role(&before[pronoun.end()..]));
+                }
+            }
+            None
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::entity_extraction::extract_entities;
+    use super::super::pattern_matching::match_patterns;
+
+    fn links(text: &str) -> Vec<(String, Option<(String, &'static str)>)> {
+        let matches = match_patterns(text);
+        let spans: Vec<(usize, usize)> = matches.iter().map(|m| (m.position, m.position + m.match_text.len())).collect();
+        let entities = extract_entities(text).entities;
+        matches
+            .iter()
+            .zip(link_spans(text, &spans, &entities))
+            .map(|(m, link)| (m.match_text.clone(), link.map(|l| (l.entity_id, l.role))))
+            .collect()
+    }
+
+    #[test]
+    fn test_source_and_target_in_sentence() {
+        assert_eq!(links("My boss called me an idiot."), vec![("idiot".to_string(), Some(("boss@0".to_string(), "source")))]);
+        assert_eq!(links("My husband John is an idiot."), vec![("idiot".to_string(), Some(("john@0".to_string(), "target")))]);
+        assert_eq!(links("What an idiot."), vec![("idiot".to_string(), None)]);
+    }
+
+    #[test]
+    fn test_pronoun_refers_back_to_earlier_mention() {
+        let text = "I had lunch with my sister Amy. Later she told me I'm a failure.";
+        assert_eq!(links(text), vec![("failure".to_string(), Some(("amy@17".to_string(), "source")))]);
+    }
+}
@@ -0,0 +1,305 @@
+//! Pattern authoring tools
+//! Compiles candidate patterns the same way as the built-ins and runs them over examples,
+//! so contributors can validate patterns from a playground driven by the module itself,
+//! and lints them for constructs that make matching slow or overly broad
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::scheduler::now_ms;
+
+/// Span matched by a candidate pattern
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateMatch {
+    pub text: String,
+    pub position: usize,
+    pub end: usize,
+}
+
+/// Candidate pattern run over one example
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExampleResult {
+    pub index: usize,
+    pub example: String,
+    pub matched: bool,
+    pub matches: Vec<CandidateMatch>,
+    pub elapsed_ms: f64,
+}
+
+/// Outcome of testing a candidate pattern
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternTestResult {
+    /// Whether the pattern compiled
+    pub valid: bool,
+    pub error: Option<String>,
+    pub compile_ms: f64,
+    /// Total time spent matching all examples
+    pub match_ms: f64,
+    /// Examples with at least one match
+    pub matched_examples: usize,
+    pub examples: Vec<ExampleResult>,
+    /// Lint warnings for the pattern (empty when it fails to compile)
+    pub lints: Vec<PatternLint>,
+}
+
+/// Top-level alternatives in one group above which a pattern is flagged as a slow word list
+const MAX_ALTERNATIVES: usize = 40;
+
+/// Structured warning about a candidate pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternLint {
+    /// `nested_quantifier`, `excessive_alternation` or `missing_word_boundary`
+    pub rule: String,
+    pub message: String,
+    /// Byte offset in the pattern the warning refers to
+    pub position: usize,
+}
+
+impl PatternLint {
+    fn new(rule: &str, message: String, position: usize) -> Self {
+        PatternLint { rule: rule.to_string(), message, position }
+    }
+}
+
+/// Open group while scanning a pattern
+struct GroupScan {
+    start: usize,
+    alternatives: usize,
+    /// Whether anything inside the group repeats without bound
+    unbounded: bool,
+}
+
+/// Length of an unbounded quantifier (`*`, `+`, `{n,}`) at the start of `rest`, if any
+fn unbounded_quantifier(rest: &str) -> Option<usize> {
+    if rest.starts_with(['*', '+']) {
+        return Some(1);
+    }
+    let close = rest.strip_prefix('{')?.find('}')? + 2;
+    rest[1..close - 1].ends_with(',').then_some(close)
+}
+
+/// Byte index just past the character class starting at `start`
+fn class_end(pattern: &str, start: usize) -> usize {
+    let bytes = pattern.as_bytes();
+    let mut i = start + 1;
+    let mut depth = 1;
+    // A leading `]` (after an optional `^`) is a literal
+    if bytes.get(i) == Some(&b'^') {
+        i += 1;
+    }
+    if bytes.get(i) == Some(&b']') {
+        i += 1;
+    }
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => depth += 1,
+            b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Lint a candidate pattern for nested unbounded quantifiers, oversized alternations and
+/// word-character edges without `\b`; an uncompilable pattern is returned as an error
+pub fn lint_pattern(pattern: &str) -> Result<Vec<PatternLint>, String> {
+    compile_candidate(pattern)?;
+
+    let mut lints = Vec::new();
+    let mut stack = vec![GroupScan { start: 0, alternatives: 1, unbounded: false }];
+    // Unboundedness of the group that just closed, until we see whether it is quantified
+    let mut closed: Option<(usize, bool)> = None;
+    let mut i = 0;
+
+    while i < pattern.len() {
+        let rest = &pattern[i..];
+        if let Some(len) = unbounded_quantifier(rest) {
+            if let Some((start, true)) = closed {
+                lints.push(PatternLint::new(
+                    "nested_quantifier",
+                    format!("group at {} repeats without bound and is itself repeated; rewrite it to repeat once", start),
+                    start,
+                ));
+            }
+            if let Some(group) = stack.last_mut() {
+                group.unbounded = true;
+            }
+            closed = None;
+            i += len;
+            continue;
+        }
+        closed = None;
+
+        let c = rest.chars().next().unwrap_or_default();
+        match c {
+            '\\' => i += 1 + rest[1..].chars().next().map(char::len_utf8).unwrap_or(0),
+            '[' => i = class_end(pattern, i),
+            '(' => {
+                stack.push(GroupScan { start: i, alternatives: 1, unbounded: false });
+                i += 1;
+            }
+            ')' => {
+                let group = stack.pop().unwrap_or(GroupScan { start: i, alternatives: 1, unbounded: false });
+                check_alternatives(&group, &mut lints);
+                if let Some(parent) = stack.last_mut() {
+                    parent.unbounded |= group.unbounded;
+                }
+                closed = Some((group.start, group.unbounded));
+                i += 1;
+            }
+            '|' => {
+                if let Some(group) = stack.last_mut() {
+                    group.alternatives += 1;
+                }
+                i += 1;
+            }
+            _ => i += c.len_utf8(),
+        }
+    }
+    for group in &stack {
+        check_alternatives(group, &mut lints);
+    }
+
+    let head = pattern.trim_start_matches(['(', '?', ':']);
+    let tail = pattern.trim_end_matches([')', '?']);
+    if head.starts_with(char::is_alphanumeric) || head.starts_with("\\w") {
+        lints.push(PatternLint::new(
+            "missing_word_boundary",
+            "pattern starts with a word character but no \\b, so it also matches inside longer words".to_string(),
+            0,
+        ));
+    }
+    let ends_with_word = tail.chars().last().is_some_and(|last| {
+        last.is_alphanumeric() && !tail[..tail.len() - last.len_utf8()].ends_with('\\')
+    });
+    if ends_with_word || tail.ends_with("\\w") {
+        lints.push(PatternLint::new(
+            "missing_word_boundary",
+            "pattern ends with a word character but no \\b, so it also matches prefixes of longer words".to_string(),
+            pattern.len(),
+        ));
+    }
+
+    lints.sort_by_key(|lint| lint.position);
+    Ok(lints)
+}
+
+fn check_alternatives(group: &GroupScan, lints: &mut Vec<PatternLint>) {
+    if group.alternatives > MAX_ALTERNATIVES {
+        lints.push(PatternLint::new(
+            "excessive_alternation",
+            format!(
+                "{} alternatives in one group (limit {}); split it or use a lexicon instead",
+                group.alternatives, MAX_ALTERNATIVES
+            ),
+            group.start,
+        ));
+    }
+}
+
+/// Compile a pattern as built-in patterns are compiled (case-insensitive)
+pub fn compile_candidate(pattern: &str) -> Result<Regex, String> {
+    Regex::new(&format!("(?i){}", pattern)).map_err(|e| e.to_string())
+}
+
+/// Compile a candidate pattern and report which examples it matches, with timing
+pub fn test_candidate(pattern: &str, examples: &[String]) -> PatternTestResult {
+    let started = now_ms();
+    let compiled = compile_candidate(pattern);
+    let compile_ms = now_ms() - started;
+
+    let regex = match compiled {
+        Ok(regex) => regex,
+        Err(error) => {
+            return PatternTestResult {
+                valid: false,
+                error: Some(error),
+                compile_ms,
+                match_ms: 0.0,
+                matched_examples: 0,
+                examples: Vec::new(),
+                lints: Vec::new(),
+            }
+        }
+    };
+
+    let examples: Vec<ExampleResult> = examples
+        .iter()
+        .enumerate()
+        .map(|(index, example)| {
+            let started = now_ms();
+            let matches: Vec<CandidateMatch> = regex
+                .find_iter(example)
+                .map(|m| CandidateMatch { text: m.as_str().to_string(), position: m.start(), end: m.end() })
+                .collect();
+            ExampleResult {
+                index,
+                example: example.clone(),
+                matched: !matches.is_empty(),
+                matches,
+                elapsed_ms: now_ms() - started,
+            }
+        })
+        .collect();
+
+    PatternTestResult {
+        valid: true,
+        error: None,
+        compile_ms,
+        match_ms: examples.iter().map(|e| e.elapsed_ms).sum(),
+        matched_examples: examples.iter().filter(|e| e.matched).count(),
+        examples,
+        lints: lint_pattern(pattern).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_reports_matching_examples() {
+        let examples = vec!["You NEVER listen".to_string(), "I never said that".to_string(), "Nice day".to_string()];
+        let result = test_candidate(r"\byou\s+never\b", &examples);
+        assert!(result.valid);
+        assert_eq!(result.matched_examples, 1);
+        assert_eq!(result.examples[0].matches[0].text, "You NEVER");
+        assert!(!result.examples[2].matched);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let result = test_candidate(r"(unclosed", &["text".to_string()]);
+        assert!(!result.valid);
+        assert!(result.error.is_some());
+        assert!(result.examples.is_empty());
+        assert!(lint_pattern(r"(unclosed").is_err());
+    }
+
+    #[test]
+    fn test_lint_flags_slow_and_broad_patterns() {
+        let rules = |pattern: &str| -> Vec<String> {
+            lint_pattern(pattern).unwrap().into_iter().map(|lint| lint.rule).collect()
+        };
+        assert_eq!(rules(r"\b(\w+\s*)+\b"), vec!["nested_quantifier"]);
+        assert_eq!(rules(r"\b(a{2,})*\b"), vec!["nested_quantifier"]);
+        assert!(rules(r"\b(\w+\s){1,3}\b").is_empty());
+        assert_eq!(rules(r"you\s+never"), vec!["missing_word_boundary", "missing_word_boundary"]);
+        assert!(rules(r"\byou\s+never\b").is_empty());
+
+        let words: Vec<String> = (0..50).map(|i| format!("w{}", i)).collect();
+        assert_eq!(rules(&format!(r"\b({})\b", words.join("|"))), vec!["excessive_alternation"]);
+    }
+}
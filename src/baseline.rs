@@ -0,0 +1,168 @@
+//! Reference baselines
+//! Where a score falls among a bundled sample of everyday messages, so raw scores are interpretable,
+//! and per-category deviations from a host's own corpus baseline (mean/variance per pattern type)
+
+use std::collections::BTreeMap;
+
+use lazy_static::lazy_static;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::analyze;
+use super::pattern_matching::{match_patterns, PatternMatch};
+use super::scoring::calculate_text_score;
+
+/// Variance floor so categories that never varied in the corpus don't divide by zero
+const MIN_VARIANCE: f64 = 1e-4;
+
+/// Everyday messages (one per line): logistics, small talk, mild complaints and friction
+pub const REFERENCE_CORPUS: &str = include_str!("reference_corpus.txt");
+
+lazy_static! {
+    /// Sorted scores of the reference messages, computed once with the current patterns
+    static ref REFERENCE_SCORES: Vec<f64> = {
+        let mut scores: Vec<f64> =
+            REFERENCE_CORPUS.lines().filter(|l| !l.trim().is_empty()).map(|l| analyze(l).score).collect();
+        scores.sort_by(f64::total_cmp);
+        scores
+    };
+}
+
+/// Percent of reference messages scoring strictly lower (0 - 100)
+pub fn score_percentile(score: f64) -> f64 {
+    let below = REFERENCE_SCORES.partition_point(|s| *s < score);
+    100.0 * below as f64 / REFERENCE_SCORES.len().max(1) as f64
+}
+
+/// Mean and variance of one category's per-text score over a corpus
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryStats {
+    pub mean: f64,
+    pub variance: f64,
+}
+
+/// Corpus baseline: overall score statistics and per-pattern-type statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CorpusBaseline {
+    /// Number of texts the statistics were computed from
+    pub text_count: usize,
+    pub score: Option<CategoryStats>,
+    pub categories: BTreeMap<String, CategoryStats>,
+}
+
+/// How far a text sits from the baseline for one category
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryDeviation {
+    pub category: String,
+    /// The text's score for the category (0 - 1)
+    pub value: f64,
+    pub mean: f64,
+    /// Standard deviations above (positive) or below the baseline mean
+    pub z_score: f64,
+}
+
+/// Deviations of a text from a corpus baseline
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BaselineComparison {
+    /// Overall score deviation, when the baseline has score statistics
+    pub score: Option<CategoryDeviation>,
+    /// Per-category deviations, largest first
+    pub categories: Vec<CategoryDeviation>,
+}
+
+/// Per-pattern-type scores of a text
+fn category_scores(text: &str) -> BTreeMap<String, f64> {
+    let mut by_type: BTreeMap<String, Vec<PatternMatch>> = BTreeMap::new();
+    for m in match_patterns(text) {
+        by_type.entry(m.pattern_type.clone()).or_default().push(m);
+    }
+    by_type.into_iter().map(|(pattern_type, matches)| (pattern_type, calculate_text_score(&matches))).collect()
+}
+
+fn stats(values: &[f64]) -> CategoryStats {
+    let n = values.len().max(1) as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    CategoryStats { mean, variance }
+}
+
+fn deviation(category: &str, value: f64, stats: &CategoryStats) -> CategoryDeviation {
+    CategoryDeviation {
+        category: category.to_string(),
+        value,
+        mean: stats.mean,
+        z_score: (value - stats.mean) / stats.variance.max(MIN_VARIANCE).sqrt(),
+    }
+}
+
+/// Compute a baseline from a host corpus; categories that never fire are omitted
+pub fn build_baseline(texts: &[String]) -> CorpusBaseline {
+    let per_text: Vec<BTreeMap<String, f64>> = texts.iter().map(|t| category_scores(t)).collect();
+    let mut categories: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for scores in &per_text {
+        for category in scores.keys() {
+            categories.entry(category.clone()).or_default();
+        }
+    }
+    let categories = categories
+        .into_keys()
+        .map(|category| {
+            let values: Vec<f64> = per_text.iter().map(|s| s.get(&category).copied().unwrap_or(0.0)).collect();
+            (category, stats(&values))
+        })
+        .collect();
+    let scores: Vec<f64> = texts.iter().map(|t| analyze(t).score).collect();
+
+    CorpusBaseline { text_count: texts.len(), score: Some(stats(&scores)), categories }
+}
+
+/// Compare a text with a baseline; categories the baseline lacks are not reported
+pub fn compare_to_baseline(text: &str, baseline: &CorpusBaseline) -> BaselineComparison {
+    let scores = category_scores(text);
+    let mut categories: Vec<CategoryDeviation> = baseline
+        .categories
+        .iter()
+        .map(|(category, stats)| deviation(category, scores.get(category).copied().unwrap_or(0.0), stats))
+        .collect();
+    categories.sort_by(|a, b| b.z_score.abs().total_cmp(&a.z_score.abs()));
+
+    BaselineComparison {
+        score: baseline.score.as_ref().map(|stats| deviation("score", analyze(text).score, stats)),
+        categories,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_percentiles() {
+        assert!(REFERENCE_SCORES.len() >= 50);
+        assert_eq!(score_percentile(0.0), 0.0);
+        assert_eq!(score_percentile(1.01), 100.0);
+        let hostile = analyze("You're a pathetic, worthless idiot and it's all your fault. You never listen.").score;
+        assert!(score_percentile(hostile) > 80.0);
+    }
+
+    #[test]
+    fn test_deviation_from_host_baseline() {
+        let corpus: Vec<String> = ["Calm down, it's fine.", "Lunch at noon?", "Calm down everyone.", "See you soon."]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        let baseline = build_baseline(&corpus);
+        assert_eq!(baseline.text_count, 4);
+        assert!(baseline.categories.contains_key("tone_policing"));
+
+        let comparison = compare_to_baseline("Calm down. Calm down! I said calm down.", &baseline);
+        let tone = comparison.categories.iter().find(|c| c.category == "tone_policing").unwrap();
+        assert!(tone.z_score > 0.0);
+        assert!(compare_to_baseline("Lunch at noon?", &baseline).categories.iter().all(|c| c.z_score <= 0.0));
+        assert!(comparison.score.is_some());
+    }
+}
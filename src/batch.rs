@@ -0,0 +1,67 @@
+//! Batch analysis
+//! Analyze many texts in one call, as a JSON array or newline-delimited JSON
+
+use super::versioning::{to_versioned_json, SchemaVersion};
+use super::{analyze, TextProcessingResult};
+
+/// Batch output layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchOutput {
+    /// Single JSON array of results
+    Json,
+    /// One JSON result per line
+    Ndjson,
+}
+
+impl BatchOutput {
+    /// Parse an output mode name, defaulting to a JSON array
+    pub fn parse(name: Option<&str>) -> Result<Self, String> {
+        match name.map(str::to_lowercase).as_deref() {
+            None | Some("json") => Ok(BatchOutput::Json),
+            Some("ndjson") | Some("jsonl") => Ok(BatchOutput::Ndjson),
+            Some(other) => Err(format!("unsupported batch output '{}'", other)),
+        }
+    }
+}
+
+/// Analyze each text in order
+pub fn analyze_all(texts: &[String]) -> Vec<TextProcessingResult> {
+    texts.iter().map(|text| analyze(text)).collect()
+}
+
+/// Analyze each text, writing one serialized result per line as it goes
+/// so no intermediate array of results is held in memory
+pub fn analyze_to_ndjson(texts: &[String]) -> Result<String, String> {
+    let mut out = String::new();
+    for text in texts {
+        let line = to_versioned_json(&analyze(text), SchemaVersion::CURRENT)?;
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ndjson_has_one_line_per_text() {
+        let texts = vec!["You're so selfish".to_string(), "Nice day".to_string(), String::new()];
+        let ndjson = analyze_to_ndjson(&texts).unwrap();
+        let lines: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(lines.len(), 3);
+
+        let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["detected"], serde_json::Value::Bool(true));
+        assert_eq!(first["schemaVersion"], SchemaVersion::CURRENT.number());
+        assert_eq!(analyze_all(&texts).len(), 3);
+    }
+
+    #[test]
+    fn test_parse_output() {
+        assert_eq!(BatchOutput::parse(None), Ok(BatchOutput::Json));
+        assert_eq!(BatchOutput::parse(Some("NDJSON")), Ok(BatchOutput::Ndjson));
+        assert!(BatchOutput::parse(Some("csv")).is_err());
+    }
+}
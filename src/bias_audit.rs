@@ -0,0 +1,186 @@
+//! Bias audit
+//! Runs the detector over counterfactual templates (the same sentence with different identity
+//! terms swapped in) and reports how far scores move with the identity term alone
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::analyze_with_config;
+use super::scoring::ScoringConfig;
+
+/// Placeholder replaced by each identity term
+pub const TERM_PLACEHOLDER: &str = "{term}";
+
+/// Templates and the identity terms to swap into them
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateSet {
+    #[serde(default)]
+    pub name: String,
+    /// Sentences containing `{term}`
+    pub templates: Vec<String>,
+    /// Identity terms, at least two
+    pub terms: Vec<String>,
+}
+
+/// Audit request supplied by the host
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BiasAuditRequest {
+    pub sets: Vec<TemplateSet>,
+    /// Scoring options used for every variant
+    #[serde(default)]
+    pub config: ScoringConfig,
+}
+
+/// How one identity term fared across a set's templates
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TermAudit {
+    pub term: String,
+    pub mean_score: f64,
+    /// Share of templates detected with this term (0 - 1)
+    pub detection_rate: f64,
+    /// Mean score minus the set's mean score over all terms
+    pub disparity: f64,
+}
+
+/// Score spread for one template across the identity terms
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateAudit {
+    pub template: String,
+    /// Scores in the order of the set's terms
+    pub scores: Vec<f64>,
+    /// Highest minus lowest score; 0 when the identity term makes no difference
+    pub spread: f64,
+}
+
+/// Audit results for one template set
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateSetAudit {
+    pub name: String,
+    pub terms: Vec<TermAudit>,
+    /// Templates with a non-zero spread first, largest spread first
+    pub templates: Vec<TemplateAudit>,
+    /// Highest minus lowest per-term mean score
+    pub max_disparity: f64,
+}
+
+/// Audit results for every template set
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BiasAuditReport {
+    pub sets: Vec<TemplateSetAudit>,
+    /// Largest `maxDisparity` over all sets
+    pub max_disparity: f64,
+}
+
+fn validate(set: &TemplateSet) -> Result<(), String> {
+    if set.terms.len() < 2 {
+        return Err(format!("set '{}' needs at least two terms", set.name));
+    }
+    if set.templates.is_empty() {
+        return Err(format!("set '{}' has no templates", set.name));
+    }
+    match set.templates.iter().find(|t| !t.contains(TERM_PLACEHOLDER)) {
+        Some(template) => Err(format!("template '{}' has no {} placeholder", template, TERM_PLACEHOLDER)),
+        None => Ok(()),
+    }
+}
+
+fn spread(values: impl Iterator<Item = f64> + Clone) -> f64 {
+    let max = values.clone().fold(f64::MIN, f64::max);
+    let min = values.fold(f64::MAX, f64::min);
+    (max - min).max(0.0)
+}
+
+fn audit_set(set: &TemplateSet, config: &ScoringConfig) -> TemplateSetAudit {
+    // results[template][term] = (score, detected)
+    let results: Vec<Vec<(f64, bool)>> = set
+        .templates
+        .iter()
+        .map(|template| {
+            set.terms
+                .iter()
+                .map(|term| {
+                    let result = analyze_with_config(&template.replace(TERM_PLACEHOLDER, term), config);
+                    (result.score, result.detected)
+                })
+                .collect()
+        })
+        .collect();
+
+    let count = set.templates.len() as f64;
+    let means: Vec<f64> = (0..set.terms.len()).map(|t| results.iter().map(|row| row[t].0).sum::<f64>() / count).collect();
+    let overall = means.iter().sum::<f64>() / means.len() as f64;
+
+    let terms = set
+        .terms
+        .iter()
+        .enumerate()
+        .map(|(t, term)| TermAudit {
+            term: term.clone(),
+            mean_score: means[t],
+            detection_rate: results.iter().filter(|row| row[t].1).count() as f64 / count,
+            disparity: means[t] - overall,
+        })
+        .collect();
+
+    let mut templates: Vec<TemplateAudit> = set
+        .templates
+        .iter()
+        .zip(&results)
+        .map(|(template, row)| {
+            let scores: Vec<f64> = row.iter().map(|&(score, _)| score).collect();
+            TemplateAudit { template: template.clone(), spread: spread(scores.iter().copied()), scores }
+        })
+        .collect();
+    templates.sort_by(|a, b| b.spread.total_cmp(&a.spread));
+
+    TemplateSetAudit { name: set.name.clone(), terms, templates, max_disparity: spread(means.iter().copied()) }
+}
+
+/// Run every template set and report score disparities between identity terms
+pub fn audit_bias(request: &BiasAuditRequest) -> Result<BiasAuditReport, String> {
+    request.sets.iter().try_for_each(validate)?;
+    let sets: Vec<TemplateSetAudit> = request.sets.iter().map(|set| audit_set(set, &request.config)).collect();
+    let max_disparity = sets.iter().map(|s| s.max_disparity).fold(0.0, f64::max);
+    Ok(BiasAuditReport { sets, max_disparity })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(templates: &[&str], terms: &[&str]) -> BiasAuditRequest {
+        BiasAuditRequest {
+            sets: vec![TemplateSet {
+                name: "origin".to_string(),
+                templates: templates.iter().map(|t| t.to_string()).collect(),
+                terms: terms.iter().map(|t| t.to_string()).collect(),
+            }],
+            config: ScoringConfig::default(),
+        }
+    }
+
+    #[test]
+    fn test_reports_identity_term_disparity() {
+        let report = audit_bias(&request(&["The {term} are vermin", "The {term} are late again"], &["immigrants", "neighbors"])).unwrap();
+        let set = &report.sets[0];
+        assert!(set.max_disparity > 0.0);
+        assert_eq!(set.templates[0].template, "The {term} are vermin");
+        assert_eq!(set.templates[1].spread, 0.0);
+        assert!(set.terms[0].disparity > 0.0 && set.terms[1].disparity < 0.0);
+        assert_eq!(report.max_disparity, set.max_disparity);
+    }
+
+    #[test]
+    fn test_rejects_invalid_sets() {
+        assert!(audit_bias(&request(&["No placeholder here"], &["a", "b"])).is_err());
+        assert!(audit_bias(&request(&["{term} is here"], &["only"])).is_err());
+        let neutral = audit_bias(&request(&["You always ignore {term} people"], &["old", "young"])).unwrap();
+        assert_eq!(neutral.max_disparity, 0.0);
+    }
+}
@@ -0,0 +1,248 @@
+//! Batch analysis CLI
+//! Runs the same detection logic that ships in the wasm module over files or stdin
+
+use std::fs;
+use std::io::{self, BufWriter, Read, Write};
+use std::process;
+
+use affectively_text_processor::{analyze, TextProcessingResult};
+use serde::Serialize;
+
+const USAGE: &str = "\
+Usage: text-processor [OPTIONS] [FILE...]
+
+Reads each FILE (or stdin when none is given, or FILE is '-') and writes one
+analysis record per document.
+
+Options:
+  --input <txt|ndjson>    Input format (default: inferred from extension, else txt)
+  --format <ndjson|csv>   Output format (default: ndjson)
+  --per-line              Treat each line of txt input as a separate document
+  -h, --help              Show this help";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InputFormat {
+    Txt,
+    Ndjson,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Ndjson,
+    Csv,
+}
+
+#[derive(Debug)]
+struct Options {
+    input: Option<InputFormat>,
+    format: OutputFormat,
+    per_line: bool,
+    files: Vec<String>,
+}
+
+/// A single document to analyze
+#[derive(Debug)]
+struct Document {
+    source: String,
+    id: String,
+    text: String,
+}
+
+/// One output record
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Record<'a> {
+    source: &'a str,
+    id: &'a str,
+    #[serde(flatten)]
+    result: &'a TextProcessingResult,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options { input: None, format: OutputFormat::Ndjson, per_line: false, files: Vec::new() };
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--input" => {
+                options.input = match iter.next().map(String::as_str) {
+                    Some("txt") => Some(InputFormat::Txt),
+                    Some("ndjson") => Some(InputFormat::Ndjson),
+                    other => return Err(format!("invalid --input value: {}", other.unwrap_or(""))),
+                }
+            }
+            "--format" => {
+                options.format = match iter.next().map(String::as_str) {
+                    Some("ndjson") => OutputFormat::Ndjson,
+                    Some("csv") => OutputFormat::Csv,
+                    other => return Err(format!("invalid --format value: {}", other.unwrap_or(""))),
+                }
+            }
+            "--per-line" => options.per_line = true,
+            "-h" | "--help" => return Err(String::new()),
+            flag if flag.starts_with("--") => return Err(format!("unknown option: {}", flag)),
+            file => options.files.push(file.to_string()),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Infer the input format from a file extension
+fn infer_input(path: &str) -> InputFormat {
+    if path.ends_with(".ndjson") || path.ends_with(".jsonl") {
+        InputFormat::Ndjson
+    } else {
+        InputFormat::Txt
+    }
+}
+
+/// Split raw input into documents
+fn read_documents(source: &str, content: &str, input: InputFormat, per_line: bool) -> Result<Vec<Document>, String> {
+    let mut documents = Vec::new();
+
+    match input {
+        InputFormat::Txt if !per_line => documents.push(Document {
+            source: source.to_string(),
+            id: "1".to_string(),
+            text: content.to_string(),
+        }),
+        InputFormat::Txt => {
+            for (index, line) in content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+                documents.push(Document { source: source.to_string(), id: (index + 1).to_string(), text: line.to_string() });
+            }
+        }
+        InputFormat::Ndjson => {
+            for (index, line) in content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+                let value: serde_json::Value = serde_json::from_str(line)
+                    .map_err(|e| format!("{}:{}: invalid JSON: {}", source, index + 1, e))?;
+                let text = value
+                    .get("text")
+                    .and_then(|t| t.as_str())
+                    .ok_or_else(|| format!("{}:{}: missing string field 'text'", source, index + 1))?;
+                let id = match value.get("id") {
+                    Some(serde_json::Value::String(id)) => id.clone(),
+                    Some(id) if !id.is_null() => id.to_string(),
+                    _ => (index + 1).to_string(),
+                };
+                documents.push(Document { source: source.to_string(), id, text: text.to_string() });
+            }
+        }
+    }
+
+    Ok(documents)
+}
+
+/// Quote a CSV field when needed
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_row(document: &Document, result: &TextProcessingResult) -> String {
+    let mut pattern_types: Vec<&str> = result.patterns.iter().map(|p| p.pattern_type.as_str()).collect();
+    pattern_types.sort();
+    pattern_types.dedup();
+
+    [
+        csv_field(&document.source),
+        csv_field(&document.id),
+        result.detected.to_string(),
+        result.confidence.to_string(),
+        result.score.to_string(),
+        result.patterns.len().to_string(),
+        csv_field(&pattern_types.join(";")),
+    ]
+    .join(",")
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let sources = if options.files.is_empty() { vec!["-".to_string()] } else { options.files.clone() };
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let write_error = |e: io::Error| e.to_string();
+
+    if options.format == OutputFormat::Csv {
+        writeln!(out, "source,id,detected,confidence,score,pattern_count,pattern_types").map_err(write_error)?;
+    }
+
+    for source in &sources {
+        let content = if source == "-" {
+            let mut buffer = String::new();
+            io::stdin().read_to_string(&mut buffer).map_err(|e| format!("stdin: {}", e))?;
+            buffer
+        } else {
+            fs::read_to_string(source).map_err(|e| format!("{}: {}", source, e))?
+        };
+        let input = options.input.unwrap_or_else(|| infer_input(source));
+
+        for document in read_documents(source, &content, input, options.per_line)? {
+            let result = analyze(&document.text);
+            let line = match options.format {
+                OutputFormat::Ndjson => {
+                    let record = Record { source: &document.source, id: &document.id, result: &result };
+                    serde_json::to_string(&record).map_err(|e| e.to_string())?
+                }
+                OutputFormat::Csv => csv_row(&document, &result),
+            };
+            writeln!(out, "{}", line).map_err(write_error)?;
+        }
+    }
+
+    out.flush().map_err(write_error)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(message) if message.is_empty() => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(message) = run(&options) {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_ndjson_documents() {
+        let content = "{\"id\":\"a\",\"text\":\"You're so lazy\"}\n\n{\"id\":7,\"text\":\"hi\"}\n{\"text\":\"x\"}";
+        let documents = read_documents("in.ndjson", content, InputFormat::Ndjson, false).unwrap();
+        let ids: Vec<&str> = documents.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "7", "4"]);
+
+        assert!(read_documents("in.ndjson", "{\"id\":1}", InputFormat::Ndjson, false).is_err());
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args: Vec<String> = ["--format", "csv", "--per-line", "notes.txt"].iter().map(|s| s.to_string()).collect();
+        let options = parse_args(&args).unwrap();
+        assert_eq!(options.format, OutputFormat::Csv);
+        assert!(options.per_line);
+        assert_eq!(options.files, vec!["notes.txt"]);
+        assert_eq!(infer_input("batch.jsonl"), InputFormat::Ndjson);
+    }
+}
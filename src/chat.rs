@@ -0,0 +1,475 @@
+//! Chat export parsing
+//! Splits chat logs ("[10:42] Alice: ...", WhatsApp exports) into messages and analyzes them per speaker
+
+use std::collections::BTreeMap;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::incident_timing::{incident_timing, IncidentTiming};
+use super::pattern_matching::{match_patterns, PatternMatch};
+use super::positive::{empathy_score, match_positive};
+use super::scoring::{calculate_confidence, calculate_text_score};
+use super::tokenize::tokenize;
+use super::turns::{message_bursts, turn_statistics, TurnStats, Utterance};
+use super::{build_processing_result, TextProcessingResult, DETECTION_THRESHOLD};
+
+lazy_static! {
+    /// `[10:42] Alice: text`, `[31/12/2023, 10:42:05] Alice: text` (iOS WhatsApp)
+    static ref BRACKETED_LINE: Regex = Regex::new(r"^\[([^\]]+)\]\s*([^:\[\]]{1,40}?):\s?(.*)$").unwrap();
+    /// `12/31/23, 10:42 PM - Alice: text` (Android WhatsApp)
+    static ref DASHED_LINE: Regex = Regex::new(
+        r"^(\d{1,4}[/.\-]\d{1,2}[/.\-]\d{1,4},?\s+\d{1,2}:\d{2}(?::\d{2})?(?:\s?[APap]\.?[Mm]\.?)?)\s+[-–]\s+([^:]{1,40}?):\s?(.*)$"
+    ).unwrap();
+    /// Timestamped line without a speaker (joins, encryption notices)
+    /// Optional date (either day/month order, or ISO 8601) then a clock time with optional AM/PM
+    static ref TIMESTAMP: Regex = Regex::new(
+        r"^(?:(\d{1,4})[/.\-](\d{1,2})[/.\-](\d{1,4})(?:,?\s+|T))?(\d{1,2}):(\d{2})(?::(\d{2})(?:\.\d+)?)?\s?(?:([APap])\.?[Mm]\.?|Z)?$"
+    ).unwrap();
+    static ref SYSTEM_LINE: Regex = Regex::new(r"^(\[[^\]]+\]|\d{1,4}[/.\-]\d{1,2}[/.\-]\d{1,4},?\s+\d{1,2}:\d{2})").unwrap();
+}
+
+/// Categories listed in a risk profile
+const DOMINANT_CATEGORIES: usize = 3;
+/// Change in mean message score between halves of a speaker's messages that counts as a trend
+const TREND_DELTA: f64 = 0.1;
+
+/// Single message parsed from a chat export
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    pub timestamp: Option<String>,
+    pub speaker: String,
+    pub text: String,
+    /// Byte offset of the message text in the export
+    pub position: usize,
+}
+
+/// Message with its detection result (pattern positions are offsets in the export)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageAnalysis {
+    pub index: usize,
+    pub timestamp: Option<String>,
+    pub speaker: String,
+    pub text: String,
+    pub position: usize,
+    pub result: TextProcessingResult,
+}
+
+/// Aggregate detection result for one speaker
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeakerAnalysis {
+    pub speaker: String,
+    pub message_count: usize,
+    pub word_count: usize,
+    /// Messages that were individually flagged
+    pub detected_messages: usize,
+    pub detected: bool,
+    pub confidence: f64,
+    pub score: f64,
+    /// Empathic statements across the speaker's messages, 0 - 1
+    pub empathy_score: f64,
+    pub pattern_counts: BTreeMap<String, usize>,
+    pub risk_profile: RiskProfile,
+}
+
+/// How a speaker's flagged language is distributed and how it develops over the conversation
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskProfile {
+    /// Most frequent pattern types, most frequent first
+    pub dominant_categories: Vec<String>,
+    /// Match counts by severity ("high", "medium", "low")
+    pub severity_counts: BTreeMap<String, usize>,
+    /// Mean message score over the first half of the speaker's messages
+    pub early_score: f64,
+    /// Mean message score over the second half
+    pub late_score: f64,
+    /// "rising", "falling" or "stable"
+    pub trend: String,
+}
+
+/// Conversation-level event spanning several messages
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationEvent {
+    /// "burst": many messages from one speaker in a short window (flooding)
+    pub kind: String,
+    pub speaker: String,
+    /// Indices of the speaker's messages in the event
+    pub message_indices: Vec<usize>,
+    pub start_timestamp: Option<String>,
+    pub end_timestamp: Option<String>,
+    pub duration_ms: i64,
+}
+
+/// Message-level and speaker-level results for a chat export
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatAnalysis {
+    pub messages: Vec<MessageAnalysis>,
+    /// Speakers in order of first appearance
+    pub speakers: Vec<SpeakerAnalysis>,
+    /// Turn counts, word share, interruptions and response latencies, in the same speaker order
+    pub turn_taking: Vec<TurnStats>,
+    /// Conversation-level events such as message bursts, in order of their first message
+    pub events: Vec<ConversationEvent>,
+    /// When flagged messages occur: hour-of-day counts, late-night share and incident intervals
+    pub incident_timing: IncidentTiming,
+}
+
+/// Days from 1970-01-01 to a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Parse a chat timestamp into milliseconds since the Unix epoch, or since midnight when it has
+/// no date. Dates are read day-first unless the month can't be over 12 or an AM/PM clock
+/// (US-style Android export) says otherwise
+pub fn timestamp_ms(timestamp: &str) -> Option<i64> {
+    let captures = TIMESTAMP.captures(timestamp.trim())?;
+    let number = |i: usize| captures.get(i).and_then(|m| m.as_str().parse::<i64>().ok());
+
+    let mut hour = number(4)?;
+    let (minute, second) = (number(5)?, number(6).unwrap_or(0));
+    if let Some(meridiem) = captures.get(7) {
+        hour = hour % 12 + if meridiem.as_str().eq_ignore_ascii_case("p") { 12 } else { 0 };
+    }
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    let clock = ((hour * 60 + minute) * 60 + second) * 1000;
+
+    let (Some(a), Some(b), Some(c)) = (number(1), number(2), number(3)) else {
+        return Some(clock);
+    };
+    let (year, month, day) = if captures[1].len() == 4 {
+        (a, b, c)
+    } else if b > 12 || (a <= 12 && captures.get(7).is_some()) {
+        (c, a, b)
+    } else {
+        (c, b, a)
+    };
+    let year = if year < 100 { year + 2000 } else { year };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * 86_400_000 + clock)
+}
+
+/// Message times on a monotonic clock; dateless times that go backwards are taken to have
+/// crossed midnight
+pub fn message_times(messages: &[ChatMessage]) -> Vec<Option<i64>> {
+    let mut day_offset = 0;
+    let mut last: Option<i64> = None;
+    messages
+        .iter()
+        .map(|message| {
+            let timestamp = message.timestamp.as_deref()?;
+            let mut time = timestamp_ms(timestamp)?;
+            if time < 86_400_000 {
+                if last.is_some_and(|last| time + day_offset < last) {
+                    day_offset += 86_400_000;
+                }
+                time += day_offset;
+            }
+            last = Some(time);
+            Some(time)
+        })
+        .collect()
+}
+
+/// Parse a chat export; unrecognised lines continue the previous message
+pub fn parse_chat(source: &str) -> Vec<ChatMessage> {
+    let mut messages: Vec<ChatMessage> = Vec::new();
+    // Whether unrecognised lines should be appended to the last message
+    let mut open = false;
+    let mut line_start = 0;
+
+    for raw_line in source.split_inclusive('\n') {
+        let line = raw_line.trim_end_matches(['\n', '\r']);
+        // iOS exports prefix some lines with a left-to-right mark
+        let offset = line.len() - line.trim_start_matches('\u{200e}').len();
+        let content = &line[offset..];
+
+        let captures = BRACKETED_LINE.captures(content).or_else(|| DASHED_LINE.captures(content));
+        if let Some(captures) = captures {
+            let text = &captures[3];
+            messages.push(ChatMessage {
+                timestamp: Some(captures[1].trim().to_string()),
+                speaker: captures[2].trim().to_string(),
+                text: text.to_string(),
+                position: line_start + offset + captures.get(3).map(|m| m.start()).unwrap_or(content.len()),
+            });
+            open = true;
+        } else if SYSTEM_LINE.is_match(content) {
+            open = false;
+        } else if let (true, Some(last)) = (open, messages.last_mut()) {
+            // Message text stays a contiguous slice of the export so offsets map directly
+            last.text = source[last.position..line_start + line.len()].to_string();
+        }
+
+        line_start += raw_line.len();
+    }
+
+    messages
+}
+
+/// Running totals for a speaker: the analysis, its matches, its positive matches and per-message scores
+type SpeakerState = (SpeakerAnalysis, Vec<PatternMatch>, Vec<PatternMatch>, Vec<f64>);
+
+/// Parse a chat export and analyze each message and each speaker
+pub fn analyze_chat(source: &str) -> ChatAnalysis {
+    let parsed = parse_chat(source);
+    let times = message_times(&parsed);
+    let mut messages = Vec::new();
+    let mut speakers: Vec<SpeakerState> = Vec::new();
+
+    for (index, message) in parsed.into_iter().enumerate() {
+        let matches: Vec<PatternMatch> = match_patterns(&message.text)
+            .into_iter()
+            .map(|mut m| {
+                m.position += message.position;
+                m
+            })
+            .collect();
+        let words = tokenize(&message.text).len();
+        let result = build_processing_result(&matches, words);
+
+        let slot = match speakers.iter().position(|(s, _, _, _)| s.speaker == message.speaker) {
+            Some(slot) => slot,
+            None => {
+                speakers.push((
+                    SpeakerAnalysis {
+                        speaker: message.speaker.clone(),
+                        message_count: 0,
+                        word_count: 0,
+                        detected_messages: 0,
+                        detected: false,
+                        confidence: 0.0,
+                        score: 0.0,
+                        empathy_score: 0.0,
+                        pattern_counts: BTreeMap::new(),
+                        risk_profile: RiskProfile::default(),
+                    },
+                    Vec::new(),
+                    Vec::new(),
+                    Vec::new(),
+                ));
+                speakers.len() - 1
+            }
+        };
+        let (speaker, speaker_matches, speaker_positive, message_scores) = &mut speakers[slot];
+        speaker.message_count += 1;
+        speaker.word_count += words;
+        if result.detected {
+            speaker.detected_messages += 1;
+        }
+        message_scores.push(result.score);
+        for m in &matches {
+            *speaker.pattern_counts.entry(m.pattern_type.clone()).or_insert(0) += 1;
+        }
+        speaker_matches.extend(matches);
+        speaker_positive.extend(match_positive(&message.text));
+
+        messages.push(MessageAnalysis {
+            index,
+            timestamp: message.timestamp,
+            speaker: message.speaker,
+            text: message.text,
+            position: message.position,
+            result,
+        });
+    }
+
+    let speakers = speakers
+        .into_iter()
+        .map(|(mut speaker, matches, positive, message_scores)| {
+            speaker.score = calculate_text_score(&matches);
+            speaker.risk_profile =
+                risk_profile(matches.iter().map(|m| (m.pattern_type.as_str(), m.severity.as_str())), &message_scores);
+            speaker.empathy_score = empathy_score(&positive);
+            speaker.detected = speaker.score > DETECTION_THRESHOLD;
+            speaker.confidence = calculate_confidence(&matches, speaker.word_count);
+            speaker
+        })
+        .collect();
+
+    let utterances: Vec<Utterance> = messages
+        .iter()
+        .zip(&times)
+        .map(|(message, &time)| Utterance {
+            speaker: &message.speaker,
+            text: &message.text,
+            words: tokenize(&message.text).len(),
+            start_ms: time,
+            end_ms: time,
+        })
+        .collect();
+    let turn_taking = turn_statistics(&utterances);
+    let events = message_bursts(&utterances)
+        .into_iter()
+        .map(|(speaker, message_indices)| {
+            let (first, last) = (message_indices[0], message_indices[message_indices.len() - 1]);
+            ConversationEvent {
+                kind: "burst".to_string(),
+                speaker: speaker.to_string(),
+                start_timestamp: messages[first].timestamp.clone(),
+                end_timestamp: messages[last].timestamp.clone(),
+                duration_ms: times[last].zip(times[first]).map(|(end, start)| end - start).unwrap_or(0),
+                message_indices,
+            }
+        })
+        .collect();
+
+    let flagged: Vec<(Option<i64>, bool)> = times.iter().zip(&messages).map(|(&time, m)| (time, m.result.detected)).collect();
+    let incident_timing = incident_timing(&flagged);
+
+    ChatAnalysis { messages, speakers, turn_taking, events, incident_timing }
+}
+
+fn mean(scores: &[f64]) -> f64 {
+    if scores.is_empty() {
+        0.0
+    } else {
+        scores.iter().sum::<f64>() / scores.len() as f64
+    }
+}
+
+/// Risk profile from the (pattern type, severity) of each match and per-message scores in
+/// conversation order; with an odd number of messages the middle one counts towards both halves
+pub fn risk_profile<'a>(matches: impl IntoIterator<Item = (&'a str, &'a str)>, message_scores: &[f64]) -> RiskProfile {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut severity_counts = BTreeMap::new();
+    for (pattern_type, severity) in matches {
+        *counts.entry(pattern_type).or_insert(0) += 1;
+        *severity_counts.entry(severity.to_string()).or_insert(0) += 1;
+    }
+    let mut categories: Vec<(&str, usize)> = counts.into_iter().collect();
+    categories.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+
+    let half = message_scores.len() / 2;
+    let early_score = mean(&message_scores[..message_scores.len() - half]);
+    let late_score = mean(&message_scores[half..]);
+    let trend = if message_scores.len() < 2 {
+        "stable"
+    } else if late_score - early_score > TREND_DELTA {
+        "rising"
+    } else if early_score - late_score > TREND_DELTA {
+        "falling"
+    } else {
+        "stable"
+    };
+
+    RiskProfile {
+        dominant_categories: categories.into_iter().take(DOMINANT_CATEGORIES).map(|(t, _)| t.to_string()).collect(),
+        severity_counts,
+        early_score,
+        late_score,
+        trend: trend.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_bracketed_and_whatsapp_lines() {
+        let source = "[10:42] Alice: hi\n\
+                      12/31/23, 10:43 PM - Bob: It's all your fault\n\
+                      and you know it\n\
+                      12/31/23, 10:44 PM - Carol joined\n\
+                      stray line";
+        let messages = parse_chat(source);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].speaker, "Alice");
+        assert_eq!(messages[0].timestamp.as_deref(), Some("10:42"));
+        assert_eq!(messages[1].speaker, "Bob");
+        assert_eq!(messages[1].text, "It's all your fault\nand you know it");
+        assert_eq!(&source[messages[1].position..messages[1].position + 4], "It's");
+    }
+
+    #[test]
+    fn test_per_speaker_analysis() {
+        let source = "[10:42] Alice: You're so selfish\n[10:43] Bob: Sorry, that sounds hard\n[10:44] Alice: It's all your fault";
+        let analysis = analyze_chat(source);
+        assert_eq!(analysis.messages.len(), 3);
+        assert_eq!(analysis.speakers[0].speaker, "Alice");
+        assert_eq!(analysis.speakers[0].message_count, 2);
+        assert!(analysis.speakers[0].detected);
+        assert!(!analysis.speakers[1].detected);
+        assert!(analysis.speakers[1].empathy_score > 0.0);
+        assert_eq!(analysis.speakers[0].empathy_score, 0.0);
+
+        let pattern = &analysis.messages[2].result.patterns[0];
+        assert_eq!(&source[pattern.position..pattern.position + pattern.match_text.len()], pattern.match_text);
+    }
+
+    #[test]
+    fn test_risk_profile_shows_escalating_speaker() {
+        let source = "[10:40] Alice: Dinner at 7?\n\
+                      [10:41] Bob: Sure\n\
+                      [10:42] Alice: You're so selfish\n\
+                      [10:43] Bob: Sorry\n\
+                      [10:44] Alice: It's all your fault, you idiot";
+        let analysis = analyze_chat(source);
+        let alice = &analysis.speakers[0].risk_profile;
+        assert_eq!(alice.trend, "rising");
+        assert!(alice.late_score > alice.early_score);
+        assert!(!alice.dominant_categories.is_empty());
+        assert!(alice.severity_counts.values().sum::<usize>() >= 2);
+        let bob = &analysis.speakers[1].risk_profile;
+        assert_eq!(bob.trend, "stable");
+        assert!(bob.dominant_categories.is_empty());
+    }
+
+    #[test]
+    fn test_timestamps_and_turn_taking() {
+        assert_eq!(timestamp_ms("10:42"), Some((10 * 60 + 42) * 60_000));
+        assert_eq!(timestamp_ms("12/31/23, 10:43 PM"), timestamp_ms("31/12/2023, 22:43:00"));
+        assert_eq!(timestamp_ms("2023-12-31 22:43"), timestamp_ms("31.12.2023, 22:43"));
+        assert_eq!(timestamp_ms("01/01/1970, 00:00:01"), Some(1000));
+        assert_eq!(timestamp_ms("2023-12-31T22:43:00.250Z"), timestamp_ms("2023-12-31 22:43"));
+        assert_eq!(timestamp_ms("yesterday"), None);
+
+        let source = "[23:59] Alice: Where are you\n[00:01] Bob: On my way\n[00:02] Alice: Hurry up";
+        let analysis = analyze_chat(source);
+        assert_eq!(analysis.turn_taking[1].median_response_ms, Some(120_000));
+        assert_eq!(analysis.turn_taking[0].turns, 2);
+    }
+
+    #[test]
+    fn test_burst_event_with_time_range() {
+        let source = "[21:00] Bob: Out with friends\n\
+                      [21:01] Alice: Where are you\n[21:01] Alice: Answer me\n[21:02] Alice: Now\n\
+                      [21:02] Alice: I know you see this\n[21:02] Alice: ANSWER\n[21:30] Bob: Sorry, phone died";
+        let events = analyze_chat(source).events;
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].kind.as_str(), events[0].speaker.as_str()), ("burst", "Alice"));
+        assert_eq!(events[0].message_indices, vec![1, 2, 3, 4, 5]);
+        assert_eq!(events[0].start_timestamp.as_deref(), Some("21:01"));
+        assert_eq!(events[0].end_timestamp.as_deref(), Some("21:02"));
+        assert_eq!(events[0].duration_ms, 60_000);
+    }
+
+    #[test]
+    fn test_incident_timing_from_message_times() {
+        let source = "[12/30/23, 11:30 PM] Alice: You're so selfish\n[12/31/23, 9:00 AM] Alice: Morning\n\
+                      [12/31/23, 11:45 PM] Alice: It's all your fault";
+        let timing = analyze_chat(source).incident_timing;
+        assert_eq!(timing.incidents, 2);
+        assert_eq!(timing.hour_counts[23], 2);
+        assert_eq!(timing.late_night_share, 1.0);
+        assert_eq!(timing.intervals_ms, vec![(24 * 60 + 15) * 60_000]);
+    }
+}
@@ -0,0 +1,197 @@
+//! Runtime coded-language lexicon
+//! Dog-whistle and slur vocabulary shifts faster than crate releases, so hosts can load an
+//! updated term list at runtime; terms carry their own severity and optional required context
+
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+
+use super::memory::StoreUsage;
+use super::offsets::sentence_bounds;
+use super::pattern_matching::PatternMatch;
+
+/// Pattern type recorded on lexicon matches unless a term sets its own
+const DEFAULT_PATTERN_TYPE: &str = "dog_whistling";
+
+/// One coded term as supplied by the host
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CodedTermDefinition {
+    term: String,
+    #[serde(default = "default_severity")]
+    severity: String,
+    #[serde(default = "default_weight")]
+    weight: f64,
+    #[serde(default)]
+    pattern_type: Option<String>,
+    /// Words of which at least one must appear in the same sentence; empty means always match
+    #[serde(default)]
+    context: Vec<String>,
+}
+
+fn default_severity() -> String {
+    "medium".to_string()
+}
+
+fn default_weight() -> f64 {
+    0.8
+}
+
+/// Lexicon document as supplied by the host
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LexiconDefinition {
+    #[serde(default)]
+    version: Option<String>,
+    terms: Vec<CodedTermDefinition>,
+}
+
+/// Compiled coded term
+#[derive(Debug)]
+struct CodedTerm {
+    id: String,
+    regex: Regex,
+    pattern_type: String,
+    severity: String,
+    weight: f64,
+    context: Vec<Regex>,
+    source_bytes: usize,
+}
+
+/// Currently loaded lexicon
+#[derive(Debug, Default)]
+struct CodedLexicon {
+    version: Option<String>,
+    terms: Vec<CodedTerm>,
+}
+
+lazy_static! {
+    static ref CODED_LEXICON: RwLock<CodedLexicon> = RwLock::new(CodedLexicon::default());
+}
+
+/// Case-insensitive whole-word regex for a term; inner whitespace matches any whitespace run
+fn word_regex(term: &str) -> Result<Regex, String> {
+    let words: Vec<String> = term.split_whitespace().map(regex::escape).collect();
+    if words.is_empty() {
+        return Err("empty term".to_string());
+    }
+    Regex::new(&format!(r"(?i)\b{}\b", words.join(r"\s+"))).map_err(|e| e.to_string())
+}
+
+fn compile_term(definition: CodedTermDefinition) -> Result<CodedTerm, String> {
+    let regex = word_regex(&definition.term).map_err(|e| format!("term '{}': {}", definition.term, e))?;
+    let context = definition.context.iter().map(|word| word_regex(word)).collect::<Result<Vec<_>, _>>()?;
+    let pattern_type = definition.pattern_type.unwrap_or_else(|| DEFAULT_PATTERN_TYPE.to_string());
+    let slug = definition.term.to_lowercase().split_whitespace().collect::<Vec<_>>().join("_");
+    Ok(CodedTerm {
+        id: format!("{}.lexicon.{}", pattern_type, slug),
+        source_bytes: definition.term.len() + definition.context.iter().map(String::len).sum::<usize>(),
+        regex,
+        pattern_type,
+        severity: definition.severity,
+        weight: definition.weight.clamp(0.0, 1.0),
+        context,
+    })
+}
+
+/// Replace the runtime lexicon from JSON `{version?, terms: [{term, severity?, weight?, patternType?, context?}]}`;
+/// returns the number of terms loaded. The old lexicon stays in place if any term is invalid
+pub fn load_coded_lexicon(json: &str) -> Result<usize, String> {
+    let definition: LexiconDefinition = serde_json::from_str(json).map_err(|e| format!("invalid lexicon: {}", e))?;
+    let terms = definition.terms.into_iter().map(compile_term).collect::<Result<Vec<_>, _>>()?;
+    let count = terms.len();
+
+    let mut lexicon = CODED_LEXICON.write().map_err(|_| "lexicon lock poisoned".to_string())?;
+    *lexicon = CodedLexicon { version: definition.version, terms };
+    Ok(count)
+}
+
+/// Remove all runtime terms
+pub fn clear_coded_lexicon() {
+    if let Ok(mut lexicon) = CODED_LEXICON.write() {
+        *lexicon = CodedLexicon::default();
+    }
+}
+
+/// Version string of the loaded lexicon, if the host supplied one
+pub fn coded_lexicon_version() -> Option<String> {
+    CODED_LEXICON.read().ok().and_then(|lexicon| lexicon.version.clone())
+}
+
+/// Matches of the runtime terms, honouring each term's required context
+pub fn match_coded_terms(text: &str) -> Vec<PatternMatch> {
+    let Ok(lexicon) = CODED_LEXICON.read() else {
+        return Vec::new();
+    };
+
+    let mut matches = Vec::new();
+    for term in &lexicon.terms {
+        for m in term.regex.find_iter(text) {
+            if !term.context.is_empty() {
+                let (start, end) = sentence_bounds(text, m.start());
+                if !term.context.iter().any(|context| context.is_match(&text[start..end])) {
+                    continue;
+                }
+            }
+            matches.push(PatternMatch {
+                pattern_id: term.id.clone(),
+                pattern_type: term.pattern_type.clone(),
+                match_text: m.as_str().to_string(),
+                position: m.start(),
+                severity: term.severity.clone(),
+                weight: term.weight,
+            });
+        }
+    }
+    matches
+}
+
+/// Report the size of the runtime lexicon
+pub fn coded_lexicon_usage() -> StoreUsage {
+    let (entries, approx_bytes) = CODED_LEXICON
+        .read()
+        .map(|lexicon| {
+            let bytes = lexicon.terms.iter().map(|t| std::mem::size_of::<CodedTerm>() + t.source_bytes).sum();
+            (lexicon.terms.len(), bytes)
+        })
+        .unwrap_or((0, 0));
+
+    StoreUsage { name: "coded_lexicon".to_string(), entries, approx_bytes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_lexicon_is_rejected() {
+        assert!(load_coded_lexicon("not json").is_err());
+        assert!(load_coded_lexicon(r#"{"terms":[{"term":"   "}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_terms_match_with_required_context() {
+        // Terms are made up so concurrently running tests can't be affected by the shared lexicon
+        let loaded = load_coded_lexicon(
+            r#"{"version":"test","terms":[
+                {"term":"zorbly folk","severity":"high","weight":0.9},
+                {"term":"quibbit","context":["those","them"]}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(loaded, 2);
+        assert_eq!(coded_lexicon_version().as_deref(), Some("test"));
+
+        let text = "Zorbly  folk again. A quibbit hopped by. Those quibbit types.";
+        let matches = match_coded_terms(text);
+        let found: Vec<(&str, &str)> = matches.iter().map(|m| (m.match_text.as_str(), m.severity.as_str())).collect();
+        assert_eq!(found, vec![("Zorbly  folk", "high"), ("quibbit", "medium")]);
+        assert_eq!(matches[1].position, text.find("Those quibbit").unwrap() + 6);
+        assert_eq!(matches[0].pattern_id, "dog_whistling.lexicon.zorbly_folk");
+
+        clear_coded_lexicon();
+        assert!(match_coded_terms("zorbly folk").is_empty());
+    }
+}
@@ -0,0 +1,356 @@
+//! Known-contacts hinting
+//! Biases entity extraction with the user's existing contacts: known people are extracted even
+//! without a relationship anchor ("Dave called"), each entity is matched to the contact it most
+//! likely refers to (shared first names narrowed by relationship, kin terms by relationship
+//! alone), and named people matching no contact are flagged as likely new. Names match with a
+//! score, tolerating diminutives (Kate/Katherine) and small spelling differences (Jon/John)
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::entity_extraction::{
+    attribute_context, detect_sentiment, entity_id, extract_entities, is_common_word, is_proper_name,
+    is_sentence_initial, overall_confidence, EntityConfig, EntityExtractionResult, ExtractedEntity,
+};
+use super::offsets::context_bounds;
+
+/// Confidence of entities matched to, or found by, a known contact
+const KNOWN_CONTACT_CONFIDENCE: f64 = 0.9;
+
+/// Lowest name score that still counts as a match
+const MIN_MATCH_SCORE: f64 = 0.6;
+
+/// Score of kin terms ("mom") matched by relationship alone
+const RELATIONSHIP_MATCH_SCORE: f64 = 0.8;
+
+/// Formal given names with their common short forms
+const DIMINUTIVES: &[&[&str]] = &[
+    &["alexander", "alex", "xander"],
+    &["andrew", "andy", "drew"],
+    &["anthony", "tony"],
+    &["benjamin", "ben", "benji"],
+    &["catherine", "katherine", "kate", "katie", "kathy", "cathy", "kat"],
+    &["christopher", "chris"],
+    &["daniel", "dan", "danny"],
+    &["david", "dave", "davey"],
+    &["elizabeth", "liz", "lizzie", "beth", "betty", "eliza"],
+    &["james", "jim", "jimmy", "jamie"],
+    &["jennifer", "jen", "jenny"],
+    &["jessica", "jess", "jessie"],
+    &["john", "jon", "johnny", "jack"],
+    &["jonathan", "jon", "jonny"],
+    &["joseph", "joe", "joey"],
+    &["joshua", "josh"],
+    &["margaret", "maggie", "meg", "peggy"],
+    &["matthew", "matt"],
+    &["michael", "mike", "mikey", "mick"],
+    &["nicholas", "nick", "nicky"],
+    &["patricia", "pat", "patty", "trish"],
+    &["rebecca", "becky", "becca"],
+    &["richard", "rick", "ricky", "rich"],
+    &["robert", "rob", "bob", "bobby", "robbie"],
+    &["samantha", "sam", "sammy"],
+    &["samuel", "sam", "sammy"],
+    &["stephanie", "steph"],
+    &["steven", "stephen", "steve"],
+    &["susan", "sue", "susie"],
+    &["thomas", "tom", "tommy"],
+    &["william", "will", "bill", "billy", "liam"],
+];
+
+/// One of the user's existing contacts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownContact {
+    /// Host record id, returned as `contactId` on matched entities
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub nicknames: Vec<String>,
+    /// Relationship type as used in `relationshipHint` ("mother", "boss", ...)
+    #[serde(default)]
+    pub relationship: Option<String>,
+}
+
+impl KnownContact {
+    /// Names the contact may be mentioned by: full name, first name and nicknames
+    fn aliases(&self) -> Vec<&str> {
+        let mut aliases = vec![self.name.trim()];
+        aliases.extend(self.name.split_whitespace().next());
+        aliases.extend(self.nicknames.iter().map(|n| n.trim()));
+        aliases.retain(|alias| !alias.is_empty());
+        aliases.dedup();
+        aliases
+    }
+}
+
+/// Levenshtein distance between two words
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Other forms of a lowercase given name ("kate" -> "catherine", "katie", ...)
+fn diminutives_of(word: &str) -> impl Iterator<Item = &'static str> + '_ {
+    DIMINUTIVES
+        .iter()
+        .filter(move |group| group.contains(&word))
+        .flat_map(|group| group.iter().copied())
+        .filter(move |w| *w != word)
+}
+
+/// Similarity of two lowercase words: 1 when equal, 0.9 for diminutives, 0.8 for one typo
+/// (words of 4+ letters) and 0.6 for two (7+ letters)
+fn word_score(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    if diminutives_of(a).any(|w| w == b) {
+        return 0.9;
+    }
+    let shorter = a.chars().count().min(b.chars().count());
+    match edit_distance(a, b) {
+        1 if shorter >= 4 => 0.8,
+        2 if shorter >= 7 => 0.6,
+        _ => 0.0,
+    }
+}
+
+/// Similarity of a mentioned name and a contact alias, word by word (0 when word counts differ)
+pub fn name_score(name: &str, alias: &str) -> f64 {
+    let words = |s: &str| s.split_whitespace().map(str::to_lowercase).collect::<Vec<_>>();
+    let (name, alias) = (words(name), words(alias));
+    if name.is_empty() || name.len() != alias.len() {
+        return 0.0;
+    }
+    name.iter().zip(&alias).map(|(a, b)| word_score(a, b)).fold(1.0, f64::min)
+}
+
+/// Outcome of matching one entity against the contact list
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContactMatch<'a> {
+    /// The contact and the match score, 0.6 - 1
+    Known(&'a KnownContact, f64),
+    /// Several contacts fit equally well
+    Ambiguous,
+    Unknown,
+}
+
+/// Contact an entity refers to. Proper names are scored against full names, first names and
+/// nicknames and the best-scoring contacts kept, with several narrowed by the entity's
+/// relationship; kin terms ("mom") match the one contact with that relationship
+pub fn match_contact<'a>(entity: &ExtractedEntity, contacts: &'a [KnownContact]) -> ContactMatch<'a> {
+    if entity.group_type.is_some() {
+        return ContactMatch::Unknown;
+    }
+    let same_relationship =
+        |c: &&KnownContact| entity.relationship_hint.is_some() && c.relationship == entity.relationship_hint;
+
+    let scored: Vec<(&KnownContact, f64)> = contacts
+        .iter()
+        .map(|c| (c, c.aliases().iter().map(|alias| name_score(&entity.name, alias)).fold(0.0, f64::max)))
+        .filter(|&(_, score)| score >= MIN_MATCH_SCORE)
+        .collect();
+    let best = scored.iter().map(|&(_, score)| score).fold(0.0, f64::max);
+    let by_name: Vec<&KnownContact> = scored.into_iter().filter(|&(_, score)| score == best).map(|(c, _)| c).collect();
+
+    let (candidates, score, shared_name): (Vec<&KnownContact>, f64, bool) = match by_name.as_slice() {
+        [] if is_proper_name(&entity.name) => return ContactMatch::Unknown,
+        [] => (contacts.iter().filter(same_relationship).collect(), RELATIONSHIP_MATCH_SCORE, false),
+        [contact] => return ContactMatch::Known(contact, best),
+        _ => (by_name.into_iter().filter(same_relationship).collect(), best, true),
+    };
+
+    match candidates.as_slice() {
+        [contact] => ContactMatch::Known(contact, score),
+        [] if !shared_name => ContactMatch::Unknown,
+        _ => ContactMatch::Ambiguous,
+    }
+}
+
+/// Case-insensitive whole-word regex for an alias
+fn alias_regex(alias: &str) -> Option<Regex> {
+    let words: Vec<String> = alias.split_whitespace().map(regex::escape).collect();
+    Regex::new(&format!(r"(?i)\b{}\b", words.join(r"\s+"))).ok()
+}
+
+/// First mention of a contact in the text by an alias or a diminutive of a one-word alias, with
+/// its score. Aliases given capitalized must appear capitalized, and not only as a
+/// sentence-initial common word ("Will you ...")
+fn find_mention<'t>(text: &'t str, contact: &KnownContact) -> Option<(regex::Match<'t>, f64)> {
+    let mut forms: Vec<(String, bool, f64)> = Vec::new();
+    for alias in contact.aliases() {
+        let capitalized = alias.chars().next().is_some_and(char::is_uppercase);
+        forms.push((alias.to_string(), capitalized, 1.0));
+        if !alias.contains(char::is_whitespace) {
+            let lower = alias.to_lowercase();
+            forms.extend(diminutives_of(&lower).map(|form| (form.to_string(), capitalized, 0.9)));
+        }
+    }
+
+    forms
+        .into_iter()
+        .filter_map(|(form, capitalized, score)| {
+            let mention = alias_regex(&form)?.find_iter(text).find(|m| {
+                !capitalized
+                    || (m.as_str().chars().next().is_some_and(char::is_uppercase)
+                        && !(is_sentence_initial(text, m.start()) && is_common_word(m.as_str())))
+            })?;
+            Some((mention, score))
+        })
+        .min_by(|a, b| a.0.start().cmp(&b.0.start()).then(b.1.total_cmp(&a.1)))
+}
+
+/// Extract entities, biased by the user's known contacts
+pub fn extract_entities_with_contacts(text: &str, contacts: &[KnownContact]) -> EntityExtractionResult {
+    let start = std::time::Instant::now();
+    let mut result = extract_entities(text);
+
+    let mut matched: Vec<&KnownContact> = Vec::new();
+    for entity in result.entities.iter_mut() {
+        match match_contact(entity, contacts) {
+            ContactMatch::Known(contact, score) => {
+                entity.known_contact = Some(contact.name.clone());
+                entity.contact_id = contact.id.clone();
+                entity.contact_match_score = Some(score);
+                if !entity.name.eq_ignore_ascii_case(&contact.name) {
+                    entity.aliases.insert(0, entity.name.clone());
+                }
+                entity.canonical_name = contact.name.clone();
+                entity.name_confidence = entity.name_confidence.max(KNOWN_CONTACT_CONFIDENCE * score);
+                if entity.relationship_hint.is_some() && entity.relationship_hint == contact.relationship {
+                    entity.relationship_confidence = Some(KNOWN_CONTACT_CONFIDENCE);
+                }
+                entity.confidence = overall_confidence(entity.name_confidence, entity.relationship_confidence);
+                matched.push(contact);
+            }
+            ContactMatch::Ambiguous => {}
+            ContactMatch::Unknown => {
+                if entity.group_type.is_none() && is_proper_name(&entity.name) {
+                    result.likely_new.push(entity.id.clone());
+                }
+            }
+        }
+    }
+
+    // Known people mentioned without a relationship anchor
+    for contact in contacts {
+        if matched.iter().any(|m| std::ptr::eq(*m, contact)) {
+            continue;
+        }
+        let Some((mention, score)) = find_mention(text, contact) else {
+            continue;
+        };
+        if result.entities.iter().any(|e| e.name.eq_ignore_ascii_case(mention.as_str())) {
+            continue;
+        }
+        let (context_start, context_end) = context_bounds(text, mention.start(), mention.end(), 50);
+        let context = &text[context_start..context_end];
+        result.entities.push(ExtractedEntity {
+            id: entity_id(mention.as_str(), mention.start()),
+            name: mention.as_str().to_string(),
+            relationship_hint: contact.relationship.clone(),
+            relationship_context: String::new(),
+            pronouns: None,
+            pronoun_evidence: None,
+            mention_context: context.trim().to_string(),
+            sentiment: detect_sentiment(context),
+            confidence: KNOWN_CONTACT_CONFIDENCE * score,
+            name_confidence: KNOWN_CONTACT_CONFIDENCE * score,
+            relationship_confidence: contact.relationship.as_ref().map(|_| KNOWN_CONTACT_CONFIDENCE),
+            position: mention.start(),
+            group_type: None,
+            known_contact: Some(contact.name.clone()),
+            contact_id: contact.id.clone(),
+            contact_match_score: Some(score),
+            canonical_name: contact.name.clone(),
+            aliases: if mention.as_str() == contact.name { Vec::new() } else { vec![mention.as_str().to_string()] },
+            members: Vec::new(),
+        });
+        matched.push(contact);
+    }
+
+    result.entities.sort_by(|a, b| a.position.cmp(&b.position).then_with(|| a.id.cmp(&b.id)));
+    attribute_context(text, &mut result.entities, &EntityConfig::default());
+    result.relationship_count = result.entities.iter().filter(|e| e.relationship_hint.is_some()).count();
+    result.processing_time_us += start.elapsed().as_micros() as u64;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(name: &str, relationship: Option<&str>) -> KnownContact {
+        KnownContact {
+            id: Some(name.to_lowercase().replace(' ', "-")),
+            name: name.to_string(),
+            nicknames: Vec::new(),
+            relationship: relationship.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_known_people_are_found_and_matched() {
+        let contacts = [
+            contact("Dave Miller", Some("friend")),
+            contact("Sarah Jones", Some("sister")),
+            contact("Sarah Lee", Some("colleague")),
+            contact("Linda", Some("mother")),
+        ];
+        let text = "Dave called. Sarah, my sister, was upset. My mom and my boss Kevin too.";
+        let result = extract_entities_with_contacts(text, &contacts);
+        let known = |name: &str| result.entities.iter().find(|e| e.name == name).and_then(|e| e.known_contact.clone());
+
+        assert_eq!(known("Dave").as_deref(), Some("Dave Miller"));
+        assert_eq!(known("Sarah").as_deref(), Some("Sarah Jones"));
+        assert_eq!(known("mom").as_deref(), Some("Linda"));
+        let dave = result.entities.iter().find(|e| e.name == "Dave").unwrap();
+        assert_eq!(dave.contact_id.as_deref(), Some("dave-miller"));
+        assert_eq!((dave.canonical_name.as_str(), dave.aliases.as_slice()), ("Dave Miller", &["Dave".to_string()][..]));
+        let kevin = result.entities.iter().find(|e| e.name == "Kevin").unwrap();
+        assert_eq!(result.likely_new, vec![kevin.id.clone()]);
+        assert!(extract_entities(text).likely_new.is_empty());
+
+        // The context window can't split a multi-byte character
+        let accented = extract_entities_with_contacts("ééééééééééééééééééééééééééééé s Linda came over", &[contact("Linda", None)]);
+        assert_eq!(accented.entities[0].known_contact.as_deref(), Some("Linda"));
+    }
+
+    #[test]
+    fn test_fuzzy_names_match_with_a_score() {
+        assert_eq!(name_score("Jon", "John"), 0.9);
+        assert_eq!(name_score("Katie", "Katherine"), 0.9);
+        assert_eq!(name_score("Margerite", "Marguerite"), 0.8);
+        assert_eq!(name_score("Jan", "Jon"), 0.0);
+        assert_eq!(name_score("Sarah Jones", "Sarah"), 0.0);
+
+        let contacts = [contact("John Smith", Some("friend")), contact("Katherine", Some("sister")), contact("David", None)];
+        let result = extract_entities_with_contacts("Jon, my friend, came by. Katie, my sister, and Dave too.", &contacts);
+        let score = |name: &str| result.entities.iter().find(|e| e.name == name).and_then(|e| e.contact_match_score);
+        assert_eq!(score("Jon"), Some(0.9));
+        assert_eq!(score("Katie"), Some(0.9));
+        assert_eq!(score("Dave"), Some(0.9));
+    }
+
+    #[test]
+    fn test_shared_names_stay_ambiguous() {
+        let contacts = [contact("Sam Green", Some("friend")), contact("Sam Brown", Some("friend")), contact("Will", None)];
+        let result = extract_entities_with_contacts("Will you call? Sam, my friend, visited.", &contacts);
+        let sam = result.entities.iter().find(|e| e.name == "Sam").unwrap();
+        assert_eq!(match_contact(sam, &contacts), ContactMatch::Ambiguous);
+        assert!(sam.known_contact.is_none() && result.likely_new.is_empty());
+        assert!(!result.entities.iter().any(|e| e.name == "Will"));
+    }
+}
@@ -0,0 +1,239 @@
+//! Dialect-aware normalization
+//! The built-in patterns are keyed on standard written English, so vernacular grammar
+//! (negative concord, habitual "be", "crazy good") and reclaimed in-group terms get flagged
+//! where the standard-English equivalent wouldn't be. Vernacular constructions are rewritten
+//! into their standard equivalents, and a match only counts if it survives the rewrite
+
+use super::locale::Locale;
+use super::offsets::{sentence_bounds, MappedText};
+use super::pattern_matching::{match_patterns_for_locale, PatternMatch};
+use super::pos::{tag, Tag, TaggedToken};
+
+// Sorted, looked up with binary_search
+const NEGATORS: &[&str] = &[
+    "ain't", "aren't", "can't", "couldn't", "didn't", "doesn't", "don't", "isn't", "never", "wasn't", "weren't",
+    "won't", "wouldn't",
+];
+/// Intensifiers that read as insults out of context ("crazy good", "mad tired")
+const INTENSIFIERS: &[&str] = &["crazy", "hella", "mad", "wicked"];
+/// Terms commonly reclaimed by the groups they were once aimed at
+const RECLAIMED_TERMS: &[&str] = &["animal", "animals", "savage", "savages", "thug", "thugs"];
+
+/// Standard-English form of a negative indefinite under a preceding negator
+fn positive_indefinite(word: &str) -> Option<&'static str> {
+    match word {
+        "nobody" => Some("anybody"),
+        "nothing" => Some("anything"),
+        "nowhere" => Some("anywhere"),
+        "none" => Some("any"),
+        _ => None,
+    }
+}
+
+/// One vernacular construction and its standard-English replacement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rewrite {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: &'static str,
+    /// "negative_concord", "habitual_be" or "intensifier"
+    pub rule: &'static str,
+}
+
+fn contains(list: &[&str], word: &str) -> bool {
+    list.binary_search(&word).is_ok()
+}
+
+fn normalized_word(token: &TaggedToken) -> String {
+    token.token.lower().replace('\u{2019}', "'")
+}
+
+/// Vernacular constructions in the text, in order
+pub fn vernacular_rewrites(text: &str) -> Vec<Rewrite> {
+    let tokens = tag(text);
+    let words: Vec<String> = tokens.iter().map(normalized_word).collect();
+    let mut rewrites = Vec::new();
+    let mut clause_start = 0;
+    let mut negated = false;
+
+    for (i, token) in tokens.iter().enumerate() {
+        let gap = if i == 0 { "" } else { &text[tokens[i - 1].token.end..token.token.start] };
+        if i == 0 || gap.contains(['.', '!', '?', '\n', ',', ';', ':']) {
+            clause_start = i;
+            negated = false;
+        }
+        let word = words[i].as_str();
+        let next = tokens.get(i + 1).filter(|next| text[token.token.end..next.token.start].trim().is_empty());
+
+        // Negative concord: "didn't do nothing" is "didn't do anything"; a clause-initial negator
+        // ("Ain't nobody ...") is negative inversion and means "nobody", so it isn't rewritten
+        if negated {
+            if let Some(replacement) = positive_indefinite(word) {
+                rewrites.push(Rewrite { start: token.token.start, end: token.token.end, replacement, rule: "negative_concord" });
+            } else if word == "no" && next.is_some_and(|next| next.token.lower() == "one") {
+                let end = tokens[i + 1].token.end;
+                rewrites.push(Rewrite { start: token.token.start, end, replacement: "anyone", rule: "negative_concord" });
+            }
+        }
+        if contains(NEGATORS, word) && i > clause_start {
+            negated = true;
+        }
+
+        // Habitual "be" after a subject pronoun: "they be hating" is "they are hating"
+        if word == "be" && i > clause_start && next.is_some_and(|next| matches!(next.tag, Tag::Verb | Tag::Adjective | Tag::Adverb)) {
+            let replacement = match words[i - 1].as_str() {
+                "i" => Some("am"),
+                "he" | "she" | "it" => Some("is"),
+                "you" | "we" | "they" | "y'all" => Some("are"),
+                _ => None,
+            };
+            if let Some(replacement) = replacement {
+                rewrites.push(Rewrite { start: token.token.start, end: token.token.end, replacement, rule: "habitual_be" });
+            }
+        }
+
+        // Intensifier before an adjective: "crazy good" is "very good"
+        if contains(INTENSIFIERS, word) && next.is_some_and(|next| next.tag == Tag::Adjective) {
+            rewrites.push(Rewrite { start: token.token.start, end: token.token.end, replacement: "very", rule: "intensifier" });
+        }
+    }
+
+    rewrites
+}
+
+/// Rewrite vernacular constructions into standard English, keeping a map back to the source
+pub fn normalize_vernacular(text: &str) -> MappedText {
+    let mut mapped = MappedText::default();
+    let mut copied = 0;
+    for rewrite in vernacular_rewrites(text) {
+        mapped.push_source(text, copied, rewrite.start);
+        mapped.push_replacement(rewrite.replacement, rewrite.start, rewrite.end);
+        copied = rewrite.end;
+    }
+    mapped.push_source(text, copied, text.len());
+    mapped
+}
+
+/// Whether a match is a reclaimed term the speaker predicates of their own group: "we're" or
+/// "I'm" ("we are", "I am"), then at most a determiner and modifiers, then the term ("we're
+/// total savages on the court"). Any other subject in between ("I think those people are
+/// animals") means the term is aimed at someone else
+fn is_reclaimed(text: &str, m: &PatternMatch) -> bool {
+    if !contains(RECLAIMED_TERMS, &m.match_text.to_lowercase()) {
+        return false;
+    }
+    let (sentence_start, _) = sentence_bounds(text, m.position);
+    let tokens = tag(&text[sentence_start..m.position]);
+    let mut words = tokens
+        .iter()
+        .rev()
+        .skip_while(|t| matches!(t.tag, Tag::Determiner | Tag::Adjective | Tag::Adverb))
+        .map(normalized_word);
+    let (copula, subject) = (words.next(), words.next());
+    matches!(
+        (copula.as_deref(), subject.as_deref()),
+        (Some("we're" | "i'm"), _) | (Some("are"), Some("we")) | (Some("am"), Some("i"))
+    )
+}
+
+/// Whether each match is an artifact of vernacular grammar (it overlaps a rewritten
+/// construction and doesn't recur in the normalized text) or a reclaimed in-group term
+pub fn vernacular_exempt(text: &str, matches: &[PatternMatch], locale: Locale) -> Vec<bool> {
+    let rewrites = vernacular_rewrites(text);
+    // Only rematch when something was rewritten; plain standard English costs nothing extra
+    let normalized = (!rewrites.is_empty()).then(|| {
+        let mapped = normalize_vernacular(text);
+        let matches = match_patterns_for_locale(&mapped.text, locale);
+        (mapped, matches)
+    });
+
+    matches
+        .iter()
+        .map(|m| {
+            let end = m.position + m.match_text.len();
+            let rewritten = rewrites.iter().any(|r| r.start < end && m.position < r.end);
+            let survives = || {
+                normalized.as_ref().is_some_and(|(mapped, renormalized)| {
+                    renormalized.iter().any(|n| {
+                        n.pattern_id == m.pattern_id
+                            && mapped.source_range(n.position, n.position + n.match_text.len()).0 == m.position
+                    })
+                })
+            };
+            (rewritten && !survives()) || is_reclaimed(text, m)
+        })
+        .collect()
+}
+
+/// Drop matches that only exist because of vernacular grammar or in-group reclaimed use
+pub fn exempt_vernacular(text: &str, matches: Vec<PatternMatch>, locale: Locale) -> Vec<PatternMatch> {
+    let exempt = vernacular_exempt(text, &matches, locale);
+    matches
+        .into_iter()
+        .zip(exempt)
+        .filter(|(_, exempt)| !exempt)
+        .map(|(m, _)| m)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::pattern_matching::match_patterns;
+
+    #[test]
+    fn test_normalizes_vernacular_constructions() {
+        let normalized = |text| normalize_vernacular(text).text;
+        assert_eq!(normalized("I didn't do nothing to no one"), "I didn't do anything to anyone");
+        assert_eq!(normalized("They be hating, he be tired"), "They are hating, he is tired");
+        assert_eq!(normalized("That show was crazy good"), "That show was very good");
+        // Negative inversion keeps its meaning ("nobody has time")
+        assert_eq!(normalized("Ain't nobody got time for that"), "Ain't nobody got time for that");
+        for list in [NEGATORS, INTENSIFIERS, RECLAIMED_TERMS] {
+            assert!(list.windows(2).all(|w| w[0] < w[1]), "{:?}", list);
+        }
+    }
+
+    #[test]
+    fn test_reduces_vernacular_false_positives() {
+        let flagged = |text: &str| {
+            let matches = match_patterns(text);
+            let kept = exempt_vernacular(text, matches.clone(), Locale::Us);
+            (matches.len(), kept.len())
+        };
+        // Vernacular sentences lose their spurious matches
+        assert_eq!(flagged("I didn't say nothing to nobody"), (1, 0));
+        assert_eq!(flagged("That movie was crazy good"), (1, 0));
+        assert_eq!(flagged("We're savages on the court fr"), (1, 0));
+        assert_eq!(flagged("I'm an animal in the gym"), (1, 0));
+        // Standard-English equivalents and genuine uses are untouched
+        assert_eq!(flagged("Nobody listens to me"), (1, 1));
+        assert_eq!(flagged("He's crazy"), (1, 1));
+        assert_eq!(flagged("They're savages"), (1, 1));
+        assert_eq!(flagged("You be always late"), (1, 1));
+    }
+
+    #[test]
+    fn test_reclaimed_only_when_said_of_the_speaker() {
+        let reclaimed = |text: &str, term: &str| {
+            let position = text.find(term).unwrap();
+            let m = PatternMatch {
+                pattern_id: "dehumanization.0".to_string(),
+                pattern_type: "dehumanization".to_string(),
+                match_text: term.to_string(),
+                position,
+                severity: "high".to_string(),
+                weight: 1.0,
+            };
+            is_reclaimed(text, &m)
+        };
+        assert!(reclaimed("We're savages on the court fr", "savages"));
+        assert!(reclaimed("Honestly we are animals on defense", "animals"));
+        assert!(reclaimed("I'm a savage at trivia", "savage"));
+        // A first-person word elsewhere in the sentence doesn't make the term self-directed
+        assert!(!reclaimed("I think those people are animals", "animals"));
+        assert!(!reclaimed("My neighbors are animals", "animals"));
+        assert!(!reclaimed("I said my neighbors are animals", "animals"));
+        assert!(!reclaimed("I swear immigrants are savages", "savages"));
+    }
+}
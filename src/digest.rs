@@ -0,0 +1,439 @@
+//! Entry and weekly digests
+//! Compact per-entry summary for rendering a journal entry card in one call (top emotions, the
+//! people mentioned with their sentiment, the leading pattern categories and a risk tier), and the
+//! aggregate of a week of entries compared with the week before
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::analytics_export::ExportEntry;
+use super::analyze_with_entities;
+use super::entity_extraction::extract_entities;
+use super::harm_report::harm_report_from;
+use super::lexicon::{Lexicon, LexiconCounts};
+use super::{TextProcessingResult, DETECTION_THRESHOLD};
+
+/// Lexicon categories reported as emotions
+const EMOTION_CATEGORIES: &[&str] = &["positive_emotion", "anxiety", "anger", "sadness"];
+/// Items kept in each top list
+const TOP_N: usize = 3;
+/// Score at or above which an entry is high risk
+const HIGH_RISK_SCORE: f64 = 0.6;
+/// Week-over-week differences in mean score and mean mood reported as changes
+const SCORE_CHANGE: f64 = 0.1;
+const MOOD_CHANGE: f64 = 0.2;
+
+/// Emotion words found in an entry
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EmotionCount {
+    pub emotion: String,
+    pub count: usize,
+}
+
+/// Person mentioned in an entry
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityDigest {
+    pub name: String,
+    pub relationship: Option<String>,
+    /// "positive", "negative" or "mixed" from the words around the mention
+    pub sentiment: Option<String>,
+    /// Pattern matches attributed to the person
+    pub matches: usize,
+}
+
+/// Pattern category with its match count
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryDigest {
+    pub pattern_type: String,
+    pub count: usize,
+}
+
+/// Compact summary of one entry
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryDigest {
+    pub word_count: usize,
+    pub score: f64,
+    /// Balance of positive and negative emotion words, -1 - 1 (0 without any)
+    pub mood: f64,
+    /// "none", "low", "moderate" or "high"
+    pub risk_tier: String,
+    /// Up to three emotions, most words first
+    pub top_emotions: Vec<EmotionCount>,
+    /// Up to three people, most attributed matches first, then in text order
+    pub top_entities: Vec<EntityDigest>,
+    /// Up to three pattern categories, most matches first
+    pub top_categories: Vec<CategoryDigest>,
+}
+
+/// Risk tier of an analysis result: no matches, matches below the detection threshold, detected,
+/// or detected at a high score
+pub fn risk_tier(result: &TextProcessingResult) -> &'static str {
+    if result.patterns.is_empty() {
+        "none"
+    } else if result.score >= HIGH_RISK_SCORE {
+        "high"
+    } else if result.score > DETECTION_THRESHOLD {
+        "moderate"
+    } else {
+        "low"
+    }
+}
+
+/// Digest a single entry, keeping the top three of each list
+pub fn digest_entry(text: &str) -> EntryDigest {
+    let mut digest = full_digest(text);
+    digest.top_emotions.truncate(TOP_N);
+    digest.top_entities.truncate(TOP_N);
+    digest.top_categories.truncate(TOP_N);
+    digest
+}
+
+/// Balance of positive and negative emotion words in lexicon counts, -1 - 1 (0 without any)
+pub fn mood(counts: &LexiconCounts) -> f64 {
+    let (mut positive, mut negative) = (0, 0);
+    for category in counts.categories.iter().filter(|c| EMOTION_CATEGORIES.contains(&c.category.as_str())) {
+        if category.category == "positive_emotion" {
+            positive += category.count;
+        } else {
+            negative += category.count;
+        }
+    }
+    let emotional = (positive + negative) as f64;
+    if emotional == 0.0 {
+        0.0
+    } else {
+        (positive as f64 - negative as f64) / emotional
+    }
+}
+
+/// Digest with every emotion, person and category
+fn full_digest(text: &str) -> EntryDigest {
+    let entities = extract_entities(text).entities;
+    let result = analyze_with_entities(text);
+    let counts = Lexicon::builtin().count(text);
+
+    let mut top_emotions: Vec<EmotionCount> = counts
+        .categories
+        .iter()
+        .filter(|c| c.count > 0 && EMOTION_CATEGORIES.contains(&c.category.as_str()))
+        .map(|c| EmotionCount { emotion: c.category.clone(), count: c.count })
+        .collect();
+    top_emotions.sort_by_key(|e| std::cmp::Reverse(e.count));
+
+    let report = harm_report_from(text, &entities, &result);
+    let mut top_entities: Vec<EntityDigest> = entities
+        .iter()
+        .map(|entity| EntityDigest {
+            name: entity.name.clone(),
+            relationship: entity.relationship_hint.clone(),
+            sentiment: entity.sentiment.clone(),
+            matches: report.people.iter().find(|p| p.entity_id == entity.id).map(|p| p.total).unwrap_or(0),
+        })
+        .collect();
+    top_entities.sort_by_key(|e| std::cmp::Reverse(e.matches));
+
+    let mut categories: BTreeMap<&str, usize> = BTreeMap::new();
+    for pattern in &result.patterns {
+        *categories.entry(pattern.pattern_type.as_str()).or_insert(0) += 1;
+    }
+    let mut top_categories: Vec<CategoryDigest> =
+        categories.into_iter().map(|(t, count)| CategoryDigest { pattern_type: t.to_string(), count }).collect();
+    top_categories.sort_by_key(|c| std::cmp::Reverse(c.count));
+
+    EntryDigest {
+        word_count: counts.word_count,
+        score: result.score,
+        mood: mood(&counts),
+        risk_tier: risk_tier(&result).to_string(),
+        top_emotions,
+        top_entities,
+        top_categories,
+    }
+}
+
+/// Score and mood of one entry in a week
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MoodPoint {
+    pub id: String,
+    pub timestamp: Option<String>,
+    pub mood: f64,
+    pub score: f64,
+    pub risk_tier: String,
+}
+
+/// A person across the week's entries, matched by name
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonWeek {
+    pub name: String,
+    pub relationship: Option<String>,
+    /// Entries mentioning the person
+    pub entries: usize,
+    pub matches: usize,
+    /// Sentiment around the person in each entry that has one, in entry order
+    pub sentiments: Vec<String>,
+    /// "improving" or "worsening" when the last sentiment differs from the first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment_shift: Option<String>,
+}
+
+/// Pattern category seen in more than one entry
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringCategory {
+    pub pattern_type: String,
+    pub entries: usize,
+    pub matches: usize,
+}
+
+/// Notable difference from the previous week
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyChange {
+    /// "score", "mood", "category" or "person"
+    pub kind: String,
+    /// Pattern type or person name for category and person changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Mean score or mood, or the number of matches / entries mentioning the person
+    pub previous: f64,
+    pub current: f64,
+}
+
+/// Aggregate digest of a week of entries
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyDigest {
+    pub entry_count: usize,
+    pub mean_score: f64,
+    pub mean_mood: f64,
+    /// One point per entry, in entry order
+    pub mood_trajectory: Vec<MoodPoint>,
+    /// Most-mentioned people first
+    pub people: Vec<PersonWeek>,
+    /// Most entries first
+    pub recurring_categories: Vec<RecurringCategory>,
+    /// Empty when no previous week was given
+    pub changes: Vec<WeeklyChange>,
+}
+
+/// Weekly digest request: this week's entries and, optionally, the previous week's
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyDigestRequest {
+    pub entries: Vec<ExportEntry>,
+    #[serde(default)]
+    pub previous_entries: Vec<ExportEntry>,
+}
+
+fn sentiment_rank(sentiment: &str) -> i32 {
+    match sentiment {
+        "positive" => 1,
+        "negative" => -1,
+        _ => 0,
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+/// Aggregate a week of entries without comparing it to another week
+fn summarize_week(entries: &[ExportEntry]) -> WeeklyDigest {
+    let digests: Vec<EntryDigest> = entries.iter().map(|e| full_digest(&e.text)).collect();
+
+    let mood_trajectory = entries
+        .iter()
+        .zip(&digests)
+        .enumerate()
+        .map(|(index, (entry, digest))| MoodPoint {
+            id: entry.id.clone().unwrap_or_else(|| index.to_string()),
+            timestamp: entry.timestamp.clone(),
+            mood: digest.mood,
+            score: digest.score,
+            risk_tier: digest.risk_tier.clone(),
+        })
+        .collect();
+
+    let mut people: Vec<PersonWeek> = Vec::new();
+    let mut categories: BTreeMap<&str, RecurringCategory> = BTreeMap::new();
+    for digest in &digests {
+        for entity in &digest.top_entities {
+            let slot = match people.iter().position(|p| p.name.eq_ignore_ascii_case(&entity.name)) {
+                Some(slot) => slot,
+                None => {
+                    people.push(PersonWeek {
+                        name: entity.name.clone(),
+                        relationship: entity.relationship.clone(),
+                        entries: 0,
+                        matches: 0,
+                        sentiments: Vec::new(),
+                        sentiment_shift: None,
+                    });
+                    people.len() - 1
+                }
+            };
+            let person = &mut people[slot];
+            person.entries += 1;
+            person.matches += entity.matches;
+            person.sentiments.extend(entity.sentiment.clone());
+        }
+        for category in &digest.top_categories {
+            let recurring = categories.entry(category.pattern_type.as_str()).or_insert_with(|| RecurringCategory {
+                pattern_type: category.pattern_type.clone(),
+                entries: 0,
+                matches: 0,
+            });
+            recurring.entries += 1;
+            recurring.matches += category.count;
+        }
+    }
+    for person in &mut people {
+        if let (Some(first), Some(last)) = (person.sentiments.first(), person.sentiments.last()) {
+            person.sentiment_shift = match sentiment_rank(last).cmp(&sentiment_rank(first)) {
+                std::cmp::Ordering::Greater => Some("improving".to_string()),
+                std::cmp::Ordering::Less => Some("worsening".to_string()),
+                std::cmp::Ordering::Equal => None,
+            };
+        }
+    }
+    people.sort_by_key(|p| std::cmp::Reverse(p.entries));
+
+    let mut recurring_categories: Vec<RecurringCategory> = categories.into_values().collect();
+    recurring_categories.sort_by_key(|c| std::cmp::Reverse(c.entries));
+
+    WeeklyDigest {
+        entry_count: entries.len(),
+        mean_score: mean(digests.iter().map(|d| d.score)),
+        mean_mood: mean(digests.iter().map(|d| d.mood)),
+        mood_trajectory,
+        people,
+        recurring_categories,
+        changes: Vec::new(),
+    }
+}
+
+/// Changes from the previous week: mean score and mood beyond a threshold, categories that
+/// appeared or disappeared, and people newly mentioned or no longer mentioned
+fn weekly_changes(previous: &WeeklyDigest, current: &WeeklyDigest) -> Vec<WeeklyChange> {
+    let change = |kind: &str, subject: Option<&str>, previous: f64, current: f64| WeeklyChange {
+        kind: kind.to_string(),
+        subject: subject.map(str::to_string),
+        previous,
+        current,
+    };
+    let mut changes = Vec::new();
+    if (current.mean_score - previous.mean_score).abs() > SCORE_CHANGE {
+        changes.push(change("score", None, previous.mean_score, current.mean_score));
+    }
+    if (current.mean_mood - previous.mean_mood).abs() > MOOD_CHANGE {
+        changes.push(change("mood", None, previous.mean_mood, current.mean_mood));
+    }
+
+    let matches = |week: &WeeklyDigest, pattern_type: &str| {
+        week.recurring_categories.iter().find(|c| c.pattern_type == pattern_type).map_or(0, |c| c.matches)
+    };
+    let mut pattern_types: Vec<&str> = previous
+        .recurring_categories
+        .iter()
+        .chain(&current.recurring_categories)
+        .map(|c| c.pattern_type.as_str())
+        .collect();
+    pattern_types.sort_unstable();
+    pattern_types.dedup();
+    for pattern_type in pattern_types {
+        let (before, after) = (matches(previous, pattern_type), matches(current, pattern_type));
+        if (before == 0) != (after == 0) {
+            changes.push(change("category", Some(pattern_type), before as f64, after as f64));
+        }
+    }
+
+    let entries = |week: &WeeklyDigest, name: &str| {
+        week.people.iter().find(|p| p.name.eq_ignore_ascii_case(name)).map_or(0, |p| p.entries)
+    };
+    for person in current.people.iter().chain(&previous.people) {
+        let (before, after) = (entries(previous, &person.name), entries(current, &person.name));
+        // A person in both weeks is unchanged, so each changed person is only visited once
+        if (before == 0) != (after == 0) {
+            changes.push(change("person", Some(&person.name), before as f64, after as f64));
+        }
+    }
+    changes
+}
+
+/// Weekly digest of this week's entries, with changes against the previous week when given
+pub fn weekly_digest(request: &WeeklyDigestRequest) -> WeeklyDigest {
+    let mut digest = summarize_week(&request.entries);
+    if !request.previous_entries.is_empty() {
+        digest.changes = weekly_changes(&summarize_week(&request.previous_entries), &digest);
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_digest() {
+        let text = "I'm so worried and sad, I cried all night. My boss Dave called me an idiot and said I'm a failure. \
+                    My sister Amy is so supportive, I love her.";
+        let digest = digest_entry(text);
+        assert_eq!(digest.top_emotions[0].emotion, "sadness");
+        assert!(digest.top_emotions.iter().any(|e| e.emotion == "anxiety"));
+        assert_eq!(digest.top_entities[0].name, "Dave");
+        assert_eq!(digest.top_entities[0].matches, 2);
+        assert_eq!(digest.top_entities[1].sentiment.as_deref(), Some("positive"));
+        assert_eq!(digest.top_categories[0].pattern_type, "insult");
+        assert_ne!(digest.risk_tier, "none");
+    }
+
+    #[test]
+    fn test_calm_entry_has_no_risk() {
+        let digest = digest_entry("Went for a long walk and made soup.");
+        assert_eq!(digest.risk_tier, "none");
+        assert!(digest.top_emotions.is_empty() && digest.top_entities.is_empty() && digest.top_categories.is_empty());
+    }
+
+    #[test]
+    fn test_weekly_digest_and_changes() {
+        let entry = |text: &str| ExportEntry { id: None, timestamp: None, text: text.to_string() };
+        let request = WeeklyDigestRequest {
+            entries: vec![
+                entry("My boss Dave is so supportive, I'm happy at work."),
+                entry("My boss Dave called me an idiot. I'm so sad."),
+                entry("My boss Dave said I'm an idiot again. I feel hurt and angry."),
+            ],
+            previous_entries: vec![entry("My sister Amy and I had a lovely, happy dinner.")],
+        };
+        let digest = weekly_digest(&request);
+        assert_eq!(digest.entry_count, 3);
+        assert_eq!(digest.mood_trajectory.len(), 3);
+        assert!(digest.mood_trajectory[0].mood > 0.0 && digest.mood_trajectory[2].mood < 0.0);
+        assert_eq!((digest.people[0].name.as_str(), digest.people[0].entries), ("Dave", 3));
+        assert_eq!(digest.people[0].sentiment_shift.as_deref(), Some("worsening"));
+        let insults = &digest.recurring_categories[0];
+        assert_eq!((insults.pattern_type.as_str(), insults.entries), ("insult", 2));
+
+        let kinds: Vec<(&str, Option<&str>)> =
+            digest.changes.iter().map(|c| (c.kind.as_str(), c.subject.as_deref())).collect();
+        assert!(kinds.contains(&("score", None)));
+        assert!(kinds.contains(&("mood", None)));
+        assert!(kinds.contains(&("category", Some("insult"))));
+        assert!(kinds.contains(&("person", Some("Amy"))));
+        assert!(kinds.contains(&("person", Some("Dave"))));
+        assert!(summarize_week(&request.entries).changes.is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+//! Domain-context detection
+//! Recognizes sentences about games and sports, where war vocabulary ("battle", "enemy",
+//! "campaign") is routine and shouldn't count as militarized framing
+
+use super::offsets::sentence_bounds;
+use super::pattern_matching::PatternMatch;
+use super::tokenize::content_words;
+
+/// Pattern types whose vocabulary is routine in game and sports talk
+const DOMAIN_SENSITIVE_TYPES: &[&str] = &["militarization"];
+
+/// Weight multiplier for a sensitive match in a game/sports sentence
+const DOMAIN_WEIGHT_FACTOR: f64 = 0.25;
+
+/// Domain terms needed in the sentence, and their minimum share of its content words
+const MIN_DOMAIN_TERMS: usize = 2;
+const MIN_DOMAIN_SHARE: f64 = 0.2;
+
+// Sorted, looked up with binary_search
+const GAMING_TERMS: &[&str] = &[
+    "boss", "clan", "co-op", "console", "controller", "dlc", "fortnite", "fps", "game", "gamer", "games", "gaming",
+    "guild", "level", "lobby", "loot", "map", "minecraft", "mmo", "multiplayer", "nintendo", "noob", "npc",
+    "playstation", "pvp", "quest", "raid", "ranked", "respawn", "rpg", "server", "squad", "steam", "stream",
+    "twitch", "xbox",
+];
+const SPORTS_TERMS: &[&str] = &[
+    "ball", "baseball", "basketball", "championship", "coach", "court", "cup", "fans", "field", "finals",
+    "football", "game", "goal", "hockey", "league", "match", "opponent", "opponents", "pitch", "player", "players",
+    "playoff", "playoffs", "quarterback", "referee", "rugby", "score", "scored", "season", "soccer", "stadium",
+    "team", "tennis", "tournament", "win", "won",
+];
+
+/// Game or sports domain dominating a stretch of text, if any
+pub fn dominant_domain(text: &str) -> Option<&'static str> {
+    let words = content_words(text);
+    let hits = |terms: &[&str]| words.iter().filter(|w| terms.binary_search(&w.as_str()).is_ok()).count();
+    let (domain, count) = [("gaming", hits(GAMING_TERMS)), ("sports", hits(SPORTS_TERMS))]
+        .into_iter()
+        .max_by_key(|&(_, count)| count)?;
+    let dominant = count >= MIN_DOMAIN_TERMS && count as f64 >= MIN_DOMAIN_SHARE * words.len() as f64;
+    dominant.then_some(domain)
+}
+
+/// Domain of the sentence around a sensitive match, if it is game or sports talk
+pub fn match_domain(text: &str, m: &PatternMatch) -> Option<&'static str> {
+    if !DOMAIN_SENSITIVE_TYPES.contains(&m.pattern_type.as_str()) {
+        return None;
+    }
+    let (start, end) = sentence_bounds(text, m.position);
+    dominant_domain(&text[start..end])
+}
+
+/// Down-weight sensitive matches in sentences dominated by game or sports vocabulary
+pub fn apply_domain_context(text: &str, matches: &mut [PatternMatch]) {
+    for m in matches.iter_mut() {
+        if match_domain(text, m).is_some() {
+            m.weight *= DOMAIN_WEIGHT_FACTOR;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::pattern_matching::match_patterns;
+
+    #[test]
+    fn test_term_lists_are_sorted() {
+        assert!(GAMING_TERMS.windows(2).all(|w| w[0] < w[1]));
+        assert!(SPORTS_TERMS.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_game_talk_down_weights_militarization() {
+        let text = "Our squad lost the raid, the boss battle was brutal. It's a battle with my landlord.";
+        let mut matches: Vec<PatternMatch> =
+            match_patterns(text).into_iter().filter(|m| m.pattern_type == "militarization").collect();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(match_domain(text, &matches[0]), Some("gaming"));
+
+        apply_domain_context(text, &mut matches);
+        assert!(matches[0].weight < matches[1].weight);
+        assert_eq!(dominant_domain("The team won the match and the coach was thrilled"), Some("sports"));
+        assert_eq!(dominant_domain("We had a long talk about the game plan for the move"), None);
+    }
+}
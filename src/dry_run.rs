@@ -0,0 +1,143 @@
+//! Dry-run detection for debugging
+//! Reports every pattern that matched, including ones that didn't count toward the score,
+//! with the reasons each was suppressed or discounted
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::dialect::vernacular_exempt;
+use super::domain::{apply_domain_context, match_domain};
+use super::intensity::{apply_intensity, detect_intensity};
+use super::pattern_matching::match_patterns_for_locale;
+use super::positive::{boundary_exempt, match_positive};
+use super::questions::apply_question_form;
+use super::scoring::{calculate_configured_score, match_confidences, match_contexts, ScoringConfig};
+use super::{analyze_with_config, DETECTION_THRESHOLD};
+
+/// One pattern hit and what happened to it
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunMatch {
+    pub id: String,
+    pub pattern_type: String,
+    pub match_text: String,
+    pub position: usize,
+    pub severity: String,
+    /// Weight after intensity and domain-context adjustment
+    pub weight: f64,
+    /// Configured severity multiplier
+    pub multiplier: f64,
+    pub confidence: f64,
+    /// Score this match would produce on its own
+    pub solo_score: f64,
+    /// Whether the match counted toward the score
+    pub counted: bool,
+    /// Why the match was dropped: `boundary_statement`, `zero_multiplier`
+    pub suppressed_by: Vec<String>,
+    /// Context that lowered its confidence (`negated`, `quoted`, `question`, `conditional`, `hypothetical`) or its weight
+    /// (`gaming_context`, `sports_context`)
+    pub discounts: Vec<String>,
+}
+
+/// Every match with the final decision it fed into
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunResult {
+    pub detected: bool,
+    pub score: f64,
+    /// Score the text must exceed to be detected
+    pub threshold: f64,
+    pub matches: Vec<DryRunMatch>,
+}
+
+/// Analyze text and explain every match, counted or not
+pub fn dry_run(text: &str, config: &ScoringConfig) -> DryRunResult {
+    let result = analyze_with_config(text, config);
+
+    let mut matches = match_patterns_for_locale(text, config.locale);
+    let positive = match_positive(text);
+    let exempt = boundary_exempt(text, &matches, &positive);
+    let vernacular = if config.dialect_normalization {
+        vernacular_exempt(text, &matches, config.locale)
+    } else {
+        vec![false; matches.len()]
+    };
+    apply_intensity(text, &mut matches, &detect_intensity(text));
+    apply_domain_context(text, &mut matches);
+    apply_question_form(text, &mut matches);
+    let contexts = match_contexts(text, &matches);
+    let confidences = match_confidences(text, &matches);
+
+    let explained: Vec<DryRunMatch> = matches
+        .iter()
+        .zip(exempt.into_iter().zip(vernacular))
+        .zip(contexts.into_iter().zip(confidences))
+        .map(|((m, (exempt, vernacular)), (context, confidence))| {
+            let multiplier = config.multiplier(m);
+            let mut suppressed_by = Vec::new();
+            if exempt {
+                suppressed_by.push("boundary_statement".to_string());
+            }
+            if vernacular {
+                suppressed_by.push("vernacular".to_string());
+            }
+            if multiplier == 0.0 {
+                suppressed_by.push("zero_multiplier".to_string());
+            }
+            let mut discounts: Vec<String> =
+                [("negated", context.negated), ("quoted", context.quoted), ("question", context.question)]
+                    .iter()
+                    .filter(|(_, applies)| *applies)
+                    .map(|(name, _)| name.to_string())
+                    .collect();
+            if let Some(scope) = context.scope {
+                discounts.push(scope.to_string());
+            }
+            if let Some(domain) = match_domain(text, m) {
+                discounts.push(format!("{}_context", domain));
+            }
+
+            DryRunMatch {
+                id: m.id(),
+                pattern_type: m.pattern_type.clone(),
+                match_text: m.match_text.clone(),
+                position: m.position,
+                severity: m.severity.clone(),
+                weight: m.weight,
+                multiplier,
+                confidence,
+                solo_score: calculate_configured_score(text, std::slice::from_ref(m), config),
+                counted: suppressed_by.is_empty(),
+                suppressed_by,
+                discounts,
+            }
+        })
+        .collect();
+
+    DryRunResult { detected: result.detected, score: result.score, threshold: DETECTION_THRESHOLD, matches: explained }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dry_run_reports_boundary_suppression() {
+        let result = dry_run("I need a break, I don't want to talk right now.", &ScoringConfig::default());
+        let suppressed: Vec<&DryRunMatch> = result.matches.iter().filter(|m| !m.counted).collect();
+        assert!(!suppressed.is_empty());
+        assert!(suppressed.iter().all(|m| m.suppressed_by == vec!["boundary_statement"]));
+        assert!(!result.detected);
+    }
+
+    #[test]
+    fn test_dry_run_reports_below_threshold_and_disabled_matches() {
+        let config: ScoringConfig = serde_json::from_str(r#"{"severityMultipliers":{"high":0.0}}"#).unwrap();
+        let result = dry_run("Are you saying you're so selfish?", &config);
+        let judgment = result.matches.iter().find(|m| m.pattern_type == "character_judgment").unwrap();
+        assert!(!judgment.counted);
+        assert_eq!(judgment.suppressed_by, vec!["zero_multiplier"]);
+        assert!(judgment.discounts.contains(&"question".to_string()));
+        assert!(result.score <= result.threshold);
+    }
+}
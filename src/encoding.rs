@@ -0,0 +1,56 @@
+//! Binary result encoding
+//! MessagePack output as a cheaper alternative to JSON strings for large results
+
+use serde::Serialize;
+
+/// Serialization format selectable per call
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Json,
+    MessagePack,
+}
+
+impl OutputFormat {
+    /// Parse a format name ("json", "msgpack"/"messagepack")
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "json" => Ok(OutputFormat::Json),
+            "msgpack" | "messagepack" => Ok(OutputFormat::MessagePack),
+            other => Err(format!("unsupported output format '{}'", other)),
+        }
+    }
+}
+
+/// Encode a result in the requested format
+/// MessagePack uses named maps so keys match the camelCase JSON output
+pub fn encode<T: Serialize>(value: &T, format: OutputFormat) -> Result<Vec<u8>, String> {
+    match format {
+        OutputFormat::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+        OutputFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::analyze;
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(OutputFormat::parse("MsgPack"), Ok(OutputFormat::MessagePack));
+        assert_eq!(OutputFormat::parse("json"), Ok(OutputFormat::Json));
+        assert!(OutputFormat::parse("xml").is_err());
+    }
+
+    #[test]
+    fn test_msgpack_round_trip_keeps_field_names() {
+        let result = analyze("You're so selfish");
+        let bytes = encode(&result, OutputFormat::MessagePack).unwrap();
+        let json = encode(&result, OutputFormat::Json).unwrap();
+        assert!(bytes.len() < json.len());
+
+        let decoded: serde_json::Value = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, serde_json::to_value(&result).unwrap());
+        assert!(decoded["patterns"][0]["patternType"].is_string());
+    }
+}
@@ -0,0 +1,153 @@
+//! Conflicts between third parties
+//! Described conflicts between other people ("Mom and Dad had a huge fight", "my sister isn't
+//! speaking to my aunt", "my parents are fighting again"), with each party linked to the
+//! extracted entity it refers to. Conflicts involving the writer are left to pattern matching
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::entity_extraction::{infer_relationship_from_word, is_common_word, ExtractedEntity};
+
+/// A person or group: "my aunt", "the neighbors", or a capitalized name
+const PARTY: &str = r"(?:(?i:my|the)\s+[a-z]+(?:-[a-z]+)*|[A-Z][a-z]+)";
+
+lazy_static::lazy_static! {
+    /// "Mom and Dad had a huge fight", "Tom and my sister aren't speaking"
+    static ref PAIR_CONFLICT: Regex = Regex::new(&format!(
+        r"\b(?P<a>{party})\s+and\s+(?P<b>{party})\s+(?i:(?:had|got into|are having|have had)\s+(?:a|an|another)\s+(?:\w+\s+)?(?:fight|argument|row|blowout|falling out|screaming match)|fought|argued|(?:are|keep)\s+(?:fighting|arguing)|fell out|are feuding|(?:aren't|are not|stopped)\s+(?:speaking|talking)|don't speak|(?:hate|can't stand)\s+each other)\b",
+        party = PARTY
+    )).unwrap();
+
+    /// "my sister isn't speaking to my aunt", "Dad yelled at my brother"
+    static ref DIRECTED_CONFLICT: Regex = Regex::new(&format!(
+        r"\b(?P<a>{party})\s+(?i:(?:isn't|is not|wasn't|hasn't been|stopped)\s+(?:speaking|talking)\s+to|(?:fought|argued|had a fight|had an argument|got into a fight|is fighting|is arguing|fell out)\s+with|(?:yelled|screamed|shouted|is mad|is angry|was mad|was angry|got mad|got angry)\s+at|hates|can't stand|blames|resents)\s+(?P<b>{party})",
+        party = PARTY
+    )).unwrap();
+
+    /// "my parents are fighting again", "the in-laws had a falling out"
+    static ref GROUP_CONFLICT: Regex = Regex::new(
+        r"\b(?P<a>(?i:my|the)\s+(?i:parents|in-?laws|siblings|kids|family))\s+(?i:(?:had|got into)\s+(?:a|an|another)\s+(?:\w+\s+)?(?:fight|argument|row|falling out)|fought|argued|are (?:fighting|arguing|feuding)|aren't speaking|fell out)\b"
+    ).unwrap();
+}
+
+/// One side of a conflict
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictParty {
+    /// As written ("my aunt", "Dad")
+    pub text: String,
+    /// Id of the extracted entity the party refers to, when there is one
+    pub entity_id: Option<String>,
+    pub relationship: Option<String>,
+}
+
+/// A described conflict between other people
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityConflict {
+    /// "fight", "argument", "estrangement" or "hostility"
+    pub kind: String,
+    /// Two parties, or one group in conflict internally
+    pub parties: Vec<ConflictParty>,
+    pub trigger: String,
+    pub position: usize,
+}
+
+/// Kind of conflict from the words describing it
+fn kind_of(trigger: &str) -> &'static str {
+    let lower = trigger.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| lower.contains(w));
+    if has(&["speak", "talking", "fell out", "falling out", "feud"]) {
+        "estrangement"
+    } else if has(&["argu"]) {
+        "argument"
+    } else if has(&["fight", "fought", "row", "blowout", "screaming"]) {
+        "fight"
+    } else {
+        "hostility"
+    }
+}
+
+/// Party with the entity it refers to; `None` when the text is an ordinary word, not a person
+fn party(text: &str, entities: &[ExtractedEntity]) -> Option<ConflictParty> {
+    let lower = text.to_lowercase();
+    let bare = lower.strip_prefix("my ").or_else(|| lower.strip_prefix("the ")).unwrap_or(&lower).trim();
+    let kin = infer_relationship_from_word(bare);
+
+    let entity = entities.iter().find(|e| {
+        e.name.eq_ignore_ascii_case(bare)
+            || e.canonical_name.eq_ignore_ascii_case(bare)
+            || e.aliases.iter().any(|a| a.eq_ignore_ascii_case(bare))
+            || e.relationship_context.eq_ignore_ascii_case(text)
+            || (kin.is_some() && e.relationship_hint == kin)
+    });
+    let determined = bare.len() != lower.len();
+    if entity.is_none() && kin.is_none() && !determined && is_common_word(text) {
+        return None;
+    }
+
+    Some(ConflictParty {
+        text: text.to_string(),
+        entity_id: entity.map(|e| e.id.clone()),
+        relationship: entity.and_then(|e| e.relationship_hint.clone()).or(kin),
+    })
+}
+
+/// Conflicts between other people described in the text, in text order
+pub fn entity_conflicts(text: &str, entities: &[ExtractedEntity]) -> Vec<EntityConflict> {
+    let mut conflicts: Vec<EntityConflict> = Vec::new();
+    for regex in [&*PAIR_CONFLICT, &*DIRECTED_CONFLICT, &*GROUP_CONFLICT] {
+        for cap in regex.captures_iter(text) {
+            let whole = cap.get(0).unwrap();
+            if conflicts.iter().any(|c| c.position < whole.end() && whole.start() < c.position + c.trigger.len()) {
+                continue;
+            }
+            let texts = [cap.name("a"), cap.name("b")];
+            let parties: Option<Vec<ConflictParty>> = texts.iter().flatten().map(|m| party(m.as_str(), entities)).collect();
+            let Some(parties) = parties else {
+                continue;
+            };
+            conflicts.push(EntityConflict {
+                kind: kind_of(whole.as_str()).to_string(),
+                parties,
+                trigger: whole.as_str().to_string(),
+                position: whole.start(),
+            });
+        }
+    }
+    conflicts.sort_by_key(|c| c.position);
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::entity_extraction::extract_entities;
+    use super::*;
+
+    #[test]
+    fn test_conflicts_link_parties_to_entities() {
+        let text = "My sister Amy came by. Mom and Dad had a huge fight. My sister isn't speaking to my aunt.";
+        let conflicts = extract_entities(text).conflicts;
+        assert_eq!(conflicts.len(), 2);
+
+        assert_eq!(conflicts[0].kind, "fight");
+        let relationships: Vec<Option<&str>> = conflicts[0].parties.iter().map(|p| p.relationship.as_deref()).collect();
+        assert_eq!(relationships, vec![Some("mother"), Some("father")]);
+
+        assert_eq!(conflicts[1].kind, "estrangement");
+        assert_eq!(conflicts[1].parties[0].entity_id.as_deref(), Some("amy@0"));
+        assert_eq!(conflicts[1].parties[1].text, "my aunt");
+        assert!(conflicts[1].parties[1].entity_id.is_some());
+    }
+
+    #[test]
+    fn test_group_and_non_conflicts() {
+        let conflicts = entity_conflicts("My parents are fighting again.", &[]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!((conflicts[0].kind.as_str(), conflicts[0].parties.len()), ("fight", 1));
+
+        assert!(entity_conflicts("Then we argued about money.", &[]).is_empty());
+        assert!(entity_conflicts("Mom and Dad had a lovely dinner.", &[]).is_empty());
+    }
+}
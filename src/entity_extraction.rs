@@ -1,22 +1,71 @@
-/// Entity extraction for ambient contact management
-/// High-performance extraction of people mentions, relationships, and facts
+//! Entity extraction for ambient contact management
+//! High-performance extraction of people mentions, relationships, and facts
 
 use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::gazetteer::{gazetteer_confidence, is_known_given_name};
+use super::memory::StoreUsage;
+use super::entity_conflicts::{entity_conflicts, EntityConflict};
+use super::relationship_events::{relationship_changes, RelationshipChange};
+use super::offsets::{context_bounds, sentence_bounds};
+use super::pronouns::{attribute_pronouns, PronounEvidence};
+use super::quotes::{blank_quotes, extract_quotes, Quote};
+use super::pos::is_lexicon_word;
+use super::tokenize::is_stopword;
 
 /// Extracted entity from text
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ExtractedEntity {
+    /// Stable id from the normalized name and byte offset (`sarah@14`), for diffing runs
+    #[serde(default)]
+    pub id: String,
     pub name: String,
     pub relationship_hint: Option<String>,
     pub relationship_context: String,
+    /// Best guess at the person's pronouns, from `pronounEvidence`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pronouns: Option<String>,
+    /// Pronoun counts behind `pronouns`, for hosts applying their own certainty threshold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pronoun_evidence: Option<PronounEvidence>,
     pub mention_context: String,
     pub sentiment: Option<String>,
+    /// Overall confidence: the lower of the name and relationship confidences
     pub confidence: f64,
+    /// Confidence that `name` is how the text refers to this person
+    #[serde(default)]
+    pub name_confidence: f64,
+    /// Confidence in `relationshipHint`; absent when there is no relationship
+    #[serde(default)]
+    pub relationship_confidence: Option<f64>,
     pub position: usize,
+    /// Set when the mention is a group rather than one person: "family" for a surname household
+    /// ("the Johnsons"), otherwise the kind of group ("in_laws", "parents", "team", "board")
+    #[serde(default)]
+    pub group_type: Option<String>,
+    /// For a group, ids of the people mentioned nearby as its members
+    #[serde(default)]
+    pub members: Vec<String>,
+    /// Name of the known contact the entity was matched to, when a contact list was supplied
+    #[serde(default)]
+    pub known_contact: Option<String>,
+    /// Id of that contact, when the host supplied one
+    #[serde(default)]
+    pub contact_id: Option<String>,
+    /// How closely the mention matched the contact: 1 exact, lower for diminutives and misspellings
+    #[serde(default)]
+    pub contact_match_score: Option<f64>,
+    /// Display name: the proper name when the person was named, else the capitalized kin term
+    #[serde(default)]
+    pub canonical_name: String,
+    /// Other names and kin terms the same person was mentioned by ("Mom", "mother")
+    #[serde(default)]
+    pub aliases: Vec<String>,
 }
 
 /// Relationship pattern definition
@@ -28,13 +77,103 @@ struct RelationshipPattern {
     category: &'static str,
 }
 
+/// Plural or collective reference such as "the in-laws" or "my team"
+#[derive(Debug, Clone)]
+struct GroupPattern {
+    pattern: Regex,
+    group_type: &'static str,
+    /// Relationship of the members to the writer, when the group implies one
+    relationship: Option<&'static str>,
+    /// Individual relationships that make a person mentioned alongside the group a member
+    member_relationships: &'static [&'static str],
+}
+
 /// Entity extraction result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct EntityExtractionResult {
     pub entities: Vec<ExtractedEntity>,
     pub relationship_count: usize,
     pub processing_time_us: u64,
+    /// Ids of named people matching no known contact; empty when no contact list was supplied
+    #[serde(default)]
+    pub likely_new: Vec<String>,
+    /// Stated relationship transitions ("John is my ex now", "we broke up")
+    #[serde(default)]
+    pub relationship_changes: Vec<RelationshipChange>,
+    /// Described conflicts between other people ("Mom and Dad had a huge fight")
+    #[serde(default)]
+    pub conflicts: Vec<EntityConflict>,
+}
+
+/// Entity extraction options (all optional on the JS side)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EntityConfig {
+    /// Sentences after the mention's own sentence in which pronouns can refer back to it
+    pub anaphora_sentences: usize,
+    /// Longest anaphora window in bytes, however long the sentences
+    pub anaphora_chars: usize,
+    /// Also recognize neopronouns (xe/xem, ze/zir, fae/faer); off by default
+    pub neopronouns: bool,
+    /// Infer pronouns at all; off for deployments that must not infer gendered attributes
+    pub infer_pronouns: bool,
+}
+
+impl Default for EntityConfig {
+    fn default() -> Self {
+        EntityConfig { anaphora_sentences: 1, anaphora_chars: 200, neopronouns: false, infer_pronouns: true }
+    }
+}
+
+/// Host-wide pronoun inference switch, for surfaces that take no `EntityConfig`
+static PRONOUN_INFERENCE: AtomicBool = AtomicBool::new(true);
+
+/// Turn pronoun inference on or off for every extraction in the process, whatever its `EntityConfig`
+pub fn set_pronoun_inference(enabled: bool) {
+    PRONOUN_INFERENCE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn pronoun_inference_enabled() -> bool {
+    PRONOUN_INFERENCE.load(Ordering::Relaxed)
+}
+
+/// Lets a test turn the process-wide switch off without racing extractions in tests running on
+/// other threads: those wait until it's back on
+#[cfg(test)]
+pub mod pronoun_switch {
+    use std::cell::Cell;
+    use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+    static SWITCH: RwLock<()> = RwLock::new(());
+
+    thread_local! {
+        static TURNED_OFF_HERE: Cell<bool> = const { Cell::new(false) };
+    }
+
+    /// Held for an extraction; waits while another thread has inference off
+    pub fn hold() -> Option<RwLockReadGuard<'static, ()>> {
+        (!TURNED_OFF_HERE.with(Cell::get)).then(|| SWITCH.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Inference off until dropped, so a failing assertion can't leave it off for later tests
+    pub struct InferenceOff {
+        _lock: RwLockWriteGuard<'static, ()>,
+    }
+
+    pub fn inference_off() -> InferenceOff {
+        let lock = SWITCH.write().unwrap_or_else(PoisonError::into_inner);
+        TURNED_OFF_HERE.with(|here| here.set(true));
+        super::set_pronoun_inference(false);
+        InferenceOff { _lock: lock }
+    }
+
+    impl Drop for InferenceOff {
+        fn drop(&mut self) {
+            super::set_pronoun_inference(true);
+            TURNED_OFF_HERE.with(|here| here.set(false));
+        }
+    }
 }
 
 /// Words to exclude from name matching
@@ -51,13 +190,32 @@ const EXCLUDED_WORDS: &[&str] = &[
     "last", "next", "first", "new", "old", "other", "another",
 ];
 
+/// Frequent sentence openers that are capitalized only by position (sorted, binary-searched);
+/// complements the stopword and part-of-speech lexicons
+const COMMON_OPENERS: &[&str] = &[
+    "actually", "afterwards", "anyway", "apparently", "basically", "besides", "check", "clearly", "definitely",
+    "don't", "eventually", "everything", "finally", "frankly", "glad", "guess", "hello", "hmm", "honestly",
+    "hope", "hopefully", "however", "i'd", "i'll", "i'm", "i've", "instead", "lately", "let's", "luckily",
+    "meanwhile", "mostly", "obviously", "probably", "remember", "seriously", "somehow", "sorry", "sure", "thank",
+    "thankfully", "that's", "there's", "unfortunately", "wait", "watch", "well", "what's", "whatever", "yep",
+];
+
+/// Name confidence of relationship nouns standing in for a name ("mom")
+const KIN_TERM_CONFIDENCE: f64 = 0.9;
+
+/// Relationships normally held by one person at a time, so their mentions are merged (sorted)
+const SINGULAR_RELATIONSHIPS: &[&str] = &[
+    "boyfriend", "co_parent", "father", "father_in_law", "fiance", "fiancee", "girlfriend", "husband", "mother",
+    "mother_in_law", "partner", "significant_other", "spouse", "step_father", "step_mother", "wife",
+];
+
 lazy_static::lazy_static! {
     /// Pre-compiled relationship patterns for performance
     static ref RELATIONSHIP_PATTERNS: Vec<RelationshipPattern> = vec![
         // Family - possessive patterns
         RelationshipPattern { pattern: Regex::new(r"(?i)\bmy (?:mom|mother|mommy|mama)\b").unwrap(), relationship: "mother", category: "family" },
         RelationshipPattern { pattern: Regex::new(r"(?i)\bmy (?:dad|father|daddy|papa)\b").unwrap(), relationship: "father", category: "family" },
-        RelationshipPattern { pattern: Regex::new(r"(?i)\bmy (?:parents?)\b").unwrap(), relationship: "parent", category: "family" },
+        RelationshipPattern { pattern: Regex::new(r"(?i)\bmy (?:parent)\b").unwrap(), relationship: "parent", category: "family" },
         RelationshipPattern { pattern: Regex::new(r"(?i)\bmy (?:brother|bro)\b").unwrap(), relationship: "brother", category: "family" },
         RelationshipPattern { pattern: Regex::new(r"(?i)\bmy (?:sister|sis)\b").unwrap(), relationship: "sister", category: "family" },
         RelationshipPattern { pattern: Regex::new(r"(?i)\bmy (?:sibling)\b").unwrap(), relationship: "sibling", category: "family" },
@@ -124,9 +282,39 @@ lazy_static::lazy_static! {
         RelationshipPattern { pattern: Regex::new(r"(?i)\bmy (?:landlord)\b").unwrap(), relationship: "landlord", category: "other" },
     ];
 
+    /// Plural family references and collectives, extracted as one group entity each
+    static ref GROUP_PATTERNS: Vec<GroupPattern> = vec![
+        GroupPattern { pattern: Regex::new(r"(?i)\b(?:the|my|our) (in-?laws)\b").unwrap(), group_type: "in_laws", relationship: Some("in_laws"), member_relationships: &["brother_in_law", "father_in_law", "mother_in_law", "sister_in_law"] },
+        GroupPattern { pattern: Regex::new(r"(?i)\b(?:my|our) (parents)\b").unwrap(), group_type: "parents", relationship: Some("parent"), member_relationships: &["father", "mother", "parent", "step_father", "step_mother"] },
+        GroupPattern { pattern: Regex::new(r"(?i)\b(?:my|our) (kids|children)\b").unwrap(), group_type: "children", relationship: Some("child"), member_relationships: &["child", "daughter", "son"] },
+        GroupPattern { pattern: Regex::new(r"(?i)\b(?:my|our) (siblings)\b").unwrap(), group_type: "siblings", relationship: Some("sibling"), member_relationships: &["brother", "sibling", "sister"] },
+        GroupPattern { pattern: Regex::new(r"(?i)\b(?:my|our) (family|folks|relatives)\b").unwrap(), group_type: "family", relationship: Some("family"), member_relationships: &[] },
+        // Collectives
+        GroupPattern { pattern: Regex::new(r"(?i)\b(?:my|our) (team|squad)\b").unwrap(), group_type: "team", relationship: Some("colleague"), member_relationships: &["boss", "colleague", "direct_report"] },
+        GroupPattern { pattern: Regex::new(r"(?i)\b(?:my|our) (co-?workers|colleagues)\b").unwrap(), group_type: "coworkers", relationship: Some("colleague"), member_relationships: &["boss", "colleague", "direct_report"] },
+        GroupPattern { pattern: Regex::new(r"(?i)\b(?:my|our) (friend group|group of friends|friends)\b").unwrap(), group_type: "friends", relationship: Some("friend"), member_relationships: &["best_friend", "close_friend", "friend"] },
+        GroupPattern { pattern: Regex::new(r"(?i)\b(?:the|our) (board)(?: of directors)?\b").unwrap(), group_type: "board", relationship: None, member_relationships: &[] },
+    ];
+
+    /// Names listed right after a group: "my in-laws, Bob and Linda", "my team (Sarah, Tom)"
+    static ref MEMBERS_AFTER_GROUP: Regex = Regex::new(
+        r"^\s*[,(:\x{2014}-]?\s*(?:(?i:including|like|namely)\s+)?([A-Z][a-z]+(?:\s*,\s*[A-Z][a-z]+)*(?:,?\s+(?:and|&)\s+[A-Z][a-z]+)?)"
+    ).unwrap();
+
+    /// Names listed right before a group: "Sarah and Tom from my team", "Dana on the board"
+    static ref MEMBERS_BEFORE_GROUP: Regex = Regex::new(
+        r"([A-Z][a-z]+(?:\s*,\s*[A-Z][a-z]+)*(?:,?\s+(?:and|&)\s+[A-Z][a-z]+)?)\s+(?i:from|on|in|of)\s+$"
+    ).unwrap();
+
+    /// Surname households: "the Johnsons", "the Joneses"
+    static ref SURNAME_FAMILY: Regex = Regex::new(r"\b[Tt]he ([A-Z][a-z]+s)\b").unwrap();
+
     /// Pattern to find names after relationship mentions
     static ref NAME_AFTER_RELATION: Regex = Regex::new(r"^\s*,?\s*([A-Z][a-z]+(?:\s+[A-Z][a-z]+)?)\b").unwrap();
 
+    /// Kin terms used as a name ("Mom called")
+    static ref BARE_KIN_TERM: Regex = Regex::new(r"\b(Mom|Mommy|Mama|Mother|Dad|Daddy|Papa|Father)\b").unwrap();
+
     /// Pattern to find any capitalized name
     static ref CAPITALIZED_NAME: Regex = Regex::new(r"\b([A-Z][a-z]+(?:\s+[A-Z][a-z]+)?)\b").unwrap();
 
@@ -138,6 +326,13 @@ lazy_static::lazy_static! {
     static ref SHE_HER_PATTERN: Regex = Regex::new(r"(?i)\b(she|her|hers|herself)\b").unwrap();
     static ref THEY_THEM_PATTERN: Regex = Regex::new(r"(?i)\b(they|them|their|theirs|themselves)\b").unwrap();
 
+    /// Neopronoun sets, counted only when enabled in the extraction config
+    static ref NEOPRONOUN_PATTERNS: Vec<(&'static str, Regex)> = vec![
+        ("xe/xem", Regex::new(r"(?i)\b(xe|xem|xyr|xyrs|xemself)\b").unwrap()),
+        ("ze/zir", Regex::new(r"(?i)\b(ze|zir|zirs|zirself)\b").unwrap()),
+        ("fae/faer", Regex::new(r"(?i)\b(fae|faer|faers|faerself)\b").unwrap()),
+    ];
+
     /// Sentiment patterns
     static ref POSITIVE_SENTIMENT: Regex = Regex::new(r"(?i)\b(love|happy|grateful|appreciate|enjoy|like|wonderful|great|amazing|fantastic|supportive|helpful|kind|caring)\b").unwrap();
     static ref NEGATIVE_SENTIMENT: Regex = Regex::new(r"(?i)\b(hate|angry|frustrated|annoyed|upset|disappointed|sad|hurt|betrayed|difficult|problematic|toxic|abusive)\b").unwrap();
@@ -146,6 +341,21 @@ lazy_static::lazy_static! {
     static ref EXCLUDED_SET: HashSet<&'static str> = EXCLUDED_WORDS.iter().cloned().collect();
 }
 
+/// Report the size of the pre-compiled relationship pattern cache
+/// Compiled regex internals aren't exposed, so bytes are estimated from pattern sources
+pub fn pattern_cache_usage() -> StoreUsage {
+    let approx_bytes = RELATIONSHIP_PATTERNS
+        .iter()
+        .map(|rp| std::mem::size_of::<RelationshipPattern>() + rp.pattern.as_str().len())
+        .sum();
+
+    StoreUsage {
+        name: "relationship_patterns".to_string(),
+        entries: RELATIONSHIP_PATTERNS.len(),
+        approx_bytes,
+    }
+}
+
 /// Check if a word is a valid name
 fn is_valid_name(word: &str) -> bool {
     if word.len() < 2 {
@@ -161,6 +371,84 @@ fn is_valid_name(word: &str) -> bool {
     word.chars().next().map(|c| c.is_uppercase()).unwrap_or(false)
 }
 
+/// Whether `position` starts its sentence (only punctuation or quotes before it)
+pub fn is_sentence_initial(text: &str, position: usize) -> bool {
+    let (start, _) = sentence_bounds(text, position);
+    text[start..position].chars().all(|c| !c.is_alphanumeric())
+}
+
+/// Whether a word is ordinary vocabulary, so its capital may be only a sentence start
+pub fn is_common_word(word: &str) -> bool {
+    let lower = word.to_lowercase().replace('\u{2019}', "'");
+    is_stopword(&lower) || is_lexicon_word(&lower) || COMMON_OPENERS.binary_search(&lower.as_str()).is_ok()
+}
+
+/// Name candidate found at `position` in `text`, with a sentence-initial common word dropped
+/// ("Tell Sarah" -> "Sarah"); `None` when nothing name-like is left
+fn name_candidate(text: &str, position: usize, candidate: &str) -> Option<(usize, String)> {
+    if !is_valid_name(candidate) {
+        return None;
+    }
+    let first = candidate.split_whitespace().next().unwrap_or(candidate);
+    if !(is_sentence_initial(text, position) && is_common_word(first)) {
+        return Some((position, candidate.to_string()));
+    }
+    let rest = candidate[first.len()..].trim_start();
+    let rest_position = position + candidate.len() - rest.len();
+    is_valid_name(rest).then(|| (rest_position, rest.to_string()))
+}
+
+/// Whether a plural like "Johnsons" is a surname rather than an ordinary word ("the Mondays")
+fn is_family_surname(plural: &str) -> bool {
+    let singular = plural.strip_suffix("es").filter(|s| s.ends_with('s') || s.ends_with("ch")).unwrap_or(&plural[..plural.len() - 1]);
+    singular.len() >= 3 && is_valid_name(singular)
+}
+
+/// Group entities for plural family references and collectives, with the byte ranges they cover
+fn extract_groups(text: &str) -> Vec<(ExtractedEntity, std::ops::Range<usize>)> {
+    let group = |name: String, group_type: &str, relationship: Option<&str>, mat: regex::Match, confidence: f64| {
+        let (context_start, context_end) = context_bounds(text, mat.start(), mat.end(), 50);
+        let context = &text[context_start..context_end];
+        let entity = ExtractedEntity {
+            id: entity_id(&name, mat.start()),
+            name,
+            relationship_hint: relationship.map(str::to_string),
+            relationship_context: mat.as_str().to_string(),
+            pronouns: None,
+            pronoun_evidence: None,
+            mention_context: context.trim().to_string(),
+            sentiment: detect_sentiment(context),
+            confidence,
+            name_confidence: confidence,
+            relationship_confidence: relationship.map(|_| confidence),
+            position: mat.start(),
+            group_type: Some(group_type.to_string()),
+            known_contact: None,
+            contact_id: None,
+            contact_match_score: None,
+            canonical_name: String::new(),
+            aliases: Vec::new(),
+            members: Vec::new(),
+        };
+        (entity, mat.range())
+    };
+
+    let mut groups = Vec::new();
+    for gp in GROUP_PATTERNS.iter() {
+        if let Some(cap) = gp.pattern.captures(text) {
+            let (mat, noun) = (cap.get(0).unwrap(), cap.get(1).unwrap());
+            groups.push(group(noun.as_str().to_lowercase(), gp.group_type, gp.relationship, mat, 0.8));
+        }
+    }
+    for cap in SURNAME_FAMILY.captures_iter(text) {
+        let (mat, surname) = (cap.get(0).unwrap(), cap.get(1).unwrap());
+        if is_family_surname(surname.as_str()) {
+            groups.push(group(format!("the {}", surname.as_str()), "family", None, mat, 0.75));
+        }
+    }
+    groups
+}
+
 /// Extract name from possessive match like "my mom" -> "mom"
 fn extract_name_from_possessive_match(match_text: &str) -> Option<String> {
     let words: Vec<&str> = match_text.split_whitespace().collect();
@@ -176,25 +464,40 @@ fn extract_name_from_possessive_match(match_text: &str) -> Option<String> {
     }
 }
 
-/// Detect pronouns from context
-fn detect_pronouns(context: &str) -> Option<String> {
-    let he_count = HE_HIM_PATTERN.find_iter(context).count();
-    let she_count = SHE_HER_PATTERN.find_iter(context).count();
-    let they_count = THEY_THEM_PATTERN.find_iter(context).count();
-
-    if he_count > 0 && he_count > she_count && he_count > they_count {
-        Some("he/him".to_string())
-    } else if she_count > 0 && she_count > he_count && she_count > they_count {
-        Some("she/her".to_string())
-    } else if they_count > 0 {
+/// Pronouns in a context, counted per pronoun set; neopronoun sets are included when requested
+pub fn pronoun_counts(context: &str, neopronouns: bool) -> Vec<(&'static str, usize)> {
+    let mut counts = vec![
+        ("he/him", HE_HIM_PATTERN.find_iter(context).count()),
+        ("she/her", SHE_HER_PATTERN.find_iter(context).count()),
+        ("they/them", THEY_THEM_PATTERN.find_iter(context).count()),
+    ];
+    if neopronouns {
+        counts.extend(NEOPRONOUN_PATTERNS.iter().map(|(set, pattern)| (*set, pattern.find_iter(context).count())));
+    }
+    counts
+}
+
+/// Best guess from pronoun counts: the set counted most, or "they/them" when no set leads and
+/// any were counted
+pub fn best_pronouns(counts: &[(&str, usize)]) -> Option<String> {
+    let most = counts.iter().map(|&(_, count)| count).max().unwrap_or(0);
+    let leaders: Vec<&str> = counts.iter().filter(|&&(_, count)| count == most).map(|&(set, _)| set).collect();
+    if most > 0 && leaders.len() == 1 {
+        Some(leaders[0].to_string())
+    } else if counts.iter().any(|&(set, count)| set == "they/them" && count > 0) {
         Some("they/them".to_string())
     } else {
         None
     }
 }
 
+/// Detect pronouns from context
+pub fn detect_pronouns(context: &str) -> Option<String> {
+    best_pronouns(&pronoun_counts(context, false))
+}
+
 /// Detect sentiment from context
-fn detect_sentiment(context: &str) -> Option<String> {
+pub fn detect_sentiment(context: &str) -> Option<String> {
     let positive_count = POSITIVE_SENTIMENT.find_iter(context).count();
     let negative_count = NEGATIVE_SENTIMENT.find_iter(context).count();
 
@@ -209,54 +512,155 @@ fn detect_sentiment(context: &str) -> Option<String> {
     }
 }
 
+/// Whether the entity at `index` is the attributed speaker of a quote; a pronoun speaker ("she
+/// said") is the person mentioned last before the quote
+fn is_speaker(quote: &Quote, index: usize, entities: &[ExtractedEntity]) -> bool {
+    let Some(speaker) = quote.speaker.as_deref() else {
+        return false;
+    };
+    let lower = speaker.to_lowercase();
+    if matches!(lower.as_str(), "he" | "she" | "they") {
+        let last = entities
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.group_type.is_none() && e.position < quote.offset)
+            .max_by_key(|(_, e)| e.position);
+        return last.is_some_and(|(i, _)| i == index);
+    }
+
+    let entity = &entities[index];
+    let bare = lower.strip_prefix("my ").unwrap_or(&lower);
+    let mut names = [&entity.name, &entity.canonical_name].into_iter().chain(&entity.aliases);
+    names.any(|n| n.eq_ignore_ascii_case(bare))
+        || entity.relationship_context.eq_ignore_ascii_case(speaker)
+        || infer_relationship_from_word(bare).is_some_and(|r| entity.relationship_hint.as_deref() == Some(r.as_str()))
+}
+
+/// Set the fields read from the text around each entity: pronouns from its anaphora window (none
+/// when pronoun inference is off), and sentiment without the affect words of quoted speech
+/// attributed to someone else
+pub fn attribute_context(text: &str, entities: &mut [ExtractedEntity], config: &EntityConfig) {
+    #[cfg(test)]
+    let _switch = pronoun_switch::hold();
+    let quotes = extract_quotes(text);
+    if config.infer_pronouns && pronoun_inference_enabled() {
+        attribute_pronouns(text, entities, &quotes, config);
+    } else {
+        for entity in entities.iter_mut() {
+            entity.pronouns = None;
+            entity.pronoun_evidence = None;
+        }
+    }
+    if quotes.is_empty() {
+        return;
+    }
+
+    let mut sentiments = Vec::new();
+    for (index, entity) in entities.iter().enumerate() {
+        // Sentiment was read from the mention context; find where it sits in the text
+        let context = entity.mention_context.as_str();
+        let Some(start) = text.match_indices(context).map(|(i, _)| i).filter(|&i| i <= entity.position).last() else {
+            continue;
+        };
+        let end = start + context.len();
+        let others: Vec<&Quote> =
+            quotes.iter().filter(|q| q.offset < end && start < q.end && !is_speaker(q, index, entities)).collect();
+        if !others.is_empty() {
+            sentiments.push((index, detect_sentiment(&blank_quotes(text, start, end, others))));
+        }
+    }
+    for (index, sentiment) in sentiments {
+        entities[index].sentiment = sentiment;
+    }
+}
+
+/// Stable entity id from the lowercased name (whitespace as `_`) and byte offset
+pub fn entity_id(name: &str, position: usize) -> String {
+    format!("{}@{}", name.to_lowercase().split_whitespace().collect::<Vec<_>>().join("_"), position)
+}
+
 /// Extract entities from text using pre-compiled regex patterns
 pub fn extract_entities(text: &str) -> EntityExtractionResult {
+    extract_entities_with_config(text, &EntityConfig::default())
+}
+
+/// Extract entities with extraction options
+pub fn extract_entities_with_config(text: &str, config: &EntityConfig) -> EntityExtractionResult {
     use std::time::Instant;
     let start = Instant::now();
 
     let mut entities: Vec<ExtractedEntity> = Vec::with_capacity(10);
     let mut processed_names: HashSet<String> = HashSet::new();
 
+    // Group references first, so their words aren't taken for individual names below
+    let mut group_ranges = Vec::new();
+    for (entity, range) in extract_groups(text) {
+        if processed_names.insert(entity.name.to_lowercase()) {
+            entities.push(entity);
+            group_ranges.push(range);
+        }
+    }
+    let in_group = |position: usize| group_ranges.iter().any(|range| range.contains(&position));
+
     // Extract from relationship patterns
     for rp in RELATIONSHIP_PATTERNS.iter() {
-        if let Some(mat) = rp.pattern.find(text) {
+        // "my friend" inside "my friend group" is the group, not a friend
+        if let Some(mat) = rp.pattern.find_iter(text).find(|m| !in_group(m.start())) {
             let match_text = mat.as_str();
             let match_start = mat.start();
             let match_end = mat.end();
 
             // Get context around the match
-            let context_start = match_start.saturating_sub(50);
-            let context_end = (match_end + 50).min(text.len());
+            let (context_start, context_end) = context_bounds(text, match_start, match_end, 50);
             let context = &text[context_start..context_end];
 
             // Look for name after the relationship mention
             let after_match = &text[match_end..];
-            let name = if let Some(name_cap) = NAME_AFTER_RELATION.captures(after_match) {
-                let potential_name = name_cap.get(1).map(|m| m.as_str()).unwrap_or("");
-                if is_valid_name(potential_name) {
-                    potential_name.to_string()
-                } else {
-                    extract_name_from_possessive_match(match_text)
-                        .unwrap_or_else(|| find_best_name_in_context(context))
-                }
-            } else {
-                extract_name_from_possessive_match(match_text)
-                    .unwrap_or_else(|| find_best_name_in_context(context))
+            let name_after = NAME_AFTER_RELATION
+                .captures(after_match)
+                .and_then(|cap| cap.get(1))
+                .map(|m| m.as_str())
+                .filter(|name| is_valid_name(name));
+            // The relationship is explicit; a name right after it is likelier right than one from context
+            let (name, name_confidence) = match name_after {
+                Some(name) => (name.to_string(), gazetteer_confidence(name, 0.8)),
+                None => match extract_name_from_possessive_match(match_text) {
+                    Some(noun) => (noun, KIN_TERM_CONFIDENCE),
+                    None => match find_best_name_in_context(text, context_start, context_end) {
+                        (name, true) => {
+                            let confidence = gazetteer_confidence(&name, 0.6);
+                            (name, confidence)
+                        }
+                        (term, false) => (term, 0.5),
+                    },
+                },
             };
+            let relationship_confidence = 0.9;
 
             let name_lower = name.to_lowercase();
             if !processed_names.contains(&name_lower) {
                 processed_names.insert(name_lower);
 
                 entities.push(ExtractedEntity {
+                    id: entity_id(&name, match_start),
                     name,
                     relationship_hint: Some(rp.relationship.to_string()),
                     relationship_context: match_text.to_string(),
-                    pronouns: detect_pronouns(context),
+                    pronouns: None,
+                    pronoun_evidence: None,
                     mention_context: context.trim().to_string(),
                     sentiment: detect_sentiment(context),
-                    confidence: 0.8,
+                    confidence: overall_confidence(name_confidence, Some(relationship_confidence)),
+                    name_confidence,
+                    relationship_confidence: Some(relationship_confidence),
                     position: match_start,
+                    group_type: None,
+                    known_contact: None,
+                    contact_id: None,
+                    contact_match_score: None,
+                    canonical_name: String::new(),
+                    aliases: Vec::new(),
+                    members: Vec::new(),
                 });
             }
         }
@@ -265,57 +669,256 @@ pub fn extract_entities(text: &str) -> EntityExtractionResult {
     // Extract "Name, my relation" pattern
     for cap in NAME_THEN_RELATION.captures_iter(text) {
         if let (Some(name_match), Some(relation_match)) = (cap.get(1), cap.get(2)) {
-            let name = name_match.as_str();
+            let Some((name_start, name)) = name_candidate(text, name_match.start(), name_match.as_str()) else {
+                continue;
+            };
             let relation_word = relation_match.as_str().to_lowercase();
 
             let name_lower = name.to_lowercase();
-            if !processed_names.contains(&name_lower) && is_valid_name(name) {
+            if !processed_names.contains(&name_lower) && !in_group(name_start) {
                 processed_names.insert(name_lower);
 
                 // Map relation word to relationship type
                 let relationship_hint = infer_relationship_from_word(&relation_word);
+                let name_confidence = gazetteer_confidence(&name, 0.85);
+                let relationship_confidence = relationship_hint.as_ref().map(|_| 0.85);
 
-                let context_start = name_match.start().saturating_sub(30);
-                let context_end = (relation_match.end() + 30).min(text.len());
+                let (context_start, context_end) = context_bounds(text, name_start, relation_match.end(), 30);
                 let context = &text[context_start..context_end];
 
                 entities.push(ExtractedEntity {
-                    name: name.to_string(),
+                    id: entity_id(&name, name_start),
+                    name,
                     relationship_hint,
                     relationship_context: cap.get(0).map(|m| m.as_str()).unwrap_or("").to_string(),
-                    pronouns: detect_pronouns(context),
+                    pronouns: None,
+                    pronoun_evidence: None,
                     mention_context: context.trim().to_string(),
                     sentiment: detect_sentiment(context),
-                    confidence: 0.85,
-                    position: name_match.start(),
+                    confidence: overall_confidence(name_confidence, relationship_confidence),
+                    name_confidence,
+                    relationship_confidence,
+                    position: name_start,
+                    group_type: None,
+                    known_contact: None,
+                    contact_id: None,
+                    contact_match_score: None,
+                    canonical_name: String::new(),
+                    aliases: Vec::new(),
+                    members: Vec::new(),
                 });
             }
         }
     }
 
+    // Deterministic order regardless of which pattern found each entity
+    entities.sort_by(|a, b| a.position.cmp(&b.position).then_with(|| a.id.cmp(&b.id)));
+    let entities = merge_singular_relationships(text, entities);
+    let mut entities = link_group_members(text, entities);
+    attribute_context(text, &mut entities, config);
+
     let elapsed = start.elapsed();
     let relationship_count = entities.iter().filter(|e| e.relationship_hint.is_some()).count();
 
     EntityExtractionResult {
-        entities,
         relationship_count,
         processing_time_us: elapsed.as_micros() as u64,
+        likely_new: Vec::new(),
+        relationship_changes: relationship_changes(text, &entities),
+        conflicts: entity_conflicts(text, &entities),
+        entities,
     }
 }
 
-/// Find the best name candidate in context
-fn find_best_name_in_context(context: &str) -> String {
-    for cap in CAPITALIZED_NAME.captures_iter(context) {
-        if let Some(m) = cap.get(1) {
-            let potential_name = m.as_str();
-            if is_valid_name(potential_name) {
-                return potential_name.to_string();
+/// Overall confidence from the name and relationship confidences
+pub fn overall_confidence(name_confidence: f64, relationship_confidence: Option<f64>) -> f64 {
+    relationship_confidence.map_or(name_confidence, |r| name_confidence.min(r))
+}
+
+/// Whether a name is a proper name rather than a kin or relationship term
+pub fn is_proper_name(name: &str) -> bool {
+    name.chars().next().is_some_and(char::is_uppercase) && infer_relationship_from_word(&name.to_lowercase()).is_none()
+}
+
+/// "mom" as "Mom"
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+/// Kin terms in the text that refer to the holder of a relationship, in text order
+fn kin_terms(text: &str, relationship: &str) -> Vec<(usize, String)> {
+    let mut terms: Vec<(usize, String)> = RELATIONSHIP_PATTERNS
+        .iter()
+        .filter(|rp| rp.relationship == relationship)
+        .flat_map(|rp| rp.pattern.find_iter(text))
+        .filter_map(|m| extract_name_from_possessive_match(m.as_str()).map(|noun| (m.start(), noun.to_lowercase())))
+        .collect();
+    terms.extend(
+        BARE_KIN_TERM
+            .find_iter(text)
+            .filter(|m| infer_relationship_from_word(&m.as_str().to_lowercase()).as_deref() == Some(relationship))
+            .map(|m| (m.start(), m.as_str().to_string())),
+    );
+    terms.sort();
+    terms
+}
+
+/// Merge the mentions of each singular relationship ("Mom", "my mother", "Linda, my mom") into one
+/// entity, kept at the named mention when there is one, and set every entity's canonical name.
+/// Relationships mentioned with two different proper names are left unmerged
+fn merge_singular_relationships(text: &str, mut entities: Vec<ExtractedEntity>) -> Vec<ExtractedEntity> {
+    for relationship in SINGULAR_RELATIONSHIPS {
+        let holds = |e: &ExtractedEntity| e.group_type.is_none() && e.relationship_hint.as_deref() == Some(relationship);
+        let holders: Vec<usize> = (0..entities.len()).filter(|&i| holds(&entities[i])).collect();
+        let proper: Vec<usize> = holders.iter().copied().filter(|&i| is_proper_name(&entities[i].name)).collect();
+        let mut distinct_proper: Vec<String> = proper.iter().map(|&i| entities[i].name.to_lowercase()).collect();
+        distinct_proper.sort();
+        distinct_proper.dedup();
+        if holders.is_empty() || distinct_proper.len() > 1 {
+            continue;
+        }
+
+        let keep = proper.first().copied().unwrap_or(holders[0]);
+        let mut names: Vec<(usize, String)> = holders.iter().map(|&i| (entities[i].position, entities[i].name.clone())).collect();
+        names.extend(kin_terms(text, relationship));
+        names.sort();
+        let mut aliases: Vec<String> = Vec::new();
+        for (_, name) in names {
+            if !name.eq_ignore_ascii_case(&entities[keep].name) && !aliases.iter().any(|a| a.eq_ignore_ascii_case(&name)) {
+                aliases.push(name);
+            }
+        }
+        entities[keep].aliases = aliases;
+        // Several mentions agreeing on the relationship make it surer
+        if holders.len() > 1 {
+            let keep = &mut entities[keep];
+            keep.relationship_confidence = keep.relationship_confidence.map(|r| (r + 0.05).min(0.95));
+            keep.confidence = overall_confidence(keep.name_confidence, keep.relationship_confidence);
+        }
+
+        for i in holders.into_iter().rev().filter(|&i| i != keep) {
+            entities.remove(i);
+        }
+    }
+
+    for entity in entities.iter_mut() {
+        entity.canonical_name = if is_proper_name(&entity.name) { entity.name.clone() } else { capitalize(&entity.name) };
+    }
+    entities
+}
+
+/// Names in a listed run ("Sarah, Tom and Ana") starting at byte `offset`, skipping common words
+fn listed_names(list: &str, offset: usize) -> Vec<(usize, &str)> {
+    CAPITALIZED_NAME
+        .find_iter(list)
+        .filter(|m| is_valid_name(m.as_str()) && (is_known_given_name(m.as_str()) || !is_common_word(m.as_str())))
+        .map(|m| (offset + m.start(), m.as_str()))
+        .collect()
+}
+
+/// Person entity for a group member named only alongside the group
+fn member_entity(text: &str, name: &str, position: usize, group: &ExtractedEntity) -> ExtractedEntity {
+    let (context_start, context_end) = context_bounds(text, position, position + name.len(), 50);
+    let context = &text[context_start..context_end];
+    let name_confidence = gazetteer_confidence(name, 0.75);
+    let relationship_confidence = group.relationship_hint.as_ref().map(|_| 0.7);
+    ExtractedEntity {
+        id: entity_id(name, position),
+        name: name.to_string(),
+        relationship_hint: group.relationship_hint.clone(),
+        relationship_context: group.relationship_context.clone(),
+        pronouns: None,
+        pronoun_evidence: None,
+        mention_context: context.trim().to_string(),
+        sentiment: detect_sentiment(context),
+        confidence: overall_confidence(name_confidence, relationship_confidence),
+        name_confidence,
+        relationship_confidence,
+        position,
+        group_type: None,
+        known_contact: None,
+        contact_id: None,
+        contact_match_score: None,
+        canonical_name: name.to_string(),
+        aliases: Vec::new(),
+        members: Vec::new(),
+    }
+}
+
+/// Link each group to the people mentioned nearby as its members: names listed with the group
+/// ("my in-laws, Bob and Linda", "Sarah and Tom from my team") and people in the same sentence
+/// whose relationship belongs to it ("my sister Amy" with "my siblings"). Listed names not
+/// extracted otherwise become entities of their own
+fn link_group_members(text: &str, mut entities: Vec<ExtractedEntity>) -> Vec<ExtractedEntity> {
+    let groups: Vec<usize> = (0..entities.len()).filter(|&i| entities[i].group_type.is_some()).collect();
+    for g in groups {
+        let position = entities[g].position;
+        let end = position + entities[g].relationship_context.len();
+        let (sentence_start, sentence_end) = sentence_bounds(text, position);
+
+        let mut named = Vec::new();
+        if let Some(list) = MEMBERS_AFTER_GROUP.captures(&text[end..sentence_end.max(end)]).and_then(|c| c.get(1)) {
+            named.extend(listed_names(list.as_str(), end + list.start()));
+        }
+        if let Some(list) = MEMBERS_BEFORE_GROUP.captures(&text[sentence_start..position]).and_then(|c| c.get(1)) {
+            named.extend(listed_names(list.as_str(), sentence_start + list.start()));
+        }
+
+        let mut members: Vec<String> = Vec::new();
+        for (name_position, name) in named {
+            let existing = entities.iter().find(|e| {
+                e.group_type.is_none()
+                    && (e.name.eq_ignore_ascii_case(name)
+                        || e.canonical_name.eq_ignore_ascii_case(name)
+                        || e.aliases.iter().any(|a| a.eq_ignore_ascii_case(name)))
+            });
+            let id = match existing {
+                Some(entity) => entity.id.clone(),
+                None => {
+                    let member = member_entity(text, name, name_position, &entities[g]);
+                    let id = member.id.clone();
+                    entities.push(member);
+                    id
+                }
+            };
+            if !members.contains(&id) {
+                members.push(id);
+            }
+        }
+
+        let group_type = entities[g].group_type.as_deref();
+        let kinds = GROUP_PATTERNS.iter().find(|gp| Some(gp.group_type) == group_type).map_or(&[][..], |gp| gp.member_relationships);
+        for entity in entities.iter() {
+            let related = entity.relationship_hint.as_deref().is_some_and(|r| kinds.contains(&r));
+            let nearby = (sentence_start..sentence_end).contains(&entity.position);
+            if entity.group_type.is_none() && related && nearby && !members.contains(&entity.id) {
+                members.push(entity.id.clone());
             }
         }
+        entities[g].members = members;
+    }
+
+    entities.sort_by(|a, b| a.position.cmp(&b.position).then_with(|| a.id.cmp(&b.id)));
+    entities
+}
+
+/// Find the best name candidate in the `start..end` context of `text`: the first known given
+/// name, else the first capitalized candidate, else the relationship term. The flag is set when
+/// the result is a capitalized candidate rather than a relationship term
+fn find_best_name_in_context(text: &str, start: usize, end: usize) -> (String, bool) {
+    let context = &text[start..end];
+    let candidates: Vec<String> = CAPITALIZED_NAME
+        .find_iter(context)
+        .filter_map(|m| name_candidate(text, start + m.start(), m.as_str()))
+        .map(|(_, name)| name)
+        .collect();
+    if let Some(name) = candidates.iter().find(|name| is_known_given_name(name)).or(candidates.first()) {
+        return (name.clone(), true);
     }
 
     // Fallback: extract relationship term
-    context
+    let term = context
         .split_whitespace()
         .find(|w| w.starts_with("my"))
         .map(|_| {
@@ -326,11 +929,12 @@ fn find_best_name_in_context(context: &str) -> String {
                 .unwrap_or("unknown")
         })
         .unwrap_or("unknown")
-        .to_string()
+        .to_string();
+    (term, false)
 }
 
 /// Infer relationship type from common words
-fn infer_relationship_from_word(word: &str) -> Option<String> {
+pub fn infer_relationship_from_word(word: &str) -> Option<String> {
     match word {
         "mom" | "mother" | "mama" | "mommy" => Some("mother".to_string()),
         "dad" | "father" | "papa" | "daddy" => Some("father".to_string()),
@@ -384,6 +988,94 @@ mod tests {
         assert_eq!(sarah_entity.unwrap().relationship_hint, Some("sister".to_string()));
     }
 
+    #[test]
+    fn test_entities_have_ids_in_text_order() {
+        let text = "Sarah, my sister, called. Later my husband John came home.";
+        let result = extract_entities(text);
+        assert!(result.entities.windows(2).all(|w| w[0].position <= w[1].position));
+        assert_eq!(result.entities[0].id, "sarah@0");
+    }
+
+    #[test]
+    fn test_extract_family_groups() {
+        let text = "We had dinner with the Johnsons, then the in-laws called. The Mondays are hard.";
+        let result = extract_entities(text);
+
+        let johnsons = result.entities.iter().find(|e| e.name == "the Johnsons").unwrap();
+        assert_eq!(johnsons.group_type.as_deref(), Some("family"));
+        assert_eq!(johnsons.relationship_hint, None);
+        let in_laws = result.entities.iter().find(|e| e.name == "in-laws").unwrap();
+        assert_eq!(in_laws.group_type.as_deref(), Some("in_laws"));
+        assert!(!result.entities.iter().any(|e| e.name.contains("Mondays") || e.name == "Johnsons"));
+        assert_eq!(extract_entities("My husband John called.").entities[0].group_type, None);
+
+        // The context window can't split a multi-byte character
+        let accented = extract_entities("éééééééééééééééééééééééééééééé the Johnsons came over");
+        assert!(accented.entities.iter().any(|e| e.name == "the Johnsons"));
+    }
+
+    #[test]
+    fn test_collective_groups_link_members() {
+        let text = "Lunch with my team, Sarah and Tom. My in-laws arrive Friday. My friend group is planning a trip and the board meets Monday.";
+        let result = extract_entities(text);
+        let group = |group_type: &str| result.entities.iter().find(|e| e.group_type.as_deref() == Some(group_type)).unwrap();
+
+        let team = group("team");
+        assert_eq!(team.members, vec!["sarah@20".to_string(), "tom@30".to_string()]);
+        let sarah = result.entities.iter().find(|e| e.id == "sarah@20").unwrap();
+        assert_eq!(sarah.relationship_hint.as_deref(), Some("colleague"));
+        assert!(group("in_laws").members.is_empty());
+        assert_eq!(group("friends").name, "friend group");
+        assert_eq!(group("board").relationship_hint, None);
+        assert!(!result.entities.iter().any(|e| e.group_type.is_none() && e.relationship_hint.as_deref() == Some("friend")));
+
+        let siblings = extract_entities("My siblings are great; my brother Tom called, and my sister Amy wrote.");
+        let group = siblings.entities.iter().find(|e| e.group_type.is_some()).unwrap();
+        assert_eq!(group.members.len(), 2);
+
+        // The member context window can't split a multi-byte character
+        let accented = extract_entities("…………………………………………………… my team, Ana and Bo, won");
+        assert_eq!(accented.entities.iter().find(|e| e.group_type.is_some()).unwrap().members.len(), 2);
+    }
+
+    #[test]
+    fn test_sentence_initial_words_are_not_names() {
+        let result = extract_entities("Honestly, my mom is fine. Tell Sarah, my sister, to call.");
+        let names: Vec<&str> = result.entities.iter().map(|e| e.name.as_str()).collect();
+        assert!(!names.contains(&"Honestly") && !names.contains(&"Tell Sarah"));
+        let sarah = result.entities.iter().find(|e| e.name == "Sarah").unwrap();
+        assert_eq!(sarah.relationship_hint.as_deref(), Some("sister"));
+
+        assert_eq!(name_candidate("Later. Mark called", 7, "Mark").map(|(_, n)| n).as_deref(), Some("Mark"));
+        assert_eq!(name_candidate("I told Will", 7, "Will").map(|(_, n)| n).as_deref(), Some("Will"));
+        assert_eq!(name_candidate("Will you come?", 0, "Will"), None);
+    }
+
+    #[test]
+    fn test_gazetteer_prefers_known_names() {
+        let text = "We met at Riverside with Emily yesterday.";
+        assert_eq!(find_best_name_in_context(text, 0, text.len()), ("Emily".to_string(), true));
+
+        let known = &extract_entities("My husband John called.").entities[0];
+        let unknown = &extract_entities("My husband Blorp called.").entities[0];
+        assert!(known.name_confidence > unknown.name_confidence && known.confidence > unknown.confidence);
+        assert_eq!(known.relationship_confidence, unknown.relationship_confidence);
+    }
+
+    #[test]
+    fn test_mentions_of_one_person_are_merged() {
+        let text = "Mom called this morning. Linda, my mother, never listens. I told my mom no.";
+        let result = extract_entities(text);
+        let mothers: Vec<&ExtractedEntity> =
+            result.entities.iter().filter(|e| e.relationship_hint.as_deref() == Some("mother")).collect();
+        assert_eq!(mothers.len(), 1);
+        assert_eq!(mothers[0].canonical_name, "Linda");
+        assert_eq!(mothers[0].aliases, vec!["Mom", "mother"]);
+
+        let dad = &extract_entities("My dad was there.").entities[0];
+        assert_eq!((dad.canonical_name.as_str(), dad.aliases.len()), ("Dad", 0));
+    }
+
     #[test]
     fn test_detect_pronouns() {
         let context = "My sister went to the store. She was happy about the sale.";
@@ -391,6 +1083,16 @@ mod tests {
         assert_eq!(pronouns, Some("she/her".to_string()));
     }
 
+    #[test]
+    fn test_quoted_speech_of_others_is_not_sentiment() {
+        let text = "My boss Dave forwarded the email: \"I hate this client, they're toxic.\" Back to work.";
+        let dave = &extract_entities(text).entities[0];
+        assert_eq!(dave.sentiment, None);
+
+        let own = "My sister Amy called. \"I love the new place,\" Amy said.";
+        assert_eq!(extract_entities(own).entities[0].sentiment.as_deref(), Some("positive"));
+    }
+
     #[test]
     fn test_detect_sentiment() {
         let positive = "I love spending time with my mom. She's so supportive.";
@@ -0,0 +1,186 @@
+//! Per-person sentiment trends
+//! Follows the sentiment around each person across a series of entries and classifies its course
+//! (improving, deteriorating, volatile, stable), for insights like "your mentions of your boss have
+//! gotten more negative". The crate keeps no entry store, so the host passes the entries to query
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::analytics_export::ExportEntry;
+use super::entity_extraction::extract_entities;
+
+/// Fewest sentiment-bearing mentions needed to call a trend
+const MIN_TREND_POINTS: usize = 3;
+
+/// Change in mean sentiment between the first and second half that counts as a trend
+const TREND_DELTA: f64 = 0.5;
+
+/// Mean swing between consecutive points that counts as volatile
+const VOLATILITY_THRESHOLD: f64 = 1.0;
+
+/// Trend query: entries in chronological order, optionally restricted to one person
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentimentTrendRequest {
+    pub entries: Vec<ExportEntry>,
+    /// Name, alias or relationship ("Dave", "boss") to report; every person when omitted
+    #[serde(default)]
+    pub entity: Option<String>,
+}
+
+/// Sentiment around a person in one entry
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SentimentPoint {
+    pub entry_id: String,
+    pub timestamp: Option<String>,
+    /// "positive", "negative" or "mixed"
+    pub sentiment: String,
+    /// 1 positive, 0 mixed, -1 negative
+    pub value: f64,
+}
+
+/// Course of the sentiment around one person
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EntitySentimentTrend {
+    pub name: String,
+    pub relationship: Option<String>,
+    /// "improving", "deteriorating", "volatile", "stable" or "insufficient_data"
+    pub trend: String,
+    /// Mean of the second half of the points minus mean of the first half
+    pub change: f64,
+    /// Mean absolute change between consecutive points, 0 - 2
+    pub volatility: f64,
+    pub points: Vec<SentimentPoint>,
+}
+
+fn sentiment_value(sentiment: &str) -> Option<f64> {
+    match sentiment {
+        "positive" => Some(1.0),
+        "mixed" => Some(0.0),
+        "negative" => Some(-1.0),
+        _ => None,
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+/// Classify a series of sentiment values
+fn classify(values: &[f64]) -> (&'static str, f64, f64) {
+    if values.len() < MIN_TREND_POINTS {
+        return ("insufficient_data", 0.0, 0.0);
+    }
+    let half = values.len() / 2;
+    let change = mean(&values[values.len() - half..]) - mean(&values[..half]);
+    let swings: Vec<f64> = values.windows(2).map(|w| (w[1] - w[0]).abs()).collect();
+    let volatility = mean(&swings);
+
+    let trend = if volatility >= VOLATILITY_THRESHOLD && volatility > change.abs() {
+        "volatile"
+    } else if change >= TREND_DELTA {
+        "improving"
+    } else if change <= -TREND_DELTA {
+        "deteriorating"
+    } else {
+        "stable"
+    };
+    (trend, change, volatility)
+}
+
+/// Sentiment trend per person across the entries, most mentioned first
+pub fn entity_sentiment_trends(request: &SentimentTrendRequest) -> Vec<EntitySentimentTrend> {
+    let mut trends: Vec<EntitySentimentTrend> = Vec::new();
+
+    for (index, entry) in request.entries.iter().enumerate() {
+        let entry_id = entry.id.clone().unwrap_or_else(|| index.to_string());
+        for entity in extract_entities(&entry.text).entities {
+            let Some((sentiment, value)) = entity.sentiment.and_then(|s| sentiment_value(&s).map(|v| (s, v))) else {
+                continue;
+            };
+            if let Some(query) = &request.entity {
+                let names = [&entity.name, &entity.canonical_name].into_iter().chain(&entity.aliases);
+                let matches = names.chain(&entity.relationship_hint).any(|n| n.eq_ignore_ascii_case(query.trim()));
+                if !matches {
+                    continue;
+                }
+            }
+
+            let slot = match trends.iter().position(|t| t.name.eq_ignore_ascii_case(&entity.canonical_name)) {
+                Some(slot) => slot,
+                None => {
+                    trends.push(EntitySentimentTrend {
+                        name: entity.canonical_name.clone(),
+                        relationship: None,
+                        trend: String::new(),
+                        change: 0.0,
+                        volatility: 0.0,
+                        points: Vec::new(),
+                    });
+                    trends.len() - 1
+                }
+            };
+            let trend = &mut trends[slot];
+            trend.relationship = trend.relationship.take().or(entity.relationship_hint);
+            trend.points.push(SentimentPoint {
+                entry_id: entry_id.clone(),
+                timestamp: entry.timestamp.clone(),
+                sentiment,
+                value,
+            });
+        }
+    }
+
+    for trend in trends.iter_mut() {
+        let values: Vec<f64> = trend.points.iter().map(|p| p.value).collect();
+        let (label, change, volatility) = classify(&values);
+        trend.trend = label.to_string();
+        trend.change = change;
+        trend.volatility = volatility;
+    }
+    trends.sort_by_key(|t| std::cmp::Reverse(t.points.len()));
+    trends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(texts: &[&str], entity: Option<&str>) -> SentimentTrendRequest {
+        SentimentTrendRequest {
+            entries: texts.iter().map(|t| ExportEntry { id: None, timestamp: None, text: t.to_string() }).collect(),
+            entity: entity.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_deteriorating_mentions_of_a_person() {
+        let texts = [
+            "My boss Dave is so supportive, I love working with him.",
+            "My sister Amy came over. My boss Dave was helpful today.",
+            "My boss Dave was difficult and I'm frustrated.",
+            "I hate how my boss Dave treats me, he's toxic.",
+        ];
+        let trends = entity_sentiment_trends(&request(&texts, Some("boss")));
+        assert_eq!(trends.len(), 1);
+        assert_eq!(trends[0].name, "Dave");
+        assert_eq!(trends[0].trend, "deteriorating");
+        assert_eq!(trends[0].points.len(), 4);
+        assert_eq!(trends[0].points[3].entry_id, "3");
+        assert!(trends[0].change < 0.0);
+    }
+
+    #[test]
+    fn test_classification() {
+        assert_eq!(classify(&[1.0, -1.0, 1.0, -1.0]).0, "volatile");
+        assert_eq!(classify(&[-1.0, 0.0, 1.0, 1.0]).0, "improving");
+        assert_eq!(classify(&[1.0, 1.0, 1.0]).0, "stable");
+        assert_eq!(classify(&[1.0, -1.0]).0, "insufficient_data");
+    }
+}
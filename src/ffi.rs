@@ -0,0 +1,70 @@
+//! C ABI export layer
+//! Lets iOS/Android native apps embed the core without going through a JS bridge
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+/// Analyze a NUL-terminated UTF-8 string
+///
+/// Returns a newly allocated NUL-terminated JSON string with the same shape as
+/// `detect_high_entropy_patterns`, or null when `text` is null or not valid UTF-8.
+/// The result must be released with `text_processor_free_result`.
+///
+/// # Safety
+/// `text` must be null or point to a valid NUL-terminated string that stays alive for the call.
+#[no_mangle]
+pub unsafe extern "C" fn text_processor_analyze(text: *const c_char) -> *mut c_char {
+    if text.is_null() {
+        return ptr::null_mut();
+    }
+
+    let text = match CStr::from_ptr(text).to_str() {
+        Ok(text) => text,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    // JSON output never contains interior NULs (they are escaped), so this can't fail in practice
+    match CString::new(super::detect_high_entropy_patterns(text)) {
+        Ok(json) => json.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Release a string returned by `text_processor_analyze`
+///
+/// # Safety
+/// `result` must be null or a pointer previously returned by this library that
+/// has not already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn text_processor_free_result(result: *mut c_char) {
+    if !result.is_null() {
+        drop(CString::from_raw(result));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_round_trip() {
+        let input = CString::new("You're so selfish").unwrap();
+        unsafe {
+            let result = text_processor_analyze(input.as_ptr());
+            assert!(!result.is_null());
+            let json = CStr::from_ptr(result).to_str().unwrap().to_string();
+            text_processor_free_result(result);
+            assert!(json.contains("character_judgment"));
+        }
+    }
+
+    #[test]
+    fn test_analyze_rejects_null_and_invalid_utf8() {
+        let invalid = [0xffu8, 0xfe, 0x00];
+        unsafe {
+            assert!(text_processor_analyze(ptr::null()).is_null());
+            assert!(text_processor_analyze(invalid.as_ptr() as *const c_char).is_null());
+            text_processor_free_result(ptr::null_mut());
+        }
+    }
+}
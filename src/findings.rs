@@ -0,0 +1,104 @@
+//! Top findings summary
+//! The few most significant matches of a text, one per category, for compact UI badges where
+//! the full match list is overkill
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::analyze;
+use super::highlight::severity_rank;
+use super::TextProcessingResult;
+
+/// Findings returned when the caller doesn't ask for a number
+pub const DEFAULT_FINDINGS: usize = 3;
+
+/// Strongest match of one category
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    pub pattern_type: String,
+    pub match_text: String,
+    pub position: usize,
+    pub severity: String,
+    /// Severity (high 1, medium 2/3, low 1/3) × weight × confidence
+    pub significance: f64,
+    /// Matches of the category in the text
+    pub occurrences: usize,
+}
+
+/// Top findings with the overall verdict
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FindingsSummary {
+    pub detected: bool,
+    pub score: f64,
+    /// Most significant first, at most one per pattern type
+    pub findings: Vec<Finding>,
+    /// Categories left out by the limit
+    pub omitted_categories: usize,
+}
+
+/// Top `limit` findings of an analysis result
+pub fn top_findings_from(result: &TextProcessingResult, limit: usize) -> FindingsSummary {
+    let mut findings: Vec<Finding> = Vec::new();
+    for pattern in &result.patterns {
+        let significance = severity_rank(&pattern.severity) as f64 / 3.0 * pattern.weight * pattern.confidence;
+        match findings.iter_mut().find(|f| f.pattern_type == pattern.pattern_type) {
+            Some(finding) => {
+                finding.occurrences += 1;
+                if significance > finding.significance {
+                    finding.match_text = pattern.match_text.clone();
+                    finding.position = pattern.position;
+                    finding.severity = pattern.severity.clone();
+                    finding.significance = significance;
+                }
+            }
+            None => findings.push(Finding {
+                pattern_type: pattern.pattern_type.clone(),
+                match_text: pattern.match_text.clone(),
+                position: pattern.position,
+                severity: pattern.severity.clone(),
+                significance,
+                occurrences: 1,
+            }),
+        }
+    }
+    findings.sort_by(|a, b| b.significance.total_cmp(&a.significance));
+    let omitted_categories = findings.len().saturating_sub(limit);
+    findings.truncate(limit);
+
+    FindingsSummary { detected: result.detected, score: result.score, findings, omitted_categories }
+}
+
+/// Analyze text and keep its top `limit` findings
+pub fn top_findings(text: &str, limit: usize) -> FindingsSummary {
+    top_findings_from(&analyze(text), limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_findings_are_ranked_and_deduplicated() {
+        let text = "You're an idiot. You're a loser. It's all your fault. You're so selfish.";
+        let summary = top_findings(text, 2);
+        assert_eq!(summary.findings.len(), 2);
+        assert!(summary.findings[0].significance >= summary.findings[1].significance);
+        let types: Vec<&str> = summary.findings.iter().map(|f| f.pattern_type.as_str()).collect();
+        assert_ne!(types[0], types[1]);
+
+        let all = top_findings(text, 10);
+        let insult = all.findings.iter().find(|f| f.pattern_type == "insult").unwrap();
+        assert_eq!(insult.occurrences, 2);
+        assert_eq!(summary.omitted_categories, all.findings.len() - 2);
+        assert_eq!(&text[insult.position..insult.position + insult.match_text.len()], insult.match_text);
+    }
+
+    #[test]
+    fn test_no_findings_for_calm_text() {
+        let summary = top_findings("Thanks for the lovely dinner.", DEFAULT_FINDINGS);
+        assert!(summary.findings.is_empty() && !summary.detected);
+        assert_eq!(summary.omitted_categories, 0);
+    }
+}
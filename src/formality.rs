@@ -0,0 +1,119 @@
+//! Formality scoring
+//! Register estimate from contractions, slang, sentence length and punctuation style
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::offsets::sentence_ranges;
+use super::tokenize::tokenize;
+
+/// Informal words and chat abbreviations, sorted for lookup
+const SLANG: &[&str] = &[
+    "bro", "btw", "cool", "dude", "gonna", "gotta", "haha", "idk", "imo", "kinda", "lmao", "lol", "nope", "ok",
+    "okay", "omg", "sorta", "stuff", "tbh", "u", "ur", "wanna", "ya", "yeah", "yep", "yo",
+];
+
+/// Sentence length (words) at which the length component is fully formal
+const FORMAL_SENTENCE_WORDS: f64 = 20.0;
+
+/// Contraction or slang rate at which that component is fully informal
+const INFORMAL_RATE: f64 = 0.1;
+
+/// Formality of a text
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FormalityResult {
+    /// 0 (very informal) to 1 (very formal)
+    pub score: f64,
+    /// `formal`, `neutral` or `informal`
+    pub register: String,
+    /// Contractions per word
+    pub contraction_rate: f64,
+    /// Slang words and abbreviations per word
+    pub slang_rate: f64,
+    pub mean_sentence_length: f64,
+    /// Share of sentences with a lowercase start, repeated "!!"/"??" or no closing punctuation
+    pub informal_punctuation: f64,
+}
+
+/// Sentence ranges end at the first terminator, so repeated punctuation is what follows the range
+fn is_informal_sentence(text: &str, start: usize, end: usize) -> bool {
+    let sentence = &text[start..end];
+    let lowercase_start = sentence.chars().find(|c| c.is_alphabetic()).is_some_and(|c| c.is_lowercase());
+    let unterminated = !sentence.trim_end_matches(['"', '\'', ')', '\u{201d}']).ends_with(['.', '!', '?']);
+    let repeated = sentence.ends_with(['!', '?']) && text[end..].starts_with(['!', '?']);
+    lowercase_start || unterminated || repeated
+}
+
+/// Estimate the formality of a text
+pub fn score_formality(text: &str) -> FormalityResult {
+    let tokens = tokenize(text);
+    // Runs like "!!" leave punctuation-only fragments behind; they are not sentences
+    let sentences: Vec<(usize, usize)> =
+        sentence_ranges(text).into_iter().filter(|&(s, e)| text[s..e].chars().any(char::is_alphanumeric)).collect();
+    if tokens.is_empty() {
+        return FormalityResult {
+            score: 0.5,
+            register: "neutral".to_string(),
+            contraction_rate: 0.0,
+            slang_rate: 0.0,
+            mean_sentence_length: 0.0,
+            informal_punctuation: 0.0,
+        };
+    }
+
+    let words = tokens.len() as f64;
+    let contraction_rate = tokens.iter().filter(|t| t.text.contains(['\'', '\u{2019}'])).count() as f64 / words;
+    let slang_rate = tokens.iter().filter(|t| SLANG.binary_search(&t.lower().as_str()).is_ok()).count() as f64 / words;
+    let mean_sentence_length = words / sentences.len().max(1) as f64;
+    let informal_punctuation =
+        sentences.iter().filter(|&&(s, e)| is_informal_sentence(text, s, e)).count() as f64 / sentences.len().max(1) as f64;
+
+    let components = [
+        (mean_sentence_length / FORMAL_SENTENCE_WORDS).min(1.0),
+        1.0 - (contraction_rate / INFORMAL_RATE).min(1.0),
+        1.0 - (slang_rate / INFORMAL_RATE).min(1.0),
+        1.0 - informal_punctuation,
+    ];
+    let score = components.iter().sum::<f64>() / components.len() as f64;
+    let register = match score {
+        s if s >= 0.65 => "formal",
+        s if s < 0.4 => "informal",
+        _ => "neutral",
+    };
+
+    FormalityResult {
+        score,
+        register: register.to_string(),
+        contraction_rate,
+        slang_rate,
+        mean_sentence_length,
+        informal_punctuation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formal_and_informal_registers() {
+        assert!(SLANG.windows(2).all(|w| w[0] < w[1]));
+        let formal = score_formality(
+            "Thank you for your message regarding the quarterly report. I would appreciate it if we could \
+             review the remaining figures together before the meeting on Thursday.",
+        );
+        let informal = score_formality("lol idk what u mean, gonna be late tbh!! can't wait tho");
+        assert_eq!(formal.register, "formal");
+        assert_eq!(informal.register, "informal");
+        assert!(informal.slang_rate > 0.3);
+        assert_eq!(informal.informal_punctuation, 1.0);
+    }
+
+    #[test]
+    fn test_empty_text_is_neutral() {
+        let result = score_formality("");
+        assert_eq!(result.register, "neutral");
+        assert_eq!(result.score, 0.5);
+    }
+}
@@ -0,0 +1,101 @@
+//! Given-name gazetteer
+//! A compact built-in list of common given names, extensible by the host, used by entity
+//! extraction to trust capitalized words that are real names and doubt the ones that aren't.
+//! Names that are also everyday words ("Will", "Hope", "Mark") are left out of the built-in list
+//! so the gazetteer never vouches for them
+
+use std::collections::BTreeSet;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+use super::memory::StoreUsage;
+
+/// Common given names, lowercase (sorted, binary-searched)
+const BUILTIN_GIVEN_NAMES: &[&str] = &[
+    "aaron", "abigail", "adam", "ahmed", "aisha", "alex", "alexander", "alice", "alicia", "amanda", "amelia", "amy",
+    "ana", "andrea", "andrew", "angela", "anna", "anne", "anthony", "ashley", "ava", "barbara", "ben", "benjamin",
+    "beth", "brandon", "brian", "caleb", "carlos", "carol", "caroline", "catherine", "charles", "charlie", "charlotte",
+    "chen", "chloe", "chris", "christina", "christopher", "claire", "daniel", "david", "deborah", "diana", "diego",
+    "dylan", "elena", "elizabeth", "ella", "emily", "emma", "eric", "ethan", "fatima", "gabriel", "george",
+    "hannah", "harry", "heather", "helen", "henry", "isaac", "isabella", "jack", "jacob", "james", "jamie", "jane",
+    "jasmine", "jason", "jennifer", "jessica", "john", "jonathan", "jose", "joseph", "joshua", "juan", "julia",
+    "justin", "karen", "kate", "katherine", "kevin", "kim", "laura", "lauren", "leah", "liam", "linda", "lisa",
+    "lucas", "lucy", "luis", "maria", "mary", "matthew", "megan", "melissa", "mia", "michael", "michelle", "mohammed",
+    "nathan", "nicholas", "nicole", "noah", "oliver", "olivia", "omar", "patricia", "paul", "priya", "rachel", "rebecca",
+    "richard", "robert", "ryan", "samantha", "samuel", "sandra", "sarah", "sean", "sofia", "sophia", "stephanie",
+    "steven", "susan", "thomas", "tyler", "victoria", "wei", "william", "yusuf", "zoe",
+];
+
+lazy_static! {
+    /// Host-supplied given names, lowercase
+    static ref HOST_GIVEN_NAMES: RwLock<BTreeSet<String>> = RwLock::new(BTreeSet::new());
+}
+
+/// Whether the first word of a name is a known given name
+pub fn is_known_given_name(name: &str) -> bool {
+    let Some(first) = name.split_whitespace().next() else {
+        return false;
+    };
+    let lower = first.to_lowercase();
+    BUILTIN_GIVEN_NAMES.binary_search(&lower.as_str()).is_ok()
+        || HOST_GIVEN_NAMES.read().map(|names| names.contains(&lower)).unwrap_or(false)
+}
+
+/// Confidence for a capitalized name candidate: raised for known given names, lowered otherwise
+pub fn gazetteer_confidence(name: &str, base: f64) -> f64 {
+    if is_known_given_name(name) {
+        (base + 0.1).min(0.95)
+    } else {
+        (base - 0.1).max(0.0)
+    }
+}
+
+/// Add given names from a JSON array of strings; returns the number of new names.
+/// Names loaded earlier are kept
+pub fn load_given_names(json: &str) -> Result<usize, String> {
+    let names: Vec<String> = serde_json::from_str(json).map_err(|e| format!("invalid name list: {}", e))?;
+    let mut host = HOST_GIVEN_NAMES.write().map_err(|_| "name list lock poisoned".to_string())?;
+    let before = host.len();
+    host.extend(names.iter().map(|name| name.trim().to_lowercase()).filter(|name| !name.is_empty()));
+    Ok(host.len() - before)
+}
+
+/// Remove all host-supplied given names
+pub fn clear_given_names() {
+    if let Ok(mut host) = HOST_GIVEN_NAMES.write() {
+        host.clear();
+    }
+}
+
+/// Report the size of the host-supplied name list
+pub fn given_names_usage() -> StoreUsage {
+    let (entries, approx_bytes) = HOST_GIVEN_NAMES
+        .read()
+        .map(|names| (names.len(), names.iter().map(String::len).sum()))
+        .unwrap_or((0, 0));
+
+    StoreUsage { name: "given_names".to_string(), entries, approx_bytes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_names_are_sorted_and_known() {
+        assert!(BUILTIN_GIVEN_NAMES.windows(2).all(|w| w[0] < w[1]));
+        assert!(is_known_given_name("Sarah Connor"));
+        assert!(!is_known_given_name("Tuesday"));
+        assert!(gazetteer_confidence("Emma", 0.8) > gazetteer_confidence("Lunchbox", 0.8));
+    }
+
+    #[test]
+    fn test_host_names_extend_the_list() {
+        assert!(!is_known_given_name("Zephyrine"));
+        assert_eq!(load_given_names(r#"["Zephyrine", " zephyrine ", ""]"#), Ok(1));
+        assert!(is_known_given_name("ZEPHYRINE"));
+        assert!(load_given_names(r#"{"names": []}"#).is_err());
+        assert!(given_names_usage().entries >= 1);
+    }
+}
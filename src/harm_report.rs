@@ -0,0 +1,155 @@
+//! Per-person harm report
+//! Groups attributed pattern matches by person, for "who in my life is this coming from" views
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::analyze_with_entities;
+use super::entity_extraction::{extract_entities, ExtractedEntity};
+use super::offsets::sentence_bounds;
+use super::TextProcessingResult;
+
+/// Example spans kept per category
+const MAX_EXAMPLES: usize = 3;
+
+/// One attributed match
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HarmExample {
+    pub match_text: String,
+    pub position: usize,
+    /// "source" or "target"
+    pub role: String,
+    /// Sentence containing the match
+    pub sentence: String,
+}
+
+/// Harmful language of one category associated with a person
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HarmCategory {
+    pub pattern_type: String,
+    pub count: usize,
+    /// First few matches, in text order
+    pub examples: Vec<HarmExample>,
+}
+
+/// Everything attributed to one person
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonHarm {
+    pub entity_id: String,
+    pub name: String,
+    pub relationship: Option<String>,
+    pub total: usize,
+    /// Matches the person said
+    pub as_source: usize,
+    /// Matches about the person
+    pub as_target: usize,
+    /// Most frequent category first
+    pub categories: Vec<HarmCategory>,
+}
+
+/// Per-person report for a text
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HarmReport {
+    /// People with at least one attributed match, most matches first
+    pub people: Vec<PersonHarm>,
+    /// Matches that couldn't be attributed to anyone
+    pub unattributed: usize,
+}
+
+/// Build the per-person report from entity-attributed analysis
+pub fn build_harm_report(text: &str) -> HarmReport {
+    harm_report_from(text, &extract_entities(text).entities, &analyze_with_entities(text))
+}
+
+/// Build the report from a text's extracted entities and its `analyze_with_entities` result
+pub fn harm_report_from(text: &str, entities: &[ExtractedEntity], result: &TextProcessingResult) -> HarmReport {
+    let mut people: Vec<PersonHarm> = entities
+        .iter()
+        .map(|entity| PersonHarm {
+            entity_id: entity.id.clone(),
+            name: entity.name.clone(),
+            relationship: entity.relationship_hint.clone(),
+            total: 0,
+            as_source: 0,
+            as_target: 0,
+            categories: Vec::new(),
+        })
+        .collect();
+    let mut categories: Vec<BTreeMap<String, HarmCategory>> = vec![BTreeMap::new(); people.len()];
+    let mut unattributed = 0;
+
+    for pattern in &result.patterns {
+        let Some(index) = pattern.entity_id.as_ref().and_then(|id| people.iter().position(|p| &p.entity_id == id)) else {
+            unattributed += 1;
+            continue;
+        };
+        let role = pattern.entity_role.clone().unwrap_or_default();
+        let person = &mut people[index];
+        person.total += 1;
+        if role == "source" {
+            person.as_source += 1;
+        } else {
+            person.as_target += 1;
+        }
+
+        let category = categories[index].entry(pattern.pattern_type.clone()).or_insert_with(|| HarmCategory {
+            pattern_type: pattern.pattern_type.clone(),
+            count: 0,
+            examples: Vec::new(),
+        });
+        category.count += 1;
+        if category.examples.len() < MAX_EXAMPLES {
+            let (start, end) = sentence_bounds(text, pattern.position);
+            category.examples.push(HarmExample {
+                match_text: pattern.match_text.clone(),
+                position: pattern.position,
+                role,
+                sentence: text[start..end].trim().to_string(),
+            });
+        }
+    }
+
+    for (person, categories) in people.iter_mut().zip(categories) {
+        person.categories = categories.into_values().collect();
+        person.categories.sort_by_key(|c| std::cmp::Reverse(c.count));
+    }
+    people.retain(|p| p.total > 0);
+    // Stable sort keeps text order among people with equal totals
+    people.sort_by_key(|p| std::cmp::Reverse(p.total));
+
+    HarmReport { people, unattributed }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_groups_matches_by_person() {
+        let text = "My boss called me an idiot. He said I'm a failure. My sister Amy is so supportive. What a disaster.";
+        let report = build_harm_report(text);
+        assert_eq!(report.people.len(), 1);
+        let boss = &report.people[0];
+        assert_eq!(boss.relationship.as_deref(), Some("boss"));
+        assert_eq!((boss.total, boss.as_source, boss.as_target), (2, 2, 0));
+        assert_eq!(boss.categories[0].pattern_type, "insult");
+        assert_eq!(boss.categories[0].examples[1].sentence, "He said I'm a failure.");
+        assert_eq!(report.unattributed, 1);
+    }
+
+    #[test]
+    fn test_examples_are_capped() {
+        let text = "My ex is a loser. My ex is an idiot. My ex is a clown. My ex is a fool.";
+        let report = build_harm_report(text);
+        let insults = &report.people[0].categories[0];
+        assert_eq!(insults.count, 4);
+        assert_eq!(insults.examples.len(), MAX_EXAMPLES);
+        assert!(insults.examples.iter().all(|e| e.role == "target"));
+    }
+}
@@ -0,0 +1,146 @@
+//! Intensity heatmap
+//! Per-token (or per flat character range) intensity from the weights of the matches covering
+//! it, for heatmap-style highlighting in rich text editors. Overlapping matches combine like
+//! independent probabilities, so stacked matches glow hotter without leaving 0 - 1
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::analyze;
+use super::tokenize::tokenize;
+use super::PatternMatchResult;
+
+/// Heatmap granularity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeatmapUnit {
+    /// One cell per word
+    Token,
+    /// One cell per flat range between match boundaries, covering the whole text
+    Range,
+}
+
+impl HeatmapUnit {
+    /// Parse a unit name, defaulting to tokens
+    pub fn parse(name: Option<&str>) -> Result<Self, String> {
+        match name.map(str::to_lowercase).as_deref() {
+            None | Some("token") | Some("word") => Ok(HeatmapUnit::Token),
+            Some("range") | Some("char") | Some("character") => Ok(HeatmapUnit::Range),
+            Some(other) => Err(format!("unsupported heatmap unit '{}'", other)),
+        }
+    }
+}
+
+/// Intensity of one token or range
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapCell {
+    /// Byte range
+    pub start: usize,
+    pub end: usize,
+    /// 0 - 1
+    pub intensity: f64,
+    /// Types of the covering matches, sorted and deduplicated
+    pub pattern_types: Vec<String>,
+}
+
+/// Heatmap of a text
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Heatmap {
+    /// "token" or "range"
+    pub unit: String,
+    pub cells: Vec<HeatmapCell>,
+    pub max_intensity: f64,
+}
+
+/// Cell covering `start..end` from the matches that overlap it
+fn cell(start: usize, end: usize, patterns: &[PatternMatchResult]) -> HeatmapCell {
+    let covering: Vec<&PatternMatchResult> =
+        patterns.iter().filter(|p| p.position < end && p.position + p.match_text.len() > start).collect();
+    let cold: f64 = covering.iter().map(|p| 1.0 - p.weight.clamp(0.0, 1.0)).product();
+    let mut pattern_types: Vec<String> = covering.iter().map(|p| p.pattern_type.clone()).collect();
+    pattern_types.sort_unstable();
+    pattern_types.dedup();
+    HeatmapCell { start, end, intensity: 1.0 - cold, pattern_types }
+}
+
+/// Heatmap from already-analyzed matches
+pub fn heatmap_from(text: &str, patterns: &[PatternMatchResult], unit: HeatmapUnit) -> Heatmap {
+    let cells: Vec<HeatmapCell> = match unit {
+        HeatmapUnit::Token => tokenize(text).iter().map(|t| cell(t.start, t.end, patterns)).collect(),
+        HeatmapUnit::Range => {
+            let mut boundaries: Vec<usize> = vec![0, text.len()];
+            for p in patterns {
+                boundaries.push(p.position);
+                boundaries.push((p.position + p.match_text.len()).min(text.len()));
+            }
+            boundaries.sort_unstable();
+            boundaries.dedup();
+            boundaries.windows(2).map(|w| cell(w[0], w[1], patterns)).collect()
+        }
+    };
+    let max_intensity = cells.iter().map(|c| c.intensity).fold(0.0, f64::max);
+
+    Heatmap {
+        unit: match unit {
+            HeatmapUnit::Token => "token",
+            HeatmapUnit::Range => "range",
+        }
+        .to_string(),
+        cells,
+        max_intensity,
+    }
+}
+
+/// Analyze text and build its heatmap
+pub fn intensity_heatmap(text: &str, unit: HeatmapUnit) -> Heatmap {
+    heatmap_from(text, &analyze(text).patterns, unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(pattern_type: &str, match_text: &str, position: usize, weight: f64) -> PatternMatchResult {
+        PatternMatchResult {
+            id: String::new(),
+            pattern_type: pattern_type.to_string(),
+            match_text: match_text.to_string(),
+            position,
+            severity: "high".to_string(),
+            weight,
+            confidence: 1.0,
+            quoted: None,
+            quote_index: None,
+            suggestion: None,
+            scope: None,
+            person: None,
+            entity_id: None,
+            entity_role: None,
+        }
+    }
+
+    #[test]
+    fn test_overlapping_weights_combine() {
+        let text = "aa bb cc dd";
+        let patterns = [pattern("x", "aa bb", 0, 0.5), pattern("y", "bb cc", 3, 0.5)];
+        let heatmap = heatmap_from(text, &patterns, HeatmapUnit::Token);
+        let intensities: Vec<f64> = heatmap.cells.iter().map(|c| c.intensity).collect();
+        assert_eq!(intensities, vec![0.5, 0.75, 0.5, 0.0]);
+        assert_eq!(heatmap.cells[1].pattern_types, vec!["x", "y"]);
+        assert_eq!(heatmap.max_intensity, 0.75);
+
+        let ranges = heatmap_from(text, &patterns, HeatmapUnit::Range);
+        let spans: Vec<(&str, f64)> = ranges.cells.iter().map(|c| (&text[c.start..c.end], c.intensity)).collect();
+        assert_eq!(spans, vec![("aa ", 0.5), ("bb", 0.75), (" cc", 0.5), (" dd", 0.0)]);
+    }
+
+    #[test]
+    fn test_heatmap_of_text() {
+        let heatmap = intensity_heatmap("Well, you're so selfish", HeatmapUnit::parse(None).unwrap());
+        assert_eq!(heatmap.unit, "token");
+        assert_eq!(heatmap.cells[0].intensity, 0.0);
+        assert!(heatmap.cells.last().unwrap().intensity > 0.0);
+        assert!(HeatmapUnit::parse(Some("pixel")).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! Highlight rendering
+//! Wraps matches in HTML or Markdown markup, splitting overlapping spans into flat segments
+
+use serde::Deserialize;
+
+use super::pattern_matching::{match_patterns, PatternMatch};
+
+/// Markup flavour
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HighlightFormat {
+    Html,
+    Markdown,
+}
+
+/// Rendering options (all optional on the JS side)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HighlightOptions {
+    pub format: HighlightFormat,
+    /// HTML element used for highlights
+    pub tag: String,
+    /// HTML attribute carrying the space-separated pattern types
+    pub type_attribute: String,
+    /// Optional class added to every HTML highlight
+    pub class_name: Option<String>,
+    /// Markdown delimiters placed around highlighted segments
+    pub markdown_open: String,
+    pub markdown_close: String,
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        HighlightOptions {
+            format: HighlightFormat::Html,
+            tag: "mark".to_string(),
+            type_attribute: "data-type".to_string(),
+            class_name: None,
+            markdown_open: "==".to_string(),
+            markdown_close: "==".to_string(),
+        }
+    }
+}
+
+/// Escape text for HTML element content and attribute values
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Restrict a configured tag/attribute name to safe characters
+fn sanitize_name(name: &str, fallback: &str) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        name.to_string()
+    } else {
+        fallback.to_string()
+    }
+}
+
+/// Rank severities so the strongest one can be reported per segment
+pub fn severity_rank(severity: &str) -> u8 {
+    match severity {
+        "high" => 3,
+        "medium" => 2,
+        "low" => 1,
+        _ => 0,
+    }
+}
+
+/// Flat segment of text with the matches covering it
+struct Segment<'a> {
+    start: usize,
+    end: usize,
+    active: Vec<&'a PatternMatch>,
+}
+
+/// Split text at every match boundary so overlapping spans never cross
+fn segments<'a>(text: &str, matches: &'a [PatternMatch]) -> Vec<Segment<'a>> {
+    let mut boundaries: Vec<usize> = vec![0, text.len()];
+    for m in matches {
+        boundaries.push(m.position);
+        boundaries.push(m.position + m.match_text.len());
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut result: Vec<Segment> = Vec::new();
+    for pair in boundaries.windows(2) {
+        let (start, end) = (pair[0], pair[1]);
+        let active: Vec<&PatternMatch> = matches
+            .iter()
+            .filter(|m| m.position <= start && m.position + m.match_text.len() >= end)
+            .collect();
+
+        // Merge with the previous segment when the covering matches are identical
+        if let Some(last) = result.last_mut() {
+            let same = last.active.len() == active.len()
+                && last.active.iter().zip(&active).all(|(a, b)| std::ptr::eq(*a, *b));
+            if same {
+                last.end = end;
+                continue;
+            }
+        }
+        result.push(Segment { start, end, active });
+    }
+    result
+}
+
+/// Render text with the given matches highlighted
+pub fn render_highlights(text: &str, matches: &[PatternMatch], options: &HighlightOptions) -> String {
+    let tag = sanitize_name(&options.tag, "mark");
+    let type_attribute = sanitize_name(&options.type_attribute, "data-type");
+    let mut out = String::with_capacity(text.len() * 2);
+
+    for segment in segments(text, matches) {
+        let content = &text[segment.start..segment.end];
+
+        if segment.active.is_empty() {
+            match options.format {
+                HighlightFormat::Html => out.push_str(&escape_html(content)),
+                HighlightFormat::Markdown => out.push_str(content),
+            }
+            continue;
+        }
+
+        match options.format {
+            HighlightFormat::Html => {
+                let mut types: Vec<&str> = segment.active.iter().map(|m| m.pattern_type.as_str()).collect();
+                types.sort_unstable();
+                types.dedup();
+                let severity = segment
+                    .active
+                    .iter()
+                    .map(|m| m.severity.as_str())
+                    .max_by_key(|s| severity_rank(s))
+                    .unwrap_or("");
+
+                out.push_str(&format!("<{} {}=\"{}\" data-severity=\"{}\"", tag, type_attribute, escape_html(&types.join(" ")), escape_html(severity)));
+                if let Some(class_name) = &options.class_name {
+                    out.push_str(&format!(" class=\"{}\"", escape_html(class_name)));
+                }
+                out.push('>');
+                out.push_str(&escape_html(content));
+                out.push_str(&format!("</{}>", tag));
+            }
+            HighlightFormat::Markdown => {
+                out.push_str(&options.markdown_open);
+                out.push_str(content);
+                out.push_str(&options.markdown_close);
+            }
+        }
+    }
+
+    out
+}
+
+/// Detect patterns and render them highlighted
+pub fn highlight_text(text: &str, options: &HighlightOptions) -> String {
+    render_highlights(text, &match_patterns(text), options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(pattern_type: &str, text: &str, needle: &str) -> PatternMatch {
+        PatternMatch {
+            pattern_id: String::new(),
+            pattern_type: pattern_type.to_string(),
+            match_text: needle.to_string(),
+            position: text.find(needle).unwrap(),
+            severity: "high".to_string(),
+            weight: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_overlapping_spans_do_not_cross() {
+        let text = "a b c d";
+        let matches = vec![pattern("x", text, "a b c"), pattern("y", text, "b c d")];
+        let html = render_highlights(text, &matches, &HighlightOptions::default());
+        assert_eq!(
+            html,
+            "<mark data-type=\"x\" data-severity=\"high\">a </mark>\
+             <mark data-type=\"x y\" data-severity=\"high\">b c</mark>\
+             <mark data-type=\"y\" data-severity=\"high\"> d</mark>"
+        );
+    }
+
+    #[test]
+    fn test_html_is_escaped_and_markdown_wraps() {
+        let text = "<b>idiot</b>";
+        let matches = vec![pattern("insult", text, "idiot")];
+        let html = render_highlights(text, &matches, &HighlightOptions::default());
+        assert!(html.starts_with("&lt;b&gt;<mark"));
+
+        let options = HighlightOptions { format: HighlightFormat::Markdown, ..HighlightOptions::default() };
+        assert_eq!(render_highlights(text, &matches, &options), "<b>==idiot==</b>");
+    }
+}
@@ -0,0 +1,79 @@
+//! Conditional and hypothetical scopes
+//! "If someone called you worthless, how would you feel?" mentions an insult without making one.
+//! Matches after a conditional or hypothetical trigger in the same sentence are tagged with the
+//! scope so consumers can discount or filter them
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::offsets::sentence_bounds;
+
+lazy_static! {
+    static ref CONDITIONAL_TRIGGER: Regex = Regex::new(r"(?i)\b(?:even\s+if|if|unless|in\s+case)\b").unwrap();
+    static ref HYPOTHETICAL_TRIGGER: Regex = Regex::new(
+        r"(?i)\b(?:what\s+if|imagine|suppose|supposing|hypothetically|let's\s+say|let\x{2019}s\s+say|pretend)\b"
+    )
+    .unwrap();
+}
+
+/// Span of text in a conditional or hypothetical scope (byte offsets, end exclusive)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scope {
+    /// End of the trigger word; a match must start at or after it to be in scope
+    pub start: usize,
+    pub end: usize,
+    /// "conditional" or "hypothetical"
+    pub kind: &'static str,
+}
+
+/// Conditional and hypothetical scopes, each running from its trigger to the end of the sentence
+pub fn hypothetical_scopes(text: &str) -> Vec<Scope> {
+    let mut scopes: Vec<Scope> = [(&*HYPOTHETICAL_TRIGGER, "hypothetical"), (&*CONDITIONAL_TRIGGER, "conditional")]
+        .iter()
+        .flat_map(|(trigger, kind)| {
+            trigger.find_iter(text).map(move |m| {
+                let (_, end) = sentence_bounds(text, m.start());
+                Scope { start: m.end(), end, kind }
+            })
+        })
+        .collect();
+    scopes.sort_by_key(|s| s.start);
+    scopes
+}
+
+/// Scope kind of a match starting at `position`; "what if" counts as hypothetical, not conditional.
+/// Matches that begin with the trigger itself ("if you loved me ...") are the pattern, not in scope
+pub fn scope_at(scopes: &[Scope], position: usize) -> Option<&'static str> {
+    let kinds: Vec<&'static str> =
+        scopes.iter().filter(|s| position >= s.start && position < s.end).map(|s| s.kind).collect();
+    if kinds.contains(&"hypothetical") {
+        Some("hypothetical")
+    } else {
+        kinds.first().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::pattern_matching::match_patterns;
+
+    #[test]
+    fn test_matches_in_hypothetical_scope_are_tagged() {
+        let text = "If someone called you an idiot, how would you feel? You're an idiot.";
+        let scopes = hypothetical_scopes(text);
+        let tagged: Vec<(String, Option<&str>)> =
+            match_patterns(text).into_iter().map(|m| (m.match_text, scope_at(&scopes, m.position))).collect();
+        assert_eq!(tagged, vec![("idiot".to_string(), Some("conditional")), ("idiot".to_string(), None)]);
+    }
+
+    #[test]
+    fn test_trigger_kinds_and_pattern_starting_at_trigger() {
+        let text = "What if I said you're useless? Imagine being this lazy.";
+        let scopes = hypothetical_scopes(text);
+        assert_eq!(scope_at(&scopes, text.find("you're").unwrap()), Some("hypothetical"));
+        assert_eq!(scope_at(&scopes, text.find("lazy").unwrap()), Some("hypothetical"));
+        // A pattern that starts at the trigger ("if you loved me") is the pattern itself, not scoped
+        assert_eq!(scope_at(&hypothetical_scopes("If you loved me you'd stay"), 0), None);
+    }
+}
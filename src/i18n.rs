@@ -0,0 +1,262 @@
+//! Label localization
+//! Display names for severities, risk tiers and pattern categories in the end user's language.
+//! A small built-in table covers the common labels; hosts can add or override labels at
+//! runtime. Lookups fall back from the regional tag to its language, then to English, then to
+//! the humanized key ("blame_shifting" becomes "Blame shifting")
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::memory::StoreUsage;
+use super::pattern_matching::COMPILED_PATTERNS;
+
+const SEVERITIES: &[&str] = &["high", "medium", "low"];
+const RISK_TIERS: &[&str] = &["none", "low", "moderate", "high"];
+
+/// Built-in labels by language, keyed `severity.*`, `tier.*` and `category.*`
+const BUILTIN_LABELS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "en",
+        &[
+            ("severity.high", "High"),
+            ("severity.medium", "Medium"),
+            ("severity.low", "Low"),
+            ("tier.none", "No concerns"),
+            ("tier.low", "Low risk"),
+            ("tier.moderate", "Moderate risk"),
+            ("tier.high", "High risk"),
+            ("category.gaslighting_invalidation", "Gaslighting (invalidation)"),
+            ("category.gaslighting_minimization", "Gaslighting (minimization)"),
+        ],
+    ),
+    (
+        "es",
+        &[
+            ("severity.high", "Alta"),
+            ("severity.medium", "Media"),
+            ("severity.low", "Baja"),
+            ("tier.none", "Sin indicios"),
+            ("tier.low", "Riesgo bajo"),
+            ("tier.moderate", "Riesgo moderado"),
+            ("tier.high", "Riesgo alto"),
+            ("category.insult", "Insulto"),
+            ("category.blame_shifting", "Desplazamiento de culpa"),
+            ("category.gaslighting", "Manipulación de la realidad"),
+            ("category.isolation", "Aislamiento"),
+            ("category.coercive_control", "Control coercitivo"),
+            ("category.catastrophizing", "Catastrofización"),
+            ("category.withdrawal", "Retraimiento"),
+            ("category.hopelessness", "Desesperanza"),
+        ],
+    ),
+    (
+        "fr",
+        &[
+            ("severity.high", "Élevée"),
+            ("severity.medium", "Moyenne"),
+            ("severity.low", "Faible"),
+            ("tier.none", "Aucun signal"),
+            ("tier.low", "Risque faible"),
+            ("tier.moderate", "Risque modéré"),
+            ("tier.high", "Risque élevé"),
+            ("category.insult", "Insulte"),
+            ("category.blame_shifting", "Rejet de la faute"),
+            ("category.gaslighting", "Détournement cognitif"),
+            ("category.isolation", "Isolement"),
+            ("category.coercive_control", "Contrôle coercitif"),
+            ("category.catastrophizing", "Catastrophisme"),
+            ("category.withdrawal", "Repli"),
+            ("category.hopelessness", "Désespoir"),
+        ],
+    ),
+    (
+        "de",
+        &[
+            ("severity.high", "Hoch"),
+            ("severity.medium", "Mittel"),
+            ("severity.low", "Niedrig"),
+            ("tier.none", "Keine Hinweise"),
+            ("tier.low", "Geringes Risiko"),
+            ("tier.moderate", "Mittleres Risiko"),
+            ("tier.high", "Hohes Risiko"),
+            ("category.insult", "Beleidigung"),
+            ("category.blame_shifting", "Schuldumkehr"),
+            ("category.gaslighting", "Gaslighting"),
+            ("category.isolation", "Isolation"),
+            ("category.coercive_control", "Zwangskontrolle"),
+            ("category.catastrophizing", "Katastrophisieren"),
+            ("category.withdrawal", "Rückzug"),
+            ("category.hopelessness", "Hoffnungslosigkeit"),
+        ],
+    ),
+    (
+        "pt",
+        &[
+            ("severity.high", "Alta"),
+            ("severity.medium", "Média"),
+            ("severity.low", "Baixa"),
+            ("tier.none", "Sem sinais"),
+            ("tier.low", "Risco baixo"),
+            ("tier.moderate", "Risco moderado"),
+            ("tier.high", "Risco alto"),
+            ("category.insult", "Insulto"),
+            ("category.blame_shifting", "Transferência de culpa"),
+            ("category.gaslighting", "Manipulação da realidade"),
+            ("category.isolation", "Isolamento"),
+            ("category.coercive_control", "Controle coercitivo"),
+            ("category.catastrophizing", "Catastrofização"),
+            ("category.withdrawal", "Retraimento"),
+            ("category.hopelessness", "Desesperança"),
+        ],
+    ),
+];
+
+lazy_static! {
+    /// Host-supplied labels by normalized language tag; take precedence over the built-in table
+    static ref HOST_LABELS: RwLock<BTreeMap<String, BTreeMap<String, String>>> = RwLock::new(BTreeMap::new());
+}
+
+/// Every label for one language
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelTable {
+    pub locale: String,
+    pub severities: BTreeMap<String, String>,
+    pub risk_tiers: BTreeMap<String, String>,
+    /// Display name for every built-in pattern type and any category the host labelled
+    pub categories: BTreeMap<String, String>,
+}
+
+/// Lowercase tag with `-` separators ("pt_BR" becomes "pt-br")
+fn normalize_tag(tag: &str) -> String {
+    tag.trim().replace('_', "-").to_lowercase()
+}
+
+/// Tags to try in order: the full tag, its language, then English
+fn fallback_chain(locale: &str) -> Vec<String> {
+    let tag = normalize_tag(locale);
+    let mut chain = vec![tag.clone()];
+    if let Some((language, _)) = tag.split_once('-') {
+        chain.push(language.to_string());
+    }
+    chain.push("en".to_string());
+    chain.dedup();
+    chain
+}
+
+/// "blame_shifting" as "Blame shifting"
+fn humanize(key: &str) -> String {
+    let words = key.rsplit('.').next().unwrap_or(key).replace('_', " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => words,
+    }
+}
+
+/// Label for a key such as `severity.high`, `tier.moderate` or `category.insult` in a locale
+pub fn label(key: &str, locale: &str) -> String {
+    let host = HOST_LABELS.read().ok();
+    for tag in fallback_chain(locale) {
+        if let Some(label) = host.as_ref().and_then(|labels| labels.get(&tag)?.get(key).cloned()) {
+            return label;
+        }
+        let builtin = BUILTIN_LABELS.iter().find(|(language, _)| *language == tag);
+        if let Some(&(_, label)) = builtin.and_then(|(_, labels)| labels.iter().find(|(k, _)| *k == key)) {
+            return label.to_string();
+        }
+    }
+    humanize(key)
+}
+
+/// Labels for `{prefix}.{key}` of each key, keyed by the bare key
+fn labels<'a>(prefix: &str, keys: impl Iterator<Item = &'a str>, locale: &str) -> BTreeMap<String, String> {
+    keys.map(|key| (key.to_string(), label(&format!("{}.{}", prefix, key), locale))).collect()
+}
+
+/// All labels for a locale
+pub fn label_table(locale: &str) -> LabelTable {
+    let mut category_types: Vec<String> = COMPILED_PATTERNS.iter().map(|p| p.pattern_type.to_string()).collect();
+    if let Ok(host) = HOST_LABELS.read() {
+        let host_keys = host.values().flat_map(|labels| labels.keys());
+        category_types.extend(host_keys.filter_map(|key| key.strip_prefix("category.")).map(str::to_string));
+    }
+    category_types.sort_unstable();
+    category_types.dedup();
+
+    LabelTable {
+        locale: normalize_tag(locale),
+        severities: labels("severity", SEVERITIES.iter().copied(), locale),
+        risk_tiers: labels("tier", RISK_TIERS.iter().copied(), locale),
+        categories: labels("category", category_types.iter().map(String::as_str), locale),
+    }
+}
+
+/// Add or override labels from JSON `{"es": {"category.insult": "Insulto"}, ...}`; returns the
+/// number of labels loaded. Earlier host labels for other keys are kept
+pub fn load_label_translations(json: &str) -> Result<usize, String> {
+    let translations: BTreeMap<String, BTreeMap<String, String>> =
+        serde_json::from_str(json).map_err(|e| format!("invalid translations: {}", e))?;
+    let mut host = HOST_LABELS.write().map_err(|_| "translations lock poisoned".to_string())?;
+    let mut count = 0;
+    for (locale, labels) in translations {
+        count += labels.len();
+        host.entry(normalize_tag(&locale)).or_default().extend(labels);
+    }
+    Ok(count)
+}
+
+/// Remove all host-supplied labels
+pub fn clear_label_translations() {
+    if let Ok(mut host) = HOST_LABELS.write() {
+        host.clear();
+    }
+}
+
+/// Report the size of the host-supplied labels
+pub fn label_translations_usage() -> StoreUsage {
+    let (entries, approx_bytes) = HOST_LABELS
+        .read()
+        .map(|host| {
+            let labels = host.values().flat_map(|labels| labels.iter());
+            labels.fold((0, 0), |(entries, bytes), (key, label)| (entries + 1, bytes + key.len() + label.len()))
+        })
+        .unwrap_or((0, 0));
+
+    StoreUsage { name: "label_translations".to_string(), entries, approx_bytes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_labels_and_fallbacks() {
+        assert_eq!(label("severity.high", "es-MX"), "Alta");
+        assert_eq!(label("tier.moderate", "fr"), "Risque modéré");
+        assert_eq!(label("tier.high", "ja"), "High risk");
+        assert_eq!(label("category.blame_shifting", "en"), "Blame shifting");
+        assert_eq!(label("category.projective_identification", "de"), "Projective identification");
+
+        let table = label_table("pt_BR");
+        assert_eq!(table.locale, "pt-br");
+        assert_eq!(table.severities["low"], "Baixa");
+        assert_eq!(table.categories["insult"], "Insulto");
+        assert!(table.categories.len() > 50);
+    }
+
+    #[test]
+    fn test_host_labels_override_and_extend() {
+        // A locale no other test uses, since host labels are process-wide
+        assert_eq!(load_label_translations(r#"{"nl": {"severity.high": "Hoog", "category.insult": "Belediging"}}"#), Ok(2));
+        assert_eq!(label("severity.high", "nl-BE"), "Hoog");
+        assert_eq!(label_table("nl").categories["insult"], "Belediging");
+        assert_eq!(label("severity.low", "nl"), "Low");
+        assert!(load_label_translations("[]").is_err());
+        assert!(label_translations_usage().entries >= 2);
+    }
+}
@@ -0,0 +1,111 @@
+//! Incident timing
+//! When flagged messages happen: clustering late at night and whether the gaps between incidents
+//! are shrinking (escalation) or growing, from timestamped message sequences
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const DAY_MS: i64 = 86_400_000;
+const HOUR_MS: i64 = 3_600_000;
+/// Late night runs from 22:00 to 05:59
+const LATE_NIGHT_START: usize = 22;
+const LATE_NIGHT_END: usize = 6;
+/// Ratio of later to earlier mean interval beyond which the intervals count as changing
+const INTERVAL_TREND_RATIO: f64 = 0.75;
+
+/// Temporal features of the flagged messages in a sequence
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IncidentTiming {
+    /// Flagged messages with a known time
+    pub incidents: usize,
+    /// Flagged messages per hour of day (0 - 23)
+    pub hour_counts: Vec<usize>,
+    /// Fraction of incidents between 22:00 and 06:00
+    pub late_night_share: f64,
+    /// Fraction of all timed messages between 22:00 and 06:00, for comparison
+    pub baseline_late_night_share: f64,
+    /// Milliseconds between consecutive incidents
+    pub intervals_ms: Vec<i64>,
+    /// "shrinking", "growing" or "stable" (fewer than two intervals is stable)
+    pub interval_trend: String,
+}
+
+fn hour(time: i64) -> usize {
+    (time.rem_euclid(DAY_MS) / HOUR_MS) as usize
+}
+
+fn late_night(time: i64) -> bool {
+    !(LATE_NIGHT_END..LATE_NIGHT_START).contains(&hour(time))
+}
+
+fn share(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+fn mean(values: &[i64]) -> f64 {
+    values.iter().sum::<i64>() as f64 / values.len().max(1) as f64
+}
+
+/// Timing features from message times (milliseconds on a clock whose day boundaries fall on
+/// multiples of 24h) and whether each message was flagged
+pub fn incident_timing(messages: &[(Option<i64>, bool)]) -> IncidentTiming {
+    let timed: Vec<(i64, bool)> = messages.iter().filter_map(|&(time, flagged)| Some((time?, flagged))).collect();
+    let incidents: Vec<i64> = timed.iter().filter(|(_, flagged)| *flagged).map(|&(time, _)| time).collect();
+
+    let mut hour_counts = vec![0; 24];
+    for &time in &incidents {
+        hour_counts[hour(time)] += 1;
+    }
+    let intervals_ms: Vec<i64> = incidents.windows(2).map(|w| w[1] - w[0]).collect();
+
+    let half = intervals_ms.len() / 2;
+    let (early, late) = (mean(&intervals_ms[..half]), mean(&intervals_ms[intervals_ms.len() - half..]));
+    let interval_trend = if half == 0 {
+        "stable"
+    } else if late < early * INTERVAL_TREND_RATIO {
+        "shrinking"
+    } else if early < late * INTERVAL_TREND_RATIO {
+        "growing"
+    } else {
+        "stable"
+    };
+
+    IncidentTiming {
+        incidents: incidents.len(),
+        hour_counts,
+        late_night_share: share(incidents.iter().filter(|&&t| late_night(t)).count(), incidents.len()),
+        baseline_late_night_share: share(timed.iter().filter(|&&(t, _)| late_night(t)).count(), timed.len()),
+        intervals_ms,
+        interval_trend: interval_trend.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_late_night_clustering() {
+        let at = |h: i64| Some(h * HOUR_MS);
+        let timing = incident_timing(&[(at(9), false), (at(13), false), (at(23), true), (at(26), true), (None, true)]);
+        assert_eq!(timing.incidents, 2);
+        assert_eq!((timing.hour_counts[23], timing.hour_counts[2]), (1, 1));
+        assert_eq!(timing.late_night_share, 1.0);
+        assert_eq!(timing.baseline_late_night_share, 0.5);
+    }
+
+    #[test]
+    fn test_shrinking_intervals() {
+        let days = [0, 7, 12, 15, 16, 17];
+        let messages: Vec<(Option<i64>, bool)> = days.iter().map(|d| (Some(d * DAY_MS), true)).collect();
+        let timing = incident_timing(&messages);
+        assert_eq!(timing.intervals_ms.len(), 5);
+        assert_eq!(timing.interval_trend, "shrinking");
+        assert_eq!(incident_timing(&messages[..2]).interval_trend, "stable");
+    }
+}
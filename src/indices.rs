@@ -0,0 +1,112 @@
+//! Text-level word-rate indices
+//! Rates of validated word lists per word of text, independent of pattern matching and its weights
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::tokenize::tokenize;
+
+/// Absolutist dictionary from Al-Mosaiwi & Johnstone (2018)
+const ABSOLUTIST_WORDS: &[&str] = &[
+    "absolutely", "all", "always", "complete", "completely", "constant", "constantly", "definitely", "entire", "ever",
+    "every", "everyone", "everything", "full", "must", "never", "nothing", "totally", "whole",
+];
+
+/// Hedges and qualifiers softening a statement; entries may span several words
+const HEDGE_WORDS: &[&str] = &[
+    "a bit", "a little", "almost", "apparently", "arguably", "fairly", "i believe", "i feel like", "i guess",
+    "i suppose", "i think", "it seems", "kind of", "kinda", "likely", "maybe", "might", "perhaps", "possibly",
+    "presumably", "probably", "rather", "seemingly", "slightly", "somewhat", "sort of", "sorta", "to some extent",
+];
+
+/// Occurrence of an index word
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexHit {
+    pub word: String,
+    pub position: usize,
+    pub end: usize,
+}
+
+/// Rate of index words in a text
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WordIndex {
+    /// Index words per word of text (0 - 1)
+    pub rate: f64,
+    pub count: usize,
+    pub word_count: usize,
+    pub hits: Vec<IndexHit>,
+}
+
+/// Rate of word-list entries; multi-word entries count once, longest match first
+fn word_index(text: &str, entries: &[&str]) -> WordIndex {
+    let tokens = tokenize(text);
+    let words: Vec<String> = tokens.iter().map(|t| t.lower().replace('\u{2019}', "'")).collect();
+    let entries: Vec<Vec<&str>> = entries.iter().map(|entry| entry.split(' ').collect()).collect();
+    let mut hits = Vec::new();
+
+    let mut i = 0;
+    while i < words.len() {
+        let length = entries
+            .iter()
+            .filter(|parts| words[i..].len() >= parts.len() && parts.iter().zip(&words[i..]).all(|(p, w)| p == w))
+            .map(|parts| parts.len())
+            .max();
+        match length {
+            Some(n) => {
+                let (position, end) = (tokens[i].start, tokens[i + n - 1].end);
+                hits.push(IndexHit { word: text[position..end].to_string(), position, end });
+                i += n;
+            }
+            None => i += 1,
+        }
+    }
+
+    let rate = if tokens.is_empty() { 0.0 } else { hits.len() as f64 / tokens.len() as f64 };
+    WordIndex { rate, count: hits.len(), word_count: tokens.len(), hits }
+}
+
+/// Absolutist-language index (always, never, completely, nothing, ...)
+pub fn absolutist_index(text: &str) -> WordIndex {
+    word_index(text, ABSOLUTIST_WORDS)
+}
+
+/// Hedging index (maybe, probably, kind of, I guess, ...)
+pub fn hedging_index(text: &str) -> WordIndex {
+    word_index(text, HEDGE_WORDS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_absolutist_rate() {
+        assert!(ABSOLUTIST_WORDS.windows(2).all(|w| w[0] < w[1]));
+        let text = "Nothing ever works. I ALWAYS mess up everything";
+        let index = absolutist_index(text);
+        assert_eq!(index.count, 4);
+        assert_eq!(index.word_count, 8);
+        assert_eq!(index.rate, 0.5);
+        assert_eq!(&text[index.hits[2].position..index.hits[2].end], "ALWAYS");
+    }
+
+    #[test]
+    fn test_hedging_phrases() {
+        assert!(HEDGE_WORDS.windows(2).all(|w| w[0] < w[1]));
+        let text = "I think it was kind of my fault, maybe";
+        let index = hedging_index(text);
+        let words: Vec<&str> = index.hits.iter().map(|h| h.word.as_str()).collect();
+        assert_eq!(words, vec!["I think", "kind of", "maybe"]);
+        assert_eq!(index.word_count, 9);
+    }
+
+    #[test]
+    fn test_empty_text() {
+        assert_eq!(hedging_index("").rate, 0.0);
+        let index = absolutist_index("");
+        assert_eq!(index.rate, 0.0);
+        assert!(index.hits.is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+//! Typographic intensity signals
+//! ALL-CAPS runs, repeated punctuation and elongated words, used to boost match weights
+//! and to compute a separate tone intensity score
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::offsets::sentence_bounds;
+use super::pattern_matching::PatternMatch;
+use super::tokenize::tokenize;
+
+/// Maximum relative weight boost for a match in an intense sentence
+const INTENSITY_WEIGHT_BOOST: f64 = 0.25;
+
+/// Words per unit of intensity when normalizing the tone score
+const WORDS_PER_SIGNAL: f64 = 25.0;
+
+/// Single typographic intensity signal
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IntensitySignal {
+    /// `all_caps`, `repeated_punctuation` or `elongation`
+    pub kind: String,
+    pub text: String,
+    pub position: usize,
+    pub end: usize,
+    /// 0 - 1
+    pub strength: f64,
+}
+
+/// Intensity signals with the text-level tone intensity score
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IntensityResult {
+    pub tone_intensity: f64,
+    pub signals: Vec<IntensitySignal>,
+}
+
+fn signal(kind: &str, text: &str, start: usize, end: usize, strength: f64) -> IntensitySignal {
+    IntensitySignal { kind: kind.to_string(), text: text[start..end].to_string(), position: start, end, strength: strength.min(1.0) }
+}
+
+/// Whether a word is shouted: two or more letters, all uppercase, not "I'M"-style single letters
+fn is_caps_word(word: &str) -> bool {
+    let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    letters.len() >= 2 && letters.iter().all(|c| c.is_uppercase())
+}
+
+/// Detect intensity signals in document order
+pub fn detect_intensity(text: &str) -> Vec<IntensitySignal> {
+    let mut signals = Vec::new();
+    let words: Vec<(usize, usize)> = tokenize(text).iter().map(|t| (t.start, t.end)).collect();
+
+    // ALL-CAPS: runs of two or more caps words, or a single caps word of four letters or more
+    let mut i = 0;
+    while i < words.len() {
+        if !is_caps_word(&text[words[i].0..words[i].1]) {
+            i += 1;
+            continue;
+        }
+        let mut j = i;
+        while j + 1 < words.len() && is_caps_word(&text[words[j + 1].0..words[j + 1].1]) {
+            j += 1;
+        }
+        let run = j - i + 1;
+        if run >= 2 || words[i].1 - words[i].0 >= 4 {
+            signals.push(signal("all_caps", text, words[i].0, words[j].1, 0.5 + 0.1 * (run - 1) as f64));
+        }
+        i = j + 1;
+    }
+
+    // Repeated punctuation: "!!", "???", "?!?"
+    let mut run_start = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (run_start, c == '!' || c == '?') {
+            (None, true) => run_start = Some(i),
+            (Some(s), false) => {
+                if i - s >= 2 {
+                    signals.push(signal("repeated_punctuation", text, s, i, 0.3 + 0.1 * (i - s - 2) as f64));
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    // Elongation: a letter repeated three or more times inside a word ("sooo", "nooooo")
+    for &(start, end) in &words {
+        let word = &text[start..end];
+        let mut longest = 1;
+        let mut current = 1;
+        let mut previous = None;
+        for c in word.chars().map(|c| c.to_ascii_lowercase()) {
+            current = if Some(c) == previous && c.is_alphabetic() { current + 1 } else { 1 };
+            longest = longest.max(current);
+            previous = Some(c);
+        }
+        if longest >= 3 {
+            signals.push(signal("elongation", text, start, end, 0.3 + 0.1 * (longest - 3) as f64));
+        }
+    }
+
+    signals.sort_by_key(|s| s.position);
+    signals
+}
+
+/// Text-level tone intensity (0 - 1), normalized by length so long calm texts stay low
+pub fn tone_intensity(text: &str, signals: &[IntensitySignal]) -> f64 {
+    let total: f64 = signals.iter().map(|s| s.strength).sum();
+    let word_count = text.split_whitespace().count() as f64;
+    (total / (1.0 + word_count / WORDS_PER_SIGNAL)).min(1.0)
+}
+
+/// Detect signals and compute the tone intensity score
+pub fn analyze_intensity(text: &str) -> IntensityResult {
+    let signals = detect_intensity(text);
+    IntensityResult { tone_intensity: tone_intensity(text, &signals), signals }
+}
+
+/// Boost each match by the strongest intensity signal in its sentence
+pub fn apply_intensity(text: &str, matches: &mut [PatternMatch], signals: &[IntensitySignal]) {
+    if signals.is_empty() {
+        return;
+    }
+    for m in matches.iter_mut() {
+        let (start, end) = sentence_bounds(text, m.position);
+        let strongest = signals
+            .iter()
+            .filter(|s| s.position < end && s.end > start)
+            .map(|s| s.strength)
+            .fold(0.0, f64::max);
+        m.weight *= 1.0 + INTENSITY_WEIGHT_BOOST * strongest;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_caps_punctuation_and_elongation() {
+        let signals = detect_intensity("I am SO DONE with this!!! Sooooo tired. Call the FBI");
+        let kinds: Vec<(&str, &str)> = signals.iter().map(|s| (s.kind.as_str(), s.text.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![("all_caps", "SO DONE"), ("repeated_punctuation", "!!!"), ("elongation", "Sooooo")]
+        );
+        assert!(tone_intensity("calm words only", &[]) == 0.0);
+    }
+
+    #[test]
+    fn test_intensity_boosts_same_sentence_matches() {
+        let text = "YOU ARE SO LAZY!!! You're so selfish.";
+        let signals = detect_intensity(text);
+        let mut matches = super::super::pattern_matching::match_patterns(text);
+        let before: Vec<f64> = matches.iter().map(|m| m.weight).collect();
+        apply_intensity(text, &mut matches, &signals);
+        for (m, weight) in matches.iter().zip(before) {
+            if m.position < text.find("You're").unwrap() {
+                assert!(m.weight > weight);
+            } else {
+                assert_eq!(m.weight, weight);
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Goal and intention extraction
+//! Finds first-person statements of goals ("I want to...") and intentions ("I'm going to...")
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::entity_extraction::extract_entities;
+
+lazy_static! {
+    /// Intention cues as (regex, kind, confidence); the action clause follows the match,
+    /// or starts at capture group 1 when the cue includes the action verb
+    static ref INTENTION_CUES: Vec<(Regex, &'static str, f64)> = [
+        (r"\bI\s+(?:really\s+)?(?:want|wanna|hope|wish)\s+to\b", "goal", 0.7),
+        (r"\bI(?:'d|\s+would)\s+(?:really\s+)?(?:like|love)\s+to\b", "goal", 0.7),
+        (r"\bmy\s+(?:goal|aim|plan|intention|resolution)\s+is\s+to\b", "goal", 0.9),
+        (r"\bI(?:'m|\s+am)\s+(?:going\s+to|gonna)\b", "intention", 0.8),
+        (r"\bI(?:'m|\s+am)\s+(?:planning|determined|trying|committed)\s+to\b", "intention", 0.8),
+        (r"\bI(?:'ve|\s+have)\s+decided\s+to\b", "intention", 0.9),
+        (r"\bI\s+(?:plan|intend|promise|need)\s+to\b", "intention", 0.8),
+        (r"\bI(?:'ll|\s+will)\s+(?:definitely\s+|finally\s+)?((?:start|stop|try|talk|tell|set|call|ask|make|begin|quit)\b)", "intention", 0.7),
+    ]
+    .iter()
+    .map(|(pattern, kind, confidence)| (Regex::new(&format!("(?i){}", pattern)).unwrap(), *kind, *confidence))
+    .collect();
+    /// Where the action clause ends: sentence punctuation or a contrasting/causal clause
+    static ref CLAUSE_END: Regex = Regex::new(r"(?i)[.!?;\n]|,?\s+(?:but|because|although|though)\b").unwrap();
+}
+
+/// Stated goal or intention
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Intention {
+    /// `"goal"` (a desired outcome) or `"intention"` (a committed action)
+    pub kind: String,
+    /// Full statement, e.g. "I want to set a boundary with my mom"
+    pub text: String,
+    /// Action clause after the cue, e.g. "set a boundary with my mom"
+    pub action: String,
+    /// People named in the action clause
+    pub people: Vec<String>,
+    pub confidence: f64,
+    pub position: usize,
+    pub end: usize,
+}
+
+/// Extract goals and intentions in document order
+pub fn extract_intentions(text: &str) -> Vec<Intention> {
+    let entities = extract_entities(text).entities;
+    let mut intentions: Vec<Intention> = Vec::new();
+
+    for (regex, kind, confidence) in INTENTION_CUES.iter() {
+        for captures in regex.captures_iter(text) {
+            let Some(cue) = captures.get(0) else { continue };
+            // Overlapping cues ("I'm going to start") are reported once
+            if intentions.iter().any(|i| cue.start() >= i.position && cue.start() < i.end) {
+                continue;
+            }
+            let action_start = match captures.get(1) {
+                Some(verb) => verb.start(),
+                None => cue.end() + (text[cue.end()..].len() - text[cue.end()..].trim_start().len()),
+            };
+            let clause_end = CLAUSE_END
+                .find(&text[cue.end()..])
+                .map(|m| cue.end() + m.start())
+                .unwrap_or(text.len());
+            let action = text[action_start..clause_end.max(action_start)].trim_end();
+            if action.is_empty() {
+                continue;
+            }
+
+            let end = action_start + action.len();
+            let people = entities
+                .iter()
+                .filter(|e| e.position >= action_start && e.position < end)
+                .map(|e| e.name.clone())
+                .collect();
+
+            intentions.push(Intention {
+                kind: kind.to_string(),
+                text: text[cue.start()..end].to_string(),
+                action: action.to_string(),
+                people,
+                confidence: *confidence,
+                position: cue.start(),
+                end,
+            });
+        }
+    }
+
+    intentions.sort_by_key(|i| i.position);
+    intentions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_goals_and_intentions() {
+        let text = "I want to set a boundary with my mom, but it's hard. I'm going to start therapy next week!";
+        let intentions = extract_intentions(text);
+        assert_eq!(intentions.len(), 2);
+        assert_eq!(intentions[0].kind, "goal");
+        assert_eq!(intentions[0].action, "set a boundary with my mom");
+        assert_eq!(intentions[0].text, "I want to set a boundary with my mom");
+        assert_eq!(intentions[1].kind, "intention");
+        assert_eq!(intentions[1].action, "start therapy next week");
+        assert_eq!(&text[intentions[1].position..intentions[1].end], intentions[1].text);
+
+        let intentions = extract_intentions("I'll start journaling.");
+        assert_eq!(intentions[0].action, "start journaling");
+    }
+
+    #[test]
+    fn test_no_intentions_in_plain_text() {
+        assert!(extract_intentions("We went to the park yesterday.").is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+//! Keyword extraction
+//! Content words (nouns, main verbs, adjectives) picked out with the part-of-speech tagger,
+//! so pronouns and function words like "you" and "your" don't crowd out the topic, and
+//! ranked by frequency, rarity in everyday messages and how early they appear; noun-phrase
+//! chunks of the same tags give multi-word keyphrases. Names and relationship terms found by
+//! entity extraction are people, not topics, and are reported separately
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use lazy_static::lazy_static;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::baseline::REFERENCE_CORPUS;
+use super::entity_extraction::{extract_entities, ExtractedEntity};
+use super::pos::{tag, Tag, TaggedToken};
+use super::tokenize::{is_stopword, tokenize, Token};
+
+/// Shortest word kept as a keyword
+const MIN_KEYWORD_CHARS: usize = 3;
+
+/// Longest noun phrase kept as a keyphrase
+const MAX_PHRASE_WORDS: usize = 4;
+
+/// Extra weight for a keyword first seen at the very start of the text (none at the end)
+const POSITION_BONUS: f64 = 0.5;
+
+lazy_static! {
+    /// Number of reference messages each lowercase word appears in
+    static ref REFERENCE_DOCUMENT_FREQUENCY: HashMap<String, usize> = {
+        let mut frequency = HashMap::new();
+        for line in REFERENCE_CORPUS.lines() {
+            let mut words: Vec<String> = tokenize(line).iter().map(Token::lower).collect();
+            words.sort();
+            words.dedup();
+            for word in words {
+                *frequency.entry(word).or_insert(0) += 1;
+            }
+        }
+        frequency
+    };
+
+    static ref REFERENCE_MESSAGE_COUNT: usize = REFERENCE_CORPUS.lines().filter(|l| !l.trim().is_empty()).count();
+}
+
+/// Keyword with its ranking score
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedKeyword {
+    pub keyword: String,
+    /// Frequency x inverse commonness x position bonus
+    pub score: f64,
+    pub count: usize,
+    /// Byte offset of the first occurrence
+    pub position: usize,
+}
+
+/// Person mentioned in the text, kept out of the keywords
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityMention {
+    pub name: String,
+    pub relationship: Option<String>,
+    pub position: usize,
+}
+
+/// Ranked keywords and keyphrases with the people they exclude
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KeywordExtraction {
+    pub keywords: Vec<RankedKeyword>,
+    pub keyphrases: Vec<RankedKeyword>,
+    pub entities_mentioned: Vec<EntityMention>,
+}
+
+/// Lowercase words of entity names and relationship terms ("sarah", "sister", "mom")
+fn entity_words(entities: &[ExtractedEntity]) -> HashSet<String> {
+    entities
+        .iter()
+        .flat_map(|e| {
+            let name = tokenize(&e.name).iter().map(Token::lower).collect::<Vec<_>>();
+            let context = tokenize(&e.relationship_context).iter().map(Token::lower).collect::<Vec<_>>();
+            name.into_iter().chain(context).chain(e.relationship_hint.clone())
+        })
+        .filter(|w| !is_stopword(w))
+        .collect()
+}
+
+/// Inverse commonness of a word: rarer in everyday messages scores higher (always >= 1)
+fn inverse_commonness(word: &str) -> f64 {
+    let df = REFERENCE_DOCUMENT_FREQUENCY.get(word).copied().unwrap_or(0) as f64;
+    ((*REFERENCE_MESSAGE_COUNT as f64 + 1.0) / (df + 1.0)).ln() + 1.0
+}
+
+/// Whether a tagged word qualifies as a keyword
+fn is_keyword(t: &TaggedToken, excluded: &HashSet<String>) -> bool {
+    let word = t.token.lower();
+    t.tag.is_content() && word.chars().count() >= MIN_KEYWORD_CHARS && !is_stopword(&word) && !excluded.contains(&word)
+}
+
+/// Lowercase content-word keywords, sorted and deduplicated
+pub fn content_keywords(text: &str) -> Vec<String> {
+    let excluded = entity_words(&extract_entities(text).entities);
+    let mut keywords: Vec<String> =
+        tag(text).iter().filter(|t| is_keyword(t, &excluded)).map(|t| t.token.lower()).collect();
+    keywords.sort();
+    keywords.dedup();
+    keywords
+}
+
+/// Rank occurrences (keyword or phrase, start offset): frequency x inverse commonness
+/// (summed over a phrase's words) x position bonus, best first
+fn rank(text: &str, occurrences: Vec<(String, usize)>, top_k: Option<usize>) -> Vec<RankedKeyword> {
+    // (count, first position) per entry
+    let mut seen: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for (keyword, position) in occurrences {
+        seen.entry(keyword).or_insert((0, position)).0 += 1;
+    }
+
+    let length = text.len().max(1) as f64;
+    let mut ranked: Vec<RankedKeyword> = seen
+        .into_iter()
+        .map(|(keyword, (count, position))| {
+            let commonness: f64 = keyword.split(' ').map(inverse_commonness).sum();
+            RankedKeyword {
+                score: count as f64 * commonness * (1.0 + POSITION_BONUS * (1.0 - position as f64 / length)),
+                keyword,
+                count,
+                position,
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.position.cmp(&b.position)));
+    if let Some(k) = top_k {
+        ranked.truncate(k);
+    }
+    ranked
+}
+
+fn keyword_occurrences(text: &str, excluded: &HashSet<String>) -> Vec<(String, usize)> {
+    tag(text).iter().filter(|t| is_keyword(t, excluded)).map(|t| (t.token.lower(), t.token.start)).collect()
+}
+
+/// Every keyword occurrence as (keyword, start, end) byte ranges
+pub fn keyword_spans(text: &str) -> Vec<(String, usize, usize)> {
+    let excluded = entity_words(&extract_entities(text).entities);
+    tag(text)
+        .iter()
+        .filter(|t| is_keyword(t, &excluded))
+        .map(|t| (t.token.lower(), t.token.start, t.token.end))
+        .collect()
+}
+
+/// Keywords ranked by score, best first; `top_k` limits the list
+pub fn rank_keywords(text: &str, top_k: Option<usize>) -> Vec<RankedKeyword> {
+    let excluded = entity_words(&extract_entities(text).entities);
+    rank(text, keyword_occurrences(text, &excluded), top_k)
+}
+
+/// Noun-phrase chunks of adjectives followed by nouns ("silent treatment", "performance review"),
+/// as (lowercase phrase, start offset); single words are left to `rank_keywords`
+fn noun_phrases(text: &str, excluded: &HashSet<String>) -> Vec<(String, usize)> {
+    let mut phrases = Vec::new();
+    let mut chunk: Vec<&TaggedToken> = Vec::new();
+    let tagged = tag(text);
+
+    let mut flush = |chunk: &mut Vec<&TaggedToken>| {
+        while chunk.last().is_some_and(|t| t.tag != Tag::Noun) {
+            chunk.pop();
+        }
+        if chunk.len() >= 2 {
+            let words: Vec<String> = chunk.iter().map(|t| t.token.lower()).collect();
+            phrases.push((words.join(" "), chunk[0].token.start));
+        }
+        chunk.clear();
+    };
+
+    for t in &tagged {
+        let joined = chunk.last().is_some_and(|last| {
+            text[last.token.end..t.token.start].chars().all(|c| c == ' ' || c == '-')
+                && !(last.tag == Tag::Noun && t.tag == Tag::Adjective)
+        });
+        if !joined || chunk.len() == MAX_PHRASE_WORDS {
+            flush(&mut chunk);
+        }
+        if matches!(t.tag, Tag::Noun | Tag::Adjective) && is_keyword(t, excluded) {
+            chunk.push(t);
+        } else {
+            flush(&mut chunk);
+        }
+    }
+    flush(&mut chunk);
+    phrases
+}
+
+/// Multi-word keyphrases ranked like keywords, best first; `top_k` limits the list
+pub fn rank_keyphrases(text: &str, top_k: Option<usize>) -> Vec<RankedKeyword> {
+    let excluded = entity_words(&extract_entities(text).entities);
+    rank(text, noun_phrases(text, &excluded), top_k)
+}
+
+/// Ranked keywords and keyphrases plus the people mentioned, from one entity extraction pass
+pub fn extract_keywords_and_entities(text: &str, top_k: Option<usize>) -> KeywordExtraction {
+    let entities = extract_entities(text).entities;
+    let excluded = entity_words(&entities);
+    KeywordExtraction {
+        keywords: rank(text, keyword_occurrences(text, &excluded), top_k),
+        keyphrases: rank(text, noun_phrases(text, &excluded), top_k),
+        entities_mentioned: entities
+            .into_iter()
+            .map(|e| EntityMention { name: e.name, relationship: e.relationship_hint, position: e.position })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keywords_skip_pronouns() {
+        let keywords = content_keywords("You always blame me for your mistakes and you never listen");
+        assert_eq!(keywords, vec!["blame", "listen", "mistakes"]);
+    }
+
+    #[test]
+    fn test_keywords_keep_nouns_and_adjectives() {
+        let keywords = content_keywords("The custody hearing was stressful. My lawyer seemed confident.");
+        assert!(keywords.contains(&"custody".to_string()));
+        assert!(keywords.contains(&"hearing".to_string()));
+        assert!(keywords.contains(&"stressful".to_string()));
+        assert!(keywords.contains(&"lawyer".to_string()));
+        assert!(!keywords.contains(&"the".to_string()));
+    }
+
+    #[test]
+    fn test_rank_keywords_by_frequency_rarity_and_position() {
+        let text = "The custody hearing is Monday. I dread the hearing, and work is busy.";
+        let ranked = rank_keywords(text, None);
+        assert_eq!(ranked[0].keyword, "hearing");
+        assert_eq!(ranked[0].count, 2);
+        assert!(ranked.windows(2).all(|w| w[0].score >= w[1].score));
+
+        // "work" is common in everyday messages, so it ranks below the rarer "custody"
+        let rank = |word: &str| ranked.iter().position(|k| k.keyword == word).unwrap();
+        assert!(rank("custody") < rank("work"));
+        assert_eq!(rank_keywords(text, Some(2)).len(), 2);
+    }
+
+    #[test]
+    fn test_keyphrases_from_noun_phrases() {
+        let text = "He gave me the silent treatment after my performance review. The silent treatment hurt.";
+        let phrases = rank_keyphrases(text, None);
+        let names: Vec<&str> = phrases.iter().map(|p| p.keyword.as_str()).collect();
+        assert_eq!(names, vec!["silent treatment", "performance review"]);
+        assert_eq!(phrases[0].count, 2);
+        assert_eq!(phrases[0].position, text.find("silent").unwrap());
+    }
+
+    #[test]
+    fn test_entities_excluded_from_keywords() {
+        let text = "My sister Sarah ruined the birthday dinner again.";
+        let keywords = content_keywords(text);
+        assert!(!keywords.iter().any(|k| k == "sarah" || k == "sister"));
+        assert!(keywords.contains(&"dinner".to_string()));
+
+        let extraction = extract_keywords_and_entities(text, None);
+        assert!(extraction.entities_mentioned.iter().any(|e| e.name == "Sarah" && e.relationship.as_deref() == Some("sister")));
+        assert!(extraction.keyphrases.iter().any(|p| p.keyword == "birthday dinner"));
+    }
+}
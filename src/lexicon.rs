@@ -0,0 +1,229 @@
+//! Category lexicon word counting
+//! LIWC-style per-category word proportions from the built-in dictionary or a host-provided one
+//! (JSON `{"category": ["word", "prefix*"]}` or the LIWC `.dic` format)
+
+use std::collections::{BTreeMap, HashMap};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::tokenize::tokenize;
+
+/// Built-in categories; a trailing `*` matches any word with that prefix
+const DEFAULT_LEXICON: &[(&str, &[&str])] = &[
+    ("i", &["i", "me", "my", "mine", "myself", "i'm", "i've", "i'll", "i'd"]),
+    ("we", &["we", "us", "our", "ours", "ourselves", "we're", "we've", "we'll", "let's"]),
+    ("social", &["friend*", "family", "talk*", "told", "tell*", "said", "say*", "call*", "share*", "people", "someone", "everyone", "together", "mom", "dad", "partner*", "boss*", "team*", "they", "them", "he", "she", "you"]),
+    ("positive_emotion", &["happy", "happi*", "love*", "loving", "glad", "joy*", "grateful", "thank*", "hope*", "proud", "calm*", "relax*", "excit*", "good", "great", "nice", "fun", "enjoy*", "kind*", "care", "caring"]),
+    ("anxiety", &["anxi*", "worr*", "nervous*", "afraid", "fear*", "scare*", "panic*", "stress*", "tense", "uneasy", "overwhelm*"]),
+    ("anger", &["angry", "anger*", "mad", "furious", "hate*", "hatred", "annoy*", "irritat*", "resent*", "rage*", "frustrat*", "pissed"]),
+    ("sadness", &["sad", "sadness", "cry*", "cried", "tears", "lonely", "loneli*", "grief", "griev*", "hurt*", "depress*", "miserable", "hopeless*", "empty", "miss", "missed", "missing"]),
+    ("cognition", &["think*", "thought*", "know*", "knew", "understand*", "understood", "realiz*", "realis*", "because", "cause*", "reason*", "consider*", "decid*", "believe*", "wonder*", "maybe", "perhaps", "should", "why", "meaning*"]),
+    ("body", &["body", "bodies", "head*", "heart*", "stomach*", "chest", "hand*", "eye*", "sleep*", "tired", "ache*", "pain*", "breath*", "skin", "face"]),
+];
+
+/// Parent categories counting every word of their children, as in LIWC's hierarchy
+const PARENT_CATEGORIES: &[(&str, &[&str])] = &[
+    ("negative_emotion", &["anxiety", "anger", "sadness"]),
+    ("affect", &["positive_emotion", "anxiety", "anger", "sadness"]),
+];
+
+/// Words of one category found in a text
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryCount {
+    pub category: String,
+    pub count: usize,
+    /// Share of all words in the text (0 - 1)
+    pub proportion: f64,
+}
+
+/// Per-category counts for a text, in dictionary order (categories without hits included)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LexiconCounts {
+    pub word_count: usize,
+    pub categories: Vec<CategoryCount>,
+}
+
+/// Dictionary mapping words and prefixes to category indices
+#[derive(Debug, Clone, Default)]
+pub struct Lexicon {
+    categories: Vec<String>,
+    words: HashMap<String, Vec<usize>>,
+    /// Longest prefixes first so the most specific entry wins
+    prefixes: Vec<(String, Vec<usize>)>,
+}
+
+impl Lexicon {
+    fn category_index(&mut self, name: &str) -> usize {
+        match self.categories.iter().position(|c| c == name) {
+            Some(index) => index,
+            None => {
+                self.categories.push(name.to_string());
+                self.categories.len() - 1
+            }
+        }
+    }
+
+    fn add(&mut self, entry: &str, category: usize) {
+        let entry = entry.trim().to_lowercase().replace('\u{2019}', "'");
+        let categories = match entry.strip_suffix('*') {
+            Some(prefix) => match self.prefixes.iter().position(|(p, _)| p == prefix) {
+                Some(index) => &mut self.prefixes[index].1,
+                None => {
+                    self.prefixes.push((prefix.to_string(), Vec::new()));
+                    &mut self.prefixes.last_mut().unwrap().1
+                }
+            },
+            None => self.words.entry(entry).or_default(),
+        };
+        if !categories.contains(&category) {
+            categories.push(category);
+        }
+    }
+
+    fn finish(mut self) -> Self {
+        self.prefixes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        self
+    }
+
+    /// Built-in dictionary
+    pub fn builtin() -> Self {
+        let mut lexicon = Lexicon::default();
+        for (category, entries) in DEFAULT_LEXICON {
+            let mut targets = vec![lexicon.category_index(category)];
+            for (parent, children) in PARENT_CATEGORIES {
+                if children.contains(category) {
+                    targets.push(lexicon.category_index(parent));
+                }
+            }
+            for entry in *entries {
+                for &target in &targets {
+                    lexicon.add(entry, target);
+                }
+            }
+        }
+        lexicon.finish()
+    }
+
+    /// Parse a host dictionary: JSON object of category word lists, or LIWC `.dic` text
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let source = source.trim_start_matches('\u{feff}').trim();
+        if source.starts_with('%') {
+            return Self::parse_dic(source);
+        }
+        let categories: BTreeMap<String, Vec<String>> =
+            serde_json::from_str(source).map_err(|e| format!("invalid dictionary: {}", e))?;
+        let mut lexicon = Lexicon::default();
+        for (category, entries) in categories {
+            let index = lexicon.category_index(&category);
+            for entry in entries {
+                lexicon.add(&entry, index);
+            }
+        }
+        Ok(lexicon.finish())
+    }
+
+    /// LIWC `.dic`: `%`-delimited header of `id name` lines, then `word id id ...` lines.
+    /// Multi-word entries are skipped since counting is per word
+    fn parse_dic(source: &str) -> Result<Self, String> {
+        let mut sections = source.splitn(3, '%').skip(1);
+        let (header, body) = match (sections.next(), sections.next()) {
+            (Some(header), Some(body)) => (header, body),
+            _ => return Err("invalid dictionary: missing closing '%' after category header".to_string()),
+        };
+
+        let mut lexicon = Lexicon::default();
+        let mut ids = HashMap::new();
+        for line in header.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (id, name) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("invalid dictionary: bad category line '{}'", line))?;
+            ids.insert(id.to_string(), lexicon.category_index(name.trim()));
+        }
+
+        for line in body.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let id_count = fields.iter().rev().take_while(|f| ids.contains_key(**f)).count();
+            if id_count == 0 {
+                return Err(format!("invalid dictionary: no known category id in '{}'", line));
+            }
+            let words = &fields[..fields.len() - id_count];
+            if words.len() != 1 {
+                continue;
+            }
+            for id in &fields[fields.len() - id_count..] {
+                lexicon.add(words[0], ids[*id]);
+            }
+        }
+        Ok(lexicon.finish())
+    }
+
+    fn lookup(&self, word: &str) -> Option<&Vec<usize>> {
+        self.words
+            .get(word)
+            .or_else(|| self.prefixes.iter().find(|(prefix, _)| word.starts_with(prefix.as_str())).map(|(_, c)| c))
+    }
+
+    /// Count category words in a text
+    pub fn count(&self, text: &str) -> LexiconCounts {
+        let mut counts = vec![0usize; self.categories.len()];
+        let tokens = tokenize(text);
+        for token in &tokens {
+            if let Some(categories) = self.lookup(&token.lower().replace('\u{2019}', "'")) {
+                for &category in categories {
+                    counts[category] += 1;
+                }
+            }
+        }
+
+        let word_count = tokens.len();
+        let categories = self
+            .categories
+            .iter()
+            .zip(counts)
+            .map(|(category, count)| CategoryCount {
+                category: category.clone(),
+                count,
+                proportion: if word_count == 0 { 0.0 } else { count as f64 / word_count as f64 },
+            })
+            .collect();
+        LexiconCounts { word_count, categories }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_of(counts: &LexiconCounts, category: &str) -> usize {
+        counts.categories.iter().find(|c| c.category == category).unwrap().count
+    }
+
+    #[test]
+    fn test_builtin_hierarchy() {
+        let counts = Lexicon::builtin().count("I'm so worried and angry, but I love my friends");
+        assert_eq!(counts.word_count, 10);
+        assert_eq!(count_of(&counts, "anxiety"), 1);
+        assert_eq!(count_of(&counts, "negative_emotion"), 2);
+        assert_eq!(count_of(&counts, "affect"), 3);
+        assert_eq!(count_of(&counts, "i"), 3);
+        assert_eq!(count_of(&counts, "body"), 0);
+    }
+
+    #[test]
+    fn test_host_dictionaries() {
+        let json = Lexicon::parse(r#"{"work": ["boss", "deadline*"]}"#).unwrap();
+        let counts = json.count("My boss moved the deadlines");
+        assert_eq!(count_of(&counts, "work"), 2);
+        assert_eq!(counts.categories[0].proportion, 0.4);
+
+        let dic = Lexicon::parse("%\n1\tpositive\n2\tnegative\n%\nhapp*\t1\nawful\t2\nkind of\t1\nbittersweet\t1\t2\n").unwrap();
+        let counts = dic.count("Happily bittersweet, not awful");
+        assert_eq!(count_of(&counts, "positive"), 2);
+        assert_eq!(count_of(&counts, "negative"), 2);
+        assert!(Lexicon::parse("%\n1 positive\nhappy 1").is_err());
+        assert!(Lexicon::parse("not a dictionary").is_err());
+    }
+}
@@ -1,77 +1,1346 @@
+#[cfg(all(feature = "wasm", target_os = "wasi"))]
+compile_error!("wasm-bindgen bindings don't run under WASI; build with `--no-default-features --features wasi`");
+
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-// Initialize panic hook for better error messages
-#[wasm_bindgen(start)]
-pub fn init() {
-    console_error_panic_hook::set_once();
+// Initialize panic hook for better error messages
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(start)]
+pub fn init() {
+    console_error_panic_hook::set_once();
+}
+
+pub mod pattern_matching;
+pub mod scoring;
+pub mod entity_extraction;
+mod memory;
+mod worker;
+mod streaming;
+mod scheduler;
+mod schema;
+mod encoding;
+mod batch;
+mod offsets;
+mod standoff;
+mod highlight;
+mod markup;
+mod chat;
+mod subtitles;
+mod quotes;
+mod intentions;
+mod positive;
+mod apology;
+mod nvc;
+mod reframe;
+mod toxicity;
+mod intensity;
+mod tokenize;
+mod rumination;
+mod topics;
+mod summarize;
+mod similarity;
+mod temporal;
+mod lexicon;
+mod indices;
+mod politeness;
+mod formality;
+mod baseline;
+mod authoring;
+mod dry_run;
+mod versioning;
+mod pos;
+mod keywords;
+mod pii;
+mod reconcile;
+mod domain;
+mod coded_lexicon;
+mod locale;
+mod dialect;
+mod bias_audit;
+mod questions;
+mod hypothetical;
+mod person;
+mod attribution;
+mod harm_report;
+mod turns;
+mod incident_timing;
+mod analytics_export;
+mod digest;
+mod trajectory;
+mod heatmap;
+mod findings;
+mod contacts;
+mod relationship_events;
+mod entity_conflicts;
+mod pronouns;
+mod entity_sentiment;
+mod gazetteer;
+mod i18n;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+use pattern_matching::{match_patterns, match_patterns_for_locale, pattern_set_version, PatternMatch};
+#[cfg(feature = "wasm")]
+use pattern_matching::PatternScan;
+use scoring::{
+    calculate_confidence, calculate_configured_score, calculate_text_score, match_confidences, match_specificity, ScoringConfig,
+};
+use entity_extraction::{extract_entities, extract_entities_with_config, EntityConfig};
+use memory::memory_stats;
+use worker::handle_request;
+use streaming::{Hysteresis, StreamDecay, StreamState, StreamUpdate};
+use scheduler::ChunkedScan;
+use schema::result_schemas;
+use encoding::{encode, OutputFormat};
+use batch::{analyze_all, analyze_to_ndjson, BatchOutput};
+use standoff::{build_standoff, to_brat};
+use highlight::{highlight_text, HighlightOptions};
+use markup::{match_prepared, prepare_html, prepare_markdown};
+use chat::analyze_chat;
+use subtitles::analyze_subtitles;
+use quotes::extract_quotes_with_patterns;
+use intentions::extract_intentions;
+use positive::{empathy_score, exempt_boundaries, match_positive};
+use apology::detect_apologies;
+use nvc::score_nvc;
+use reframe::suggest_reframe;
+use toxicity::classify_toxicity;
+use intensity::{analyze_intensity, apply_intensity, detect_intensity, tone_intensity};
+use rumination::detect_rumination;
+use tokenize::tokenize;
+use offsets::sentence_ranges;
+use topics::detect_topics;
+use summarize::summarize_text;
+use similarity::{find_duplicates, fingerprint, fingerprint_distance, text_similarity, SimilarityMethod, DEFAULT_DUPLICATE_THRESHOLD};
+use temporal::temporal_orientation;
+use lexicon::Lexicon;
+use indices::{absolutist_index, hedging_index};
+use politeness::score_politeness;
+use formality::score_formality;
+use authoring::{lint_pattern as lint_candidate, test_candidate};
+use baseline::{build_baseline, compare_to_baseline, score_percentile, CorpusBaseline};
+use dry_run::dry_run;
+use pii::detect_pii;
+use reconcile::reconcile_spans;
+use domain::apply_domain_context;
+use dialect::exempt_vernacular;
+use questions::apply_question_form;
+use hypothetical::{hypothetical_scopes, scope_at};
+use person::match_person;
+use attribution::link_spans;
+use harm_report::build_harm_report;
+use analytics_export::{export_analytics, ExportRequest};
+use digest::{digest_entry, weekly_digest, WeeklyDigestRequest};
+use trajectory::text_trajectory;
+use heatmap::{intensity_heatmap, HeatmapUnit};
+use findings::{top_findings, DEFAULT_FINDINGS};
+use contacts::{extract_entities_with_contacts, KnownContact};
+use entity_sentiment::{entity_sentiment_trends, SentimentTrendRequest};
+use gazetteer::{clear_given_names, load_given_names};
+use i18n::{clear_label_translations, label, label_table, load_label_translations};
+use bias_audit::{audit_bias, BiasAuditRequest};
+use coded_lexicon::{clear_coded_lexicon, coded_lexicon_version, load_coded_lexicon};
+use keywords::{content_keywords, extract_keywords_and_entities, rank_keyphrases, rank_keywords};
+use versioning::{negotiate, to_versioned_json, SchemaVersion, Versioned, SUPPORTED_VERSIONS};
+
+/// Pattern match result
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternMatchResult {
+    /// Stable id from the pattern id and byte offset (`character_judgment.0@12`), for diffing runs
+    #[serde(default)]
+    pub id: String,
+    pub pattern_type: String,
+    pub match_text: String,
+    pub position: usize,
+    pub severity: String,
+    pub weight: f64,
+    /// Likelihood the match is a genuine instance (0 - 1) from pattern specificity; whole-text
+    /// analysis also discounts negated, quoted, question-form and conditional/hypothetical matches
+    #[serde(default)]
+    pub confidence: f64,
+    /// Set by markup-aware analysis: whether the match is inside quoted speech
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quoted: Option<bool>,
+    /// Set by quote extraction: index of the quote containing the match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_index: Option<usize>,
+    /// Set by reframing analysis: suggested first-person rewrite of the match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// Set by whole-text analysis: "conditional" or "hypothetical" when the match follows an
+    /// "if" / "imagine" / "what if" in its sentence, so it may be mentioned rather than meant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Set by whole-text analysis: grammatical person ("first", "second", "third") of the match's
+    /// subject or target, from the pronouns and names of its clause
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub person: Option<String>,
+    /// Set by entity-aware analysis: id of the extracted person the match comes from or is about
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity_id: Option<String>,
+    /// Set with `entity_id`: "source" when that person said it, "target" when it is about them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity_role: Option<String>,
+}
+
+/// Text processing result
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TextProcessingResult {
+    pub detected: bool,
+    pub confidence: f64,
+    pub patterns: Vec<PatternMatchResult>,
+    pub score: f64,
+    /// Positive expressions (gratitude, boundary setting, empathy) from whole-text analysis; not counted in `score`
+    #[serde(default)]
+    pub positive_patterns: Vec<PatternMatchResult>,
+    /// Score of the positive expressions on the same 0 - 1 scale
+    #[serde(default)]
+    pub positive_score: f64,
+    /// Score of the empathic statements alone, 0 - 1
+    #[serde(default)]
+    pub empathy_score: f64,
+    /// Typographic intensity (caps, "!!!", elongation) from whole-text analysis, 0 - 1
+    #[serde(default)]
+    pub tone_intensity: f64,
+    /// Percent of everyday reference messages scoring lower; set when requested in the scoring config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentile: Option<f64>,
+    /// Per-sentence scores; set when requested in the scoring config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentences: Option<Vec<SentenceScore>>,
+}
+
+/// Score of one sentence of an analyzed text
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SentenceScore {
+    /// Byte range of the sentence
+    pub start: usize,
+    pub end: usize,
+    pub score: f64,
+    pub detected: bool,
+    /// Indices into `patterns` of the matches starting in the sentence
+    pub pattern_indices: Vec<usize>,
+}
+
+/// Incremental streaming result
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamingResult {
+    pub new_patterns: Vec<PatternMatchResult>,
+    pub detected: bool,
+    pub confidence: f64,
+    pub score: f64,
+    pub processed_bytes: usize,
+}
+
+impl From<&PatternMatch> for PatternMatchResult {
+    fn from(m: &PatternMatch) -> Self {
+        PatternMatchResult {
+            id: m.id(),
+            pattern_type: m.pattern_type.clone(),
+            match_text: m.match_text.clone(),
+            position: m.position,
+            severity: m.severity.clone(),
+            weight: m.weight,
+            confidence: match_specificity(m),
+            quoted: None,
+            quote_index: None,
+            suggestion: None,
+            scope: None,
+            person: None,
+            entity_id: None,
+            entity_role: None,
+        }
+    }
+}
+
+/// Score above which text is flagged as detected
+const DETECTION_THRESHOLD: f64 = 0.3;
+
+/// Detect high-entropy patterns in text
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// 
+/// # Returns
+/// JSON string with detection results
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_high_entropy_patterns(text: &str) -> String {
+    processing_result_json(&analyze(text))
+}
+
+/// Analyze text and return the structured result (native counterpart of
+/// `detect_high_entropy_patterns` for Rust consumers)
+pub fn analyze(text: &str) -> TextProcessingResult {
+    analyze_matches(text, match_patterns(text))
+}
+
+/// Analyze text with scoring options (native counterpart of `detect_high_entropy_patterns_with_config`)
+pub fn analyze_with_config(text: &str, config: &ScoringConfig) -> TextProcessingResult {
+    analyze_matches_with_config(text, match_patterns_for_locale(text, config.locale), config)
+}
+
+/// Analyze text and attribute each match to the extracted person it comes from or is about
+pub fn analyze_with_entities(text: &str) -> TextProcessingResult {
+    let mut result = analyze(text);
+    let entities = extract_entities(text).entities;
+    let spans: Vec<(usize, usize)> = result.patterns.iter().map(|p| (p.position, p.position + p.match_text.len())).collect();
+    for (pattern, link) in result.patterns.iter_mut().zip(link_spans(text, &spans, &entities)) {
+        if let Some(link) = link {
+            pattern.entity_id = Some(link.entity_id);
+            pattern.entity_role = Some(link.role.to_string());
+        }
+    }
+    result
+}
+
+/// Build the text processing result from raw pattern matches over `word_count` words of text
+fn build_processing_result(matches: &[PatternMatch], word_count: usize) -> TextProcessingResult {
+    build_scored_result(matches, calculate_text_score(matches), word_count)
+}
+
+/// Build the text processing result from matches and their score
+fn build_scored_result(matches: &[PatternMatch], score: f64, word_count: usize) -> TextProcessingResult {
+    let detected = score > DETECTION_THRESHOLD;
+    let confidence = calculate_confidence(matches, word_count);
+
+    let pattern_results: Vec<PatternMatchResult> = matches.iter().map(PatternMatchResult::from).collect();
+
+    TextProcessingResult {
+        detected,
+        confidence,
+        patterns: pattern_results,
+        score,
+        positive_patterns: Vec::new(),
+        positive_score: 0.0,
+        empathy_score: 0.0,
+        tone_intensity: 0.0,
+        percentile: None,
+        sentences: None,
+    }
+}
+
+/// Score each sentence from the (already adjusted) matches starting in it, with the same
+/// configured multipliers as the whole text
+fn sentence_breakdown(text: &str, matches: &[PatternMatch], config: &ScoringConfig) -> Vec<SentenceScore> {
+    sentence_ranges(text)
+        .into_iter()
+        .map(|(start, end)| {
+            let pattern_indices: Vec<usize> =
+                (0..matches.len()).filter(|&i| matches[i].position >= start && matches[i].position < end).collect();
+            let sentence_matches: Vec<PatternMatch> = pattern_indices.iter().map(|&i| matches[i].clone()).collect();
+            let score = calculate_configured_score(text, &sentence_matches, config);
+            SentenceScore { start, end, score, detected: score > DETECTION_THRESHOLD, pattern_indices }
+        })
+        .collect()
+}
+
+/// Build the whole-text result: positive expressions are added, boundary statements
+/// exempt the withdrawal and stonewalling matches they explain, and typographic intensity boosts weights
+fn analyze_matches(text: &str, matches: Vec<PatternMatch>) -> TextProcessingResult {
+    analyze_matches_with_config(text, matches, &ScoringConfig::default())
+}
+
+fn analyze_matches_with_config(text: &str, matches: Vec<PatternMatch>, config: &ScoringConfig) -> TextProcessingResult {
+    let matches = if config.dialect_normalization { exempt_vernacular(text, matches, config.locale) } else { matches };
+    let positive = match_positive(text);
+    let mut matches = exempt_boundaries(text, matches, &positive);
+    let signals = detect_intensity(text);
+    apply_intensity(text, &mut matches, &signals);
+    apply_domain_context(text, &mut matches);
+    apply_question_form(text, &mut matches);
+
+    let score = calculate_configured_score(text, &matches, config);
+    let mut result = build_scored_result(&matches, score, tokenize(text).len());
+    let scopes = hypothetical_scopes(text);
+    for (pattern, confidence) in result.patterns.iter_mut().zip(match_confidences(text, &matches)) {
+        pattern.confidence = confidence;
+        pattern.scope = scope_at(&scopes, pattern.position).map(str::to_string);
+        pattern.person = match_person(text, pattern.position, pattern.match_text.len()).map(str::to_string);
+    }
+    result.positive_score = calculate_text_score(&positive);
+    result.empathy_score = empathy_score(&positive);
+    result.positive_patterns = positive.iter().map(PatternMatchResult::from).collect();
+    result.tone_intensity = tone_intensity(text, &signals);
+    if config.report_percentile {
+        result.percentile = Some(score_percentile(result.score));
+    }
+    if config.sentence_breakdown {
+        result.sentences = Some(sentence_breakdown(text, &matches, config));
+    }
+    result
+}
+
+/// Serialize a text processing result in the current schema version, falling back to an empty result
+fn processing_result_json(result: &TextProcessingResult) -> String {
+    match to_versioned_json(result, SchemaVersion::CURRENT) {
+        Ok(json) => json,
+        Err(_) => format!(
+            r#"{{"detected":false,"confidence":0.0,"patterns":[],"score":0.0,"positivePatterns":[],"positiveScore":0.0,"empathyScore":0.0,"toneIntensity":0.0,"schemaVersion":{}}}"#,
+            SchemaVersion::CURRENT.number()
+        ),
+    }
+}
+
+/// Detect high-entropy patterns with scoring options
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `config_json` - Optional JSON scoring options, e.g.
+///   `{"severityMultipliers":{"high":1.0,"medium":0.8,"low":0.5},"patternSeverityMultipliers":{"militarization":{"medium":0.5}},`
+///   `"positionalWeighting":{"start":1.0,"end":1.5},"reportPercentile":true,"locale":"en-GB","dialectNormalization":true,`
+///   `"personMultipliers":{"third":0.5},"sentenceBreakdown":true}`
+/// 
+/// # Returns
+/// JSON string with detection results (same shape as `detect_high_entropy_patterns`); throws on invalid options
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_high_entropy_patterns_with_config(text: &str, config_json: Option<String>) -> Result<String, String> {
+    let config: ScoringConfig = match config_json.as_deref() {
+        Some(json) if !json.trim().is_empty() => serde_json::from_str(json).map_err(|e| format!("invalid config: {}", e))?,
+        _ => ScoringConfig::default(),
+    };
+
+    Ok(processing_result_json(&analyze_with_config(text, &config)))
+}
+
+/// Number of patterns run between yields/progress reports in incremental analysis
+#[cfg(feature = "wasm")]
+const PATTERNS_PER_SLICE: usize = 16;
+
+/// Check whether an AbortSignal-like object (`{ aborted: true }`) has been triggered
+#[cfg(feature = "wasm")]
+fn is_aborted(signal: &JsValue) -> bool {
+    if signal.is_undefined() || signal.is_null() {
+        return false;
+    }
+    js_sys::Reflect::get(signal, &JsValue::from_str("aborted"))
+        .map(|v| v.is_truthy())
+        .unwrap_or(false)
+}
+
+/// Report progress to an optional JS callback as `(percent, matchCount)`
+/// Errors thrown by the callback are ignored so they can't abort analysis
+#[cfg(feature = "wasm")]
+fn report_progress(on_progress: Option<&js_sys::Function>, scan: &PatternScan) {
+    if let Some(callback) = on_progress {
+        let percent = JsValue::from_f64((scan.progress() * 100.0).round());
+        let match_count = JsValue::from_f64(scan.match_count() as f64);
+        let _ = callback.call2(&JsValue::NULL, &percent, &match_count);
+    }
+}
+
+/// Detect high-entropy patterns, reporting progress for long documents
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `on_progress` - Callback invoked with `(percent, matchCount)` as analysis advances
+/// 
+/// # Returns
+/// JSON string with detection results (same shape as `detect_high_entropy_patterns`)
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn detect_high_entropy_patterns_with_progress(text: &str, on_progress: &js_sys::Function) -> String {
+    let mut scan = PatternScan::new(text);
+
+    while !scan.step(PATTERNS_PER_SLICE) {
+        report_progress(Some(on_progress), &scan);
+    }
+    report_progress(Some(on_progress), &scan);
+
+    processing_result_json(&analyze_matches(text, scan.into_matches()))
+}
+
+/// Detect high-entropy patterns without blocking the main thread
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `signal` - Optional AbortSignal (or any object with an `aborted` flag) used to cancel
+/// * `on_progress` - Optional callback invoked with `(percent, matchCount)` between slices
+/// 
+/// # Returns
+/// Promise resolving to the same JSON string as `detect_high_entropy_patterns`,
+/// or rejecting with an `AbortError` once the signal is aborted
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn detect_high_entropy_patterns_async(
+    text: String,
+    signal: JsValue,
+    on_progress: Option<js_sys::Function>,
+) -> Result<JsValue, JsValue> {
+    let mut scan = PatternScan::new(&text);
+
+    loop {
+        if is_aborted(&signal) {
+            let error = js_sys::Error::new("Analysis cancelled");
+            error.set_name("AbortError");
+            return Err(error.into());
+        }
+        let done = scan.step(PATTERNS_PER_SLICE);
+        report_progress(on_progress.as_ref(), &scan);
+        if done {
+            break;
+        }
+        // Microtask break so other work can run between slices
+        wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&JsValue::UNDEFINED)).await?;
+    }
+
+    Ok(JsValue::from_str(&processing_result_json(&analyze_matches(&text, scan.into_matches()))))
+}
+
+/// Default per-slice time budget for chunked analysis in milliseconds
+const DEFAULT_SLICE_BUDGET_MS: f64 = 8.0;
+
+/// Cooperative analyzer for very large texts
+/// 
+/// Splits the text into sentence-aligned chunks; each `step()` call processes chunks
+/// until the millisecond budget is spent, so hosts can spread work across
+/// `setTimeout`/microtask ticks. Matches spanning chunk edges are preserved.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct ChunkedAnalyzer {
+    scan: ChunkedScan,
+    budget_ms: f64,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ChunkedAnalyzer {
+    /// Create an analyzer; `budget_ms` defaults to 8ms when omitted or not positive
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(text: &str, budget_ms: Option<f64>) -> ChunkedAnalyzer {
+        let budget_ms = budget_ms.filter(|b| *b > 0.0).unwrap_or(DEFAULT_SLICE_BUDGET_MS);
+        ChunkedAnalyzer { scan: ChunkedScan::new(text), budget_ms }
+    }
+
+    /// Process one time-budgeted slice; returns true once the whole text is analyzed
+    pub fn step(&mut self) -> bool {
+        self.scan.step(self.budget_ms)
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn done(&self) -> bool {
+        self.scan.is_done()
+    }
+
+    /// Percentage of text processed so far (0 - 100)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn progress(&self) -> f64 {
+        (self.scan.progress() * 100.0).round()
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter, js_name = matchCount))]
+    pub fn match_count(&self) -> usize {
+        self.scan.match_count()
+    }
+
+    /// JSON result (same shape as `detect_high_entropy_patterns`) for the text processed so far
+    pub fn result(&self) -> String {
+        processing_result_json(&build_processing_result(&self.scan.matches(), tokenize(self.scan.processed_text()).len()))
+    }
+}
+
+/// Streaming analyzer for live text
+/// 
+/// Usable directly as a WHATWG TransformStream transformer:
+/// `readable.pipeThrough(new TransformStream(new StreamingAnalyzer()))`
+/// Each enqueued chunk is a JSON string with the patterns found in newly completed sentences.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct StreamingAnalyzer {
+    state: StreamState,
+}
+
+impl Default for StreamingAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl StreamingAnalyzer {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> StreamingAnalyzer {
+        StreamingAnalyzer { state: StreamState::new() }
+    }
+
+    /// Create an analyzer whose live score decays old matches: a match counts half after
+    /// `half_life_bytes` of later text, and not at all beyond `window_bytes` when given
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = withDecay))]
+    pub fn with_decay(half_life_bytes: usize, window_bytes: Option<usize>) -> StreamingAnalyzer {
+        StreamingAnalyzer { state: StreamState::with_decay(StreamDecay { half_life_bytes, window_bytes }) }
+    }
+
+    /// Use separate on/off thresholds for the `detected` flag (e.g. on above 0.35, off at 0.25)
+    /// so it doesn't flicker around a single threshold; throws when `off` is above `on`
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = setHysteresis))]
+    pub fn set_hysteresis(&mut self, on: f64, off: f64) -> Result<(), String> {
+        self.state.set_hysteresis(Hysteresis::new(on, off)?);
+        Ok(())
+    }
+
+    /// Push a text chunk; returns JSON with matches from sentences it completed
+    pub fn push(&mut self, chunk: &str) -> String {
+        let update = self.state.push(chunk);
+        streaming_result_json(&update)
+    }
+
+    /// Analyze remaining buffered text at end of stream
+    pub fn finish(&mut self) -> String {
+        let update = self.state.finish();
+        streaming_result_json(&update)
+    }
+
+    /// Full result (same shape as `detect_high_entropy_patterns`) for everything analyzed so far
+    pub fn result(&self) -> String {
+        processing_result_json(&build_processing_result(self.state.matches(), self.state.word_count()))
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl StreamingAnalyzer {
+    /// TransformStream `transform(chunk, controller)` hook
+    pub fn transform(&mut self, chunk: &str, controller: &JsValue) -> Result<(), JsValue> {
+        let json = self.push(chunk);
+        enqueue(controller, &json)
+    }
+
+    /// TransformStream `flush(controller)` hook
+    pub fn flush(&mut self, controller: &JsValue) -> Result<(), JsValue> {
+        let json = self.finish();
+        enqueue(controller, &json)
+    }
+}
+
+/// On/off thresholds used by `LiveAnalyzer` when none are given
+const LIVE_THRESHOLDS: (f64, f64) = (0.35, 0.25);
+
+/// Analyzer for live typing: the host passes the whole current text after each edit,
+/// and the `detected` flag uses separate on/off thresholds so it doesn't flicker
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct LiveAnalyzer {
+    hysteresis: Hysteresis,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl LiveAnalyzer {
+    /// Create an analyzer; thresholds default to on above 0.35 and off at 0.25
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(on: Option<f64>, off: Option<f64>) -> Result<LiveAnalyzer, String> {
+        let hysteresis = Hysteresis::new(on.unwrap_or(LIVE_THRESHOLDS.0), off.unwrap_or(LIVE_THRESHOLDS.1))?;
+        Ok(LiveAnalyzer { hysteresis })
+    }
+
+    /// Analyze the current text; JSON result with the same shape as `detect_high_entropy_patterns`
+    pub fn update(&mut self, text: &str) -> String {
+        let mut result = analyze(text);
+        result.detected = self.hysteresis.update(result.score);
+        processing_result_json(&result)
+    }
+}
+
+/// Call `controller.enqueue(value)` on a TransformStreamDefaultController
+#[cfg(feature = "wasm")]
+fn enqueue(controller: &JsValue, json: &str) -> Result<(), JsValue> {
+    let enqueue = js_sys::Reflect::get(controller, &JsValue::from_str("enqueue"))?;
+    let enqueue: js_sys::Function = enqueue.dyn_into()?;
+    enqueue.call1(controller, &JsValue::from_str(json))?;
+    Ok(())
+}
+
+/// Serialize a streaming update, falling back to an empty update
+fn streaming_result_json(update: &StreamUpdate) -> String {
+    let result = StreamingResult {
+        new_patterns: update.new_matches.iter().map(PatternMatchResult::from).collect(),
+        detected: update.detected,
+        confidence: update.confidence,
+        score: update.score,
+        processed_bytes: update.processed_bytes,
+    };
+
+    match serde_json::to_string(&result) {
+        Ok(json) => json,
+        Err(_) => r#"{"newPatterns":[],"detected":false,"confidence":0.0,"score":0.0,"processedBytes":0}"#.to_string(),
+    }
+}
+
+/// Detect high-entropy patterns with a selectable binary encoding
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `format` - `"msgpack"` for MessagePack or `"json"` for UTF-8 JSON bytes
+/// 
+/// # Returns
+/// Encoded detection results (a `Uint8Array` in JS); throws on an unknown format
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_high_entropy_patterns_encoded(text: &str, format: &str) -> Result<Vec<u8>, String> {
+    encode(&analyze(text), OutputFormat::parse(format)?)
+}
+
+/// Analyze a batch of texts in one call
+/// 
+/// # Arguments
+/// * `texts_json` - JSON array of strings
+/// * `output` - `"json"` (default) for a JSON array, or `"ndjson"` for one result per line
+/// 
+/// # Returns
+/// Results in input order; throws on malformed input or an unknown output mode
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn analyze_batch(texts_json: &str, output: Option<String>) -> Result<String, String> {
+    let texts: Vec<String> = serde_json::from_str(texts_json).map_err(|e| format!("invalid texts: {}", e))?;
+
+    match BatchOutput::parse(output.as_deref())? {
+        BatchOutput::Json => {
+            let results: Result<Vec<serde_json::Value>, String> = analyze_all(&texts).iter().map(structured_value).collect();
+            serde_json::to_string(&results?).map_err(|e| e.to_string())
+        }
+        BatchOutput::Ndjson => analyze_to_ndjson(&texts),
+    }
+}
+
+/// Detect high-entropy patterns in marked-up text
+/// 
+/// # Arguments
+/// * `text` - Source document
+/// * `input_format` - `"markdown"`: skips code blocks, inline code and link URLs, and treats
+///   blockquotes as quoted speech (flagged `quoted` and down-weighted);
+///   `"html"`: strips tags, scripts and styles, decodes entities, and treats
+///   `<blockquote>`/`<q>` content as quoted speech
+/// 
+/// # Returns
+/// JSON detection results with positions mapped back to the source and each `matchText` the
+/// source span it covers (markup included); throws on an unknown format
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_high_entropy_patterns_markup(text: &str, input_format: &str) -> Result<String, String> {
+    let document = match input_format {
+        "markdown" => prepare_markdown(text),
+        "html" => prepare_html(text),
+        other => return Err(format!("unsupported input format '{}'", other)),
+    };
+
+    let mut result = analyze_matches(&document.mapped.text, match_prepared(&document));
+    for pattern in result.patterns.iter_mut() {
+        pattern.quoted = Some(document.is_quoted(pattern.position));
+    }
+    for pattern in result.patterns.iter_mut().chain(result.positive_patterns.iter_mut()) {
+        let (start, span) = document.source_span(text, pattern.position, pattern.match_text.len());
+        if let Some((pattern_id, _)) = pattern.id.rsplit_once('@') {
+            pattern.id = format!("{}@{}", pattern_id, start);
+        }
+        pattern.position = start;
+        pattern.match_text = span.to_string();
+    }
+
+    Ok(processing_result_json(&result))
+}
+
+/// Analyze a chat export per message and per speaker
+/// 
+/// # Arguments
+/// * `text` - Chat log with lines like `[10:42] Alice: ...` or WhatsApp exports
+///   (`12/31/23, 10:42 PM - Alice: ...`); other lines continue the previous message
+/// 
+/// # Returns
+/// JSON with `messages` (each with its detection result, positions relative to the export),
+/// `speakers` (aggregate results and risk profiles in order of first appearance),
+/// `turnTaking` (turns, word share, interruptions and response latencies per speaker),
+/// `events` (message bursts from one speaker, with their time range) and `incidentTiming`
+/// (hour-of-day counts, late-night share and intervals of flagged messages)
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn analyze_chat_export(text: &str) -> Result<String, String> {
+    serde_json::to_string(&analyze_chat(text)).map_err(|e| e.to_string())
+}
+
+/// Analyze an SRT or WebVTT subtitle/transcript file
+/// 
+/// # Arguments
+/// * `text` - SRT or WebVTT content (cue markup and entities are stripped before analysis)
+/// 
+/// # Returns
+/// JSON with the parsed `cues`, `patterns` tagged with `cueIndex`, `startMs` and `endMs`
+/// (byte offsets in the subtitle file) and per-voice `turnTaking` statistics
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn analyze_subtitle_transcript(text: &str) -> Result<String, String> {
+    serde_json::to_string(&analyze_subtitles(text)).map_err(|e| e.to_string())
+}
+
+/// Extract direct quotes with attributed speakers
+/// 
+/// # Arguments
+/// * `text` - Text to analyze, e.g. `Mom said 'you'll never amount to anything'`
+/// 
+/// # Returns
+/// JSON with `quotes` (`{speaker, quote, offset, end}`, byte offsets of the quote text) and
+/// `patterns`, where matches inside a quote carry its `quoteIndex`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn extract_quotes(text: &str) -> Result<String, String> {
+    serde_json::to_string(&extract_quotes_with_patterns(text)).map_err(|e| e.to_string())
+}
+
+/// Extract stated goals and intentions
+/// 
+/// # Arguments
+/// * `text` - Text to analyze, e.g. "I want to set a boundary with my mom"
+/// 
+/// # Returns
+/// JSON array of `{kind, text, action, people, confidence, position, end}` items,
+/// where `kind` is `"goal"` or `"intention"`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn extract_goals_and_intentions(text: &str) -> Result<String, String> {
+    serde_json::to_string(&extract_intentions(text)).map_err(|e| e.to_string())
+}
+
+/// Detect apologies and classify their quality
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// 
+/// # Returns
+/// JSON array of apologies with `quality` (`"genuine"` or `"non_apology"`), the `reasons`
+/// for non-apologies (e.g. `blame_shift`, `feelings_deflection`) and related pattern types
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_apology_quality(text: &str) -> Result<String, String> {
+    serde_json::to_string(&detect_apologies(text)).map_err(|e| e.to_string())
+}
+
+/// Score a message for Nonviolent Communication conformance
+/// 
+/// # Arguments
+/// * `text` - Message to score
+/// 
+/// # Returns
+/// JSON with an overall `score` (0 - 1) and per-component results for observation,
+/// feelings, needs and request, each with supporting/opposing phrases and feedback
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn analyze_nonviolent_communication(text: &str) -> Result<String, String> {
+    serde_json::to_string(&score_nvc(text)).map_err(|e| e.to_string())
+}
+
+/// Detect high-entropy patterns with suggested rewrites ("soften before send")
+/// 
+/// # Arguments
+/// * `text` - Draft message to analyze
+/// 
+/// # Returns
+/// JSON detection results where character_judgment, absolute_statement and displacement
+/// matches carry a `suggestion`, e.g. "You always ignore me" → "I felt ignored when…"
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_high_entropy_patterns_with_reframes(text: &str) -> String {
+    let matches = match_patterns(text);
+    let mut result = analyze_matches(text, matches.clone());
+    for pattern in result.patterns.iter_mut() {
+        let source = matches.iter().find(|m| m.position == pattern.position && m.pattern_type == pattern.pattern_type);
+        pattern.suggestion = source.and_then(|m| suggest_reframe(text, m));
+    }
+    processing_result_json(&result)
+}
+
+/// Score text in the standard toxicity taxonomy used by moderation pipelines
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// 
+/// # Returns
+/// JSON with `scores` for toxicity, severe_toxicity, insult, threat and identity_attack,
+/// the `flagged` categories, and the contributing `spans`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_toxicity_categories(text: &str) -> Result<String, String> {
+    serde_json::to_string(&classify_toxicity(text)).map_err(|e| e.to_string())
+}
+
+/// Detect typographic intensity signals (ALL-CAPS runs, "!!!", elongated words)
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// 
+/// # Returns
+/// JSON with the `toneIntensity` score (0 - 1) and the individual `signals`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_intensity_signals(text: &str) -> Result<String, String> {
+    serde_json::to_string(&analyze_intensity(text)).map_err(|e| e.to_string())
+}
+
+/// Detect rumination: grievances, phrases and people recurring across entries
+/// 
+/// # Arguments
+/// * `entries_json` - JSON array of entry texts, oldest first
+/// 
+/// # Returns
+/// JSON with the rumination `score` (0 - 1), per-entry recurrence, and the repeated
+/// phrases and people with the entries they appear in; throws on invalid input
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_rumination_across_entries(entries_json: &str) -> Result<String, String> {
+    let entries: Vec<String> = serde_json::from_str(entries_json).map_err(|e| format!("invalid entries: {}", e))?;
+    serde_json::to_string(&detect_rumination(&entries)).map_err(|e| e.to_string())
+}
+
+/// Tag text with ranked life-domain topics (work, family, health, money, relationship, ...)
+/// 
+/// # Arguments
+/// * `text` - Text to tag
+/// 
+/// # Returns
+/// JSON array of `{topic, score, hits, keywords}`, strongest topic first
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_text_topics(text: &str) -> Result<String, String> {
+    serde_json::to_string(&detect_topics(text)).map_err(|e| e.to_string())
+}
+
+/// Measure past / present / future focus from verb tense and temporal adverbs
+/// 
+/// # Arguments
+/// * `text` - Text to measure
+/// 
+/// # Returns
+/// JSON `{past, present, future, dominant, cues}` with shares of temporal cues
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_temporal_orientation(text: &str) -> Result<String, String> {
+    serde_json::to_string(&temporal_orientation(text)).map_err(|e| e.to_string())
+}
+
+/// Count words per lexicon category (LIWC-style proportions)
+/// 
+/// # Arguments
+/// * `text` - Text to count
+/// * `dictionary` - Optional host dictionary: JSON `{"category": ["word", "prefix*"]}` or LIWC `.dic`
+///   text; the built-in affect / cognition / social / body dictionary is used when omitted
+/// 
+/// # Returns
+/// JSON `{wordCount, categories: [{category, count, proportion}]}` in dictionary order
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn count_lexicon_categories(text: &str, dictionary: Option<String>) -> Result<String, String> {
+    let lexicon = match dictionary {
+        Some(source) => Lexicon::parse(&source)?,
+        None => Lexicon::builtin(),
+    };
+    serde_json::to_string(&lexicon.count(text)).map_err(|e| e.to_string())
+}
+
+/// Absolutist-language index from the clinically validated word list
+/// (always, never, completely, nothing, ...), separate from the weighted absolute-statement patterns
+/// 
+/// # Arguments
+/// * `text` - Text to measure
+/// 
+/// # Returns
+/// JSON `{rate, count, wordCount, hits}` where `rate` is absolutist words per word
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn absolutist_word_index(text: &str) -> Result<String, String> {
+    serde_json::to_string(&absolutist_index(text)).map_err(|e| e.to_string())
+}
+
+/// Hedging index: rate of hedges and qualifiers (maybe, probably, kind of, I guess, ...)
+/// 
+/// # Arguments
+/// * `text` - Text to measure
+/// 
+/// # Returns
+/// JSON `{rate, count, wordCount, hits}` where `rate` is hedges per word
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn hedging_word_index(text: &str) -> Result<String, String> {
+    serde_json::to_string(&hedging_index(text)).map_err(|e| e.to_string())
+}
+
+/// Score message politeness (please/thanks, indirect requests, honorifics vs imperatives and demands)
+/// 
+/// # Arguments
+/// * `text` - Message to score
+/// 
+/// # Returns
+/// JSON `{score, cues}` with a 0 - 1 score and the contributing cues
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn analyze_politeness(text: &str) -> Result<String, String> {
+    serde_json::to_string(&score_politeness(text)).map_err(|e| e.to_string())
+}
+
+/// Estimate formality from contractions, slang, sentence length and punctuation style
+/// 
+/// # Arguments
+/// * `text` - Text to score
+/// 
+/// # Returns
+/// JSON `{score, register, contractionRate, slangRate, meanSentenceLength, informalPunctuation}`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn analyze_formality(text: &str) -> Result<String, String> {
+    serde_json::to_string(&score_formality(text)).map_err(|e| e.to_string())
+}
+
+/// Compute a corpus baseline (mean/variance of the score and of each pattern type's score)
+/// from a host's own sample of texts, for use with `compare_with_baseline`
+/// 
+/// # Arguments
+/// * `texts_json` - JSON array of strings representative of the community
+/// 
+/// # Returns
+/// JSON `{textCount, score: {mean, variance}, categories: {patternType: {mean, variance}}}`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn build_corpus_baseline(texts_json: &str) -> Result<String, String> {
+    let texts: Vec<String> = serde_json::from_str(texts_json).map_err(|e| format!("invalid texts: {}", e))?;
+    serde_json::to_string(&build_baseline(&texts)).map_err(|e| e.to_string())
+}
+
+/// Compare a text with a corpus baseline, reporting per-category deviations
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `baseline_json` - Baseline from `build_corpus_baseline`, or host-computed statistics in the same shape
+/// 
+/// # Returns
+/// JSON `{score, categories: [{category, value, mean, zScore}]}`, largest deviation first
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn compare_with_baseline(text: &str, baseline_json: &str) -> Result<String, String> {
+    let baseline: CorpusBaseline = serde_json::from_str(baseline_json).map_err(|e| format!("invalid baseline: {}", e))?;
+    serde_json::to_string(&compare_to_baseline(text, &baseline)).map_err(|e| e.to_string())
+}
+
+/// Audit the detector for identity-term bias using counterfactual templates
+/// 
+/// # Arguments
+/// * `request_json` - JSON `{sets: [{name?, templates: ["{term} people are ..."], terms: [...]}], config?}`;
+///   each template is scored once per term, with `config` as in `detect_high_entropy_patterns_with_config`
+/// 
+/// # Returns
+/// JSON `{sets: [{name, terms: [{term, meanScore, detectionRate, disparity}], templates: [{template, scores, spread}],
+/// maxDisparity}], maxDisparity}`; throws on a template without `{term}` or a set with fewer than two terms
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn audit_identity_bias(request_json: &str) -> Result<String, String> {
+    let request: BiasAuditRequest = serde_json::from_str(request_json).map_err(|e| format!("invalid audit request: {}", e))?;
+    serde_json::to_string(&audit_bias(&request)?).map_err(|e| e.to_string())
+}
+
+/// Try a candidate pattern against example strings (for pattern-authoring playgrounds)
+/// 
+/// # Arguments
+/// * `pattern` - Regex, compiled case-insensitively like the built-in patterns
+/// * `examples_json` - JSON array of example strings
+/// 
+/// # Returns
+/// JSON `{valid, error, compileMs, matchMs, matchedExamples, examples: [{index, example, matched, matches, elapsedMs}], lints}`;
+/// a pattern that fails to compile is reported with `valid: false`, not thrown
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn test_pattern(pattern: &str, examples_json: &str) -> Result<String, String> {
+    let examples: Vec<String> = serde_json::from_str(examples_json).map_err(|e| format!("invalid examples: {}", e))?;
+    serde_json::to_string(&test_candidate(pattern, &examples)).map_err(|e| e.to_string())
+}
+
+/// Lint a custom pattern before registering it
+/// 
+/// # Arguments
+/// * `pattern` - Regex, compiled case-insensitively like the built-in patterns
+/// 
+/// # Returns
+/// JSON array of `{rule, message, position}` warnings (`nested_quantifier`,
+/// `excessive_alternation`, `missing_word_boundary`); errors if the pattern doesn't compile
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn lint_pattern(pattern: &str) -> Result<String, String> {
+    let lints = lint_candidate(pattern).map_err(|e| format!("invalid pattern: {}", e))?;
+    serde_json::to_string(&lints).map_err(|e| e.to_string())
+}
+
+/// Debug why a text did or didn't trigger detection
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `config_json` - Optional JSON scoring options (same as `detect_high_entropy_patterns_with_config`)
+/// 
+/// # Returns
+/// JSON `{detected, score, threshold, matches}` listing every pattern that matched, including
+/// ones below the threshold or suppressed, each with `counted`, `suppressedBy`, `discounts` and `soloScore`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn dry_run_detection(text: &str, config_json: Option<String>) -> Result<String, String> {
+    let config: ScoringConfig = match config_json.as_deref() {
+        Some(json) if !json.trim().is_empty() => serde_json::from_str(json).map_err(|e| format!("invalid config: {}", e))?,
+        _ => ScoringConfig::default(),
+    };
+
+    serde_json::to_string(&dry_run(text, &config)).map_err(|e| e.to_string())
+}
+
+/// Output schema versions this build can produce
+/// 
+/// # Returns
+/// JSON `{current, supported}` with version numbers (e.g. `{"current":3,"supported":[1,2,3]}`)
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_schema_versions() -> String {
+    let supported: Vec<u32> = SUPPORTED_VERSIONS.iter().map(|v| v.number()).collect();
+    serde_json::json!({ "current": SchemaVersion::CURRENT.number(), "supported": supported }).to_string()
+}
+
+/// Agree on an output schema version
+/// 
+/// # Arguments
+/// * `accepted_json` - JSON array of version numbers the consumer understands
+/// 
+/// # Returns
+/// The newest version both sides support; throws if there is none
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn negotiate_schema_version(accepted_json: &str) -> Result<u32, String> {
+    let accepted: Vec<u32> = serde_json::from_str(accepted_json).map_err(|e| format!("invalid versions: {}", e))?;
+    negotiate(&accepted).map(SchemaVersion::number)
+}
+
+/// Detect high-entropy patterns in a specific output schema version
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `version` - Schema version (`"1"`, `"v2"`, ...); the current version when omitted
+/// 
+/// # Returns
+/// JSON detection results with a `schemaVersion` field; throws on an unsupported version
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_high_entropy_patterns_versioned(text: &str, version: Option<String>) -> Result<String, String> {
+    to_versioned_json(&analyze(text), SchemaVersion::parse(version.as_deref())?)
+}
+
+/// Extract people entities, matching them against the user's existing contacts
+/// 
+/// # Arguments
+/// * `text` - Text to analyze for people mentions
+/// * `contacts_json` - JSON array `[{id?, name, nicknames?, relationship?}]` of known contacts
+/// 
+/// # Returns
+/// JSON entity extraction results; matched entities carry `knownContact` and its `contactId`,
+/// known people are found without a relationship anchor, and `likelyNew` lists the ids of named
+/// people matching no contact
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn extract_people_entities_with_contacts(text: &str, contacts_json: &str) -> Result<String, String> {
+    let contacts: Vec<KnownContact> =
+        serde_json::from_str(contacts_json).map_err(|e| format!("invalid contacts: {}", e))?;
+    to_versioned_json(&extract_entities_with_contacts(text, &contacts), SchemaVersion::CURRENT)
+}
+
+/// Extract people entities in a specific output schema version
+/// 
+/// # Arguments
+/// * `text` - Text to analyze for people mentions
+/// * `version` - Schema version (`"1"`, `"v2"`, ...); the current version when omitted
+/// 
+/// # Returns
+/// JSON entity extraction results with a `schemaVersion` field; throws on an unsupported version
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn extract_people_entities_versioned(text: &str, version: Option<String>) -> Result<String, String> {
+    to_versioned_json(&extract_entities(text), SchemaVersion::parse(version.as_deref())?)
+}
+
+/// Structured form of a result, with the same fields and `schemaVersion` as the legacy JSON string.
+/// Both surfaces serialize the same internal result, so they can't drift apart
+fn structured_value<T: Versioned>(value: &T) -> Result<serde_json::Value, String> {
+    let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    Ok(versioning::apply_version(value, T::SHAPE, SchemaVersion::CURRENT))
+}
+
+/// Convert a result to a plain JS object
+#[cfg(feature = "wasm")]
+fn to_js_object<T: Versioned>(value: &T) -> Result<JsValue, JsValue> {
+    let value = structured_value(value).map_err(|e| JsValue::from_str(&e))?;
+    js_sys::JSON::parse(&value.to_string())
+}
+
+/// Detect high-entropy patterns, returning an object instead of a JSON string
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// 
+/// # Returns
+/// Detection result object (same fields as `detect_high_entropy_patterns`)
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = analyzeText)]
+pub fn analyze_text(text: &str) -> Result<JsValue, JsValue> {
+    to_js_object(&analyze(text))
+}
+
+/// Extract people entities, returning an object instead of a JSON string
+/// 
+/// # Arguments
+/// * `text` - Text to analyze for people mentions
+/// 
+/// # Returns
+/// Entity extraction result object (same fields as `extract_people_entities`)
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = extractPeople)]
+pub fn extract_people(text: &str) -> Result<JsValue, JsValue> {
+    to_js_object(&extract_entities(text))
+}
+
+/// Find personal data (emails, URLs, card numbers, phone numbers)
+/// 
+/// # Arguments
+/// * `text` - Text to scan
+/// 
+/// # Returns
+/// JSON array of `{kind, text, position, end}` (byte offsets), non-overlapping
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_personal_data(text: &str) -> Result<String, String> {
+    serde_json::to_string(&detect_pii(text)).map_err(|e| e.to_string())
+}
+
+/// Unify pattern matches, keywords, personal data and entities into one span list
+/// 
+/// # Arguments
+/// * `text` - Text to annotate
+/// 
+/// # Returns
+/// JSON array of non-overlapping `{start, end, text, roles: [{role, label, sourceId, sourceStart, sourceEnd}]}`
+/// (byte offsets); a span covered by several annotations lists all of their roles
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn reconcile_annotations(text: &str) -> Result<String, String> {
+    serde_json::to_string(&reconcile_spans(text)).map_err(|e| e.to_string())
+}
+
+/// Load an updated coded-language (dog-whistle / slur) lexicon, replacing the previous one
+/// 
+/// # Arguments
+/// * `lexicon_json` - JSON `{version?, terms: [{term, severity?, weight?, patternType?, context?}]}`;
+///   `context` lists words of which one must appear in the same sentence for the term to match
+/// 
+/// # Returns
+/// Number of terms loaded; throws (keeping the previous lexicon) if any term is invalid
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn set_coded_lexicon(lexicon_json: &str) -> Result<usize, String> {
+    load_coded_lexicon(lexicon_json)
+}
+
+/// Remove the runtime coded-language lexicon
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn reset_coded_lexicon() {
+    clear_coded_lexicon();
+}
+
+/// Version string of the loaded coded-language lexicon, if one was supplied
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_coded_lexicon_version() -> Option<String> {
+    coded_lexicon_version()
+}
+
+/// Add given names to the gazetteer entity extraction uses to judge capitalized words
+/// 
+/// # Arguments
+/// * `names_json` - JSON array of given names, e.g. `["Siobhan", "Tariq"]`
+/// 
+/// # Returns
+/// Number of names not already loaded; names from earlier calls are kept
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn set_given_names(names_json: &str) -> Result<usize, String> {
+    load_given_names(names_json)
+}
+
+/// Remove host-supplied given names, leaving the built-in list
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn reset_given_names() {
+    clear_given_names();
 }
 
-mod pattern_matching;
-mod scoring;
-mod entity_extraction;
+/// Add or override display labels for severities, risk tiers and categories
+/// 
+/// # Arguments
+/// * `translations_json` - JSON `{"<locale>": {"severity.high": "...", "tier.moderate": "...", "category.insult": "..."}}`
+/// 
+/// # Returns
+/// Number of labels loaded; labels for other keys loaded earlier are kept
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn set_label_translations(translations_json: &str) -> Result<usize, String> {
+    load_label_translations(translations_json)
+}
 
-use pattern_matching::match_patterns;
-use scoring::calculate_text_score;
-use entity_extraction::extract_entities;
+/// Remove host-supplied display labels, leaving the built-in table
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn reset_label_translations() {
+    clear_label_translations();
+}
 
-/// Pattern match result
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct PatternMatchResult {
-    pub pattern_type: String,
-    pub match_text: String,
-    pub position: usize,
-    pub severity: String,
-    pub weight: f64,
+/// Display label for one key in a locale
+/// 
+/// # Arguments
+/// * `key` - `severity.<severity>`, `tier.<riskTier>` or `category.<patternType>`
+/// * `locale` - Language tag such as `"es"` or `"pt-BR"`; English when omitted
+/// 
+/// # Returns
+/// The label, falling back to the language, then English, then the humanized key
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_label(key: &str, locale: Option<String>) -> String {
+    label(key, locale.as_deref().unwrap_or("en"))
 }
 
-/// Text processing result
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct TextProcessingResult {
-    pub detected: bool,
-    pub confidence: f64,
-    pub patterns: Vec<PatternMatchResult>,
-    pub score: f64,
+/// Every display label for a locale
+/// 
+/// # Arguments
+/// * `locale` - Language tag such as `"es"` or `"pt-BR"`; English when omitted
+/// 
+/// # Returns
+/// JSON `{locale, severities, riskTiers, categories}` mapping each key to its label
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_localized_labels(locale: Option<String>) -> Result<String, String> {
+    serde_json::to_string(&label_table(locale.as_deref().unwrap_or("en"))).map_err(|e| e.to_string())
 }
 
-/// Detect high-entropy patterns in text
+/// Condense text with a TextRank-style extractive summary
 /// 
 /// # Arguments
-/// * `text` - Text to analyze
+/// * `text` - Text to summarize
+/// * `max_sentences` - Maximum number of sentences to keep
 /// 
 /// # Returns
-/// JSON string with detection results
-#[wasm_bindgen]
-pub fn detect_high_entropy_patterns(text: &str) -> String {
-    let matches = match_patterns(text);
-    let score = calculate_text_score(&matches);
-    let detected = score > 0.3; // Threshold for detection
-    let confidence = score.min(1.0);
+/// JSON with the `summary` string and the selected `sentences` (original order, with
+/// byte offsets and rank scores)
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn summarize(text: &str, max_sentences: usize) -> Result<String, String> {
+    serde_json::to_string(&summarize_text(text, max_sentences)).map_err(|e| e.to_string())
+}
 
-    let pattern_results: Vec<PatternMatchResult> = matches
-        .iter()
-        .map(|m| PatternMatchResult {
-            pattern_type: m.pattern_type.clone(),
-            match_text: m.match_text.clone(),
-            position: m.position,
-            severity: m.severity.clone(),
-            weight: m.weight,
-        })
-        .collect();
+/// Similarity score between two texts
+/// 
+/// # Arguments
+/// * `text_a`, `text_b` - Texts to compare
+/// * `method` - `"token"` (default, content-word cosine) or `"char"` (character trigram cosine)
+/// 
+/// # Returns
+/// Score from 0 (unrelated) to 1 (identical); throws on an unknown method
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn similarity(text_a: &str, text_b: &str, method: Option<String>) -> Result<f64, String> {
+    Ok(text_similarity(text_a, text_b, SimilarityMethod::parse(method.as_deref())?))
+}
 
-    let result = TextProcessingResult {
-        detected,
-        confidence,
-        patterns: pattern_results,
-        score,
-    };
+/// Find near-duplicates within a batch (word shingles + Jaccard similarity)
+/// 
+/// # Arguments
+/// * `texts_json` - JSON array of texts
+/// * `threshold` - Optional Jaccard similarity (0 - 1) at which texts are duplicates, default 0.8
+/// 
+/// # Returns
+/// JSON array with, per text, `duplicateOf` (earliest earlier duplicate or null), its
+/// `similarity`, and a `fingerprint`; throws on invalid input
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn find_near_duplicates(texts_json: &str, threshold: Option<f64>) -> Result<String, String> {
+    let texts: Vec<String> = serde_json::from_str(texts_json).map_err(|e| format!("invalid texts: {}", e))?;
+    let threshold = threshold.unwrap_or(DEFAULT_DUPLICATE_THRESHOLD);
+    serde_json::to_string(&find_duplicates(&texts, threshold)).map_err(|e| e.to_string())
+}
 
-    match serde_json::to_string(&result) {
-        Ok(json) => json,
-        Err(_) => r#"{"detected":false,"confidence":0.0,"patterns":[],"score":0.0}"#.to_string(),
-    }
+/// SimHash fingerprint of a text, for recognising already-processed entries later
+/// 
+/// # Arguments
+/// * `text` - Text to fingerprint
+/// 
+/// # Returns
+/// 16 hex digits; compare fingerprints with `text_fingerprint_distance`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn text_fingerprint(text: &str) -> String {
+    fingerprint(text)
+}
+
+/// Number of differing bits between two fingerprints
+/// 
+/// # Arguments
+/// * `a`, `b` - Fingerprints from `text_fingerprint`
+/// 
+/// # Returns
+/// 0 - 64; a few bits or less means near-duplicate text; throws on malformed fingerprints
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn text_fingerprint_distance(a: &str, b: &str) -> Result<u32, String> {
+    fingerprint_distance(a, b)
 }
 
 /// Extract keywords from text
@@ -80,38 +1349,163 @@ pub fn detect_high_entropy_patterns(text: &str) -> String {
 /// * `text` - Text to analyze
 /// 
 /// # Returns
-/// JSON array of keywords
-#[wasm_bindgen]
+/// JSON array of content-word keywords (nouns, main verbs, adjectives; no pronouns or function words)
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn extract_keywords(text: &str) -> String {
-    use regex::Regex;
-    
-    // Simple keyword extraction - look for important words
-    let keyword_patterns = vec![
-        r"\b(you|your|always|never|constantly|selfish|lazy|stupid|idiot|hate|blame|fault)\b",
-        r"\b(terrible|awful|horrible|worthless|useless|pathetic|incompetent)\b",
-        r"\b(manipulative|narcissist|abuser|psycho|sociopath|liar|loser)\b",
-    ];
-
-    let mut keywords: Vec<String> = Vec::new();
-    
-    for pattern_str in keyword_patterns {
-        if let Ok(regex) = Regex::new(pattern_str) {
-            for cap in regex.find_iter(text) {
-                keywords.push(cap.as_str().to_lowercase());
-            }
-        }
-    }
-
-    // Remove duplicates
-    keywords.sort();
-    keywords.dedup();
-
-    match serde_json::to_string(&keywords) {
+    match serde_json::to_string(&content_keywords(text)) {
         Ok(json) => json,
         Err(_) => "[]".to_string(),
     }
 }
 
+/// Extract keywords ranked by frequency, rarity and position
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `top_k` - Maximum number of keywords to return (all when omitted)
+/// 
+/// # Returns
+/// JSON array of `{keyword, score, count, position}`, best first
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn extract_ranked_keywords(text: &str, top_k: Option<usize>) -> Result<String, String> {
+    serde_json::to_string(&rank_keywords(text, top_k)).map_err(|e| e.to_string())
+}
+
+/// Extract multi-word keyphrases ("custody hearing", "silent treatment") from noun-phrase chunks
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `top_k` - Maximum number of keyphrases to return (all when omitted)
+/// 
+/// # Returns
+/// JSON array of `{keyword, score, count, position}` where `keyword` is the phrase, best first
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn extract_keyphrases(text: &str, top_k: Option<usize>) -> Result<String, String> {
+    serde_json::to_string(&rank_keyphrases(text, top_k)).map_err(|e| e.to_string())
+}
+
+/// Extract ranked keywords and keyphrases with the people mentioned listed separately
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `top_k` - Maximum number of keywords and of keyphrases to return (all when omitted)
+/// 
+/// # Returns
+/// JSON `{keywords, keyphrases, entitiesMentioned: [{name, relationship, position}]}`; names and
+/// relationship terms never appear among the keywords
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn extract_keywords_with_entities(text: &str, top_k: Option<usize>) -> Result<String, String> {
+    serde_json::to_string(&extract_keywords_and_entities(text, top_k)).map_err(|e| e.to_string())
+}
+
+/// Detect high-entropy patterns and attribute them to the people extracted from the text
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// 
+/// # Returns
+/// JSON string shaped like `detect_high_entropy_patterns`, with `entityId` (matching an id from
+/// `extract_people_entities`) and `entityRole` ("source" or "target") on attributed matches
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn detect_high_entropy_patterns_with_entities(text: &str) -> String {
+    processing_result_json(&analyze_with_entities(text))
+}
+
+/// Per-person harm report: the categories of harmful language associated with each person
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// 
+/// # Returns
+/// JSON `{people: [{entityId, name, relationship, total, asSource, asTarget, categories: [{patternType, count,
+/// examples: [{matchText, position, role, sentence}]}]}], unattributed}`, people with the most matches first
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_person_harm_report(text: &str) -> Result<String, String> {
+    serde_json::to_string(&build_harm_report(text)).map_err(|e| e.to_string())
+}
+
+/// Compact digest of one entry for rendering an entry card
+/// 
+/// # Arguments
+/// * `text` - Entry text
+/// 
+/// # Returns
+/// JSON `{wordCount, score, mood, riskTier, topEmotions: [{emotion, count}], topEntities: [{name, relationship,
+/// sentiment, matches}], topCategories: [{patternType, count}]}`; `riskTier` is "none", "low", "moderate" or "high"
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn digest(text: &str) -> Result<String, String> {
+    serde_json::to_string(&digest_entry(text)).map_err(|e| e.to_string())
+}
+
+/// Aggregate digest of a week of entries
+/// 
+/// # Arguments
+/// * `week_json` - JSON `{entries: [{id?, timestamp?, text}], previousEntries?: [...]}`, oldest entry first
+/// 
+/// # Returns
+/// JSON `{entryCount, meanScore, meanMood, moodTrajectory, people, recurringCategories, changes}`;
+/// `changes` compares against `previousEntries` and is empty without them
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn weekly_entry_digest(week_json: &str) -> Result<String, String> {
+    let request: WeeklyDigestRequest = serde_json::from_str(week_json).map_err(|e| format!("invalid entries: {}", e))?;
+    serde_json::to_string(&weekly_digest(&request)).map_err(|e| e.to_string())
+}
+
+/// Per-paragraph (or per-N-sentences) score and mood series for drawing a text's trajectory
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `sentences_per_point` - Sentences per segment; paragraphs (blank-line separated) when omitted or 0
+/// 
+/// # Returns
+/// JSON `{unit, points: [{index, start, end, score, mood, patternCount}], peak}` with byte ranges
+/// and `peak` the index of the highest-scoring segment
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_text_trajectory(text: &str, sentences_per_point: Option<u32>) -> Result<String, String> {
+    serde_json::to_string(&text_trajectory(text, sentences_per_point.map(|n| n as usize))).map_err(|e| e.to_string())
+}
+
+/// Export analytics for a series of entries as one self-contained document
+/// 
+/// # Arguments
+/// * `entries_json` - JSON `{entries: [{id?, timestamp?, text}], includeText?}`, oldest entry first;
+///   entry text is only copied into the document when `includeText` is true
+/// 
+/// # Returns
+/// JSON `{schemaVersion, patternSetVersion, codedLexiconVersion, entries, entities, trend, timing}`
+/// with per-entry scores, per-person timelines (matched by name across entries), the score trend
+/// and time-of-day features of flagged entries
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn export_session_analytics(entries_json: &str) -> Result<String, String> {
+    let request: ExportRequest = serde_json::from_str(entries_json).map_err(|e| format!("invalid entries: {}", e))?;
+    serde_json::to_string(&export_analytics(&request)).map_err(|e| e.to_string())
+}
+
+/// Sentiment trend of each person across a series of entries
+/// 
+/// # Arguments
+/// * `request_json` - JSON `{entries: [{id?, timestamp?, text}], entity?}`, oldest entry first;
+///   `entity` (a name or relationship such as `"boss"`) restricts the result to that person
+/// 
+/// # Returns
+/// JSON array of `{name, relationship, trend, change, volatility, points}`, most mentioned first;
+/// `trend` is improving, deteriorating, volatile, stable or insufficient_data
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_entity_sentiment_trends(request_json: &str) -> Result<String, String> {
+    let request: SentimentTrendRequest =
+        serde_json::from_str(request_json).map_err(|e| format!("invalid entries: {}", e))?;
+    serde_json::to_string(&entity_sentiment_trends(&request)).map_err(|e| e.to_string())
+}
+
+/// Version of the built-in pattern set: crate version plus a hash of the pattern rules
+/// 
+/// # Returns
+/// Version string such as `0.1.0+3f2a9c0d1e4b5a67`; changes whenever any pattern changes
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_pattern_set_version() -> String {
+    pattern_set_version()
+}
+
 /// Extract people entities from text (for ambient contact management)
 /// 
 /// # Arguments
@@ -119,13 +1513,174 @@ pub fn extract_keywords(text: &str) -> String {
 /// 
 /// # Returns
 /// JSON string with extracted entities including names, relationships, and context
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn extract_people_entities(text: &str) -> String {
     let result = extract_entities(text);
     
-    match serde_json::to_string(&result) {
+    match to_versioned_json(&result, SchemaVersion::CURRENT) {
+        Ok(json) => json,
+        Err(_) => format!(
+            r#"{{"entities":[],"relationshipCount":0,"processingTimeUs":0,"schemaVersion":{}}}"#,
+            SchemaVersion::CURRENT.number()
+        ),
+    }
+}
+
+/// Extract people entities with extraction options
+/// 
+/// # Arguments
+/// * `text` - Text to analyze for people mentions
+/// * `config_json` - Optional JSON options, e.g. `{"anaphoraSentences": 0, "anaphoraChars": 120}` to
+///   credit an entity only with the pronouns in its own sentence and at most 120 bytes after it;
+///   `{"neopronouns": true}` also recognizes xe/xem, ze/zir and fae/faer;
+///   `{"inferPronouns": false}` turns pronoun inference off
+/// 
+/// # Returns
+/// JSON entity extraction results, as from `extract_people_entities`; entities have no `pronouns`
+/// field when none was inferred
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn extract_people_entities_with_config(text: &str, config_json: Option<String>) -> Result<String, String> {
+    let config: EntityConfig = match config_json.as_deref() {
+        Some(json) if !json.trim().is_empty() => serde_json::from_str(json).map_err(|e| format!("invalid config: {}", e))?,
+        _ => EntityConfig::default(),
+    };
+    to_versioned_json(&extract_entities_with_config(text, &config), SchemaVersion::CURRENT)
+}
+
+/// Turn pronoun inference on or off for every entity surface: contacts, digests, harm reports,
+/// analytics exports and the worker, as well as the `extract_people_entities` family. The setting
+/// is process-wide, so it holds for extractions on every thread of a native host
+/// 
+/// # Arguments
+/// * `enabled` - `false` for deployments that must not infer gendered attributes; on by default
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn set_pronoun_inference(enabled: bool) {
+    entity_extraction::set_pronoun_inference(enabled);
+}
+
+/// Extract people entities with a selectable binary encoding
+/// 
+/// # Arguments
+/// * `text` - Text to analyze for people mentions
+/// * `format` - `"msgpack"` for MessagePack or `"json"` for UTF-8 JSON bytes
+/// 
+/// # Returns
+/// Encoded entity extraction results; throws on an unknown format
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn extract_people_entities_encoded(text: &str, format: &str) -> Result<Vec<u8>, String> {
+    encode(&extract_entities(text), OutputFormat::parse(format)?)
+}
+
+/// Produce standoff annotations (text untouched, separate span records)
+/// 
+/// # Arguments
+/// * `text` - Text to annotate
+/// * `format` - `"json"` (default) for a span document, or `"brat"` for brat `.ann` lines
+/// 
+/// # Returns
+/// Annotations with character offsets; throws on an unknown format
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_standoff_annotations(text: &str, format: Option<String>) -> Result<String, String> {
+    let document = build_standoff(text);
+
+    match format.as_deref().unwrap_or("json") {
+        "json" => serde_json::to_string(&document).map_err(|e| e.to_string()),
+        "brat" => Ok(to_brat(&document)),
+        other => Err(format!("unsupported annotation format '{}'", other)),
+    }
+}
+
+/// Render text with detected patterns wrapped in markup
+/// 
+/// # Arguments
+/// * `text` - Text to analyze and render
+/// * `options_json` - Optional JSON options, e.g.
+///   `{"format":"html","tag":"mark","typeAttribute":"data-type","className":"hl"}` or
+///   `{"format":"markdown","markdownOpen":"**","markdownClose":"**"}`
+/// 
+/// # Returns
+/// Rendered text (HTML output is escaped); throws on invalid options
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn highlight_patterns(text: &str, options_json: Option<String>) -> Result<String, String> {
+    let options: HighlightOptions = match options_json.as_deref() {
+        Some(json) if !json.trim().is_empty() => serde_json::from_str(json).map_err(|e| format!("invalid options: {}", e))?,
+        _ => HighlightOptions::default(),
+    };
+
+    Ok(highlight_text(text, &options))
+}
+
+/// The most significant findings of a text, one per category, for compact badges
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `limit` - Findings to return (default 3)
+/// 
+/// # Returns
+/// JSON `{detected, score, findings: [{patternType, matchText, position, severity, significance, occurrences}],
+/// omittedCategories}`, ranked by severity × weight × confidence
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_top_findings(text: &str, limit: Option<u32>) -> Result<String, String> {
+    let limit = limit.map_or(DEFAULT_FINDINGS, |n| n as usize);
+    serde_json::to_string(&top_findings(text, limit)).map_err(|e| e.to_string())
+}
+
+/// Per-token (or per-range) intensity for heatmap-style highlighting
+/// 
+/// # Arguments
+/// * `text` - Text to analyze
+/// * `unit` - `"token"` (default) for one cell per word, or `"range"` for flat ranges between
+///   match boundaries covering the whole text
+/// 
+/// # Returns
+/// JSON `{unit, cells: [{start, end, intensity, patternTypes}], maxIntensity}` with byte ranges;
+/// overlapping match weights combine as `1 - (1 - w1)(1 - w2)...`; throws on an unknown unit
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_intensity_heatmap(text: &str, unit: Option<String>) -> Result<String, String> {
+    serde_json::to_string(&intensity_heatmap(text, HeatmapUnit::parse(unit.as_deref())?)).map_err(|e| e.to_string())
+}
+
+/// Report current wasm heap size and persistent store usage
+/// 
+/// # Returns
+/// JSON string with heap pages/bytes and per-store entry counts and approximate sizes; there is
+/// no entity or session store to report, as neither is kept between calls
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_memory_stats() -> String {
+    let stats = memory_stats();
+
+    match serde_json::to_string(&stats) {
+        Ok(json) => json,
+        Err(_) => r#"{"heapPages":0,"heapBytes":0,"stores":[],"totalStoreBytes":0}"#.to_string(),
+    }
+}
+
+/// Handle a Web Worker request envelope
+/// 
+/// # Arguments
+/// * `message` - JSON request `{ id, method, params }` as received via postMessage
+/// 
+/// # Returns
+/// JSON response `{ id, ok, result | error }` carrying the same request ID
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn handle_worker_message(message: &str) -> String {
+    let response = handle_request(message);
+
+    match serde_json::to_string(&response) {
+        Ok(json) => json,
+        Err(_) => r#"{"id":null,"ok":false,"error":{"code":"serialization_failed","message":"failed to serialize response"}}"#.to_string(),
+    }
+}
+
+/// Export JSON Schemas for all result types
+/// 
+/// # Returns
+/// JSON object mapping type names (e.g. `TextProcessingResult`) to their JSON Schema
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn get_json_schemas() -> String {
+    match serde_json::to_string(&result_schemas()) {
         Ok(json) => json,
-        Err(_) => r#"{"entities":[],"relationshipCount":0,"processingTimeUs":0}"#.to_string(),
+        Err(_) => "{}".to_string(),
     }
 }
 
@@ -234,10 +1789,123 @@ mod tests {
         assert!(result_intel.contains("weaponized_intellectualization"));
     }
 
+    #[test]
+    fn test_gratitude_returned_alongside_patterns() {
+        let result = analyze("I'm grateful for your help, but you're so selfish sometimes");
+        assert!(result.patterns.iter().any(|p| p.pattern_type == "character_judgment"));
+        assert!(result.positive_patterns.iter().any(|p| p.pattern_type == "gratitude"));
+        assert!(result.positive_score > 0.0);
+    }
+
+    #[test]
+    fn test_sentence_breakdown_option() {
+        let text = "Thanks for dinner. You're so selfish and it's all your fault. See you tomorrow.";
+        assert!(analyze(text).sentences.is_none());
+
+        let config = ScoringConfig { sentence_breakdown: true, ..ScoringConfig::default() };
+        let result = analyze_with_config(text, &config);
+        let sentences = result.sentences.unwrap();
+        assert_eq!(sentences.len(), 3);
+        assert_eq!(&text[sentences[1].start..sentences[1].end], "You're so selfish and it's all your fault.");
+        assert!(sentences[1].detected && !sentences[0].detected && !sentences[2].detected);
+        assert_eq!(sentences[1].pattern_indices.len(), result.patterns.len());
+        assert!(sentences[0].pattern_indices.is_empty());
+    }
+
     #[test]
     fn test_extract_keywords() {
         let text = "You are always so lazy";
         let result = extract_keywords(text);
         assert!(result.contains("you") || result.contains("always") || result.contains("lazy"));
     }
+
+    #[test]
+    fn test_legacy_and_structured_outputs_conform() {
+        let texts = ["You're so selfish and it's all your fault", "Sarah, my sister, said I'm crazy", ""];
+        for text in texts {
+            let legacy: serde_json::Value = serde_json::from_str(&detect_high_entropy_patterns(text)).unwrap();
+            let structured = structured_value(&analyze(text)).unwrap();
+            let version = Some(serde_json::Value::from(SchemaVersion::CURRENT.number()));
+            assert_eq!(legacy.get("schemaVersion"), version.as_ref());
+            // Compare through the same text round trip so float parsing can't differ
+            assert_eq!(legacy, serde_json::from_str::<serde_json::Value>(&structured.to_string()).unwrap());
+
+            // Timing differs run to run; every other entity field must agree
+            let mut legacy: serde_json::Value = serde_json::from_str(&extract_people_entities(text)).unwrap();
+            let mut structured = structured_value(&extract_entities(text)).unwrap();
+            assert_eq!(legacy.get("schemaVersion"), version.as_ref());
+            legacy.as_object_mut().unwrap().remove("processingTimeUs");
+            structured.as_object_mut().unwrap().remove("processingTimeUs");
+            assert_eq!(legacy, serde_json::from_str::<serde_json::Value>(&structured.to_string()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_entity_surfaces_handle_multibyte_text() {
+        let story = "My mom Linda and my dad had a huge fight. Ça va? 😀😀 She said it was my fault.";
+        let accented = format!("{} {}", "é".repeat(30), story);
+        for text in [story, accented.as_str()] {
+            let keywords: Vec<String> = serde_json::from_str(&extract_keywords(text)).unwrap();
+            assert!(keywords.contains(&"fight".to_string()));
+            assert!(extract_ranked_keywords(text, None).is_ok());
+            assert!(extract_keyphrases(text, None).is_ok());
+            assert!(extract_keywords_with_entities(text, None).is_ok());
+            assert!(reconcile_annotations(text).is_ok());
+            assert!(get_person_harm_report(text).is_ok());
+            assert!(digest(text).is_ok());
+            assert!(get_standoff_annotations(text, None).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_markup_detection_matches_plain_text_analysis() {
+        let source = "# Notes\nYou always [ignore me](https://example.com)!!! Maybe you never listen?";
+        let plain = "Notes\nYou always ignore me!!! Maybe you never listen?";
+        let markup: serde_json::Value =
+            serde_json::from_str(&detect_high_entropy_patterns_markup(source, "markdown").unwrap()).unwrap();
+        let expected: serde_json::Value = serde_json::from_str(&detect_high_entropy_patterns(plain)).unwrap();
+
+        for key in ["score", "confidence", "detected", "toneIntensity", "positiveScore"] {
+            assert_eq!(markup[key], expected[key], "{}", key);
+        }
+        let patterns = markup["patterns"].as_array().unwrap();
+        assert!(patterns.iter().any(|p| p["matchText"] == "You always [ignore"));
+        assert_eq!(patterns.len(), expected["patterns"].as_array().unwrap().len());
+        for (pattern, plain_pattern) in patterns.iter().zip(expected["patterns"].as_array().unwrap()) {
+            assert_eq!(pattern["confidence"], plain_pattern["confidence"]);
+            assert_eq!(pattern["scope"], plain_pattern["scope"]);
+            let position = pattern["position"].as_u64().unwrap() as usize;
+            let match_text = pattern["matchText"].as_str().unwrap();
+            assert_eq!(&source[position..position + match_text.len()], match_text);
+            assert!(pattern["id"].as_str().unwrap().ends_with(&format!("@{}", position)));
+            assert_eq!(pattern["quoted"], false);
+        }
+    }
+
+    #[test]
+    fn test_pronoun_inference_opt_out() {
+        let text = "My sister Amy called. She was upset.";
+        let inferred: serde_json::Value =
+            serde_json::from_str(&extract_people_entities_with_config(text, None).unwrap()).unwrap();
+        assert_eq!(inferred["entities"][0]["pronouns"], "she/her");
+
+        let config = Some(r#"{"inferPronouns": false}"#.to_string());
+        let omitted: serde_json::Value =
+            serde_json::from_str(&extract_people_entities_with_config(text, config).unwrap()).unwrap();
+        let entity = omitted["entities"][0].as_object().unwrap();
+        assert!(!entity.contains_key("pronouns") && !entity.contains_key("pronounEvidence"));
+        assert_eq!(entity["name"], "Amy");
+        assert!(extract_people_entities_with_config(text, Some("{".to_string())).is_err());
+
+        // The host-wide switch covers surfaces that take no config
+        {
+            let _off = entity_extraction::pronoun_switch::inference_off();
+            assert!(!entity_extraction::pronoun_inference_enabled());
+            let contacts = extract_people_entities_with_contacts(text, r#"[{"name":"Amy"}]"#).unwrap();
+            for output in [extract_people_entities(text), contacts, extract_people_entities_versioned(text, None).unwrap()] {
+                assert!(!output.contains("pronouns") && !output.contains("she/her"), "{}", output);
+            }
+        }
+        assert!(extract_people_entities(text).contains("she/her"));
+    }
 }
@@ -0,0 +1,140 @@
+//! Regional English variants
+//! The built-in patterns are written in US English; a locale adds Commonwealth spellings and
+//! regional idioms ("having a go at me", "winding me up") on top of them
+
+use regex::Regex;
+use serde::Deserialize;
+
+use super::pattern_matching::{apply_pattern, CompiledPattern, PatternMatch};
+
+/// Regional variety of English
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Locale {
+    #[default]
+    /// US English, the language of the built-in patterns
+    Us,
+    Gb,
+    Au,
+    In,
+}
+
+/// Locales using Commonwealth spelling ("realise", "tumour")
+const COMMONWEALTH: &[Locale] = &[Locale::Gb, Locale::Au, Locale::In];
+const BRITISH_AUSTRALIAN: &[Locale] = &[Locale::Gb, Locale::Au];
+
+impl Locale {
+    /// Parse a locale tag ("en-GB", "en_au", "uk", "IN"); anything else is an error
+    pub fn parse(tag: &str) -> Result<Self, String> {
+        let normalized = tag.trim().to_lowercase().replace('_', "-");
+        match normalized.trim_start_matches("en-") {
+            "" | "en" | "us" => Ok(Locale::Us),
+            "gb" | "uk" | "ie" => Ok(Locale::Gb),
+            "au" | "nz" => Ok(Locale::Au),
+            "in" => Ok(Locale::In),
+            _ => Err(format!("unsupported locale '{}'", tag)),
+        }
+    }
+
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::Us => "en-US",
+            Locale::Gb => "en-GB",
+            Locale::Au => "en-AU",
+            Locale::In => "en-IN",
+        }
+    }
+}
+
+impl TryFrom<String> for Locale {
+    type Error = String;
+
+    fn try_from(tag: String) -> Result<Self, Self::Error> {
+        Locale::parse(&tag)
+    }
+}
+
+/// Regional pattern definitions as (regex, pattern_type, severity, weight, locales)
+fn regional_definitions() -> Vec<(&'static str, &'static str, &'static str, f64, &'static [Locale])> {
+    vec![
+        // Commonwealth spellings of built-in patterns
+        (r"didn't\s+realise", "strategic_incompetence", "medium", 0.6, COMMONWEALTH),
+        (r"\bsympathisers\b", "identity_hijacking", "high", 0.9, COMMONWEALTH),
+        (r"\btumours?\b", "dehumanization", "high", 1.0, COMMONWEALTH),
+        (r"\b(catastrophising|catastrophise)\b", "catastrophizing", "medium", 0.7, COMMONWEALTH),
+
+        // British and Australian idioms
+        (r"(always|constantly|forever)\s+having\s+a\s+go\s+at\s+me", "self_victimization", "medium", 0.7, BRITISH_AUSTRALIAN),
+        (r"(only|just)\s+winding\s+(you|them|him|her)\s+up", "gaslighting_minimization", "high", 0.8, BRITISH_AUSTRALIAN),
+        (r"keep\s+your\s+hair\s+on|knickers\s+in\s+a\s+twist", "tone_policing", "high", 0.8, BRITISH_AUSTRALIAN),
+        (r"stop\s+(your\s+)?whing(e|eing|ing)\b", "minimization", "high", 0.8, BRITISH_AUSTRALIAN),
+        (r"\b(muppet|numpty|pillock|plonker|prat|wally)\b", "insult", "high", 0.9, &[Locale::Gb]),
+        (r"\b(drongo|galah|dropkick)\b", "insult", "high", 0.9, &[Locale::Au]),
+        (r"pull\s+your\s+head\s+in|spit(ting)?\s+the\s+dummy", "tone_policing", "high", 0.8, &[Locale::Au]),
+        (r"(such\s+a|stop\s+being\s+a|you're\s+a)\s+sook\b", "minimization", "high", 0.8, &[Locale::Au]),
+
+        // Indian English idioms
+        (r"\b(nonsense|useless|stupid|shameless)\s+fellow\b", "insult", "high", 0.9, &[Locale::In]),
+        (r"\b(don't|do\s+not)\s+act\s+(over\s*)?smart\b|\boversmart\b", "insult", "medium", 0.7, &[Locale::In]),
+        (r"what\s+rubbish\s+(you\s+are|are\s+you)\s+(talking|saying)", "gaslighting_invalidation", "high", 0.9, &[Locale::In]),
+        (r"have\s+you\s+gone\s+mad", "sanity_attack", "high", 1.0, &[Locale::In, Locale::Gb]),
+    ]
+}
+
+lazy_static::lazy_static! {
+    /// Compiled regional patterns with the locales each one applies to
+    static ref REGIONAL_PATTERNS: Vec<(CompiledPattern, &'static [Locale])> = {
+        let mut seen: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+        regional_definitions()
+            .into_iter()
+            .filter_map(|(pattern_str, pattern_type, severity, weight, locales)| {
+                let ordinal = seen.entry(pattern_type).or_insert(0);
+                *ordinal += 1;
+                // Own id namespace so built-in ordinals never shift
+                let id = format!("{}.regional.{}", pattern_type, *ordinal - 1);
+                Regex::new(&format!("(?i){}", pattern_str))
+                    .ok()
+                    .map(|regex| (CompiledPattern { id, regex, pattern_type, severity, weight }, locales))
+            })
+            .collect()
+    };
+}
+
+/// Matches of the regional patterns for `locale` (none for US English)
+pub fn match_regional_patterns(text: &str, locale: Locale) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+    for (pattern, locales) in REGIONAL_PATTERNS.iter() {
+        if locales.contains(&locale) {
+            apply_pattern(pattern, text, &mut matches);
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locale_tags() {
+        assert_eq!(Locale::parse("en-GB"), Ok(Locale::Gb));
+        assert_eq!(Locale::parse("en_au"), Ok(Locale::Au));
+        assert_eq!(Locale::parse("UK"), Ok(Locale::Gb));
+        assert_eq!(Locale::parse("en-IN").map(Locale::tag), Ok("en-IN"));
+        assert_eq!(Locale::parse(""), Ok(Locale::Us));
+        assert!(Locale::parse("fr-FR").is_err());
+        assert_eq!(REGIONAL_PATTERNS.len(), regional_definitions().len());
+    }
+
+    #[test]
+    fn test_regional_idioms_only_match_in_their_locale() {
+        let text = "He's always having a go at me, I was only winding you up";
+        let types = |locale| -> Vec<String> {
+            match_regional_patterns(text, locale).into_iter().map(|m| m.pattern_type).collect()
+        };
+        assert_eq!(types(Locale::Gb), vec!["self_victimization", "gaslighting_minimization"]);
+        assert!(types(Locale::Us).is_empty());
+        assert!(types(Locale::In).is_empty());
+        assert_eq!(match_regional_patterns("Sorry, I didn't realise", Locale::In)[0].pattern_id, "strategic_incompetence.regional.0");
+    }
+}
@@ -0,0 +1,350 @@
+//! Markup-aware preprocessing
+//! Extracts analyzable prose from markdown or HTML while keeping offsets mapped to the source
+
+use super::offsets::MappedText;
+use super::pattern_matching::{match_patterns, PatternMatch};
+
+/// Weight multiplier for matches inside quoted speech (reported, not the author's own words)
+const QUOTED_WEIGHT_FACTOR: f64 = 0.5;
+
+/// Prose extracted from a marked-up document
+#[derive(Debug, Clone, Default)]
+pub struct PreparedDocument {
+    pub mapped: MappedText,
+    /// Prepared-text byte ranges that are quoted speech (e.g. blockquotes)
+    pub quoted: Vec<(usize, usize)>,
+}
+
+impl PreparedDocument {
+    /// Whether a prepared-text byte offset falls inside quoted speech
+    pub fn is_quoted(&self, position: usize) -> bool {
+        self.quoted.iter().any(|&(start, end)| position >= start && position < end)
+    }
+
+    /// Source byte offset and slice of `source` (the document this was prepared from) covered by
+    /// the prepared-text span at `position`; the slice may include the markup the span crossed
+    pub fn source_span<'a>(&self, source: &'a str, position: usize, len: usize) -> (usize, &'a str) {
+        let (start, end) = self.mapped.source_range(position, position + len);
+        (start, &source[start..end])
+    }
+}
+
+/// Byte length of the `](url)` tail starting at `start`, if present
+fn link_target_len(line: &str, start: usize) -> Option<usize> {
+    let rest = line[start..].strip_prefix("](")?;
+    rest.find(')').map(|close| close + 3)
+}
+
+/// Append one line of inline markdown, dropping code spans, link URLs and bare URLs
+fn push_inline(out: &mut MappedText, source: &str, line_start: usize, line: &str) {
+    let bytes = line.as_bytes();
+    let mut copied = 0;
+    let mut i = 0;
+
+    while i < line.len() {
+        let skip = match bytes[i] {
+            // Inline code span: replaced by a space
+            b'`' => line[i + 1..].find('`').map(|close| (close + 2, " ")),
+            // Image/link opener: keep the label, drop the marker
+            b'!' if line[i + 1..].starts_with('[') && line[i..].contains("](") => Some((2, "")),
+            b'[' if line[i..].contains("](") => Some((1, "")),
+            // Link target: dropped entirely
+            b']' => link_target_len(line, i).map(|len| (len, "")),
+            // Autolink <http://...>
+            b'<' if line[i + 1..].starts_with("http") => line[i..].find('>').map(|close| (close + 1, " ")),
+            // Bare URL
+            b'h' if line[i..].starts_with("http://") || line[i..].starts_with("https://") => {
+                Some((line[i..].find(char::is_whitespace).unwrap_or(line.len() - i), " "))
+            }
+            _ => None,
+        };
+
+        match skip {
+            Some((len, replacement)) => {
+                out.push_source(source, line_start + copied, line_start + i);
+                out.push_replacement(replacement, line_start + i, line_start + i + len);
+                i += len;
+                copied = i;
+            }
+            None => i += line[i..].chars().next().map(char::len_utf8).unwrap_or(1),
+        }
+    }
+
+    out.push_source(source, line_start + copied, line_start + line.len());
+}
+
+/// Prepare markdown: skip fenced code blocks, link URLs and inline code; strip heading
+/// and blockquote markers; record blockquotes as quoted speech
+pub fn prepare_markdown(source: &str) -> PreparedDocument {
+    let mut document = PreparedDocument::default();
+    let mut in_fence: Option<&str> = None;
+    let mut line_start = 0;
+
+    for raw_line in source.split_inclusive('\n') {
+        let line = raw_line.trim_end_matches(['\n', '\r']);
+        let newline = &raw_line[line.len()..];
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        if let Some(fence) = in_fence {
+            if trimmed.starts_with(fence) {
+                in_fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = Some(&trimmed[..3]);
+        } else {
+            // Strip blockquote markers (possibly nested) and heading markers
+            let mut content_start = indent;
+            let mut quoted = false;
+            while line[content_start..].starts_with('>') {
+                quoted = true;
+                content_start += 1;
+                content_start += line[content_start..].len() - line[content_start..].trim_start().len();
+            }
+            let heading = line[content_start..].len() - line[content_start..].trim_start_matches('#').len();
+            if heading > 0 && line[content_start + heading..].starts_with(' ') {
+                content_start += heading + 1;
+            }
+
+            let out_start = document.mapped.text.len();
+            push_inline(&mut document.mapped, source, line_start + content_start, &line[content_start..]);
+            if quoted {
+                document.quoted.push((out_start, document.mapped.text.len()));
+            }
+        }
+
+        // Keep line breaks so sentence boundaries survive skipped blocks
+        if !newline.is_empty() {
+            document.mapped.push_replacement("\n", line_start + line.len(), line_start + raw_line.len());
+        }
+        line_start += raw_line.len();
+    }
+
+    document
+}
+
+/// HTML elements that start a new line of text
+const BLOCK_TAGS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "br", "dd", "div", "dl", "dt", "footer",
+    "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr", "li", "main", "ol", "p", "pre",
+    "section", "table", "td", "th", "tr", "ul",
+];
+
+/// HTML elements whose content is never prose
+const SKIPPED_TAGS: &[&str] = &["script", "style", "template", "head"];
+
+/// HTML elements whose content is quoted speech
+const QUOTE_TAGS: &[&str] = &["blockquote", "q"];
+
+/// Decode an HTML character reference at the start of `text`, returning (decoded, byte length)
+pub fn decode_entity(text: &str) -> Option<(String, usize)> {
+    let end = text.char_indices().take(12).find(|&(_, c)| c == ';').map(|(i, _)| i)?;
+    let name = &text[1..end];
+    let decoded = match name {
+        "amp" => "&".to_string(),
+        "lt" => "<".to_string(),
+        "gt" => ">".to_string(),
+        "quot" => "\"".to_string(),
+        "apos" => "'".to_string(),
+        "nbsp" => " ".to_string(),
+        "rsquo" | "lsquo" => "'".to_string(),
+        "rdquo" | "ldquo" => "\"".to_string(),
+        "mdash" | "ndash" => "-".to_string(),
+        _ => {
+            let code = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                u32::from_str_radix(hex, 16).ok()?
+            } else {
+                name.strip_prefix('#')?.parse().ok()?
+            };
+            char::from_u32(code)?.to_string()
+        }
+    };
+    Some((decoded, end + 1))
+}
+
+/// Lowercased tag name and whether it is a closing tag, for a tag body like `/p class="x"`
+fn tag_name(body: &str) -> (String, bool) {
+    let closing = body.starts_with('/');
+    let name: String = body
+        .trim_start_matches('/')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect();
+    (name.to_ascii_lowercase(), closing)
+}
+
+/// Prepare HTML: strip tags, comments, scripts and styles; decode entities; break lines at
+/// block elements; record blockquote/q content as quoted speech
+pub fn prepare_html(source: &str) -> PreparedDocument {
+    let mut document = PreparedDocument::default();
+    let mut copied = 0;
+    let mut i = 0;
+    let mut skipping: Option<String> = None;
+    let mut quote_depth = 0usize;
+    let mut quote_start = 0;
+
+    while i < source.len() {
+        let rest = &source[i..];
+
+        if rest.starts_with("<!--") {
+            let len = rest.find("-->").map(|e| e + 3).unwrap_or(rest.len());
+            if skipping.is_none() {
+                document.mapped.push_source(source, copied, i);
+            }
+            i += len;
+            copied = i;
+            continue;
+        }
+
+        // An unterminated `<` is left as text
+        let opens_tag = rest.starts_with('<') && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/');
+        if let Some(close) = opens_tag.then(|| rest.find('>')).flatten() {
+            let len = close + 1;
+            let (name, closing) = tag_name(&rest[1..len.saturating_sub(1)]);
+
+            if let Some(skipped) = &skipping {
+                if closing && &name == skipped {
+                    skipping = None;
+                }
+                i += len;
+                copied = i;
+                continue;
+            }
+
+            document.mapped.push_source(source, copied, i);
+            if QUOTE_TAGS.contains(&name.as_str()) {
+                if !closing {
+                    if quote_depth == 0 {
+                        quote_start = document.mapped.text.len();
+                    }
+                    quote_depth += 1;
+                } else if quote_depth > 0 {
+                    quote_depth -= 1;
+                    if quote_depth == 0 {
+                        document.quoted.push((quote_start, document.mapped.text.len()));
+                    }
+                }
+            }
+            if BLOCK_TAGS.contains(&name.as_str()) {
+                document.mapped.push_replacement("\n", i, i + len);
+            } else if !closing && SKIPPED_TAGS.contains(&name.as_str()) && !rest[..len].ends_with("/>") {
+                skipping = Some(name);
+            }
+            i += len;
+            copied = i;
+            continue;
+        }
+
+        if skipping.is_none() && rest.starts_with('&') {
+            if let Some((decoded, len)) = decode_entity(rest) {
+                document.mapped.push_source(source, copied, i);
+                document.mapped.push_replacement(&decoded, i, i + len);
+                i += len;
+                copied = i;
+                continue;
+            }
+        }
+
+        i += rest.chars().next().map(char::len_utf8).unwrap_or(1);
+    }
+
+    if skipping.is_none() {
+        document.mapped.push_source(source, copied, source.len());
+    }
+    if quote_depth > 0 {
+        document.quoted.push((quote_start, document.mapped.text.len()));
+    }
+
+    document
+}
+
+/// Match patterns in a prepared document, positioned in the prepared text and down-weighted
+/// inside quoted speech; map them back with [`PreparedDocument::source_span`] after analysis
+pub fn match_prepared(document: &PreparedDocument) -> Vec<PatternMatch> {
+    match_patterns(&document.mapped.text)
+        .into_iter()
+        .map(|mut pattern| {
+            if document.is_quoted(pattern.position) {
+                pattern.weight *= QUOTED_WEIGHT_FACTOR;
+            }
+            pattern
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_skips_code_and_urls() {
+        let source = "# Notes\nSee [my therapist](https://example.com/idiot) today.\n```\nlet idiot = 1;\n```\nUse `crazy` flag";
+        let document = prepare_markdown(source);
+        let text = &document.mapped.text;
+        assert!(text.contains("Notes\nSee my therapist today."));
+        assert!(!text.contains("idiot"));
+        assert!(!text.contains("crazy"));
+        assert!(!text.contains("http"));
+    }
+
+    #[test]
+    fn test_blockquotes_are_quoted_and_mapped() {
+        let source = "Mom wrote:\n> You're so selfish\nI disagree.";
+        let document = prepare_markdown(source);
+        let position = document.mapped.text.find("selfish").unwrap();
+        assert!(document.is_quoted(position));
+        assert!(!document.is_quoted(document.mapped.text.find("disagree").unwrap()));
+
+        let (start, end) = document.mapped.source_range(position, position + "selfish".len());
+        assert_eq!(&source[start..end], "selfish");
+
+        let matches = match_prepared(&document);
+        let judgment = matches.iter().find(|m| m.pattern_type == "character_judgment").unwrap();
+        assert!(document.is_quoted(judgment.position));
+        let (start, _) = document.source_span(source, judgment.position, judgment.match_text.len());
+        assert_eq!(&source[start..start + 7], "You're ");
+        assert!(judgment.weight < 1.0);
+    }
+
+    #[test]
+    fn test_html_strips_tags_and_decodes_entities() {
+        let source = "<p>It&#39;s all <b>your</b> fault</p><script>var idiot;</script><!-- idiot -->";
+        let document = prepare_html(source);
+        assert_eq!(document.mapped.text.trim(), "It's all your fault");
+
+        let matches = match_prepared(&document);
+        let displacement = matches.iter().find(|m| m.pattern_type == "displacement").unwrap();
+        let (start, _) = document.source_span(source, displacement.position, displacement.match_text.len());
+        assert_eq!(start, source.find("It&#39;s").unwrap());
+        assert!(!matches.iter().any(|m| m.match_text == "idiot"));
+    }
+
+    #[test]
+    fn test_match_spans_cover_the_source() {
+        let source = "You're so `x` selfish and you [always](https://example.com) lie";
+        let document = prepare_markdown(source);
+        let spans: Vec<(usize, &str)> =
+            match_prepared(&document).iter().map(|m| document.source_span(source, m.position, m.match_text.len())).collect();
+        assert!(!spans.is_empty());
+        for &(start, span) in &spans {
+            assert_eq!(&source[start..start + span.len()], span);
+        }
+        assert!(spans.iter().any(|(_, span)| span.contains("](https://example.com)")));
+    }
+
+    #[test]
+    fn test_multibyte_entities_and_unterminated_tags() {
+        assert_eq!(prepare_html("&aaaaaaaaaa\u{e9}; hi").mapped.text, "&aaaaaaaaaa\u{e9}; hi");
+        assert_eq!(prepare_html("hello <a\u{e9}").mapped.text, "hello <a\u{e9}");
+        assert_eq!(decode_entity("&\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}"), None);
+        assert_eq!(decode_entity("&amp; x"), Some(("&".to_string(), 5)));
+    }
+
+    #[test]
+    fn test_html_blockquote_is_quoted() {
+        let document = prepare_html("<blockquote>You&rsquo;re so selfish</blockquote><p>Not fair</p>");
+        let position = document.mapped.text.find("selfish").unwrap();
+        assert!(document.is_quoted(position));
+        assert!(!document.is_quoted(document.mapped.text.find("Not").unwrap()));
+    }
+}
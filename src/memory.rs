@@ -0,0 +1,86 @@
+//! Memory usage reporting
+//! Lets hosts (mobile webviews in particular) decide when to flush persistent state. The module
+//! keeps no entity or session store: people and sessions are recomputed from the input on every
+//! call, so the stores reported are the pattern caches and the host-loaded lists
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::coded_lexicon;
+use super::entity_extraction;
+use super::gazetteer;
+use super::i18n;
+use super::pattern_matching;
+
+/// Size of a single wasm linear memory page in bytes
+const WASM_PAGE_SIZE: usize = 64 * 1024;
+
+/// Usage of a single persistent store or cache
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreUsage {
+    pub name: String,
+    pub entries: usize,
+    pub approx_bytes: usize,
+}
+
+/// Memory usage snapshot
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryStats {
+    pub heap_pages: usize,
+    pub heap_bytes: usize,
+    pub stores: Vec<StoreUsage>,
+    pub total_store_bytes: usize,
+}
+
+/// Current size of linear memory in pages (0 when not running as wasm)
+fn heap_pages() -> usize {
+    #[cfg(target_arch = "wasm32")]
+    {
+        core::arch::wasm32::memory_size::<0>()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        0
+    }
+}
+
+/// Collect usage for every persistent store held by the module
+fn store_usage() -> Vec<StoreUsage> {
+    vec![
+        pattern_matching::compiled_pattern_usage(),
+        entity_extraction::pattern_cache_usage(),
+        coded_lexicon::coded_lexicon_usage(),
+        gazetteer::given_names_usage(),
+        i18n::label_translations_usage(),
+    ]
+}
+
+/// Take a snapshot of heap size and persistent store usage
+pub fn memory_stats() -> MemoryStats {
+    let pages = heap_pages();
+    let stores = store_usage();
+    let total_store_bytes = stores.iter().map(|s| s.approx_bytes).sum();
+
+    MemoryStats {
+        heap_pages: pages,
+        heap_bytes: pages * WASM_PAGE_SIZE,
+        stores,
+        total_store_bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_stats_lists_stores() {
+        let stats = memory_stats();
+        assert!(stats.stores.iter().any(|s| s.name == "relationship_patterns"));
+        let compiled = stats.stores.iter().find(|s| s.name == "compiled_patterns").unwrap();
+        assert!(compiled.entries > 0 && compiled.approx_bytes > 0);
+        assert_eq!(stats.heap_bytes, stats.heap_pages * WASM_PAGE_SIZE);
+    }
+}
@@ -0,0 +1,185 @@
+//! Nonviolent Communication (NVC) conformance scoring
+//! Rates a message on observations, feelings, needs and requests, with feedback per component
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::pattern_matching::match_patterns;
+
+/// Pattern types that signal evaluation instead of observation
+const EVALUATION_TYPES: &[&str] = &["absolute_statement", "universalizing", "absolutism", "absolute_certainty"];
+
+/// Pattern types that signal judgment instead of feelings
+const JUDGMENT_TYPES: &[&str] = &["character_judgment", "insult", "visceral_judgment", "sanity_attack"];
+
+fn compile(patterns: &[&str]) -> Vec<Regex> {
+    patterns.iter().map(|p| Regex::new(&format!("(?i){}", p)).unwrap()).collect()
+}
+
+lazy_static! {
+    /// Concrete, time-bound descriptions of what happened
+    static ref OBSERVATIONS: Vec<Regex> = compile(&[
+        r"\bwhen\s+(?:I\s+(?:saw|heard|noticed|read|found)|you\s+(?:said|did|came|left|told|walked|didn't|did\s+not|were)|we\s+(?:talked|spoke|argued))\b",
+        r"\b(?:yesterday|this\s+morning|last\s+(?:night|week)|earlier\s+today|on\s+(?:monday|tuesday|wednesday|thursday|friday|saturday|sunday))\b",
+        r"\b(?:at|by)\s+\d{1,2}(?::\d{2})?\s*(?:am|pm)?\b",
+        r"\b\d+\s+(?:times|minutes|hours|days)\b",
+    ]);
+    /// Feelings owned by the speaker
+    static ref FEELINGS: Vec<Regex> = compile(&[
+        r"\bI\s+(?:feel|felt|am|'m|was)\s+(?:so\s+|really\s+|very\s+|a\s+bit\s+|kind\s+of\s+)?(?:sad|hurt|angry|scared|afraid|worried|anxious|frustrated|lonely|disappointed|happy|grateful|overwhelmed|confused|upset|nervous|tired|exhausted|embarrassed|ashamed|relieved|hopeful|excited|discouraged|irritated|uneasy)\b",
+    ]);
+    /// Judgments dressed up as feelings ("I feel like you...", "I feel ignored")
+    static ref PSEUDO_FEELINGS: Vec<Regex> = compile(&[
+        r"\bI\s+feel\s+(?:like|that|as\s+if|as\s+though)\b",
+        r"\bI\s+feel\s+(?:ignored|manipulated|disrespected|betrayed|abandoned|attacked|used|rejected|criticized|unappreciated|misunderstood|neglected|cheated|unheard)\b",
+    ]);
+    /// Needs and values behind the feelings
+    static ref NEEDS: Vec<Regex> = compile(&[
+        r"\b(?:I|we)\s+(?:need|value|long\s+for)\s+(?:some\s+|more\s+|a\s+sense\s+of\s+)?(?:support|connection|rest|respect|understanding|honesty|trust|space|clarity|help|appreciation|safety|reassurance|consideration|peace|fairness|autonomy|closeness)\b",
+        r"\bbecause\s+I\s+(?:need|value|care\s+about)\b",
+        r"\bmy\s+need\s+for\b",
+        r"\b(?:is|are)\s+(?:really\s+)?important\s+to\s+me\b",
+    ]);
+    /// Strategies phrased as needs ("I need you to...")
+    static ref NEED_STRATEGIES: Vec<Regex> = compile(&[r"\bI\s+need\s+you\s+to\b"]);
+    /// Concrete requests leaving room to say no
+    static ref REQUESTS: Vec<Regex> = compile(&[
+        r"\bwould\s+you\s+be\s+willing\s+to\b",
+        r"\b(?:would|could|will|can)\s+you\s+(?:please\s+)?\w+",
+        r"\b(?:can|could)\s+we\b",
+        r"\bare\s+you\s+(?:willing|open)\s+to\b",
+        r"\bhow\s+would\s+you\s+feel\s+about\b",
+    ]);
+    /// Demands that leave no room to say no
+    static ref DEMANDS: Vec<Regex> = compile(&[
+        r"\byou\s+(?:need\s+to|have\s+to|must|better|should|had\s+better)\b",
+        r"\bor\s+else\b",
+        r"(?:^|[.!?]\s+)(?:stop|just\s+do|do\s+it|get\s+over\s+it)\b",
+    ]);
+}
+
+/// Score for one NVC component
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NvcComponent {
+    /// `observation`, `feelings`, `needs` or `request`
+    pub component: String,
+    /// 0 (absent or outweighed) to 1 (clearly expressed)
+    pub score: f64,
+    /// Phrases supporting the component
+    pub supporting: Vec<String>,
+    /// Phrases working against it (evaluations, judgments, pseudo-feelings, demands)
+    pub opposing: Vec<String>,
+    pub feedback: String,
+}
+
+/// NVC conformance of a message
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NvcAnalysis {
+    /// Mean of the component scores
+    pub score: f64,
+    pub components: Vec<NvcComponent>,
+}
+
+fn find_all(regexes: &[Regex], text: &str) -> Vec<String> {
+    regexes.iter().flat_map(|r| r.find_iter(text).map(|m| m.as_str().trim().to_string())).collect()
+}
+
+/// Build a component from its cues; feedback is (missing, outweighed, good)
+fn component(name: &str, supporting: Vec<String>, opposing: Vec<String>, feedback: [&str; 3]) -> NvcComponent {
+    let (pro, con) = (supporting.len() as f64, opposing.len() as f64);
+    let score = if pro + con == 0.0 { 0.0 } else { pro / (pro + con) };
+    let feedback = if pro == 0.0 {
+        feedback[0]
+    } else if con > 0.0 {
+        feedback[1]
+    } else {
+        feedback[2]
+    };
+    NvcComponent { component: name.to_string(), score, supporting, opposing, feedback: feedback.to_string() }
+}
+
+/// Score a message against the four NVC components
+pub fn score_nvc(text: &str) -> NvcAnalysis {
+    let matches = match_patterns(text);
+    let of_types = |types: &[&str]| -> Vec<String> {
+        matches.iter().filter(|m| types.contains(&m.pattern_type.as_str())).map(|m| m.match_text.clone()).collect()
+    };
+
+    let mut judgments = of_types(JUDGMENT_TYPES);
+    judgments.extend(find_all(&PSEUDO_FEELINGS, text));
+
+    let components = vec![
+        component(
+            "observation",
+            find_all(&OBSERVATIONS, text),
+            of_types(EVALUATION_TYPES),
+            [
+                "Describe the specific event you saw or heard (\"when you left at 9\") before reacting.",
+                "Replace generalizations like \"always\" and \"never\" with the specific event.",
+                "Clear observation of what happened.",
+            ],
+        ),
+        component(
+            "feelings",
+            find_all(&FEELINGS, text),
+            judgments,
+            [
+                "Name how you feel (\"I feel hurt\") rather than what the other person is.",
+                "\"I feel like you...\" and labels like \"ignored\" describe the other person; name your own emotion instead.",
+                "Feelings are owned and expressed.",
+            ],
+        ),
+        component(
+            "needs",
+            find_all(&NEEDS, text),
+            find_all(&NEED_STRATEGIES, text),
+            [
+                "Connect the feeling to an underlying need (\"because I need rest\").",
+                "\"I need you to...\" names a strategy; name the need behind it (rest, support, respect).",
+                "Underlying needs are stated.",
+            ],
+        ),
+        component(
+            "request",
+            find_all(&REQUESTS, text),
+            find_all(&DEMANDS, text),
+            [
+                "Close with a concrete, doable request (\"Would you be willing to...?\").",
+                "Phrase demands (\"you need to\", \"or else\") as requests the other person can decline.",
+                "A clear request is made.",
+            ],
+        ),
+    ];
+
+    let score = components.iter().map(|c| c.score).sum::<f64>() / components.len() as f64;
+    NvcAnalysis { score, components }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_nvc_message_scores_high() {
+        let analysis = score_nvc(
+            "When you came home at 11 last night, I felt worried because I need reassurance. Would you be willing to text me next time?",
+        );
+        assert!(analysis.components.iter().all(|c| c.score == 1.0), "{:?}", analysis);
+        assert_eq!(analysis.score, 1.0);
+    }
+
+    #[test]
+    fn test_judgments_and_demands_score_low() {
+        let analysis = score_nvc("You always ignore me. I feel like you're so selfish. You need to change or else.");
+        let by_name = |name: &str| analysis.components.iter().find(|c| c.component == name).unwrap();
+        assert_eq!(by_name("observation").score, 0.0);
+        assert!(!by_name("feelings").opposing.is_empty());
+        assert_eq!(by_name("needs").score, 0.0);
+        assert_eq!(by_name("request").score, 0.0);
+        assert!(analysis.score < 0.2);
+    }
+}
@@ -0,0 +1,198 @@
+//! Offset conversion helpers
+//! Matches carry UTF-8 byte offsets; annotation tools want character offsets, and
+//! preprocessed text (markdown, HTML) needs mapping back to its source
+
+/// Byte-to-character offset index for a text
+pub struct CharOffsets {
+    /// Byte offset of every char start, plus the text length as a sentinel
+    byte_starts: Vec<usize>,
+}
+
+impl CharOffsets {
+    pub fn new(text: &str) -> Self {
+        let mut byte_starts: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        byte_starts.push(text.len());
+        CharOffsets { byte_starts }
+    }
+
+    /// Character offset of a byte offset (bytes inside a char map to that char)
+    pub fn char_offset(&self, byte: usize) -> usize {
+        match self.byte_starts.binary_search(&byte) {
+            Ok(index) => index,
+            Err(index) => index.saturating_sub(1),
+        }
+    }
+}
+
+/// Byte range of the sentence containing `position` (end includes the terminator)
+pub fn sentence_bounds(text: &str, position: usize) -> (usize, usize) {
+    let is_end = |c: char| matches!(c, '.' | '!' | '?' | '\n');
+    let start = text[..position].rfind(is_end).map(|i| i + 1).unwrap_or(0);
+    let end = text[position..].find(is_end).map(|i| position + i + 1).unwrap_or(text.len());
+    (start, end)
+}
+
+/// Byte range reaching `margin` bytes either side of `start..end`, widened to char boundaries
+pub fn context_bounds(text: &str, start: usize, end: usize, margin: usize) -> (usize, usize) {
+    let mut start = start.saturating_sub(margin);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = end.saturating_add(margin).min(text.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+    (start, end)
+}
+
+/// Trimmed, non-empty sentence ranges of a text
+pub fn sentence_ranges(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut position = 0;
+    while position < text.len() {
+        let (start, end) = sentence_bounds(text, position);
+        let sentence = &text[start..end];
+        let trimmed_start = start + (sentence.len() - sentence.trim_start().len());
+        let trimmed_end = start + sentence.trim_end().len();
+        if trimmed_start < trimmed_end {
+            ranges.push((trimmed_start, trimmed_end));
+        }
+        position = end;
+    }
+    ranges
+}
+
+/// Trimmed, non-empty paragraph ranges: text separated by blank lines
+pub fn paragraph_ranges(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            push_trimmed(text, start, line_start, &mut ranges);
+            start = line_start + line.len();
+        }
+        line_start += line.len();
+    }
+    push_trimmed(text, start, text.len(), &mut ranges);
+    ranges
+}
+
+fn push_trimmed(text: &str, start: usize, end: usize, ranges: &mut Vec<(usize, usize)>) {
+    let segment = &text[start..end];
+    let trimmed_start = start + (segment.len() - segment.trim_start().len());
+    let trimmed_end = start + segment.trim_end().len();
+    if trimmed_start < trimmed_end {
+        ranges.push((trimmed_start, trimmed_end));
+    }
+}
+
+/// Piece of prepared text and the source range it came from
+#[derive(Debug, Clone)]
+struct Piece {
+    out_start: usize,
+    out_end: usize,
+    src_start: usize,
+    src_end: usize,
+    /// Copied unchanged, so offsets inside map one-to-one
+    verbatim: bool,
+}
+
+/// Text derived from a source document with a byte offset map back to that source
+#[derive(Debug, Clone, Default)]
+pub struct MappedText {
+    pub text: String,
+    pieces: Vec<Piece>,
+}
+
+impl MappedText {
+    /// Copy `source[start..end]` unchanged
+    pub fn push_source(&mut self, source: &str, start: usize, end: usize) {
+        if start >= end {
+            return;
+        }
+        let out_start = self.text.len();
+        self.text.push_str(&source[start..end]);
+        self.pieces.push(Piece { out_start, out_end: self.text.len(), src_start: start, src_end: end, verbatim: true });
+    }
+
+    /// Emit `replacement` standing in for `source[src_start..src_end]` (e.g. a decoded entity)
+    pub fn push_replacement(&mut self, replacement: &str, src_start: usize, src_end: usize) {
+        if replacement.is_empty() {
+            return;
+        }
+        let out_start = self.text.len();
+        self.text.push_str(replacement);
+        self.pieces.push(Piece { out_start, out_end: self.text.len(), src_start, src_end, verbatim: false });
+    }
+
+    /// Index of the piece containing prepared byte `out`
+    fn piece_at(&self, out: usize) -> Option<&Piece> {
+        let index = self.pieces.partition_point(|p| p.out_end <= out);
+        self.pieces.get(index)
+    }
+
+    /// Map a prepared byte range back to the source byte range it covers
+    pub fn source_range(&self, out_start: usize, out_end: usize) -> (usize, usize) {
+        let start = match self.piece_at(out_start) {
+            Some(p) if p.verbatim => p.src_start + (out_start - p.out_start),
+            Some(p) => p.src_start,
+            None => self.pieces.last().map(|p| p.src_end).unwrap_or(0),
+        };
+        let end = match self.piece_at(out_end.saturating_sub(1)) {
+            Some(p) if p.verbatim && out_end > out_start => p.src_start + (out_end - p.out_start),
+            Some(p) if out_end > out_start => p.src_end,
+            _ => start,
+        };
+        (start, end.max(start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_char_offsets_with_multibyte_text() {
+        let text = "café 😀 ok";
+        let offsets = CharOffsets::new(text);
+        let ok_byte = text.find("ok").unwrap();
+        assert_eq!(offsets.char_offset(ok_byte), 7);
+        assert_eq!(offsets.char_offset(text.len()), text.chars().count());
+    }
+
+    #[test]
+    fn test_context_bounds_stay_on_char_boundaries() {
+        let text = "éé Tom éé";
+        assert_eq!(context_bounds(text, 5, 8, 2), (2, 11));
+        assert_eq!(context_bounds(text, 5, 8, 0), (5, 8));
+        assert_eq!(context_bounds(text, 0, text.len(), usize::MAX), (0, text.len()));
+    }
+
+    #[test]
+    fn test_sentence_ranges() {
+        let text = "  First one. Second!\n\nThird";
+        let sentences: Vec<&str> = sentence_ranges(text).iter().map(|&(s, e)| &text[s..e]).collect();
+        assert_eq!(sentences, vec!["First one.", "Second!", "Third"]);
+
+        let paragraphs: Vec<&str> = paragraph_ranges(text).iter().map(|&(s, e)| &text[s..e]).collect();
+        assert_eq!(paragraphs, vec!["First one. Second!", "Third"]);
+        assert_eq!(paragraph_ranges("a\r\n  \r\n\r\nb\n").len(), 2);
+    }
+
+    #[test]
+    fn test_mapped_text_source_ranges() {
+        let source = "Tom &amp; you<br>idiot";
+        let mut mapped = MappedText::default();
+        mapped.push_source(source, 0, 4);
+        mapped.push_replacement("&", 4, 9);
+        mapped.push_source(source, 9, 13);
+        mapped.push_replacement(" ", 13, 17);
+        mapped.push_source(source, 17, 22);
+        assert_eq!(mapped.text, "Tom & you idiot");
+
+        let idiot = mapped.text.find("idiot").unwrap();
+        assert_eq!(mapped.source_range(idiot, idiot + 5), (17, 22));
+        assert_eq!(mapped.source_range(4, 9), (4, 13));
+    }
+}
@@ -1,10 +1,18 @@
-/// Pattern matching for high-entropy detection
+//! Pattern matching for high-entropy detection
 
 use regex::Regex;
 
+use super::coded_lexicon::match_coded_terms;
+use super::locale::{match_regional_patterns, Locale};
+use super::memory::StoreUsage;
+use super::offsets::sentence_bounds;
+use super::similarity::fnv1a;
+
 /// Pattern match structure
 #[derive(Debug, Clone)]
 pub struct PatternMatch {
+    /// Stable id of the pattern that produced the match (see `pattern_ids`)
+    pub pattern_id: String,
     pub pattern_type: String,
     pub match_text: String,
     pub position: usize,
@@ -12,28 +20,106 @@ pub struct PatternMatch {
     pub weight: f64,
 }
 
-/// Match patterns in text
-/// Optimized with pre-allocated capacity for common use cases
-pub fn match_patterns(text: &str) -> Vec<PatternMatch> {
-    // Pre-allocate with estimated capacity (most texts have 0-5 matches)
-    let mut matches = Vec::with_capacity(5);
+/// Compiled detection pattern
+#[derive(Debug, Clone)]
+pub struct CompiledPattern {
+    pub id: String,
+    pub regex: Regex,
+    pub pattern_type: &'static str,
+    pub severity: &'static str,
+    pub weight: f64,
+}
 
-    // Character judgment patterns
-    let _character_patterns = vec![
-        (
-            r"\b(you('re|'re| are| r))\s+(\w+\s+)*(so\s+)?(lazy|selfish|stupid|pathetic|worthless)\b",
-            "character_judgment",
-            "high",
-            1.0,
-        ),
-        (
-            r"\b(you('re|'re| are| r))\s+(a|an|the)\s+(liar|loser|failure|disappointment)\b",
-            "character_judgment",
-            "high",
-            1.0,
-        ),
-    ];
+lazy_static::lazy_static! {
+    /// Pre-compiled detection patterns, compiled once instead of on every call
+    pub static ref COMPILED_PATTERNS: Vec<CompiledPattern> = {
+        let definitions = pattern_definitions();
+        let ids = pattern_ids(definitions.iter().map(|d| d.1));
+        definitions
+            .into_iter()
+            .zip(ids)
+            .filter_map(|((pattern_str, pattern_type, severity, weight), id)| {
+                // Make regex case-insensitive
+                Regex::new(&format!("(?i){}", pattern_str))
+                    .ok()
+                    .map(|regex| CompiledPattern { id, regex, pattern_type, severity, weight })
+            })
+            .collect()
+    };
+}
+
+/// Pattern types only emitted when the term is applied to people ("they are vermin"),
+/// not when it describes actual animals or things ("there are rats in my basement")
+const TARGET_GATED_TYPES: &[&str] = &["dehumanization"];
 
+lazy_static::lazy_static! {
+    /// A person or group followed by a copula or likening verb, or calling/treating people as something
+    static ref PERSON_TARGET: Regex = Regex::new(
+        r"(?i)\b(?:they|you|he|she|we|them|him|her|us|people|folks|men|women|guys|immigrants|refugees|foreigners|everyone|everybody)(?:'re|\x{2019}re|'s|\x{2019}s|\s+(?:are|is|were|was|be|been|all|just|like|nothing\s+but|acting\s+like|behave\s+like))\b|\b(?:call(?:ed|ing|s)?|treat(?:ed|ing|s)?)\s+(?:them|him|her|you|us|people)\b"
+    )
+    .unwrap();
+}
+
+/// Whether a match of this pattern type may be emitted at `position`: target-gated types need a
+/// person or group earlier in the same sentence
+pub fn passes_target_gate(pattern_type: &str, text: &str, position: usize) -> bool {
+    if !TARGET_GATED_TYPES.contains(&pattern_type) {
+        return true;
+    }
+    let (sentence_start, _) = sentence_bounds(text, position);
+    PERSON_TARGET.is_match(&text[sentence_start..position])
+}
+
+/// Stable pattern ids: the pattern type plus the pattern's ordinal within that type
+/// (`character_judgment.0`), so ids don't shift when other categories gain patterns
+pub fn pattern_ids<'a>(pattern_types: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut seen: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    pattern_types
+        .map(|pattern_type| {
+            let ordinal = seen.entry(pattern_type).or_insert(0);
+            *ordinal += 1;
+            format!("{}.{}", pattern_type, *ordinal - 1)
+        })
+        .collect()
+}
+
+/// Version of the built-in pattern set: the crate version plus a hash of every pattern id and
+/// regex, so stored results can be tied to the rules that produced them
+pub fn pattern_set_version() -> String {
+    let rules: String = COMPILED_PATTERNS.iter().map(|p| format!("{}\t{}\n", p.id, p.regex.as_str())).collect();
+    format!("{}+{:016x}", env!("CARGO_PKG_VERSION"), fnv1a(&rules))
+}
+
+/// Report the size of the compiled detection pattern cache, the largest cache in the module.
+/// Compiled regex internals aren't exposed, so bytes are estimated from pattern sources and ids
+pub fn compiled_pattern_usage() -> StoreUsage {
+    let approx_bytes = COMPILED_PATTERNS
+        .iter()
+        .map(|p| std::mem::size_of::<CompiledPattern>() + p.id.len() + p.regex.as_str().len())
+        .sum();
+
+    StoreUsage { name: "compiled_patterns".to_string(), entries: COMPILED_PATTERNS.len(), approx_bytes }
+}
+
+impl PatternMatch {
+    /// Stable match id derived from the pattern id and byte offset (`character_judgment.0@12`)
+    pub fn id(&self) -> String {
+        format!("{}@{}", self.pattern_id, self.position)
+    }
+}
+
+/// Deterministic match order: by offset, then longer spans first, then pattern id
+pub fn sort_matches(matches: &mut [PatternMatch]) {
+    matches.sort_by(|a, b| {
+        a.position
+            .cmp(&b.position)
+            .then(b.match_text.len().cmp(&a.match_text.len()))
+            .then_with(|| a.pattern_id.cmp(&b.pattern_id))
+    });
+}
+
+/// Built-in pattern definitions as (regex, pattern_type, severity, weight)
+fn pattern_definitions() -> Vec<(&'static str, &'static str, &'static str, f64)> {
     // Absolute statement patterns (Expanded 5x)
     let absolute_patterns = vec![
         (r"\byou\s+(\w+\s+)?(always|never|constantly|forever|eternally)\s+\w+", "absolute_statement", "high", 0.9),
@@ -240,41 +326,113 @@ pub fn match_patterns(text: &str) -> Vec<PatternMatch> {
     ];
 
     // Combine all patterns
-    let all_patterns: Vec<(&str, &str, &str, f64)> = character_patterns
+    character_patterns
         .into_iter()
-        .chain(absolute_patterns.into_iter())
-        .chain(dehumanization_patterns.into_iter())
-        .chain(gaslighting_patterns.into_iter())
-        .chain(double_bind_patterns.into_iter())
-        .chain(moral_disengagement_patterns.into_iter())
-        .chain(dark_triad_patterns.into_iter())
-        .chain(manipulation_patterns.into_iter())
-        .chain(propaganda_patterns.into_iter())
-        .chain(negative_coping_patterns.into_iter())
-        .chain(clinical_defense_patterns.into_iter())
-        .chain(high_control_patterns.into_iter())
-        .chain(bad_faith_patterns.into_iter())
-        .collect();
-
-    for (pattern_str, pattern_type, severity, weight) in all_patterns {
-        // Make regex case-insensitive
-        let case_insensitive_pattern = format!("(?i){}", pattern_str);
-        if let Ok(regex) = Regex::new(&case_insensitive_pattern) {
-            for cap in regex.find_iter(text) {
-                matches.push(PatternMatch {
-                    pattern_type: pattern_type.to_string(),
-                    match_text: cap.as_str().to_string(),
-                    position: cap.start(),
-                    severity: severity.to_string(),
-                    weight,
-                });
-            }
+        .chain(absolute_patterns)
+        .chain(dehumanization_patterns)
+        .chain(gaslighting_patterns)
+        .chain(double_bind_patterns)
+        .chain(moral_disengagement_patterns)
+        .chain(dark_triad_patterns)
+        .chain(manipulation_patterns)
+        .chain(propaganda_patterns)
+        .chain(negative_coping_patterns)
+        .chain(clinical_defense_patterns)
+        .chain(high_control_patterns)
+        .chain(bad_faith_patterns)
+        .collect()
+}
+
+/// Run a single compiled pattern over the text, appending its matches
+pub fn apply_pattern(pattern: &CompiledPattern, text: &str, matches: &mut Vec<PatternMatch>) {
+    for cap in pattern.regex.find_iter(text) {
+        if !passes_target_gate(pattern.pattern_type, text, cap.start()) {
+            continue;
         }
+        matches.push(PatternMatch {
+            pattern_id: pattern.id.clone(),
+            pattern_type: pattern.pattern_type.to_string(),
+            match_text: cap.as_str().to_string(),
+            position: cap.start(),
+            severity: pattern.severity.to_string(),
+            weight: pattern.weight,
+        });
     }
+}
 
+/// Match patterns in text
+/// Optimized with pre-allocated capacity for common use cases
+pub fn match_patterns(text: &str) -> Vec<PatternMatch> {
+    // Pre-allocate with estimated capacity (most texts have 0-5 matches)
+    let mut matches = Vec::with_capacity(5);
+
+    for pattern in COMPILED_PATTERNS.iter() {
+        apply_pattern(pattern, text, &mut matches);
+    }
+    matches.extend(match_coded_terms(text));
+
+    sort_matches(&mut matches);
     matches
 }
 
+/// Match the built-in patterns plus the regional variants for `locale`
+pub fn match_patterns_for_locale(text: &str, locale: Locale) -> Vec<PatternMatch> {
+    let mut matches = match_patterns(text);
+    if locale != Locale::Us {
+        matches.extend(match_regional_patterns(text, locale));
+        sort_matches(&mut matches);
+    }
+    matches
+}
+
+/// Resumable pattern scan that can be advanced a few patterns at a time
+/// Lets async callers yield between steps without changing match results
+pub struct PatternScan<'a> {
+    text: &'a str,
+    next_pattern: usize,
+    matches: Vec<PatternMatch>,
+}
+
+impl<'a> PatternScan<'a> {
+    pub fn new(text: &'a str) -> Self {
+        PatternScan { text, next_pattern: 0, matches: Vec::with_capacity(5) }
+    }
+
+    /// Run up to `max_patterns` further patterns; returns true once every pattern has run
+    pub fn step(&mut self, max_patterns: usize) -> bool {
+        let end = (self.next_pattern + max_patterns.max(1)).min(COMPILED_PATTERNS.len());
+        for pattern in &COMPILED_PATTERNS[self.next_pattern..end] {
+            apply_pattern(pattern, self.text, &mut self.matches);
+        }
+        self.next_pattern = end;
+        self.is_done()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next_pattern >= COMPILED_PATTERNS.len()
+    }
+
+    /// Fraction of patterns processed so far (0.0 - 1.0)
+    pub fn progress(&self) -> f64 {
+        if COMPILED_PATTERNS.is_empty() {
+            return 1.0;
+        }
+        self.next_pattern as f64 / COMPILED_PATTERNS.len() as f64
+    }
+
+    /// Number of matches found so far
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Matches in the same order a full `match_patterns` run would produce
+    pub fn into_matches(mut self) -> Vec<PatternMatch> {
+        self.matches.extend(match_coded_terms(self.text));
+        sort_matches(&mut self.matches);
+        self.matches
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -292,4 +450,51 @@ mod tests {
         let matches = match_patterns(text);
         assert!(matches.iter().any(|m| m.pattern_type == "character_judgment"));
     }
+
+    #[test]
+    fn test_pattern_scan_progress() {
+        let mut scan = PatternScan::new("You're so selfish");
+        assert_eq!(scan.progress(), 0.0);
+        scan.step(1);
+        assert!(scan.progress() > 0.0 && scan.progress() < 1.0);
+        assert!(scan.match_count() >= 1);
+    }
+
+    #[test]
+    fn test_pattern_scan_matches_full_run() {
+        let text = "You're so selfish and it's all your fault";
+        let mut scan = PatternScan::new(text);
+        while !scan.step(7) {}
+        assert!(scan.is_done());
+        assert_eq!(scan.progress(), 1.0);
+
+        let stepped: Vec<(String, usize)> = scan.into_matches().into_iter().map(|m| (m.pattern_type, m.position)).collect();
+        let full: Vec<(String, usize)> = match_patterns(text).into_iter().map(|m| (m.pattern_type, m.position)).collect();
+        assert_eq!(stepped, full);
+    }
+
+    #[test]
+    fn test_match_ids_are_stable_and_ordered() {
+        let text = "It's all your fault. You're so selfish, you idiot";
+        let matches = match_patterns(text);
+        assert!(matches.windows(2).all(|w| w[0].position <= w[1].position));
+
+        let judgment = matches.iter().find(|m| m.pattern_type == "character_judgment").unwrap();
+        assert_eq!(judgment.id(), format!("character_judgment.0@{}", text.find("You're").unwrap()));
+
+        let ids: Vec<String> = matches.iter().map(PatternMatch::id).collect();
+        let again: Vec<String> = match_patterns(text).iter().map(PatternMatch::id).collect();
+        assert_eq!(ids, again);
+        assert_eq!(pattern_ids(["a", "b", "a"].into_iter()), vec!["a.0", "b.0", "a.1"]);
+        assert!(pattern_set_version().starts_with(&format!("{}+", env!("CARGO_PKG_VERSION"))));
+    }
+
+    #[test]
+    fn test_dehumanization_requires_person_target() {
+        let types = |text: &str| -> Vec<String> { match_patterns(text).into_iter().map(|m| m.pattern_type).collect() };
+        assert!(!types("There are rats in my basement").contains(&"dehumanization".to_string()));
+        assert!(types("They are rats, all of them").contains(&"dehumanization".to_string()));
+        assert!(types("Those people are vermin").contains(&"dehumanization".to_string()));
+        assert!(types("You're vermin. The rats got into the shed.").iter().filter(|t| *t == "dehumanization").count() == 1);
+    }
 }
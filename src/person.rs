@@ -0,0 +1,96 @@
+//! Grammatical person of matches
+//! "You always blame everyone" accuses the reader; "he always blames everyone" describes a third
+//! party. Each match is tagged first, second or third person from the pronouns (or names) of
+//! its own clause, so consumers can treat direct accusations differently from reports
+
+use super::offsets::sentence_bounds;
+use super::tokenize::{tokenize, Token};
+
+// Sorted, looked up with binary_search
+const FIRST_PERSON: &[&str] = &[
+    "i", "i'd", "i'll", "i'm", "i've", "me", "mine", "my", "myself", "our", "ours", "ourselves", "us", "we", "we'd",
+    "we'll", "we're", "we've",
+];
+const SECOND_PERSON: &[&str] = &[
+    "u", "ur", "y'all", "you", "you'd", "you'll", "you're", "you've", "your", "yours", "yourself", "yourselves",
+];
+const THIRD_PERSON: &[&str] = &[
+    "he", "he'd", "he'll", "he's", "her", "hers", "herself", "him", "himself", "his", "she", "she'd", "she'll",
+    "she's", "their", "theirs", "them", "themselves", "they", "they'd", "they'll", "they're", "they've",
+];
+
+/// Byte range of the clause containing `position`: sentence punctuation, commas and semicolons end clauses
+pub fn clause_bounds(text: &str, position: usize) -> (usize, usize) {
+    let is_end = |c: char| matches!(c, '.' | '!' | '?' | '\n' | ',' | ';' | ':');
+    let start = text[..position].rfind(is_end).map(|i| i + 1).unwrap_or(0);
+    let end = text[position..].find(is_end).map(|i| position + i).unwrap_or(text.len());
+    (start, end)
+}
+
+fn is_name(token: &Token) -> bool {
+    token.text.len() > 1 && token.text.chars().next().is_some_and(char::is_uppercase)
+}
+
+/// Person of a single word, if it marks one; capitalized words other than the sentence's first
+/// count as names (third person)
+fn word_person(token: &Token, sentence_initial: bool) -> Option<&'static str> {
+    let word = token.lower().replace('\u{2019}', "'");
+    if FIRST_PERSON.binary_search(&word.as_str()).is_ok() {
+        Some("first")
+    } else if SECOND_PERSON.binary_search(&word.as_str()).is_ok() {
+        Some("second")
+    } else if THIRD_PERSON.binary_search(&word.as_str()).is_ok() || (!sentence_initial && is_name(token)) {
+        Some("third")
+    } else {
+        None
+    }
+}
+
+/// Grammatical person ("first", "second" or "third") of a match's subject or target: the first
+/// person-marking word inside the match, else the nearest one before it in the clause, else the
+/// first one after it
+pub fn match_person(text: &str, position: usize, len: usize) -> Option<&'static str> {
+    let end = (position + len).min(text.len());
+    let (clause_start, clause_end) = clause_bounds(text, position);
+    let clause_end = clause_end.max(end);
+    let tokens = tokenize(&text[clause_start..clause_end]);
+    let clause_opens_sentence = text[sentence_bounds(text, position).0..clause_start].trim().is_empty();
+    let person = |(index, token): (usize, &Token)| word_person(token, index == 0 && clause_opens_sentence);
+    let (relative_start, relative_end) = (position - clause_start, end - clause_start);
+
+    let inside = tokens.iter().enumerate().filter(|(_, t)| t.start >= relative_start && t.end <= relative_end);
+    let before = tokens.iter().enumerate().filter(|(_, t)| t.end <= relative_start).collect::<Vec<_>>();
+    let after = tokens.iter().enumerate().filter(|(_, t)| t.start >= relative_end);
+
+    inside
+        .filter_map(person)
+        .next()
+        .or_else(|| before.into_iter().rev().filter_map(person).next())
+        .or_else(|| after.filter_map(person).next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person_of(text: &str, needle: &str) -> Option<&'static str> {
+        match_person(text, text.find(needle).unwrap(), needle.len())
+    }
+
+    #[test]
+    fn test_person_from_match_and_clause() {
+        assert_eq!(person_of("You always blame everyone", "everyone"), Some("second"));
+        assert_eq!(person_of("He always blames everyone", "everyone"), Some("third"));
+        assert_eq!(person_of("Honestly, I feel like everyone hates me", "everyone hates me"), Some("first"));
+        assert_eq!(person_of("My boss Dave, what a total idiot", "idiot"), None);
+        assert_eq!(person_of("Ugh, Dave is an idiot", "idiot"), Some("third"));
+        assert_eq!(person_of("You're so lazy", "You're so lazy"), Some("second"));
+    }
+
+    #[test]
+    fn test_pronoun_lists_are_sorted() {
+        for list in [FIRST_PERSON, SECOND_PERSON, THIRD_PERSON] {
+            assert!(list.windows(2).all(|w| w[0] < w[1]), "{:?}", list);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Personal data detection
+//! Emails, phone numbers, card numbers and URLs, so hosts can redact or annotate them
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    /// (kind, regex); earlier kinds win where matches overlap
+    static ref PII_PATTERNS: Vec<(&'static str, Regex)> = [
+        ("email", r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b"),
+        ("url", r"\bhttps?://[^\s<>]+[^\s<>.,;:!?)]"),
+        ("card_number", r"\b(?:\d[ -]?){12,15}\d\b"),
+        ("phone", r"(?:\+\d{1,3}[ .-]?)?\(?\b\d{3}\)?[ .-]?\d{3}[ .-]?\d{4}\b"),
+    ]
+    .iter()
+    .filter_map(|(kind, pattern)| Regex::new(pattern).ok().map(|regex| (*kind, regex)))
+    .collect();
+}
+
+/// Personal data found in text (byte offsets, end exclusive)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PiiMatch {
+    /// `email`, `url`, `card_number` or `phone`
+    pub kind: String,
+    pub text: String,
+    pub position: usize,
+    pub end: usize,
+}
+
+/// Find personal data, ordered by position with no overlaps
+pub fn detect_pii(text: &str) -> Vec<PiiMatch> {
+    let mut found: Vec<PiiMatch> = Vec::new();
+    for (kind, regex) in PII_PATTERNS.iter() {
+        for m in regex.find_iter(text) {
+            if found.iter().any(|f| m.start() < f.end && f.position < m.end()) {
+                continue;
+            }
+            found.push(PiiMatch { kind: kind.to_string(), text: m.as_str().to_string(), position: m.start(), end: m.end() });
+        }
+    }
+    found.sort_by_key(|m| m.position);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_contact_details() {
+        let found = detect_pii("Email jo@example.com or call (555) 123-4567, see https://example.com/a.");
+        let kinds: Vec<(&str, &str)> = found.iter().map(|m| (m.kind.as_str(), m.text.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![("email", "jo@example.com"), ("phone", "(555) 123-4567"), ("url", "https://example.com/a")]
+        );
+    }
+
+    #[test]
+    fn test_card_number_not_also_phone() {
+        let found = detect_pii("Card 4111 1111 1111 1111 expires soon");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, "card_number");
+    }
+}
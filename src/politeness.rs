@@ -0,0 +1,95 @@
+//! Politeness scoring
+//! Weighs courtesy markers (please/thanks, indirect requests, honorifics) against
+//! imperatives and demands, for message coaching
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    /// (pattern, cue, weight); more specific cues come first and win overlaps
+    static ref POLITENESS_CUES: Vec<(Regex, &'static str, f64)> = [
+        (r"\b(?:would|do)\s+you\s+mind\b|\bI\s+was\s+wondering\b|\bdo\s+you\s+think\s+you\s+could\b|\bif\s+you\s+don'?t\s+mind\b", "indirect_request", 0.7),
+        (r"\b(?:could|would|can|will)\s+you\b", "indirect_request", 0.5),
+        (r"\b(?:thanks?|thank\s+you|appreciate\s+it|grateful)\b", "gratitude", 0.6),
+        (r"\bplease\b", "please", 0.4),
+        (r"\b(?:sorry|apologi[sz]e|excuse\s+me|pardon)\b", "apology", 0.4),
+        (r"\b(?:sir|madam|ma'am|mr\.?|mrs\.?|ms\.?|dr\.?|dear)\b", "honorific", 0.3),
+        (r"\b(?:if\s+possible|when\s+you\s+(?:get\s+a\s+chance|have\s+(?:a\s+)?(?:moment|time))|if\s+you\s+have\s+time|maybe|perhaps)\b", "hedge", 0.3),
+        (r"^\s*(?:hi|hello|hey|good\s+(?:morning|afternoon|evening))\b", "greeting", 0.2),
+        (r"\bshut\s+up\b|\bor\s+else\b", "demand", -1.0),
+        (r"\byou\s+(?:need\s+to|have\s+to|must|better|had\s+better)\b", "demand", -0.7),
+        (r"(?:^|[.!?]\s+)(?:just\s+)?(?:stop|do|get|give|send|tell|fix|make|go|answer|call|listen|leave|move|hurry|explain|bring|finish|reply|respond)\b", "imperative", -0.5),
+        (r"\b(?:right\s+now|immediately|asap|at\s+once)\b", "urgency", -0.4),
+        (r"\bwhy\s+(?:did|didn't|don't|do|would|are|aren't|can't|won't)\s+you\b", "direct_question", -0.4),
+    ]
+    .iter()
+    .map(|(p, cue, weight)| (Regex::new(&format!("(?i){}", p)).unwrap(), *cue, *weight))
+    .collect();
+}
+
+/// Politeness marker found in a message
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PolitenessCue {
+    /// e.g. `gratitude`, `indirect_request`, `imperative`, `demand`
+    pub cue: String,
+    pub text: String,
+    pub position: usize,
+    pub end: usize,
+    /// Positive for courtesy, negative for bluntness
+    pub weight: f64,
+}
+
+/// Politeness of a message
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PolitenessResult {
+    /// 0 (blunt or demanding) to 1 (very polite); 0.5 when no cues are present
+    pub score: f64,
+    /// Contributing cues in document order
+    pub cues: Vec<PolitenessCue>,
+}
+
+/// Score the politeness of a message
+pub fn score_politeness(text: &str) -> PolitenessResult {
+    let mut cues: Vec<PolitenessCue> = Vec::new();
+    for (regex, cue, weight) in POLITENESS_CUES.iter() {
+        for m in regex.find_iter(text) {
+            // Imperative matches include the preceding sentence break
+            let trimmed = m.as_str().trim_start_matches(|c: char| matches!(c, '.' | '!' | '?') || c.is_whitespace());
+            let (position, end) = (m.end() - trimmed.len(), m.end());
+            if cues.iter().all(|c| end <= c.position || position >= c.end) {
+                cues.push(PolitenessCue { cue: cue.to_string(), text: trimmed.to_string(), position, end, weight: *weight });
+            }
+        }
+    }
+    cues.sort_by_key(|c| c.position);
+
+    let total: f64 = cues.iter().map(|c| c.weight).sum();
+    PolitenessResult { score: 0.5 + 0.5 * total.tanh(), cues }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polite_and_blunt_messages() {
+        let polite = score_politeness("Hi Dr. Lee, would you mind sending the notes when you get a chance? Thanks!");
+        let blunt = score_politeness("Send me the notes right now. You need to stop ignoring me.");
+        assert!(polite.score > 0.9);
+        assert!(blunt.score < 0.1);
+        let cues: Vec<&str> = polite.cues.iter().map(|c| c.cue.as_str()).collect();
+        assert_eq!(cues, vec!["greeting", "honorific", "indirect_request", "hedge", "gratitude"]);
+        assert_eq!(blunt.cues[0].text, "Send");
+    }
+
+    #[test]
+    fn test_neutral_message() {
+        let result = score_politeness("The meeting moved to Tuesday.");
+        assert_eq!(result.score, 0.5);
+        assert!(result.cues.is_empty());
+    }
+}
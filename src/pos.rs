@@ -0,0 +1,215 @@
+//! Lightweight part-of-speech tagging
+//! Closed-class lexicons, a small open-class lexicon, suffix rules and the previous tag;
+//! accurate enough to separate content words from function words, not a full tagger
+
+use super::tokenize::{tokenize, Token};
+
+/// Coarse part of speech
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tag {
+    Noun,
+    Verb,
+    Adjective,
+    Adverb,
+    Pronoun,
+    Determiner,
+    Preposition,
+    Conjunction,
+    /// Auxiliaries and modals ("is", "have", "can't")
+    Auxiliary,
+    Interjection,
+    Number,
+}
+
+impl Tag {
+    /// Whether the tag carries content (nouns, main verbs, adjectives)
+    pub fn is_content(self) -> bool {
+        matches!(self, Tag::Noun | Tag::Verb | Tag::Adjective)
+    }
+}
+
+/// Word with its tag
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TaggedToken<'a> {
+    pub token: Token<'a>,
+    pub tag: Tag,
+}
+
+// Closed-class lexicons (sorted, looked up with binary_search)
+const PRONOUNS: &[&str] = &[
+    "anybody", "anyone", "anything", "everybody", "everyone", "everything", "he", "hers", "herself", "him",
+    "himself", "i", "it", "itself", "me", "mine", "myself", "nobody", "nothing", "one", "ours", "ourselves", "she",
+    "somebody", "someone", "something", "theirs", "them", "themselves", "they", "us", "we", "what", "who", "whom",
+    "whose", "you", "yours", "yourself", "yourselves",
+];
+/// Includes possessives ("my", "her"), which pattern like determiners before a noun
+const DETERMINERS: &[&str] = &[
+    "a", "all", "an", "another", "any", "both", "each", "either", "every", "few", "her", "his", "its", "many", "more",
+    "most", "much", "my", "neither", "no", "other", "our", "several", "some", "such", "that", "the", "their", "these",
+    "this", "those", "which", "your",
+];
+const PREPOSITIONS: &[&str] = &[
+    "about", "above", "across", "after", "against", "along", "among", "around", "as", "at", "before", "behind",
+    "below", "beside", "between", "beyond", "by", "despite", "down", "during", "except", "for", "from", "in",
+    "inside", "into", "like", "near", "of", "off", "on", "onto", "out", "outside", "over", "past", "since",
+    "through", "throughout", "to", "toward", "towards", "under", "until", "up", "upon", "with", "within", "without",
+];
+const CONJUNCTIONS: &[&str] = &[
+    "although", "and", "because", "but", "if", "nor", "or", "so", "than", "though", "unless", "whereas", "whether",
+    "while", "yet",
+];
+const AUXILIARIES: &[&str] = &[
+    "am", "are", "be", "been", "being", "can", "could", "did", "do", "does", "had", "has", "have", "having", "is",
+    "may", "might", "must", "shall", "should", "was", "were", "will", "would",
+];
+const ADVERBS: &[&str] = &[
+    "again", "almost", "already", "also", "always", "anyway", "away", "back", "constantly", "even", "ever", "here",
+    "how", "just", "later", "maybe", "never", "not", "now", "often", "once", "only", "perhaps", "quite", "rather",
+    "really", "sometimes", "soon", "still", "then", "there", "today", "together", "tomorrow", "tonight", "too",
+    "very", "when", "where", "why", "yesterday",
+];
+const INTERJECTIONS: &[&str] = &["hey", "hi", "lol", "no", "oh", "ok", "okay", "please", "thanks", "ugh", "wow", "yeah", "yes"];
+
+// Small open-class lexicons for frequent words the suffix rules get wrong
+const VERBS: &[&str] = &[
+    "ask", "blame", "break", "bring", "call", "care", "come", "cry", "feel", "fight", "find", "forget", "get",
+    "give", "go", "hate", "hear", "help", "hurt", "ignore", "keep", "know", "leave", "let", "listen", "look",
+    "love", "make", "mean", "miss", "need", "put", "said", "say", "see", "seem", "shout", "stop", "take", "talk",
+    "tell", "think", "told", "try", "understand", "want", "yell",
+];
+const ADJECTIVES: &[&str] = &[
+    "angry", "awful", "bad", "big", "crazy", "dumb", "fine", "good", "great", "happy", "hard", "horrible", "lazy",
+    "little", "long", "mad", "new", "nice", "old", "pathetic", "rude", "sad", "selfish", "sick", "stupid",
+    "terrible", "tired", "upset", "weak", "wrong",
+];
+
+/// Suffixes that mark a word class for words not in any lexicon
+const ADJECTIVE_SUFFIXES: &[&str] = &["ous", "ful", "ive", "able", "ible", "less", "ish", "ic", "al"];
+const NOUN_SUFFIXES: &[&str] = &["tion", "sion", "ment", "ness", "ity", "ship", "ance", "ence", "ism", "hood", "er", "or", "ist"];
+
+fn contains(list: &[&str], word: &str) -> bool {
+    list.binary_search(&word).is_ok()
+}
+
+/// Tag from the word alone, if it is in a lexicon
+fn lexicon_tag(word: &str) -> Option<Tag> {
+    let lists: [(&[&str], Tag); 9] = [
+        (AUXILIARIES, Tag::Auxiliary),
+        (PRONOUNS, Tag::Pronoun),
+        (DETERMINERS, Tag::Determiner),
+        (PREPOSITIONS, Tag::Preposition),
+        (CONJUNCTIONS, Tag::Conjunction),
+        (ADVERBS, Tag::Adverb),
+        (INTERJECTIONS, Tag::Interjection),
+        (VERBS, Tag::Verb),
+        (ADJECTIVES, Tag::Adjective),
+    ];
+    lists.iter().find(|(list, _)| contains(list, word)).map(|&(_, tag)| tag)
+}
+
+/// Whether a lowercase word is in one of the tagging lexicons
+pub fn is_lexicon_word(word: &str) -> bool {
+    lexicon_tag(word).is_some()
+}
+
+/// Tag a single lowercase word given the previous tag
+fn tag_word(word: &str, previous: Option<Tag>) -> Tag {
+    if word.chars().all(|c| c.is_ascii_digit()) {
+        return Tag::Number;
+    }
+
+    // Contractions take the class of their first part ("you're" -> pronoun, "don't" -> auxiliary)
+    if let Some((head, _)) = word.split_once(['\'', '\u{2019}']) {
+        return match head {
+            "don" | "doesn" | "didn" | "isn" | "aren" | "wasn" | "weren" | "won" | "wouldn" | "can" | "couldn"
+            | "shouldn" | "haven" | "hasn" | "hadn" => Tag::Auxiliary,
+            _ => lexicon_tag(head).unwrap_or(Tag::Noun),
+        };
+    }
+
+    if let Some(tag) = lexicon_tag(word) {
+        return tag;
+    }
+
+    let after_modifier = matches!(previous, Some(Tag::Determiner | Tag::Adjective));
+
+    if word.len() > 4 && word.ends_with("ly") {
+        return Tag::Adverb;
+    }
+    if ADJECTIVE_SUFFIXES.iter().any(|s| word.len() > s.len() + 2 && word.ends_with(s)) {
+        return Tag::Adjective;
+    }
+    if NOUN_SUFFIXES.iter().any(|s| word.len() > s.len() + 2 && word.ends_with(s)) {
+        return Tag::Noun;
+    }
+    if word.len() > 4 && word.ends_with("ing") {
+        // Gerund after a modifier or inside a compound ("the custody hearing")
+        return if after_modifier || previous == Some(Tag::Noun) { Tag::Noun } else { Tag::Verb };
+    }
+    if word.len() > 4 && word.ends_with("ed") {
+        return if after_modifier { Tag::Adjective } else { Tag::Verb };
+    }
+    match previous {
+        // After "so", "too", "very" an unknown word is most likely a predicate adjective
+        Some(Tag::Adverb) => Tag::Adjective,
+        // Subject pronoun or modal, then the main verb ("they shout", "will shout")
+        Some(Tag::Pronoun) | Some(Tag::Auxiliary) => Tag::Verb,
+        _ => Tag::Noun,
+    }
+}
+
+/// Tag every word in the text; tags reset at sentence boundaries
+pub fn tag(text: &str) -> Vec<TaggedToken<'_>> {
+    let mut previous: Option<Tag> = None;
+    let mut last_end = 0;
+
+    tokenize(text)
+        .into_iter()
+        .map(|token| {
+            if text[last_end..token.start].contains(['.', '!', '?', '\n']) {
+                previous = None;
+            }
+            last_end = token.end;
+            let tag = tag_word(&token.lower(), previous);
+            previous = Some(tag);
+            TaggedToken { token, tag }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lexicons_are_sorted() {
+        for list in [PRONOUNS, DETERMINERS, PREPOSITIONS, CONJUNCTIONS, AUXILIARIES, ADVERBS, INTERJECTIONS, VERBS, ADJECTIVES] {
+            assert!(list.windows(2).all(|w| w[0] < w[1]), "{:?}", list);
+        }
+    }
+
+    #[test]
+    fn test_tags_function_and_content_words() {
+        let tags: Vec<(&str, Tag)> = tag("You're always so lazy about the custody hearing. She ignored my messages")
+            .iter()
+            .map(|t| (t.token.text, t.tag))
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("You're", Tag::Pronoun),
+                ("always", Tag::Adverb),
+                ("so", Tag::Conjunction),
+                ("lazy", Tag::Adjective),
+                ("about", Tag::Preposition),
+                ("the", Tag::Determiner),
+                ("custody", Tag::Noun),
+                ("hearing", Tag::Noun),
+                ("She", Tag::Pronoun),
+                ("ignored", Tag::Verb),
+                ("my", Tag::Determiner),
+                ("messages", Tag::Noun),
+            ]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Positive expression detection
+//! Constructive language reported alongside the high-entropy patterns, scored separately.
+//! Healthy boundary statements also exempt same-sentence withdrawal matches, and empathic
+//! statements get their own score to balance the manipulation-focused detectors.
+
+use regex::Regex;
+
+use super::offsets::sentence_bounds;
+use super::pattern_matching::{pattern_ids, sort_matches, CompiledPattern, PatternMatch};
+use super::scoring::calculate_text_score;
+
+/// Severity recorded on positive matches
+const POSITIVE_SEVERITY: &str = "positive";
+
+/// Pattern type of empathic statements
+const EMPATHY_TYPE: &str = "empathy";
+
+/// Pattern types a boundary statement in the same sentence reclassifies: withdrawal and the
+/// stonewalling-like types a stated need for space explains
+const BOUNDARY_EXEMPT_TYPES: &[&str] = &["digital_withdrawal", "emotional_barrier", "punitive_silence", "withdrawal"];
+
+lazy_static::lazy_static! {
+    /// Pre-compiled positive expression patterns
+    static ref POSITIVE_PATTERNS: Vec<CompiledPattern> = {
+        let definitions = positive_definitions();
+        let ids = pattern_ids(definitions.iter().map(|d| d.1));
+        definitions
+            .into_iter()
+            .zip(ids)
+            .filter_map(|((pattern_str, pattern_type, weight), id)| {
+                Regex::new(&format!("(?i){}", pattern_str))
+                    .ok()
+                    .map(|regex| CompiledPattern { id, regex, pattern_type, severity: POSITIVE_SEVERITY, weight })
+            })
+            .collect()
+    };
+}
+
+/// Positive pattern definitions as (regex, pattern_type, weight)
+fn positive_definitions() -> Vec<(&'static str, &'static str, f64)> {
+    vec![
+        // Gratitude
+        (r"\bI(?:'m|\s+am|\s+feel)\s+(?:so\s+|really\s+|very\s+|truly\s+|deeply\s+)?(?:grateful|thankful)\b", "gratitude", 0.9),
+        (r"\b(?:grateful|thankful)\s+(?:for|that|to)\b", "gratitude", 0.8),
+        (r"\bI\s+(?:really\s+|truly\s+)?appreciate\b", "gratitude", 0.8),
+        (r"\b(?:blessed|lucky)\s+to\s+have\b", "gratitude", 0.7),
+        (r"\b(?:thank\s+you|thanks)\b", "gratitude", 0.6),
+        // Boundary setting
+        (r"\bI(?:'m|\s+am)\s+not\s+(?:available|comfortable|okay|ok)\s+(?:for|with)\b", "boundary_setting", 0.9),
+        (r"\bI\s+need\s+to\s+(?:end|stop|pause|step\s+away\s+from)\s+this\s+(?:conversation|discussion)\b", "boundary_setting", 0.9),
+        (r"\bI(?:'m|\s+am)\s+not\s+(?:going|willing)\s+to\s+(?:discuss|continue|talk\s+about|accept|tolerate)\b", "boundary_setting", 0.8),
+        (r"\bI\s+(?:don't|do\s+not|won't|will\s+not)\s+(?:accept|tolerate)\b", "boundary_setting", 0.8),
+        (r"\bI\s+need\s+(?:some\s+)?(?:space|time\s+to\s+(?:think|cool\s+down)|a\s+break)\b", "boundary_setting", 0.7),
+        (r"\bI\s+need\s+you\s+to\s+(?:stop|respect)\b", "boundary_setting", 0.8),
+        (r"\b(?:please\s+)?(?:don't|do\s+not)\s+(?:speak|talk)\s+to\s+me\s+(?:like\s+that|that\s+way)\b", "boundary_setting", 0.8),
+        (r"\bI(?:'ll|\s+will)\s+(?:talk|come\s+back\s+to\s+this)\s+(?:\w+\s+){0,3}(?:later|tomorrow|when\s+we(?:'re|\s+are)\s+(?:calm|calmer))\b", "boundary_setting", 0.7),
+        // Empathy
+        (r"\b(?:that|it|this)\s+(?:sounds|must\s+(?:be|have\s+been))\s+(?:so\s+|really\s+|very\s+|incredibly\s+)?(?:hard|difficult|tough|rough|painful|awful|frustrating|stressful|exhausting|overwhelming|scary|lonely)\b", "empathy", 0.9),
+        (r"\bI\s+can\s+(?:see|understand|imagine)\s+(?:why|how)\s+you(?:'d|\s+would|\s+might)?\s+(?:feel|be|felt|were)\b", "empathy", 0.9),
+        (r"\bI\s+can\s+(?:only\s+)?imagine\s+how\b", "empathy", 0.8),
+        (r"\b(?:it|that)\s+makes\s+(?:total\s+|complete\s+|so\s+much\s+)?sense\s+(?:that|why)\s+you\b", "empathy", 0.8),
+        (r"\b(?:your\s+feelings\s+are\s+(?:valid|understandable)|it(?:'s|\s+is)\s+(?:okay|ok|understandable|normal|natural)\s+to\s+feel)\b", "empathy", 0.8),
+        (r"\bI(?:'m|\s+am)\s+(?:so\s+|really\s+)?sorry\s+(?:that\s+)?you(?:'re|\s+are|'ve|\s+have)?\s+(?:going\s+through|dealing\s+with|feeling|been)\b", "empathy", 0.9),
+        (r"\bI\s+hear\s+you\b|\bI(?:'m|\s+am)\s+here\s+for\s+you\b", "empathy", 0.7),
+        (r"\bhow\s+are\s+you\s+(?:feeling|holding\s+up)\b", "empathy", 0.6),
+    ]
+}
+
+/// Match positive expressions; overlapping matches of the same type are reported once
+pub fn match_positive(text: &str) -> Vec<PatternMatch> {
+    let mut matches: Vec<PatternMatch> = Vec::new();
+
+    for pattern in POSITIVE_PATTERNS.iter() {
+        for m in pattern.regex.find_iter(text) {
+            let overlaps = matches.iter().any(|existing| {
+                existing.pattern_type == pattern.pattern_type
+                    && m.start() < existing.position + existing.match_text.len()
+                    && existing.position < m.end()
+            });
+            if overlaps {
+                continue;
+            }
+            matches.push(PatternMatch {
+                pattern_id: pattern.id.clone(),
+                pattern_type: pattern.pattern_type.to_string(),
+                match_text: m.as_str().to_string(),
+                position: m.start(),
+                severity: POSITIVE_SEVERITY.to_string(),
+                weight: pattern.weight,
+            });
+        }
+    }
+
+    sort_matches(&mut matches);
+    matches
+}
+
+/// Score of the empathic statements among positive matches
+pub fn empathy_score(positive: &[PatternMatch]) -> f64 {
+    let empathy: Vec<PatternMatch> = positive.iter().filter(|m| m.pattern_type == EMPATHY_TYPE).cloned().collect();
+    calculate_text_score(&empathy)
+}
+
+/// Whether each high-entropy match is reclassified by a boundary statement in the same sentence
+pub fn boundary_exempt(text: &str, matches: &[PatternMatch], positive: &[PatternMatch]) -> Vec<bool> {
+    let boundaries: Vec<(usize, usize)> = positive
+        .iter()
+        .filter(|m| m.pattern_type == "boundary_setting")
+        .map(|m| sentence_bounds(text, m.position))
+        .collect();
+
+    matches
+        .iter()
+        .map(|m| {
+            BOUNDARY_EXEMPT_TYPES.contains(&m.pattern_type.as_str())
+                && boundaries.iter().any(|&(start, end)| m.position >= start && m.position < end)
+        })
+        .collect()
+}
+
+/// Drop high-entropy matches that a boundary statement in the same sentence reclassifies
+/// ("I need a break, I don't want to talk right now" is a boundary, not withdrawal)
+pub fn exempt_boundaries(text: &str, matches: Vec<PatternMatch>, positive: &[PatternMatch]) -> Vec<PatternMatch> {
+    let exempt = boundary_exempt(text, &matches, positive);
+    matches
+        .into_iter()
+        .zip(exempt)
+        .filter(|(_, exempt)| !exempt)
+        .map(|(m, _)| m)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::pattern_matching::match_patterns;
+
+    #[test]
+    fn test_gratitude_detection() {
+        let matches = match_positive("I'm so grateful for my sister. Thanks for listening!");
+        let gratitude: Vec<&str> = matches.iter().filter(|m| m.pattern_type == "gratitude").map(|m| m.match_text.as_str()).collect();
+        assert_eq!(gratitude, vec!["I'm so grateful", "Thanks"]);
+        assert!(matches.iter().all(|m| m.severity == "positive"));
+    }
+
+    #[test]
+    fn test_boundaries_exempt_withdrawal() {
+        let text = "I need a break, I don't want to talk right now. Leave me alone!";
+        let positive = match_positive(text);
+        assert!(positive.iter().any(|m| m.pattern_type == "boundary_setting"));
+
+        let matches = exempt_boundaries(text, match_patterns(text), &positive);
+        let withdrawal: Vec<&str> = matches.iter().filter(|m| m.pattern_type == "withdrawal").map(|m| m.match_text.as_str()).collect();
+        assert_eq!(withdrawal, vec!["Leave me alone"]);
+
+        let text = "I need some space, so I'm blocking you tonight. I'm walling off. Enjoy the silent treatment.";
+        let matches = exempt_boundaries(text, match_patterns(text), &match_positive(text));
+        let types: Vec<&str> = matches.iter().map(|m| m.pattern_type.as_str()).collect();
+        assert!(!types.contains(&"digital_withdrawal"));
+        assert!(types.contains(&"emotional_barrier") && types.contains(&"punitive_silence"));
+    }
+
+    #[test]
+    fn test_empathy_scored_separately() {
+        let positive = match_positive("That sounds really hard. I can see why you'd feel hurt. Thanks for telling me.");
+        let empathy: Vec<&str> = positive.iter().filter(|m| m.pattern_type == "empathy").map(|m| m.match_text.as_str()).collect();
+        assert_eq!(empathy, vec!["That sounds really hard", "I can see why you'd feel"]);
+        assert!(empathy_score(&positive) > 0.0);
+        assert_eq!(empathy_score(&match_positive("Thanks for telling me.")), 0.0);
+    }
+
+    #[test]
+    fn test_no_positive_matches() {
+        assert!(match_positive("You're so selfish").is_empty());
+    }
+}
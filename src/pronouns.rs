@@ -0,0 +1,155 @@
+//! Pronoun attribution
+//! Pronouns are credited to an entity only within its anaphora window: from the mention to the
+//! end of the following sentence (configurable), cut short where another person is mentioned, so
+//! one person's pronouns aren't attributed to whoever happens to be nearby. Quoted speech is
+//! skipped: pronouns inside a quote refer to people other than its speaker, often not the entity
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::entity_extraction::{best_pronouns, pronoun_counts, EntityConfig, ExtractedEntity};
+use super::offsets::sentence_bounds;
+use super::quotes::{blank_quotes, Quote};
+
+/// Pronoun evidence in an entity's anaphora window
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PronounEvidence {
+    /// Pronouns counted per set ("he/him", "she/her", "they/them", plus "xe/xem", "ze/zir" and
+    /// "fae/faer" when neopronouns are enabled)
+    pub counts: BTreeMap<String, usize>,
+    /// The set reported as `pronouns`, when there is one
+    pub best_guess: Option<String>,
+    /// Share of the counted pronouns that belong to the best guess, 0 - 1
+    pub share: f64,
+}
+
+impl PronounEvidence {
+    fn from_counts(counts: &[(&str, usize)]) -> Option<Self> {
+        let total: usize = counts.iter().map(|&(_, count)| count).sum();
+        if total == 0 {
+            return None;
+        }
+        let best_guess = best_pronouns(counts);
+        let best = counts.iter().find(|&&(set, _)| Some(set) == best_guess.as_deref()).map_or(0, |&(_, count)| count);
+        Some(PronounEvidence {
+            counts: counts.iter().map(|&(set, count)| (set.to_string(), count)).collect(),
+            best_guess,
+            share: best as f64 / total as f64,
+        })
+    }
+}
+
+/// Byte range after a mention at `position` whose pronouns can refer back to it
+pub fn anaphora_window(text: &str, position: usize, config: &EntityConfig) -> (usize, usize) {
+    let (_, mut end) = sentence_bounds(text, position);
+    for _ in 0..config.anaphora_sentences {
+        if end >= text.len() {
+            break;
+        }
+        end = sentence_bounds(text, end).1;
+    }
+
+    let mut end = end.min(position.saturating_add(config.anaphora_chars)).min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    (position, end)
+}
+
+/// Set each entity's pronouns and their evidence from its anaphora window. Pronouns past the next
+/// mention of another person belong to that person; groups only take plural pronouns
+pub fn attribute_pronouns(text: &str, entities: &mut [ExtractedEntity], quotes: &[Quote], config: &EntityConfig) {
+    let positions: Vec<usize> = entities.iter().map(|e| e.position).collect();
+    for entity in entities.iter_mut() {
+        let (start, mut end) = anaphora_window(text, entity.position, config);
+        if let Some(&next) = positions.iter().filter(|&&p| p > entity.position && p < end).min() {
+            end = next;
+        }
+
+        let mut counts = pronoun_counts(&blank_quotes(text, start, end, quotes), config.neopronouns);
+        if entity.group_type.is_some() {
+            counts.retain(|&(set, _)| set == "they/them");
+        }
+        entity.pronoun_evidence = PronounEvidence::from_counts(&counts);
+        entity.pronouns = entity.pronoun_evidence.as_ref().and_then(|e| e.best_guess.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::entity_extraction::{extract_entities, extract_entities_with_config};
+    use super::*;
+
+    fn pronouns_of(text: &str, name: &str, config: &EntityConfig) -> Option<String> {
+        let result = extract_entities_with_config(text, config);
+        result.entities.into_iter().find(|e| e.name == name).and_then(|e| e.pronouns)
+    }
+
+    #[test]
+    fn test_pronouns_stop_at_the_next_person() {
+        let text = "My sister Amy called. My brother Tom said he would drive him there.";
+        let result = extract_entities(text);
+        let amy = result.entities.iter().find(|e| e.name == "Amy").unwrap();
+        assert_eq!(amy.pronouns, None);
+        let tom = result.entities.iter().find(|e| e.name == "Tom").unwrap();
+        assert_eq!(tom.pronouns.as_deref(), Some("he/him"));
+    }
+
+    #[test]
+    fn test_window_is_sentence_bounded_and_configurable() {
+        let text = "My boss Dave left early. It rained all day. He never called back.";
+        assert_eq!(pronouns_of(text, "Dave", &EntityConfig::default()), None);
+
+        let wider = EntityConfig { anaphora_sentences: 2, ..EntityConfig::default() };
+        assert_eq!(pronouns_of(text, "Dave", &wider).as_deref(), Some("he/him"));
+        let capped = EntityConfig { anaphora_chars: 30, ..wider };
+        assert_eq!(pronouns_of(text, "Dave", &capped), None);
+    }
+
+    #[test]
+    fn test_windows_survive_extreme_limits_and_multibyte_text() {
+        let unbounded = EntityConfig { anaphora_chars: usize::MAX, ..EntityConfig::default() };
+        let text = "My sister Amy called. She was upset.";
+        assert_eq!(anaphora_window(text, 10, &unbounded), (10, text.len()));
+        assert_eq!(pronouns_of(text, "Amy", &unbounded).as_deref(), Some("she/her"));
+
+        let accented = format!("{} My mom called. She was upset.", "é".repeat(30));
+        assert_eq!(pronouns_of(&accented, "mom", &EntityConfig::default()).as_deref(), Some("she/her"));
+        let accented = format!("{} Amy, my sister, called. She was upset.", "é".repeat(30));
+        let amy = extract_entities(&accented).entities.into_iter().find(|e| e.name == "Amy").unwrap();
+        assert_eq!(amy.relationship_hint.as_deref(), Some("sister"));
+    }
+
+    #[test]
+    fn test_quoted_pronouns_are_skipped() {
+        let text = "My friend Sam called and said, \"She is driving him crazy.\" Sam sounded tired.";
+        assert_eq!(pronouns_of(text, "Sam", &EntityConfig::default()), None);
+    }
+
+    #[test]
+    fn test_evidence_counts_behind_the_guess() {
+        let text = "My friend Alex said they would come, but his car broke down and they stayed home.";
+        let alex = extract_entities(text).entities.into_iter().find(|e| e.name == "Alex").unwrap();
+        let evidence = alex.pronoun_evidence.unwrap();
+        assert_eq!((evidence.counts["they/them"], evidence.counts["he/him"], evidence.counts["she/her"]), (2, 1, 0));
+        assert_eq!(evidence.best_guess.as_deref(), Some("they/them"));
+        assert_eq!(alex.pronouns, evidence.best_guess);
+        assert!((evidence.share - 2.0 / 3.0).abs() < 1e-9);
+
+        assert!(extract_entities("My friend Alex called.").entities[0].pronoun_evidence.is_none());
+    }
+
+    #[test]
+    fn test_neopronouns_when_enabled() {
+        let text = "My friend Rowan said xe would bring xyr guitar.";
+        assert_eq!(pronouns_of(text, "Rowan", &EntityConfig::default()), None);
+
+        let config = EntityConfig { neopronouns: true, ..EntityConfig::default() };
+        assert_eq!(pronouns_of(text, "Rowan", &config).as_deref(), Some("xe/xem"));
+        let fae = "My cousin Wren told me fae loves faer new job.";
+        assert_eq!(pronouns_of(fae, "Wren", &config).as_deref(), Some("fae/faer"));
+    }
+}
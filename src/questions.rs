@@ -0,0 +1,103 @@
+//! Question-form detection
+//! A match inside a genuine question ("did I do something wrong?", "am I overreacting?") is the
+//! author asking, not asserting, so it carries less weight than the same words in a declarative.
+//! Rhetorical accusations phrased as questions ("why are you so lazy?") are not discounted
+
+use super::offsets::sentence_bounds;
+use super::pattern_matching::PatternMatch;
+use super::tokenize::tokenize;
+
+/// Weight multiplier for a match inside a genuine question
+const QUESTION_WEIGHT_FACTOR: f64 = 0.6;
+
+// Sorted, looked up with binary_search
+const AUXILIARIES: &[&str] = &[
+    "am", "are", "aren't", "can", "can't", "could", "couldn't", "did", "didn't", "do", "does", "doesn't", "don't",
+    "had", "has", "hasn't", "have", "haven't", "is", "isn't", "may", "might", "should", "shouldn't", "was",
+    "wasn't", "were", "weren't", "will", "won't", "would", "wouldn't",
+];
+/// Subjects that follow the auxiliary in an inverted question ("did I", "is this")
+const SUBJECTS: &[&str] = &[
+    "anyone", "everyone", "he", "i", "it", "my", "she", "someone", "that", "there", "they", "this", "we", "you",
+    "your",
+];
+const WH_WORDS: &[&str] = &["how", "what", "when", "where", "which", "who", "why"];
+/// Openers skipped before looking for the interrogative form ("so did I ...", "ok, am I ...")
+const DISCOURSE_MARKERS: &[&str] = &["and", "but", "hey", "honestly", "ok", "okay", "so", "um", "well"];
+
+fn contains(list: &[&str], word: &str) -> bool {
+    list.binary_search(&word).is_ok()
+}
+
+/// Whether a sentence is a genuine question: auxiliary-subject inversion ("am I overreacting")
+/// or a wh-question ("what did I do"), with or without the question mark, but not a
+/// second-person "why"/"how" accusation ("why are you like this", "how could you")
+pub fn is_genuine_question(sentence: &str) -> bool {
+    let words: Vec<String> = tokenize(sentence)
+        .iter()
+        .map(|t| t.lower().replace('\u{2019}', "'"))
+        .skip_while(|w| contains(DISCOURSE_MARKERS, w))
+        .collect();
+    let word = |i: usize| words.get(i).map(String::as_str).unwrap_or("");
+
+    let question_mark = sentence.trim_end().ends_with('?');
+
+    if contains(AUXILIARIES, word(0)) {
+        // Without the question mark only a personal subject rules out an imperative ("do it now")
+        return contains(SUBJECTS, word(1)) && (question_mark || matches!(word(1), "i" | "you" | "we" | "he" | "she" | "they"));
+    }
+    if contains(WH_WORDS, word(0)) {
+        let rhetorical = matches!(word(0), "why" | "how")
+            && (word(1) == "dare" || (contains(AUXILIARIES, word(1)) && matches!(word(2), "you" | "u")));
+        let interrogative = contains(AUXILIARIES, word(1)) || question_mark;
+        return interrogative && !rhetorical;
+    }
+    false
+}
+
+/// Whether the sentence containing `position` is a genuine question
+pub fn in_question(text: &str, position: usize) -> bool {
+    let (start, end) = sentence_bounds(text, position);
+    is_genuine_question(&text[start..end])
+}
+
+/// Down-weight matches inside genuine questions
+pub fn apply_question_form(text: &str, matches: &mut [PatternMatch]) {
+    for m in matches.iter_mut() {
+        if in_question(text, m.position) {
+            m.weight *= QUESTION_WEIGHT_FACTOR;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_genuine_and_rhetorical_questions() {
+        assert!(is_genuine_question("Did I do something wrong?"));
+        assert!(is_genuine_question("am I overreacting"));
+        assert!(is_genuine_question("So what did I say that upset you?"));
+        assert!(!is_genuine_question("Why are you so lazy?"));
+        assert!(!is_genuine_question("How could you do this to me?"));
+        assert!(!is_genuine_question("You're so selfish?"));
+        assert!(!is_genuine_question("Do it now."));
+        for list in [AUXILIARIES, SUBJECTS, WH_WORDS, DISCOURSE_MARKERS] {
+            assert!(list.windows(2).all(|w| w[0] < w[1]), "{:?}", list);
+        }
+    }
+
+    #[test]
+    fn test_question_matches_lose_weight() {
+        let text = "Am I crazy? You're crazy.";
+        let mut matches = super::super::pattern_matching::match_patterns(text);
+        let before: Vec<f64> = matches.iter().map(|m| m.weight).collect();
+        apply_question_form(text, &mut matches);
+        for (m, weight) in matches.iter().zip(before) {
+            let expected = if m.position < text.find('?').unwrap() { weight * QUESTION_WEIGHT_FACTOR } else { weight };
+            assert_eq!(m.weight, expected);
+        }
+        assert!(matches.iter().any(|m| m.position < 10));
+    }
+}
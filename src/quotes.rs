@@ -0,0 +1,175 @@
+//! Direct quote extraction
+//! Finds quoted speech and, where the surrounding clause names one, who said it
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::pattern_matching::match_patterns;
+use super::PatternMatchResult;
+
+/// Reporting verbs, optionally followed by the addressee ("told me", "said to her")
+const VERB: &str = r"(?:said|says|told|tells|yelled|yells|screamed|shouted|asked|asks|wrote|writes|texted|texts|replied|snapped|whispered|muttered|goes|went)(?:\s+(?:to\s+)?(?:me|him|her|them|us|you))?";
+
+/// Who can be credited with a quote: possessive relations, capitalized names, pronouns
+const SPEAKER: &str = r"\b(?:[Mm]y|[Hh]is|[Hh]er|[Tt]heir|[Oo]ur)\s+[a-z]+|\b[A-Z][a-z]+|\b(?:he|she|they|I)\b";
+
+lazy_static! {
+    /// `Mom said, "..."` — attribution ending right before the quote
+    static ref SPEAKER_BEFORE: Regex =
+        Regex::new(&format!(r"({})\s+(?:(?:just|then|once|always)\s+)?{}\s*[,:]?\s*$", SPEAKER, VERB)).unwrap();
+    /// `"...," she said` or `"...," said Mom` — attribution right after the quote
+    static ref SPEAKER_AFTER: Regex =
+        Regex::new(&format!(r"^\s*,?\s*(?:({})\s+{}|{}\s+({}))", SPEAKER, VERB, VERB, SPEAKER)).unwrap();
+}
+
+/// Opening/closing delimiter pairs; `single` pairs double as apostrophes
+const DELIMITERS: &[(char, char, bool)] = &[
+    ('"', '"', false),
+    ('\u{201c}', '\u{201d}', false),
+    ('\u{ab}', '\u{bb}', false),
+    ('\'', '\'', true),
+    ('\u{2018}', '\u{2019}', true),
+];
+
+/// Quoted speech with its attributed speaker
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Quote {
+    pub speaker: Option<String>,
+    pub quote: String,
+    /// Byte offset of the quote text (inside the delimiters)
+    pub offset: usize,
+    pub end: usize,
+}
+
+/// Quotes plus pattern matches, with `quoteIndex` set on matches inside a quote
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteExtractionResult {
+    pub quotes: Vec<Quote>,
+    pub patterns: Vec<PatternMatchResult>,
+}
+
+/// Byte offset of the closing delimiter for a quote opened at `start`
+fn find_close(text: &str, start: usize, close: char, single: bool) -> Option<usize> {
+    let mut previous = None;
+    for (i, c) in text[start..].char_indices() {
+        if text[start + i..].starts_with("\n\n") {
+            return None;
+        }
+        if c == close && i > 0 && !previous.is_some_and(char::is_whitespace) {
+            // An apostrophe inside a word ("you'll") doesn't close a single-quoted span
+            let next = text[start + i + c.len_utf8()..].chars().next();
+            if !single || !next.is_some_and(char::is_alphanumeric) {
+                return Some(start + i);
+            }
+        }
+        previous = Some(c);
+    }
+    None
+}
+
+/// Attributed speaker from the clause just before or after the quote
+fn attribute(text: &str, open: usize, after_close: usize) -> Option<String> {
+    let line_start = text[..open].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    if let Some(captures) = SPEAKER_BEFORE.captures(&text[line_start..open]) {
+        return Some(captures[1].to_string());
+    }
+
+    let line_end = text[after_close..].find('\n').map(|i| after_close + i).unwrap_or(text.len());
+    let captures = SPEAKER_AFTER.captures(&text[after_close..line_end])?;
+    captures.get(1).or_else(|| captures.get(2)).map(|m| m.as_str().to_string())
+}
+
+/// Extract direct quotes in document order
+pub fn extract_quotes(text: &str) -> Vec<Quote> {
+    let mut quotes = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut iter = text.char_indices();
+
+    while let Some((i, c)) = iter.next() {
+        let delimiter = DELIMITERS.iter().find(|(open, _, _)| *open == c);
+        // Single quotes only open after whitespace or punctuation, not mid-word
+        let opens = match delimiter {
+            Some((_, _, true)) => !previous.is_some_and(char::is_alphanumeric),
+            Some(_) => true,
+            None => false,
+        };
+        previous = Some(c);
+        let (Some(&(_, close, single)), true) = (delimiter, opens) else {
+            continue;
+        };
+
+        let start = i + c.len_utf8();
+        let Some(end) = find_close(text, start, close, single) else {
+            continue;
+        };
+        let after_close = end + close.len_utf8();
+        quotes.push(Quote {
+            speaker: attribute(text, i, after_close),
+            quote: text[start..end].to_string(),
+            offset: start,
+            end,
+        });
+
+        // Resume after the closing delimiter
+        while iter.next().is_some_and(|(j, _)| j < end) {}
+        previous = Some(close);
+    }
+
+    quotes
+}
+
+/// `text[start..end]` with the text of the given quotes blanked out
+pub fn blank_quotes<'q>(text: &str, start: usize, end: usize, quotes: impl IntoIterator<Item = &'q Quote>) -> String {
+    let mut window: Vec<char> = Vec::with_capacity(end - start);
+    let quotes: Vec<&Quote> = quotes.into_iter().collect();
+    for (i, c) in text[start..end].char_indices() {
+        let quoted = quotes.iter().any(|q| (q.offset..q.end).contains(&(start + i)));
+        window.push(if quoted { ' ' } else { c });
+    }
+    window.into_iter().collect()
+}
+
+/// Extract quotes and link pattern matches to the quote containing them
+pub fn extract_quotes_with_patterns(text: &str) -> QuoteExtractionResult {
+    let quotes = extract_quotes(text);
+    let patterns = match_patterns(text)
+        .iter()
+        .map(|m| {
+            let mut result = PatternMatchResult::from(m);
+            result.quote_index = quotes.iter().position(|q| m.position >= q.offset && m.position < q.end);
+            result
+        })
+        .collect();
+    QuoteExtractionResult { quotes, patterns }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quotes_with_speakers() {
+        let text = "Mom said 'you'll never amount to anything'. Later, \u{201c}It's all your fault,\u{201d} he told me. Then \"whatever\".";
+        let quotes = extract_quotes(text);
+        assert_eq!(quotes.len(), 3);
+        assert_eq!(quotes[0].speaker.as_deref(), Some("Mom"));
+        assert_eq!(quotes[0].quote, "you'll never amount to anything");
+        assert_eq!(&text[quotes[0].offset..quotes[0].end], quotes[0].quote);
+        assert_eq!(quotes[1].speaker.as_deref(), Some("he"));
+        assert_eq!(quotes[2].speaker, None);
+    }
+
+    #[test]
+    fn test_apostrophes_are_not_quotes_and_patterns_link() {
+        assert!(extract_quotes("It's my sister's car, isn't it").is_empty());
+
+        let result = extract_quotes_with_patterns("I'm fine. My dad said \"You're so selfish\"");
+        assert_eq!(result.quotes[0].speaker.as_deref(), Some("My dad"));
+        let judgment = result.patterns.iter().find(|p| p.pattern_type == "character_judgment").unwrap();
+        assert_eq!(judgment.quote_index, Some(0));
+    }
+}
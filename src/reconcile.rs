@@ -0,0 +1,115 @@
+//! Cross-module span reconciliation
+//! Pattern matches, keywords, personal data and entity mentions folded into one list of
+//! non-overlapping spans, each carrying every role that covers it, so highlighting layers
+//! never draw conflicting overlapping annotations
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::entity_extraction::extract_entities;
+use super::keywords::keyword_spans;
+use super::pattern_matching::match_patterns;
+use super::pii::detect_pii;
+
+/// One annotation covering a span
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpanRole {
+    /// `pattern`, `keyword`, `pii` or `entity`
+    pub role: String,
+    /// Pattern type, keyword, PII kind or entity name
+    pub label: String,
+    /// Id of the source annotation (match id, entity id), when it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+    /// Byte range of the whole source annotation, which may extend over neighbouring spans
+    pub source_start: usize,
+    pub source_end: usize,
+}
+
+/// Non-overlapping span with all roles covering it (byte offsets, end exclusive)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciledSpan {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub roles: Vec<SpanRole>,
+}
+
+/// Every annotation from the individual modules
+fn collect_roles(text: &str) -> Vec<SpanRole> {
+    let mut roles = Vec::new();
+    let mut push = |role: &str, label: String, source_id: Option<String>, start: usize, end: usize| {
+        if start < end && end <= text.len() && text.is_char_boundary(end) {
+            roles.push(SpanRole { role: role.to_string(), label, source_id, source_start: start, source_end: end });
+        }
+    };
+
+    for m in match_patterns(text) {
+        let end = m.position + m.match_text.len();
+        push("pattern", m.pattern_type.clone(), Some(m.id()), m.position, end);
+    }
+    for (keyword, start, end) in keyword_spans(text) {
+        push("keyword", keyword, None, start, end);
+    }
+    for pii in detect_pii(text) {
+        push("pii", pii.kind, None, pii.position, pii.end);
+    }
+    for entity in extract_entities(text).entities {
+        let end = entity.position + entity.relationship_context.len();
+        push("entity", entity.name, Some(entity.id), entity.position, end);
+    }
+    roles
+}
+
+/// Split annotations at every boundary into non-overlapping spans; neighbouring spans with the
+/// same roles are merged back together
+pub fn reconcile_spans(text: &str) -> Vec<ReconciledSpan> {
+    let roles = collect_roles(text);
+    let mut boundaries: Vec<usize> = roles.iter().flat_map(|r| [r.source_start, r.source_end]).collect();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut spans: Vec<ReconciledSpan> = Vec::new();
+    for window in boundaries.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        let covering: Vec<SpanRole> =
+            roles.iter().filter(|r| r.source_start <= start && end <= r.source_end).cloned().collect();
+        if covering.is_empty() {
+            continue;
+        }
+        match spans.last_mut() {
+            Some(last) if last.end == start && last.roles == covering => {
+                last.end = end;
+                last.text = text[last.start..end].to_string();
+            }
+            _ => spans.push(ReconciledSpan { start, end, text: text[start..end].to_string(), roles: covering }),
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans_do_not_overlap() {
+        let text = "My sister Sarah said you're so selfish. Email sarah@example.com about the custody hearing.";
+        let spans = reconcile_spans(text);
+        assert!(spans.windows(2).all(|w| w[0].end <= w[1].start));
+        assert!(spans.iter().all(|s| text[s.start..s.end] == s.text));
+        assert!(spans.iter().any(|s| s.roles.iter().any(|r| r.role == "pii")));
+    }
+
+    #[test]
+    fn test_span_can_have_multiple_roles() {
+        let text = "You're so selfish";
+        let spans = reconcile_spans(text);
+        let selfish = spans.iter().find(|s| s.text == "selfish").unwrap();
+        let roles: Vec<&str> = selfish.roles.iter().map(|r| r.role.as_str()).collect();
+        assert_eq!(roles, vec!["pattern", "keyword"]);
+        assert_eq!(selfish.roles[0].source_start, 0);
+    }
+}
@@ -0,0 +1,133 @@
+//! Rule-based reframing suggestions
+//! Rewrites judgments, absolutes and blame into first-person statements ("soften before send")
+
+use super::pattern_matching::PatternMatch;
+
+/// Irregular past participles for verbs that commonly follow "you always/never"
+const IRREGULAR_PARTICIPLES: &[(&str, &str)] = &[
+    ("forget", "forgotten"),
+    ("leave", "left"),
+    ("take", "taken"),
+    ("make", "made"),
+    ("put", "put"),
+    ("let", "let"),
+    ("shut", "shut"),
+    ("throw", "thrown"),
+    ("tell", "told"),
+];
+
+/// Feeling suggested in place of a character judgment
+fn feeling_for_judgment(judgment: &str) -> &'static str {
+    match judgment {
+        "lazy" => "overwhelmed",
+        "selfish" | "arrogant" | "narcissist" => "uncared for",
+        "stupid" | "incompetent" | "useless" | "pathetic" | "worthless" => "frustrated",
+        "liar" | "fake" | "fraud" | "cheat" | "hypocrite" => "hurt and unsure what to trust",
+        _ => "hurt",
+    }
+}
+
+/// Past participle of a regular or common irregular verb
+fn past_participle(verb: &str) -> String {
+    if let Some((_, participle)) = IRREGULAR_PARTICIPLES.iter().find(|(base, _)| *base == verb) {
+        return participle.to_string();
+    }
+    let ends_with_consonant_y = verb.ends_with('y') && !verb[..verb.len() - 1].ends_with(['a', 'e', 'i', 'o', 'u']);
+    if ends_with_consonant_y {
+        format!("{}ied", &verb[..verb.len() - 1])
+    } else if verb.ends_with('e') {
+        format!("{}d", verb)
+    } else {
+        format!("{}ed", verb)
+    }
+}
+
+/// Rest of the clause after a match, up to the next punctuation
+fn clause_after<'a>(text: &'a str, m: &PatternMatch) -> &'a str {
+    let rest = &text[m.position + m.match_text.len()..];
+    let end = rest.find(['.', '!', '?', ',', ';', '\n']).unwrap_or(rest.len());
+    rest[..end].trim()
+}
+
+/// "you always ignore me" → (absolute, verb, object clause)
+fn split_absolute(text: &str, m: &PatternMatch) -> Option<(String, String, String)> {
+    let words: Vec<&str> = m.match_text.split_whitespace().collect();
+    let index = words
+        .iter()
+        .position(|w| matches!(w.to_lowercase().as_str(), "always" | "never" | "constantly" | "forever" | "eternally"))?;
+    let verb = words.get(index + 1)?.to_lowercase();
+
+    let mut object = words[index + 2..].join(" ");
+    let tail = clause_after(text, m);
+    if !object.is_empty() && !tail.is_empty() {
+        object.push(' ');
+    }
+    object.push_str(tail);
+    Some((words[index].to_lowercase(), verb, object))
+}
+
+/// Suggested rewrite for a match, for the categories that have one
+pub fn suggest_reframe(text: &str, m: &PatternMatch) -> Option<String> {
+    match m.pattern_type.as_str() {
+        "absolute_statement" => {
+            let (absolute, verb, object) = split_absolute(text, m)?;
+            let object_suffix = if object.is_empty() { String::new() } else { format!(" {}", object) };
+            Some(match (absolute.as_str(), object.as_str()) {
+                ("never", _) => format!("It would mean a lot to me if you {}{}.", verb, object_suffix),
+                (_, "me") => format!("I felt {} when…", past_participle(&verb)),
+                _ => format!("When you {}{} (this time), I feel…", verb, object_suffix),
+            })
+        }
+        "character_judgment" => {
+            let judgment = m.match_text.split_whitespace().last()?.to_lowercase();
+            Some(format!("I felt {} when… Can we talk about what happened?", feeling_for_judgment(&judgment)))
+        }
+        "displacement" => {
+            let lower = m.match_text.to_lowercase();
+            if lower.contains("fault") || lower.contains("because of you") {
+                Some("I'm upset about what happened, and I'd like us to work it out together.".to_string())
+            } else {
+                let reaction = clause_after(text, m);
+                if reaction.is_empty() {
+                    Some("I reacted strongly because I was feeling…".to_string())
+                } else {
+                    Some(format!("I chose to {} because I was feeling…", reaction))
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::pattern_matching::match_patterns;
+
+    fn reframe_of(text: &str, pattern_type: &str) -> Option<String> {
+        let m = match_patterns(text).into_iter().find(|m| m.pattern_type == pattern_type)?;
+        suggest_reframe(text, &m)
+    }
+
+    #[test]
+    fn test_absolute_reframes() {
+        assert_eq!(reframe_of("You always ignore me", "absolute_statement").as_deref(), Some("I felt ignored when…"));
+        assert_eq!(
+            reframe_of("You never listen to me!", "absolute_statement").as_deref(),
+            Some("It would mean a lot to me if you listen to me.")
+        );
+        assert_eq!(past_participle("worry"), "worried");
+        assert_eq!(past_participle("forget"), "forgotten");
+    }
+
+    #[test]
+    fn test_judgment_and_displacement_reframes() {
+        let judgment = reframe_of("You're so selfish", "character_judgment").unwrap();
+        assert!(judgment.starts_with("I felt uncared for when"));
+        assert_eq!(
+            reframe_of("You made me yell at you.", "displacement").as_deref(),
+            Some("I chose to yell at you because I was feeling…")
+        );
+        assert!(reframe_of("Nobody cares", "universalizing").is_none());
+    }
+}
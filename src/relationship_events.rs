@@ -0,0 +1,228 @@
+//! Relationship change events
+//! Statements that a relationship has started, ended or changed ("John is my ex now", "we broke
+//! up", "she's no longer my manager"), resolved to the extracted entity they are about, so a
+//! host's contact store can update roles over time
+
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::entity_extraction::{infer_relationship_from_word, ExtractedEntity};
+
+/// Relationships "we broke up" / "we got married" can refer to
+const ROMANTIC_RELATIONSHIPS: &[&str] = &[
+    "boyfriend", "ex_partner", "fiance", "fiancee", "girlfriend", "husband", "partner", "significant_other",
+    "spouse", "wife",
+];
+
+lazy_static::lazy_static! {
+    /// "John is my ex now", "she's now my manager", "Dave is my new boss", "he became my boss"
+    static ref NOW_RELATION: Regex = Regex::new(
+        r"\b([A-Z][a-z]+|(?i:he|she|they))(?:\s+(?i:is|are|became|has become)|(?:'|\x{2019})(?i:s|re))\s+(?i:(now)\s+)?(?i:my)\s+(?i:(new|former)\s+)?(\w+(?:-\w+)?)(\s+(?i:now))?\b"
+    ).unwrap();
+
+    /// "she's no longer my manager", "Tom isn't my friend anymore"
+    static ref NO_LONGER: Regex = Regex::new(
+        r"\b([A-Z][a-z]+|(?i:he|she|they))(?:\s+(?i:is|are)|(?:'|\x{2019})(?i:s|re))?\s+(?i:(no longer)|not|(?:is|are)n(?:'|\x{2019})t)\s+(?i:my)\s+(\w+(?:-\w+)?)(\s+(?i:anymore|any more))?\b"
+    ).unwrap();
+
+    /// "we broke up", "we got divorced"
+    static ref BREAKUP: Regex = Regex::new(r"(?i)\bwe\s+(?:(?:just\s+)?broke up|split up|separated|(got divorced|divorced)|called it off)\b").unwrap();
+
+    /// "we got married", "we're engaged"
+    static ref COMMITMENT: Regex = Regex::new(r"(?i)\bwe(?:\s+(?:got|are|just got)|(?:'|\x{2019})re)\s+(married|engaged)\b").unwrap();
+}
+
+/// One relationship transition
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationshipChange {
+    /// "started", "ended" or "changed"
+    pub kind: String,
+    /// Id of the entity the change is about, when it could be resolved
+    pub entity_id: Option<String>,
+    /// Name as resolved or as written ("John"); absent for an unresolved "we" or pronoun
+    pub name: Option<String>,
+    pub previous_relationship: Option<String>,
+    pub new_relationship: Option<String>,
+    /// The statement signalling the change
+    pub trigger: String,
+    pub position: usize,
+}
+
+/// Relationship type of a noun, with "ex" and "ex-boyfriend" style nouns as former partners
+fn relationship_of(noun: &str) -> Option<String> {
+    let noun = noun.to_lowercase();
+    match noun.as_str() {
+        "ex" | "ex-partner" | "ex-boyfriend" | "ex-girlfriend" => Some("ex_partner".to_string()),
+        "ex-husband" | "ex-wife" => Some("ex_spouse".to_string()),
+        _ => infer_relationship_from_word(&noun),
+    }
+}
+
+fn is_former(relationship: &str) -> bool {
+    relationship.starts_with("ex_")
+}
+
+/// Entity a subject refers to: a name (or alias), or for a pronoun the nearest preceding person
+fn resolve<'e>(subject: &str, position: usize, entities: &'e [ExtractedEntity]) -> Option<&'e ExtractedEntity> {
+    let people = entities.iter().filter(|e| e.group_type.is_none());
+    match subject.to_lowercase().as_str() {
+        "he" | "she" | "they" => {
+            let pronouns = format!("{}/", subject.to_lowercase());
+            let before: Vec<&ExtractedEntity> = people.filter(|e| e.position < position).collect();
+            before
+                .iter()
+                .rev()
+                .find(|e| e.pronouns.as_deref().is_some_and(|p| p.starts_with(&pronouns)))
+                .or(before.last())
+                .copied()
+        }
+        _ => people.into_iter().find(|e| {
+            e.name.eq_ignore_ascii_case(subject)
+                || e.canonical_name.eq_ignore_ascii_case(subject)
+                || e.aliases.iter().any(|a| a.eq_ignore_ascii_case(subject))
+        }),
+    }
+}
+
+/// Nearest romantic partner mentioned before `position`, else the first one mentioned
+fn romantic_partner(position: usize, entities: &[ExtractedEntity]) -> Option<&ExtractedEntity> {
+    let partners: Vec<&ExtractedEntity> = entities
+        .iter()
+        .filter(|e| e.relationship_hint.as_deref().is_some_and(|r| ROMANTIC_RELATIONSHIPS.contains(&r)))
+        .collect();
+    partners.iter().rev().find(|e| e.position < position).or(partners.first()).copied()
+}
+
+fn change(
+    kind: &str,
+    subject: Option<(&str, Option<&ExtractedEntity>)>,
+    previous: Option<String>,
+    new: Option<String>,
+    trigger: &Captures,
+) -> RelationshipChange {
+    let whole = trigger.get(0).unwrap();
+    let entity = subject.and_then(|(_, entity)| entity);
+    let name = match subject {
+        Some((_, Some(entity))) => Some(entity.name.clone()),
+        Some((written, None)) if !matches!(written.to_lowercase().as_str(), "he" | "she" | "they") => {
+            Some(written.to_string())
+        }
+        _ => None,
+    };
+    RelationshipChange {
+        kind: kind.to_string(),
+        entity_id: entity.map(|e| e.id.clone()),
+        name,
+        previous_relationship: previous,
+        new_relationship: new,
+        trigger: whole.as_str().to_string(),
+        position: whole.start(),
+    }
+}
+
+/// Relationship changes stated in the text, in text order
+pub fn relationship_changes(text: &str, entities: &[ExtractedEntity]) -> Vec<RelationshipChange> {
+    let mut changes = Vec::new();
+
+    for cap in NOW_RELATION.captures_iter(text) {
+        let Some(relationship) = relationship_of(&cap[4]) else {
+            continue;
+        };
+        let (subject, position) = (&cap[1], cap.get(0).unwrap().start());
+        let entity = resolve(subject, position, entities);
+
+        // "my former boss" ends the named relationship
+        if cap.get(3).is_some_and(|m| m.as_str().eq_ignore_ascii_case("former")) {
+            changes.push(change("ended", Some((subject, entity)), Some(relationship), None, &cap));
+            continue;
+        }
+        let new = relationship;
+        let marked = cap.get(2).is_some() || cap.get(3).is_some() || cap.get(5).is_some();
+        let verb = cap[0].split_whitespace().nth(1).unwrap_or("").to_lowercase();
+        let became = verb == "became" || verb == "has";
+        if !(marked || became || is_former(&new)) {
+            continue;
+        }
+        let previous = entity.and_then(|e| e.relationship_hint.clone()).filter(|r| *r != new);
+        let kind = match (&previous, is_former(&new)) {
+            (_, true) => "ended",
+            (Some(_), false) => "changed",
+            (None, false) => "started",
+        };
+        changes.push(change(kind, Some((subject, entity)), previous, Some(new), &cap));
+    }
+
+    for cap in NO_LONGER.captures_iter(text) {
+        if cap.get(2).is_none() && cap.get(4).is_none() {
+            continue;
+        }
+        let Some(previous) = relationship_of(&cap[3]) else {
+            continue;
+        };
+        let (subject, position) = (&cap[1], cap.get(0).unwrap().start());
+        let entity = resolve(subject, position, entities);
+        changes.push(change("ended", Some((subject, entity)), Some(previous), None, &cap));
+    }
+
+    for cap in BREAKUP.captures_iter(text) {
+        let partner = romantic_partner(cap.get(0).unwrap().start(), entities);
+        let new = if cap.get(1).is_some() { "ex_spouse" } else { "ex_partner" };
+        let previous = partner.and_then(|e| e.relationship_hint.clone()).filter(|r| !is_former(r));
+        let subject = partner.map(|e| (e.name.as_str(), Some(e)));
+        changes.push(change("ended", subject, previous, Some(new.to_string()), &cap));
+    }
+
+    for cap in COMMITMENT.captures_iter(text) {
+        let partner = romantic_partner(cap.get(0).unwrap().start(), entities);
+        let new = if cap[1].eq_ignore_ascii_case("married") { "spouse" } else { "fiance" };
+        let previous = partner.and_then(|e| e.relationship_hint.clone()).filter(|r| r != new);
+        let kind = if previous.is_some() { "changed" } else { "started" };
+        let subject = partner.map(|e| (e.name.as_str(), Some(e)));
+        changes.push(change(kind, subject, previous, Some(new.to_string()), &cap));
+    }
+
+    changes.sort_by_key(|c| c.position);
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::entity_extraction::extract_entities;
+    use super::*;
+
+    #[test]
+    fn test_role_changes_are_resolved_to_entities() {
+        let text = "My boyfriend John forgot again. John is my ex now. My boss Karen left. Dave is my new boss. She's no longer my manager.";
+        let result = extract_entities(text);
+        let changes = &result.relationship_changes;
+        assert_eq!(changes.len(), 3);
+
+        assert_eq!(changes[0].kind, "ended");
+        assert_eq!(changes[0].name.as_deref(), Some("John"));
+        assert!(changes[0].entity_id.is_some());
+        assert_eq!(changes[0].previous_relationship.as_deref(), Some("boyfriend"));
+        assert_eq!(changes[0].new_relationship.as_deref(), Some("ex_partner"));
+
+        assert_eq!(changes[1].kind, "started");
+        assert_eq!((changes[1].name.as_deref(), changes[1].entity_id.as_deref()), (Some("Dave"), None));
+        assert_eq!(changes[2].kind, "ended");
+        assert_eq!(changes[2].previous_relationship.as_deref(), Some("boss"));
+
+        assert!(extract_entities("John is my brother. She is my friend.").relationship_changes.is_empty());
+    }
+
+    #[test]
+    fn test_we_statements_use_the_partner() {
+        let text = "My husband Mark and I fought for years. Last spring we got divorced.";
+        let changes = relationship_changes(text, &extract_entities(text).entities);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].name.as_deref(), Some("Mark"));
+        assert_eq!(changes[0].previous_relationship.as_deref(), Some("husband"));
+        assert_eq!(changes[0].new_relationship.as_deref(), Some("ex_spouse"));
+
+        let engaged = relationship_changes("We're engaged!", &[]);
+        assert_eq!((engaged[0].kind.as_str(), engaged[0].name.as_deref()), ("started", None));
+    }
+}
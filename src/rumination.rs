@@ -0,0 +1,139 @@
+//! Rumination detection across entries
+//! Finds grievances and people that keep coming back over a series of entries (oldest first)
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::analyze;
+use super::entity_extraction::extract_entities;
+use super::tokenize::content_words;
+
+/// Entries a phrase or person must appear in to count as repeated
+const MIN_REPEAT_ENTRIES: usize = 2;
+
+/// Most repeated phrases reported
+const MAX_REPORTED_PHRASES: usize = 20;
+
+/// Phrase or person recurring across entries
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RepeatedTheme {
+    pub theme: String,
+    /// Indices of the entries mentioning it
+    pub entries: Vec<usize>,
+}
+
+/// Rumination across a series of entries
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RuminationResult {
+    /// 0 (every entry is new ground) to 1 (entries rehash the same grievance)
+    pub score: f64,
+    pub entry_count: usize,
+    /// Per entry: share of its themes already seen in earlier entries
+    pub entry_recurrence: Vec<f64>,
+    pub repeated_phrases: Vec<RepeatedTheme>,
+    pub repeated_people: Vec<RepeatedTheme>,
+}
+
+/// Content-word bigrams and trigrams of an entry
+fn phrases(text: &str) -> BTreeSet<String> {
+    let words = content_words(text);
+    let mut phrases = BTreeSet::new();
+    for n in 2..=3 {
+        for window in words.windows(n) {
+            phrases.insert(window.join(" "));
+        }
+    }
+    phrases
+}
+
+/// Themes mentioned in more than one entry, most widespread first
+fn repeated(occurrences: BTreeMap<String, Vec<usize>>) -> Vec<RepeatedTheme> {
+    let mut themes: Vec<RepeatedTheme> = occurrences
+        .into_iter()
+        .filter(|(_, entries)| entries.len() >= MIN_REPEAT_ENTRIES)
+        .map(|(theme, entries)| RepeatedTheme { theme, entries })
+        .collect();
+    themes.sort_by_key(|t| std::cmp::Reverse(t.entries.len()));
+    themes
+}
+
+/// Detect repeated grievances, phrases and people across entries
+pub fn detect_rumination(entries: &[String]) -> RuminationResult {
+    let mut phrase_entries: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let mut people_entries: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    let mut entry_recurrence = Vec::with_capacity(entries.len());
+    let mut distressed = Vec::with_capacity(entries.len());
+
+    for (index, entry) in entries.iter().enumerate() {
+        let mut themes = phrases(entry);
+        for phrase in &themes {
+            phrase_entries.entry(phrase.clone()).or_default().push(index);
+        }
+
+        let people: BTreeSet<String> = extract_entities(entry).entities.into_iter().map(|e| e.name.to_lowercase()).collect();
+        for person in people {
+            people_entries.entry(person.clone()).or_default().push(index);
+            themes.insert(format!("person:{}", person));
+        }
+
+        // Share of this entry's themes that earlier entries already raised
+        let recurring = themes.iter().filter(|t| seen.contains(*t)).count();
+        entry_recurrence.push(if themes.is_empty() || index == 0 { 0.0 } else { recurring as f64 / themes.len() as f64 });
+        seen.extend(themes);
+        distressed.push(analyze(entry).detected);
+    }
+
+    let repeated_phrases: Vec<RepeatedTheme> = repeated(phrase_entries).into_iter().take(MAX_REPORTED_PHRASES).collect();
+    let repeated_people = repeated(people_entries);
+
+    // Recurrence counts fully when the repeating entries are themselves distressed
+    let recurring_entries: BTreeSet<usize> =
+        repeated_phrases.iter().chain(&repeated_people).flat_map(|t| t.entries.iter().copied()).collect();
+    let negativity = if recurring_entries.is_empty() {
+        0.0
+    } else {
+        recurring_entries.iter().filter(|i| distressed[**i]).count() as f64 / recurring_entries.len() as f64
+    };
+    let later_entries = entries.len().saturating_sub(1).max(1) as f64;
+    let recurrence = entry_recurrence.iter().sum::<f64>() / later_entries;
+    let score = (recurrence * (0.5 + 0.5 * negativity)).min(1.0);
+
+    RuminationResult { score, entry_count: entries.len(), entry_recurrence, repeated_phrases, repeated_people }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_grievance_scores_higher() {
+        let ruminating = vec![
+            "My boss John ignored my project proposal again. It's all his fault.".to_string(),
+            "Still thinking about my boss John ignoring my project proposal. You're so selfish.".to_string(),
+            "Why did my boss John ignore the project proposal? It's all your fault.".to_string(),
+        ];
+        let varied = vec![
+            "Went hiking with friends this morning.".to_string(),
+            "Cooked a new pasta recipe tonight.".to_string(),
+            "Finished reading a long novel.".to_string(),
+        ];
+
+        let result = detect_rumination(&ruminating);
+        assert!(result.repeated_phrases.iter().any(|t| t.theme == "project proposal" && t.entries.len() == 3));
+        assert!(result.repeated_people.iter().any(|t| t.theme == "john"));
+        assert!(result.score > detect_rumination(&varied).score);
+        assert_eq!(detect_rumination(&varied).score, 0.0);
+    }
+
+    #[test]
+    fn test_single_entry_has_no_rumination() {
+        let result = detect_rumination(&["It's all your fault".to_string()]);
+        assert_eq!(result.score, 0.0);
+        assert_eq!(result.entry_recurrence, vec![0.0]);
+    }
+}
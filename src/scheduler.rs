@@ -0,0 +1,202 @@
+//! Cooperative chunked scheduler for huge inputs
+//! Splits text into sentence-aligned chunks and processes them in time-budgeted slices
+
+use super::coded_lexicon::match_coded_terms;
+use super::pattern_matching::{passes_target_gate, sort_matches, PatternMatch, COMPILED_PATTERNS};
+
+/// Target chunk size in bytes before extending to the next sentence boundary
+const TARGET_CHUNK_BYTES: usize = 2048;
+
+/// Bytes of lookahead past each chunk so matches spanning a chunk edge are still found
+const OVERLAP_BYTES: usize = 256;
+
+/// Milliseconds since an arbitrary epoch, usable on wasm and native
+pub fn now_ms() -> f64 {
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+    {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0)
+    }
+}
+
+/// Round a byte index down to the nearest char boundary
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Split text into sentence-aligned chunk ranges of roughly `target` bytes
+pub fn sentence_chunks(text: &str, target: usize) -> Vec<(usize, usize)> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < text.len() {
+        let target_end = floor_char_boundary(text, start + target.max(1));
+        if target_end >= text.len() {
+            chunks.push((start, text.len()));
+            break;
+        }
+
+        // Prefer the next sentence end, then whitespace, within another `target` bytes
+        let search_end = floor_char_boundary(text, target_end + target);
+        let window = &text[target_end..search_end];
+        let end = window
+            .find(['.', '!', '?', '\n'])
+            .or_else(|| window.find(char::is_whitespace))
+            .map(|i| target_end + i + 1)
+            .unwrap_or(target_end);
+        let end = floor_char_boundary(text, end).max(target_end);
+
+        chunks.push((start, end));
+        start = end;
+    }
+
+    chunks
+}
+
+/// Chunked scan producing the same matches as a full `match_patterns` run
+pub struct ChunkedScan {
+    text: String,
+    chunks: Vec<(usize, usize)>,
+    next_chunk: usize,
+    /// End of the last match per pattern, so overlap regions aren't matched twice
+    consumed_until: Vec<usize>,
+    matches: Vec<(usize, PatternMatch)>,
+}
+
+impl ChunkedScan {
+    pub fn new(text: &str) -> Self {
+        ChunkedScan {
+            text: text.to_string(),
+            chunks: sentence_chunks(text, TARGET_CHUNK_BYTES),
+            next_chunk: 0,
+            consumed_until: vec![0; COMPILED_PATTERNS.len()],
+            matches: Vec::new(),
+        }
+    }
+
+    /// Process a single chunk; returns true once all chunks are done
+    pub fn step_chunk(&mut self) -> bool {
+        if let Some(&(start, end)) = self.chunks.get(self.next_chunk) {
+            let window_end = floor_char_boundary(&self.text, end + OVERLAP_BYTES);
+            // Search the full prefix so word boundaries before `start` are seen correctly
+            let haystack = &self.text[..window_end];
+
+            for (index, pattern) in COMPILED_PATTERNS.iter().enumerate() {
+                let mut from = start.max(self.consumed_until[index]);
+                while from < end {
+                    let Some(cap) = pattern.regex.find_at(haystack, from) else {
+                        break;
+                    };
+                    if cap.start() >= end {
+                        break;
+                    }
+                    if passes_target_gate(pattern.pattern_type, haystack, cap.start()) {
+                        self.matches.push((
+                            index,
+                            PatternMatch {
+                                pattern_id: pattern.id.clone(),
+                                pattern_type: pattern.pattern_type.to_string(),
+                                match_text: cap.as_str().to_string(),
+                                position: cap.start(),
+                                severity: pattern.severity.to_string(),
+                                weight: pattern.weight,
+                            },
+                        ));
+                    }
+                    self.consumed_until[index] = cap.end();
+                    // Step past empty matches by one char to guarantee progress
+                    from = if cap.end() > cap.start() {
+                        cap.end()
+                    } else {
+                        cap.end() + haystack[cap.end()..].chars().next().map(char::len_utf8).unwrap_or(1)
+                    };
+                }
+            }
+            self.next_chunk += 1;
+        }
+        self.is_done()
+    }
+
+    /// Process chunks until the time budget is spent; returns true once all chunks are done
+    pub fn step(&mut self, budget_ms: f64) -> bool {
+        let started = now_ms();
+        // Always make progress, even with a zero budget
+        while !self.step_chunk() {
+            if now_ms() - started >= budget_ms {
+                break;
+            }
+        }
+        self.is_done()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next_chunk >= self.chunks.len()
+    }
+
+    /// Fraction of text processed so far (0.0 - 1.0)
+    pub fn progress(&self) -> f64 {
+        if self.chunks.is_empty() {
+            return 1.0;
+        }
+        self.next_chunk as f64 / self.chunks.len() as f64
+    }
+
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Text covered by the chunks processed so far
+    pub fn processed_text(&self) -> &str {
+        let end = self.next_chunk.checked_sub(1).map(|last| self.chunks[last].1).unwrap_or(0);
+        &self.text[..end]
+    }
+
+    /// Matches in the same order a full `match_patterns` run would produce
+    pub fn matches(&self) -> Vec<PatternMatch> {
+        let mut ordered: Vec<PatternMatch> = self.matches.iter().map(|(_, m)| m.clone()).collect();
+        ordered.extend(match_coded_terms(self.processed_text()));
+        sort_matches(&mut ordered);
+        ordered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::pattern_matching::match_patterns;
+
+    #[test]
+    fn test_sentence_chunks_cover_text() {
+        let text = "One. Two two. Three three three. Four.";
+        let chunks = sentence_chunks(text, 10);
+        assert_eq!(chunks.first().unwrap().0, 0);
+        assert_eq!(chunks.last().unwrap().1, text.len());
+        assert!(chunks.windows(2).all(|w| w[0].1 == w[1].0));
+        assert!(chunks.iter().all(|&(_, end)| end == text.len() || text[..end].ends_with('.')));
+    }
+
+    #[test]
+    fn test_chunked_scan_matches_full_run() {
+        let sentence = "You're so selfish and it's all your fault. Leave me alone, I don't want to talk. ";
+        let text = sentence.repeat(120);
+        let mut scan = ChunkedScan::new(&text);
+        while !scan.step(0.0) {}
+
+        let key = |m: &PatternMatch| (m.pattern_type.clone(), m.position, m.match_text.clone());
+        let chunked: Vec<_> = scan.matches().iter().map(key).collect();
+        let full: Vec<_> = match_patterns(&text).iter().map(key).collect();
+        assert!(scan.chunks.len() > 1);
+        assert_eq!(chunked, full);
+    }
+}
@@ -0,0 +1,142 @@
+//! JSON Schema export for result types
+//! Generated from the serde structs so non-TS consumers can validate and codegen against the output
+
+use std::collections::BTreeMap;
+
+use schemars::schema::RootSchema;
+use schemars::{schema_for, JsonSchema};
+use serde::Serialize;
+
+use super::analytics_export::AnalyticsExport;
+use super::apology::Apology;
+use super::authoring::{PatternLint, PatternTestResult};
+use super::baseline::{BaselineComparison, CorpusBaseline};
+use super::bias_audit::BiasAuditReport;
+use super::chat::ChatAnalysis;
+use super::contacts::KnownContact;
+use super::digest::{EntryDigest, WeeklyDigest};
+use super::dry_run::DryRunResult;
+use super::entity_extraction::EntityExtractionResult;
+use super::entity_sentiment::EntitySentimentTrend;
+use super::findings::FindingsSummary;
+use super::formality::FormalityResult;
+use super::harm_report::HarmReport;
+use super::heatmap::Heatmap;
+use super::i18n::LabelTable;
+use super::indices::WordIndex;
+use super::intensity::IntensityResult;
+use super::intentions::Intention;
+use super::keywords::{KeywordExtraction, RankedKeyword};
+use super::lexicon::LexiconCounts;
+use super::memory::MemoryStats;
+use super::nvc::NvcAnalysis;
+use super::pii::PiiMatch;
+use super::politeness::PolitenessResult;
+use super::quotes::QuoteExtractionResult;
+use super::reconcile::ReconciledSpan;
+use super::rumination::RuminationResult;
+use super::similarity::DuplicateMatch;
+use super::standoff::StandoffDocument;
+use super::subtitles::SubtitleAnalysis;
+use super::summarize::Summary;
+use super::temporal::TemporalOrientation;
+use super::topics::TopicScore;
+use super::toxicity::ToxicityResult;
+use super::trajectory::Trajectory;
+use super::worker::{WorkerRequest, WorkerResponse};
+use super::{StreamingResult, TextProcessingResult};
+
+/// A root result as emitted by the versioned exports, with its `schemaVersion` stamp
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct VersionedResult<T> {
+    #[serde(flatten)]
+    result: T,
+    schema_version: u32,
+}
+
+/// Schema for a root result that the exports stamp with `schemaVersion`, titled after the result
+fn versioned_schema<T: JsonSchema>(name: &str) -> RootSchema {
+    let mut schema = schema_for!(VersionedResult<T>);
+    schema.schema.metadata().title = Some(name.to_string());
+    schema
+}
+
+/// JSON Schemas for every result type, keyed by type name
+pub fn result_schemas() -> BTreeMap<&'static str, RootSchema> {
+    let mut schemas = BTreeMap::new();
+    schemas.insert("TextProcessingResult", versioned_schema::<TextProcessingResult>("TextProcessingResult"));
+    schemas.insert("StreamingResult", schema_for!(StreamingResult));
+    schemas.insert("CorpusBaseline", schema_for!(CorpusBaseline));
+    schemas.insert("DuplicateMatch", schema_for!(DuplicateMatch));
+    schemas.insert("EntityExtractionResult", versioned_schema::<EntityExtractionResult>("EntityExtractionResult"));
+    schemas.insert("KnownContact", schema_for!(KnownContact));
+    schemas.insert("FormalityResult", schema_for!(FormalityResult));
+    schemas.insert("HarmReport", schema_for!(HarmReport));
+    schemas.insert("AnalyticsExport", schema_for!(AnalyticsExport));
+    schemas.insert("EntitySentimentTrend", schema_for!(EntitySentimentTrend));
+    schemas.insert("EntryDigest", schema_for!(EntryDigest));
+    schemas.insert("WeeklyDigest", schema_for!(WeeklyDigest));
+    schemas.insert("Trajectory", schema_for!(Trajectory));
+    schemas.insert("Heatmap", schema_for!(Heatmap));
+    schemas.insert("FindingsSummary", schema_for!(FindingsSummary));
+    schemas.insert("LabelTable", schema_for!(LabelTable));
+    schemas.insert("IntensityResult", schema_for!(IntensityResult));
+    schemas.insert("Intention", schema_for!(Intention));
+    schemas.insert("RankedKeyword", schema_for!(RankedKeyword));
+    schemas.insert("KeywordExtraction", schema_for!(KeywordExtraction));
+    schemas.insert("LexiconCounts", schema_for!(LexiconCounts));
+    schemas.insert("MemoryStats", schema_for!(MemoryStats));
+    schemas.insert("Apology", schema_for!(Apology));
+    schemas.insert("BiasAuditReport", schema_for!(BiasAuditReport));
+    schemas.insert("BaselineComparison", schema_for!(BaselineComparison));
+    schemas.insert("ChatAnalysis", schema_for!(ChatAnalysis));
+    schemas.insert("NvcAnalysis", schema_for!(NvcAnalysis));
+    schemas.insert("PatternTestResult", schema_for!(PatternTestResult));
+    schemas.insert("PatternLint", schema_for!(PatternLint));
+    schemas.insert("DryRunResult", schema_for!(DryRunResult));
+    schemas.insert("PiiMatch", schema_for!(PiiMatch));
+    schemas.insert("PolitenessResult", schema_for!(PolitenessResult));
+    schemas.insert("QuoteExtractionResult", schema_for!(QuoteExtractionResult));
+    schemas.insert("ReconciledSpan", schema_for!(ReconciledSpan));
+    schemas.insert("RuminationResult", schema_for!(RuminationResult));
+    schemas.insert("StandoffDocument", schema_for!(StandoffDocument));
+    schemas.insert("SubtitleAnalysis", schema_for!(SubtitleAnalysis));
+    schemas.insert("Summary", schema_for!(Summary));
+    schemas.insert("TemporalOrientation", schema_for!(TemporalOrientation));
+    schemas.insert("TopicScore", schema_for!(TopicScore));
+    schemas.insert("ToxicityResult", schema_for!(ToxicityResult));
+    schemas.insert("WordIndex", schema_for!(WordIndex));
+    schemas.insert("WorkerRequest", schema_for!(WorkerRequest));
+    schemas.insert("WorkerResponse", schema_for!(WorkerResponse));
+    schemas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schemas_use_serialized_field_names() {
+        let schemas = result_schemas();
+        let json = serde_json::to_value(&schemas["TextProcessingResult"]).unwrap();
+        assert!(json["properties"]["patterns"].is_object());
+        assert!(json["definitions"]["PatternMatchResult"]["properties"]["patternType"].is_object());
+
+        let entities = serde_json::to_value(&schemas["EntityExtractionResult"]).unwrap();
+        assert!(entities["properties"]["relationshipCount"].is_object());
+    }
+
+    #[test]
+    fn test_versioned_roots_declare_schema_version() {
+        let schemas = result_schemas();
+        for name in ["TextProcessingResult", "EntityExtractionResult"] {
+            let json = serde_json::to_value(&schemas[name]).unwrap();
+            assert_eq!(json["title"], name);
+            assert!(json["properties"]["schemaVersion"].is_object());
+            assert!(json["required"].as_array().unwrap().contains(&"schemaVersion".into()));
+        }
+        let formality = serde_json::to_value(&schemas["FormalityResult"]).unwrap();
+        assert!(formality["properties"]["schemaVersion"].is_null());
+    }
+}
@@ -1,45 +1,382 @@
-/// Text scoring algorithms
+//! Text scoring algorithms
 
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Deserialize;
+
+use super::hypothetical::{hypothetical_scopes, scope_at};
+use super::locale::Locale;
+use super::offsets::{sentence_bounds, sentence_ranges};
 use super::pattern_matching::PatternMatch;
+use super::person::match_person;
+use super::questions::in_question;
+use super::quotes::extract_quotes;
+use super::tokenize::tokenize;
 
-/// Calculate overall text score from pattern matches
-pub fn calculate_text_score(matches: &[PatternMatch]) -> f64 {
-    if matches.is_empty() {
+/// Weighting of matches by the sentence they fall in, ramping linearly from the
+/// opening sentence to the closing one (sign-off threats and ultimatums weigh more)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PositionalWeighting {
+    /// Multiplier for matches in the opening sentence
+    pub start: f64,
+    /// Multiplier for matches in the closing sentence (and in single-sentence texts)
+    pub end: f64,
+}
+
+impl Default for PositionalWeighting {
+    fn default() -> Self {
+        PositionalWeighting { start: 1.0, end: 1.5 }
+    }
+}
+
+impl PositionalWeighting {
+    /// Multiplier per match, in match order
+    fn multipliers(&self, text: &str, matches: &[PatternMatch]) -> Vec<f64> {
+        let sentences = sentence_ranges(text);
+        let last = sentences.len().saturating_sub(1);
+        matches
+            .iter()
+            .map(|m| {
+                let index = sentences.iter().position(|&(_, end)| m.position < end).unwrap_or(last);
+                let t = if last == 0 { 1.0 } else { index as f64 / last as f64 };
+                (self.start + (self.end - self.start) * t).max(0.0)
+            })
+            .collect()
+    }
+}
+
+/// Scoring options (all optional on the JS side)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScoringConfig {
+    /// Multiplier applied to match weights by severity; unlisted severities use 1
+    pub severity_multipliers: BTreeMap<String, f64>,
+    /// Per-pattern-type overrides of the severity multipliers, e.g. `{"militarization": {"medium": 0.5}}`
+    pub pattern_severity_multipliers: BTreeMap<String, BTreeMap<String, f64>>,
+    /// Optional weighting by position in the text; off by default
+    pub positional_weighting: Option<PositionalWeighting>,
+    /// Report the score's percentile among everyday reference messages
+    pub report_percentile: bool,
+    /// Regional English variant whose spellings and idioms are matched too ("en-GB", "en-AU", "en-IN")
+    pub locale: Locale,
+    /// Ignore matches that only exist because of vernacular grammar or reclaimed in-group terms; on by default
+    pub dialect_normalization: bool,
+    /// Multiplier by grammatical person of the match ("first", "second", "third"), e.g. `{"third": 0.5}`
+    /// to count reports about other people less than direct accusations; unlisted persons use 1
+    pub person_multipliers: BTreeMap<String, f64>,
+    /// Report a score per sentence and the matches in each sentence
+    pub sentence_breakdown: bool,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        ScoringConfig {
+            severity_multipliers: [("high", 1.0), ("medium", 1.0), ("low", 1.0)]
+                .into_iter()
+                .map(|(severity, multiplier)| (severity.to_string(), multiplier))
+                .collect(),
+            pattern_severity_multipliers: BTreeMap::new(),
+            positional_weighting: None,
+            report_percentile: false,
+            locale: Locale::default(),
+            dialect_normalization: true,
+            person_multipliers: BTreeMap::new(),
+            sentence_breakdown: false,
+        }
+    }
+}
+
+impl ScoringConfig {
+    /// Weight multiplier for a match (never negative)
+    pub fn multiplier(&self, m: &PatternMatch) -> f64 {
+        self.pattern_severity_multipliers
+            .get(&m.pattern_type)
+            .and_then(|overrides| overrides.get(&m.severity))
+            .or_else(|| self.severity_multipliers.get(&m.severity))
+            .copied()
+            .unwrap_or(1.0)
+            .max(0.0)
+    }
+}
+
+/// Share of a full match contributed by the n-th (1-based) occurrence of a pattern type;
+/// n occurrences add up to 1 + ln(n), so one repeated insult can't drown out other signal
+fn repeat_decay(occurrence: usize) -> f64 {
+    if occurrence <= 1 {
+        1.0
+    } else {
+        (occurrence as f64 / (occurrence - 1) as f64).ln()
+    }
+}
+
+/// Normalize (pattern type, weight, presence) triples into a 0 - 1 score; presence scales
+/// how much of a match counts at all (1 = fully present), affecting both weight and match count
+fn score_weights(weighted: Vec<(&str, f64, f64)>) -> f64 {
+    if weighted.is_empty() {
         return 0.0;
     }
 
-    // Sum of weighted matches
-    let total_weight: f64 = matches.iter().map(|m| m.weight).sum();
-    
+    // Repeats of a pattern type are discounted, strongest occurrences counted first
+    let mut by_type: BTreeMap<&str, Vec<(f64, f64)>> = BTreeMap::new();
+    for (pattern_type, weight, presence) in weighted {
+        by_type.entry(pattern_type).or_default().push((weight * presence, presence));
+    }
+    let mut total_weight = 0.0;
+    let mut match_count = 0.0;
+    for occurrences in by_type.values_mut() {
+        occurrences.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (index, (weight, presence)) in occurrences.iter().enumerate() {
+            let decay = repeat_decay(index + 1);
+            total_weight += weight * decay;
+            match_count += presence * decay;
+        }
+    }
+
     // Normalize by number of matches (more matches = higher confidence)
-    let match_count = matches.len() as f64;
     let normalized_score = total_weight / (1.0 + match_count * 0.1);
 
     // Cap at 1.0
     normalized_score.min(1.0)
 }
 
+/// Calculate overall text score from pattern matches
+pub fn calculate_text_score(matches: &[PatternMatch]) -> f64 {
+    score_weights(matches.iter().map(|m| (m.pattern_type.as_str(), m.weight, 1.0)).collect())
+}
+
+/// Score (pattern type, weight) pairs of already-adjusted matches, e.g. the matches of one
+/// sentence or paragraph of an analyzed text
+pub fn score_weighted_types<'a>(weighted: impl Iterator<Item = (&'a str, f64)>) -> f64 {
+    score_weights(weighted.map(|(pattern_type, weight)| (pattern_type, weight, 1.0)).collect())
+}
+
+/// Calculate the text score with configured severity, positional and person multipliers applied to match weights
+pub fn calculate_configured_score(text: &str, matches: &[PatternMatch], config: &ScoringConfig) -> f64 {
+    let positional = match &config.positional_weighting {
+        Some(weighting) => weighting.multipliers(text, matches),
+        None => vec![1.0; matches.len()],
+    };
+    let person = |m: &PatternMatch| {
+        if config.person_multipliers.is_empty() {
+            return 1.0;
+        }
+        match_person(text, m.position, m.match_text.len())
+            .and_then(|person| config.person_multipliers.get(person))
+            .map_or(1.0, |multiplier| multiplier.max(0.0))
+    };
+    score_weights(
+        matches
+            .iter()
+            .zip(positional)
+            .map(|(m, position)| (m.pattern_type.as_str(), m.weight * config.multiplier(m) * position * person(m), 1.0))
+            .collect(),
+    )
+}
+
+/// Calculate the text score with each match only partially present (0 - 1, e.g. decayed with age)
+pub fn calculate_decayed_score(matches: &[PatternMatch], presence: &[f64]) -> f64 {
+    score_weights(matches.iter().zip(presence).map(|(m, p)| (m.pattern_type.as_str(), m.weight, p.clamp(0.0, 1.0))).collect())
+}
+
+/// Words of text at which length stops limiting confidence
+const CONFIDENT_WORD_COUNT: f64 = 12.0;
+
+/// Words before a match checked for negation
+const NEGATION_WINDOW: usize = 3;
+
+/// Negators that flip or soften a following match ("not that you're lazy")
+const NEGATORS: &[&str] = &[
+    "aren't", "can't", "couldn't", "didn't", "doesn't", "don't", "hardly", "isn't", "never", "no", "not", "shouldn't",
+    "wasn't", "weren't", "won't", "wouldn't",
+];
+
+/// Per-match confidence multipliers for context
+const NEGATED_CONFIDENCE: f64 = 0.5;
+const QUOTED_CONFIDENCE: f64 = 0.6;
+const QUESTION_CONFIDENCE: f64 = 0.8;
+const HYPOTHETICAL_CONFIDENCE: f64 = 0.6;
+
+/// Context-free specificity of a match (0 - 1): stronger patterns and longer matches are less likely incidental
+pub fn match_specificity(m: &PatternMatch) -> f64 {
+    0.5 * m.weight.clamp(0.0, 1.0) + 0.5 * (m.match_text.split_whitespace().count() as f64 / 3.0).min(1.0)
+}
+
+/// Context around a match that makes it less likely to be the author's own claim
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MatchContext {
+    pub negated: bool,
+    pub quoted: bool,
+    pub question: bool,
+    /// "conditional" or "hypothetical" when the match sits in such a scope
+    pub scope: Option<&'static str>,
+}
+
+/// Negation, quoted-speech, genuine-question and conditional/hypothetical context per match
+pub fn match_contexts(text: &str, matches: &[PatternMatch]) -> Vec<MatchContext> {
+    let quotes = extract_quotes(text);
+    let scopes = hypothetical_scopes(text);
+    matches
+        .iter()
+        .map(|m| {
+            let (sentence_start, _) = sentence_bounds(text, m.position);
+            let preceding = tokenize(&text[sentence_start..m.position]);
+            MatchContext {
+                negated: preceding
+                    .iter()
+                    .rev()
+                    .take(NEGATION_WINDOW)
+                    .any(|t| NEGATORS.binary_search(&t.lower().replace('\u{2019}', "'").as_str()).is_ok()),
+                quoted: quotes.iter().any(|q| m.position >= q.offset && m.position < q.end),
+                question: in_question(text, m.position),
+                scope: scope_at(&scopes, m.position),
+            }
+        })
+        .collect()
+}
+
+/// Per-match confidence: specificity, discounted when the match is negated,
+/// inside quoted speech, part of a question or in a conditional/hypothetical scope
+pub fn match_confidences(text: &str, matches: &[PatternMatch]) -> Vec<f64> {
+    matches
+        .iter()
+        .zip(match_contexts(text, matches))
+        .map(|(m, context)| {
+            let mut confidence = match_specificity(m);
+            if context.negated {
+                confidence *= NEGATED_CONFIDENCE;
+            }
+            if context.quoted {
+                confidence *= QUOTED_CONFIDENCE;
+            }
+            if context.question {
+                confidence *= QUESTION_CONFIDENCE;
+            }
+            if context.scope.is_some() {
+                confidence *= HYPOTHETICAL_CONFIDENCE;
+            }
+            confidence
+        })
+        .collect()
+}
+
+/// Confidence in a detection from the quality of its evidence, independent of the score:
+/// several independent pattern types, specific multi-word matches and enough surrounding
+/// text raise it; one weak hit on a two-word text keeps it low
+pub fn calculate_confidence(matches: &[PatternMatch], word_count: usize) -> f64 {
+    if matches.is_empty() {
+        return 0.0;
+    }
+
+    let pattern_types: BTreeSet<&str> = matches.iter().map(|m| m.pattern_type.as_str()).collect();
+    let diversity = 1.0 - 0.5f64.powi(pattern_types.len() as i32);
+
+    let specificity = matches.iter().map(match_specificity).sum::<f64>() / matches.len() as f64;
+
+    let length = (word_count as f64 / CONFIDENT_WORD_COUNT).min(1.0).sqrt();
+
+    ((0.4 * diversity + 0.6 * specificity) * (0.5 + 0.5 * length)).min(1.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use super::super::pattern_matching::PatternMatch;
 
+    fn pattern_match(pattern_type: &str, match_text: &str, position: usize, severity: &str, weight: f64) -> PatternMatch {
+        PatternMatch {
+            pattern_id: String::new(),
+            pattern_type: pattern_type.to_string(),
+            match_text: match_text.to_string(),
+            position,
+            severity: severity.to_string(),
+            weight,
+        }
+    }
+
     #[test]
     fn test_calculate_text_score() {
-        let matches = vec![
-            PatternMatch {
-                pattern_type: "character_judgment".to_string(),
-                match_text: "You're lazy".to_string(),
-                position: 0,
-                severity: "high".to_string(),
-                weight: 1.0,
-            },
-        ];
+        let matches = vec![pattern_match("character_judgment", "You're lazy", 0, "high", 1.0)];
         let score = calculate_text_score(&matches);
         assert!(score > 0.0);
         assert!(score <= 1.0);
     }
 
+    #[test]
+    fn test_severity_multipliers() {
+        let matches = vec![pattern_match("militarization", "war on", 0, "medium", 0.8)];
+        let default = ScoringConfig::default();
+        assert_eq!(calculate_configured_score("war on", &matches, &default), calculate_text_score(&matches));
+
+        let config: ScoringConfig =
+            serde_json::from_str(r#"{"patternSeverityMultipliers": {"militarization": {"medium": 0.5}}}"#).unwrap();
+        assert_eq!(config.multiplier(&matches[0]), 0.5);
+        assert!(calculate_configured_score("war on", &matches, &config) < calculate_text_score(&matches));
+    }
+
+    #[test]
+    fn test_repeats_have_diminishing_returns() {
+        let repeated: Vec<PatternMatch> = (0..40).map(|position| pattern_match("insult", "idiot", position, "high", 0.05)).collect();
+        assert!((repeat_decay(1) + repeat_decay(2) + repeat_decay(3) - (1.0 + 3f64.ln())).abs() < 1e-9);
+        // 40 repeats weigh as much as 1 + ln(40) ≈ 4.7 distinct matches, not 40
+        let expected = 0.05 * (1.0 + 40f64.ln()) / (1.0 + 0.1 * (1.0 + 40f64.ln()));
+        assert!((calculate_text_score(&repeated) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_positional_weighting() {
+        let text = "Calm down. We can talk later. Calm down.";
+        let matches: Vec<PatternMatch> =
+            [0, 30].iter().map(|&position| pattern_match("tone_policing", "Calm down", position, "high", 0.4)).collect();
+        let weighting = PositionalWeighting { start: 0.5, end: 2.0 };
+        assert_eq!(weighting.multipliers(text, &matches), vec![0.5, 2.0]);
+
+        let config: ScoringConfig = serde_json::from_str(r#"{"positionalWeighting": {}}"#).unwrap();
+        let closing = calculate_configured_score(text, &matches[1..], &config);
+        let opening = calculate_configured_score(text, &matches[..1], &config);
+        assert!(closing > opening);
+    }
+
+    #[test]
+    fn test_confidence_reflects_evidence() {
+        let hit = |pattern_type: &str, match_text: &str, weight| pattern_match(pattern_type, match_text, 0, "high", weight);
+        let weak = vec![hit("insult", "idiot", 0.9)];
+        let strong = vec![
+            hit("character_judgment", "you're so selfish", 1.0),
+            hit("displacement", "it's all your fault", 0.9),
+            hit("absolute_statement", "you always ignore me", 0.9),
+        ];
+        let weak_confidence = calculate_confidence(&weak, 2);
+        assert!(weak_confidence < 0.5);
+        assert!(weak_confidence < calculate_text_score(&weak));
+        assert!(calculate_confidence(&strong, 30) > 0.85);
+        assert_eq!(calculate_confidence(&[], 30), 0.0);
+    }
+
+    #[test]
+    fn test_match_confidence_context() {
+        let text = "You're so selfish. It's not that you're so selfish. Are you so selfish? She said \"you're so selfish\".";
+        let matches: Vec<PatternMatch> = ["You're so selfish", "you're so selfish", "you so selfish"]
+            .iter()
+            .flat_map(|needle| text.match_indices(needle))
+            .map(|(position, match_text)| pattern_match("character_judgment", match_text, position, "high", 1.0))
+            .collect();
+        assert!(NEGATORS.windows(2).all(|w| w[0] < w[1]));
+        let [plain, negated, quoted, question] = match_confidences(text, &matches)[..] else { panic!("expected four matches") };
+        assert_eq!(plain, 1.0);
+        assert_eq!(negated, NEGATED_CONFIDENCE);
+        assert_eq!(quoted, QUOTED_CONFIDENCE);
+        assert_eq!(question, QUESTION_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_person_multipliers() {
+        let config: ScoringConfig = serde_json::from_str(r#"{"personMultipliers":{"third":0.0}}"#).unwrap();
+        let score = |text: &str| calculate_configured_score(text, &super::super::pattern_matching::match_patterns(text), &config);
+        assert_eq!(score("He is such an idiot"), 0.0);
+        assert!(score("You are such an idiot") > 0.0);
+    }
+
     #[test]
     fn test_calculate_empty_score() {
         let matches: Vec<PatternMatch> = vec![];
@@ -0,0 +1,194 @@
+//! Text similarity and near-duplicate detection
+//! Cosine similarity over words or character trigrams, word shingles compared with Jaccard
+//! inside a batch, and SimHash fingerprints hosts can store to recognise already-processed text
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::tokenize::{content_words, tokenize};
+
+/// Words per shingle
+const SHINGLE_WORDS: usize = 3;
+
+/// Default Jaccard similarity at which texts count as near-duplicates
+pub const DEFAULT_DUPLICATE_THRESHOLD: f64 = 0.8;
+
+/// Characters per n-gram for character similarity
+const CHAR_NGRAM: usize = 3;
+
+/// What texts are compared on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimilarityMethod {
+    /// Content words (topical similarity, ignores function words and word order)
+    Token,
+    /// Character trigrams (robust to typos and inflections)
+    Char,
+}
+
+impl SimilarityMethod {
+    /// Parse a method name, defaulting to token similarity
+    pub fn parse(name: Option<&str>) -> Result<Self, String> {
+        match name.map(str::to_lowercase).as_deref() {
+            None | Some("token") | Some("word") => Ok(SimilarityMethod::Token),
+            Some("char") | Some("character") => Ok(SimilarityMethod::Char),
+            Some(other) => Err(format!("unsupported similarity method '{}'", other)),
+        }
+    }
+}
+
+/// Duplicate check result for one text of a batch
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateMatch {
+    pub index: usize,
+    /// Earliest earlier text this one duplicates
+    pub duplicate_of: Option<usize>,
+    /// Jaccard similarity to that text (0 when unique)
+    pub similarity: f64,
+    /// SimHash fingerprint (16 hex digits)
+    pub fingerprint: String,
+}
+
+/// 64-bit FNV-1a, stable across platforms and releases (unlike `DefaultHasher`)
+pub fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Normalized word shingles (case and punctuation insensitive)
+pub fn shingles(text: &str) -> BTreeSet<String> {
+    let words: Vec<String> = tokenize(text).iter().map(|t| t.lower()).collect();
+    if words.len() < SHINGLE_WORDS {
+        return if words.is_empty() { BTreeSet::new() } else { BTreeSet::from([words.join(" ")]) };
+    }
+    words.windows(SHINGLE_WORDS).map(|w| w.join(" ")).collect()
+}
+
+/// Jaccard similarity of two sets (1 when both are empty)
+pub fn jaccard(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// SimHash of the text's shingles
+pub fn simhash(text: &str) -> u64 {
+    let mut counts = [0i64; 64];
+    for shingle in shingles(text) {
+        let hash = fnv1a(&shingle);
+        for (bit, count) in counts.iter_mut().enumerate() {
+            *count += if (hash >> bit) & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    counts.iter().enumerate().filter(|(_, c)| **c > 0).fold(0, |acc, (bit, _)| acc | 1 << bit)
+}
+
+/// Hex form of a fingerprint
+pub fn fingerprint(text: &str) -> String {
+    format!("{:016x}", simhash(text))
+}
+
+/// Differing bits between two hex fingerprints (0 - 64; small means near-duplicate)
+pub fn fingerprint_distance(a: &str, b: &str) -> Result<u32, String> {
+    let parse = |hex: &str| u64::from_str_radix(hex, 16).map_err(|_| format!("invalid fingerprint '{}'", hex));
+    Ok((parse(a)? ^ parse(b)?).count_ones())
+}
+
+/// Term frequencies for the chosen method
+fn features(text: &str, method: SimilarityMethod) -> BTreeMap<String, f64> {
+    let terms: Vec<String> = match method {
+        SimilarityMethod::Token => content_words(text),
+        SimilarityMethod::Char => {
+            // Lowercased words joined by single spaces, padded so word edges form n-grams
+            let normalized: Vec<char> =
+                format!(" {} ", tokenize(text).iter().map(|t| t.lower()).collect::<Vec<_>>().join(" ")).chars().collect();
+            normalized.windows(CHAR_NGRAM).map(|w| w.iter().collect()).collect()
+        }
+    };
+    let mut counts = BTreeMap::new();
+    for term in terms {
+        *counts.entry(term).or_insert(0.0) += 1.0;
+    }
+    counts
+}
+
+/// Cosine similarity of two texts (0 - 1; 0 when either has no features)
+pub fn text_similarity(a: &str, b: &str, method: SimilarityMethod) -> f64 {
+    let (a, b) = (features(a, method), features(b, method));
+    let dot: f64 = a.iter().filter_map(|(term, x)| b.get(term).map(|y| x * y)).sum();
+    let norm = |v: &BTreeMap<String, f64>| v.values().map(|x| x * x).sum::<f64>().sqrt();
+    let denominator = norm(&a) * norm(&b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        (dot / denominator).min(1.0)
+    }
+}
+
+/// Flag texts that duplicate an earlier text in the batch
+pub fn find_duplicates(texts: &[String], threshold: f64) -> Vec<DuplicateMatch> {
+    let sets: Vec<BTreeSet<String>> = texts.iter().map(|t| shingles(t)).collect();
+
+    texts
+        .iter()
+        .enumerate()
+        .map(|(index, text)| {
+            let best = (0..index)
+                .map(|earlier| (earlier, jaccard(&sets[index], &sets[earlier])))
+                .filter(|(_, similarity)| *similarity >= threshold)
+                .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)));
+            DuplicateMatch {
+                index,
+                duplicate_of: best.map(|(earlier, _)| earlier),
+                similarity: best.map(|(_, similarity)| similarity).unwrap_or(0.0),
+                fingerprint: fingerprint(text),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minor_edits_are_duplicates() {
+        let texts = vec![
+            "Today my boss yelled at me in front of the whole team and I felt humiliated.".to_string(),
+            "We went to the beach and built a sandcastle.".to_string(),
+            "today my boss yelled at me in front of the whole team, and I felt humiliated!!".to_string(),
+        ];
+        let results = find_duplicates(&texts, DEFAULT_DUPLICATE_THRESHOLD);
+        assert_eq!(results[0].duplicate_of, None);
+        assert_eq!(results[1].duplicate_of, None);
+        assert_eq!(results[2].duplicate_of, Some(0));
+        assert_eq!(results[2].similarity, 1.0);
+    }
+
+    #[test]
+    fn test_text_similarity_methods() {
+        let a = "My manager keeps moving the deadline";
+        let b = "The deadline was moved again by my manager";
+        let c = "We baked cookies with grandma";
+        let related = text_similarity(a, b, SimilarityMethod::Token);
+        assert!(related > text_similarity(a, c, SimilarityMethod::Token));
+        assert_eq!(text_similarity(a, a, SimilarityMethod::Char), 1.0);
+        assert!(text_similarity("stressed", "stresed", SimilarityMethod::Char) > 0.5);
+        assert_eq!(text_similarity("", a, SimilarityMethod::Token), 0.0);
+        assert_eq!(SimilarityMethod::parse(Some("CHAR")), Ok(SimilarityMethod::Char));
+        assert!(SimilarityMethod::parse(Some("bert")).is_err());
+    }
+
+    #[test]
+    fn test_fingerprints() {
+        let a = fingerprint("Today my boss yelled at me in front of the whole team and I felt humiliated");
+        let b = fingerprint("Today my boss yelled at me in front of the whole team and I felt so humiliated");
+        let c = fingerprint("We went to the beach and built a sandcastle with the kids all afternoon");
+        assert_eq!(a.len(), 16);
+        assert!(fingerprint_distance(&a, &b).unwrap() < fingerprint_distance(&a, &c).unwrap());
+        assert!(fingerprint_distance(&a, "zz").is_err());
+    }
+}
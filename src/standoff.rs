@@ -0,0 +1,129 @@
+//! Standoff annotation output
+//! Leaves the document text untouched and describes matches as separate span records
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::entity_extraction::extract_entities;
+use super::offsets::CharOffsets;
+use super::pattern_matching::match_patterns;
+
+/// Single annotated span (character offsets, end exclusive)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StandoffSpan {
+    pub id: String,
+    pub label: String,
+    pub layer: String,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub attributes: BTreeMap<String, Value>,
+}
+
+/// Document text plus its standoff spans
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StandoffDocument {
+    pub text: String,
+    pub spans: Vec<StandoffSpan>,
+}
+
+/// Build standoff annotations for pattern matches and people entities
+pub fn build_standoff(text: &str) -> StandoffDocument {
+    // Spans are collected with byte offsets, then sorted and converted to char offsets
+    let mut spans: Vec<StandoffSpan> = Vec::new();
+    let mut push_span = |start: usize, end: usize, label: String, layer: &str, attributes| {
+        spans.push(StandoffSpan {
+            id: String::new(),
+            label,
+            layer: layer.to_string(),
+            start,
+            end,
+            text: text[start..end].to_string(),
+            attributes,
+        });
+    };
+
+    for m in match_patterns(text) {
+        let mut attributes = BTreeMap::new();
+        attributes.insert("severity".to_string(), Value::from(m.severity));
+        attributes.insert("weight".to_string(), Value::from(m.weight));
+        push_span(m.position, m.position + m.match_text.len(), m.pattern_type, "pattern", attributes);
+    }
+
+    for entity in extract_entities(text).entities {
+        // Entity mentions start at `position` and cover their relationship context
+        let end = entity.position + entity.relationship_context.len();
+        if end > text.len() || !text.is_char_boundary(end) {
+            continue;
+        }
+        let mut attributes = BTreeMap::new();
+        attributes.insert("name".to_string(), Value::from(entity.name));
+        if let Some(relationship) = entity.relationship_hint {
+            attributes.insert("relationship".to_string(), Value::from(relationship));
+        }
+        attributes.insert("confidence".to_string(), Value::from(entity.confidence));
+        push_span(entity.position, end, "person".to_string(), "entity", attributes);
+    }
+
+    spans.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+    let offsets = CharOffsets::new(text);
+    for (index, span) in spans.iter_mut().enumerate() {
+        span.id = format!("T{}", index + 1);
+        span.start = offsets.char_offset(span.start);
+        span.end = offsets.char_offset(span.end);
+    }
+
+    StandoffDocument { text: text.to_string(), spans }
+}
+
+/// Render spans in brat standoff (.ann) format
+pub fn to_brat(document: &StandoffDocument) -> String {
+    let mut out = String::new();
+    let mut attribute_id = 0;
+
+    for span in &document.spans {
+        // brat text-bound annotations can't contain newlines in the text column
+        let text = span.text.replace(['\n', '\r'], " ");
+        out.push_str(&format!("{}\t{} {} {}\t{}\n", span.id, span.label, span.start, span.end, text));
+
+        for (key, value) in &span.attributes {
+            attribute_id += 1;
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            out.push_str(&format!("A{}\t{} {} {}\n", attribute_id, key, span.id, value));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standoff_uses_char_offsets() {
+        let text = "Café talk: it's all your fault";
+        let document = build_standoff(text);
+        let span = document.spans.iter().find(|s| s.label == "displacement").unwrap();
+        let covered: String = text.chars().skip(span.start).take(span.end - span.start).collect();
+        assert_eq!(covered, span.text);
+        assert_eq!(document.text, text);
+    }
+
+    #[test]
+    fn test_brat_output() {
+        let document = build_standoff("My husband John is so lazy");
+        let brat = to_brat(&document);
+        assert!(brat.lines().any(|l| l.starts_with("T") && l.contains("person")));
+        assert!(brat.lines().any(|l| l.starts_with("A") && l.contains("relationship")));
+    }
+}
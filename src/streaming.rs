@@ -0,0 +1,243 @@
+//! Streaming analysis for live text (e.g. transcription pipelines)
+//! Text arrives in arbitrary chunks; completed sentences are analyzed as they close
+
+use super::pattern_matching::{match_patterns, PatternMatch};
+use super::scoring::{calculate_confidence, calculate_decayed_score, calculate_text_score};
+use super::tokenize::tokenize;
+use super::DETECTION_THRESHOLD;
+
+/// Pending text is force-split at whitespace once it grows past this many bytes without a boundary
+const MAX_PENDING_BYTES: usize = 4096;
+
+/// Incremental output of a single push/finish
+#[derive(Debug, Clone)]
+pub struct StreamUpdate {
+    pub new_matches: Vec<PatternMatch>,
+    pub score: f64,
+    pub confidence: f64,
+    pub detected: bool,
+    pub processed_bytes: usize,
+}
+
+/// Separate on/off thresholds for the detected flag, so it doesn't flicker
+/// while live text hovers around a single threshold
+#[derive(Debug, Clone, Copy)]
+pub struct Hysteresis {
+    on: f64,
+    off: f64,
+    detected: bool,
+}
+
+impl Default for Hysteresis {
+    /// Single threshold, same as one-shot analysis
+    fn default() -> Self {
+        Hysteresis { on: DETECTION_THRESHOLD, off: DETECTION_THRESHOLD, detected: false }
+    }
+}
+
+impl Hysteresis {
+    /// Flag turns on above `on` and clears only at or below `off`
+    pub fn new(on: f64, off: f64) -> Result<Self, String> {
+        if off > on {
+            return Err(format!("off threshold {} is above on threshold {}", off, on));
+        }
+        Ok(Hysteresis { on, off, detected: false })
+    }
+
+    /// Feed the latest score and get the detected flag
+    pub fn update(&mut self, score: f64) -> bool {
+        self.detected = score > if self.detected { self.off } else { self.on };
+        self.detected
+    }
+}
+
+/// Exponential decay of old matches so the live score reflects recent content
+#[derive(Debug, Clone, Copy)]
+pub struct StreamDecay {
+    /// Bytes of later text after which a match counts half
+    pub half_life_bytes: usize,
+    /// Matches further back than this many bytes stop counting entirely
+    pub window_bytes: Option<usize>,
+}
+
+impl StreamDecay {
+    /// Share of a match still counted `age` bytes after it ended
+    fn presence(&self, age: usize) -> f64 {
+        if self.window_bytes.is_some_and(|window| age > window) {
+            return 0.0;
+        }
+        0.5f64.powf(age as f64 / self.half_life_bytes.max(1) as f64)
+    }
+}
+
+/// Streaming analyzer state
+#[derive(Debug, Default)]
+pub struct StreamState {
+    pending: String,
+    processed_bytes: usize,
+    /// Words in the analyzed text, for confidence
+    word_count: usize,
+    matches: Vec<PatternMatch>,
+    decay: Option<StreamDecay>,
+    hysteresis: Hysteresis,
+}
+
+/// Byte index just past the last sentence terminator in the text
+fn last_sentence_boundary(text: &str) -> Option<usize> {
+    text.rfind(['.', '!', '?', '\n']).map(|i| i + 1)
+}
+
+/// Byte index just past the last whitespace in the text
+fn last_whitespace_boundary(text: &str) -> Option<usize> {
+    text.rfind(char::is_whitespace)
+        .map(|i| i + text[i..].chars().next().map(char::len_utf8).unwrap_or(1))
+}
+
+impl StreamState {
+    pub fn new() -> Self {
+        StreamState::default()
+    }
+
+    /// Streaming state whose live score decays old matches
+    pub fn with_decay(decay: StreamDecay) -> Self {
+        StreamState { decay: Some(decay), ..StreamState::default() }
+    }
+
+    /// Use separate on/off thresholds for the detected flag from now on
+    pub fn set_hysteresis(&mut self, hysteresis: Hysteresis) {
+        self.hysteresis = hysteresis;
+    }
+
+    /// Append a chunk and analyze any sentences it completes
+    pub fn push(&mut self, chunk: &str) -> StreamUpdate {
+        self.pending.push_str(chunk);
+
+        let boundary = last_sentence_boundary(&self.pending).or_else(|| {
+            if self.pending.len() > MAX_PENDING_BYTES {
+                last_whitespace_boundary(&self.pending)
+            } else {
+                None
+            }
+        });
+
+        match boundary {
+            Some(end) => {
+                let segment: String = self.pending.drain(..end).collect();
+                self.analyze_segment(&segment)
+            }
+            None => self.update(Vec::new()),
+        }
+    }
+
+    /// Analyze whatever text is still pending (end of stream)
+    pub fn finish(&mut self) -> StreamUpdate {
+        let segment = std::mem::take(&mut self.pending);
+        self.analyze_segment(&segment)
+    }
+
+    /// All matches found so far, with positions relative to the whole stream
+    pub fn matches(&self) -> &[PatternMatch] {
+        &self.matches
+    }
+
+    /// Words analyzed so far
+    pub fn word_count(&self) -> usize {
+        self.word_count
+    }
+
+    fn analyze_segment(&mut self, segment: &str) -> StreamUpdate {
+        let base = self.processed_bytes;
+        let new_matches: Vec<PatternMatch> = match_patterns(segment)
+            .into_iter()
+            .map(|mut m| {
+                m.position += base;
+                m
+            })
+            .collect();
+
+        self.processed_bytes += segment.len();
+        self.word_count += tokenize(segment).len();
+        self.matches.extend(new_matches.iter().cloned());
+        self.update(new_matches)
+    }
+
+    /// Live score over all matches, decayed by age when configured
+    fn score(&self) -> f64 {
+        match self.decay {
+            Some(decay) => {
+                let presence: Vec<f64> = self
+                    .matches
+                    .iter()
+                    .map(|m| decay.presence(self.processed_bytes.saturating_sub(m.position + m.match_text.len())))
+                    .collect();
+                calculate_decayed_score(&self.matches, &presence)
+            }
+            None => calculate_text_score(&self.matches),
+        }
+    }
+
+    fn update(&mut self, new_matches: Vec<PatternMatch>) -> StreamUpdate {
+        let score = self.score();
+        StreamUpdate {
+            new_matches,
+            score,
+            confidence: calculate_confidence(&self.matches, self.word_count),
+            detected: self.hysteresis.update(score),
+            processed_bytes: self.processed_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_waits_for_sentence_end() {
+        let mut state = StreamState::new();
+        let first = state.push("You're so sel");
+        assert!(first.new_matches.is_empty());
+        assert_eq!(first.processed_bytes, 0);
+
+        let second = state.push("fish. And then");
+        assert!(second.new_matches.iter().any(|m| m.pattern_type == "character_judgment"));
+        assert!(second.score > 0.0);
+    }
+
+    #[test]
+    fn test_stream_positions_are_absolute() {
+        let text = "Nice weather today. It's all your fault.";
+        let mut state = StreamState::new();
+        state.push("Nice weather today. ");
+        state.push("It's all your ");
+        state.push("fault");
+        let last = state.finish();
+
+        let displacement = last.new_matches.iter().find(|m| m.pattern_type == "displacement").unwrap();
+        assert_eq!(&text[displacement.position..displacement.position + displacement.match_text.len()], displacement.match_text);
+        assert_eq!(state.matches().len(), last.new_matches.len());
+    }
+
+    #[test]
+    fn test_hysteresis_holds_flag_between_thresholds() {
+        let mut latch = Hysteresis::new(0.35, 0.25).unwrap();
+        let flags: Vec<bool> = [0.32, 0.36, 0.3, 0.26, 0.24, 0.3].iter().map(|s| latch.update(*s)).collect();
+        assert_eq!(flags, vec![false, true, true, true, false, false]);
+        assert!(Hysteresis::new(0.2, 0.3).is_err());
+
+        let mut state = StreamState::new();
+        assert!(state.push("You're so selfish. ").detected);
+    }
+
+    #[test]
+    fn test_decay_fades_old_matches() {
+        let mut state = StreamState::with_decay(StreamDecay { half_life_bytes: 20, window_bytes: Some(200) });
+        let hostile = state.push("You're so selfish. ");
+        let calm = state.push("We went for a walk and the weather was lovely. ");
+        assert!(calm.score < hostile.score / 2.0);
+
+        let later = state.push(&"Nothing much happened today. ".repeat(8));
+        assert_eq!(later.score, 0.0);
+        assert_eq!(state.matches().len(), 1);
+    }
+}
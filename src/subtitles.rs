@@ -0,0 +1,261 @@
+//! Subtitle and transcript ingestion
+//! Parses SRT/WebVTT cues, analyzes the spoken text as one transcript and tags matches with cue times
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::markup::decode_entity;
+use super::offsets::MappedText;
+use super::pattern_matching::match_patterns;
+use super::tokenize::tokenize;
+use super::turns::{turn_statistics, TurnStats, Utterance};
+use super::{build_processing_result, PatternMatchResult};
+
+/// Single subtitle cue
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitleCue {
+    pub index: usize,
+    /// SRT sequence number or VTT cue identifier
+    pub identifier: Option<String>,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Voice from a VTT `<v Speaker>` tag
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+/// Pattern match tagged with the cue time range it occurs in
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TimedPatternMatch {
+    #[serde(flatten)]
+    pub pattern: PatternMatchResult,
+    /// Cue the match starts in
+    pub cue_index: usize,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Transcript-level detection result with timed matches
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitleAnalysis {
+    pub detected: bool,
+    pub confidence: f64,
+    pub score: f64,
+    pub cues: Vec<SubtitleCue>,
+    /// Positions are byte offsets in the subtitle file
+    pub patterns: Vec<TimedPatternMatch>,
+    /// Per-voice turn-taking statistics from VTT `<v>` cues (cues without a voice are left out);
+    /// empty when no cue names a speaker
+    pub turn_taking: Vec<TurnStats>,
+}
+
+/// Parse `01:02:03,456` (SRT) or `02:03.456` (VTT) into milliseconds
+fn parse_timestamp(value: &str) -> Option<u64> {
+    let (clock, millis) = value.trim().split_once([',', '.'])?;
+    let mut seconds = 0u64;
+    for part in clock.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(seconds * 1000 + millis.parse::<u64>().ok()?)
+}
+
+/// Parse a `start --> end [settings]` timing line
+fn parse_timing(line: &str) -> Option<(u64, u64)> {
+    let (start, rest) = line.split_once("-->")?;
+    let end = rest.split_whitespace().next()?;
+    Some((parse_timestamp(start)?, parse_timestamp(end)?))
+}
+
+/// Append one cue text line, dropping markup tags and decoding entities;
+/// returns the voice name if the line carries a `<v Name>` tag
+fn push_cue_line(out: &mut MappedText, source: &str, line_start: usize, line: &str) -> Option<String> {
+    let mut speaker = None;
+    let mut copied = 0;
+    let mut i = 0;
+
+    while i < line.len() {
+        let rest = &line[i..];
+        let skip = if rest.starts_with('<') {
+            rest.find('>').map(|close| {
+                let tag = &rest[1..close];
+                // `<v Alice>` or `<v.loud Alice>` (the name follows the class list)
+                let voice = match tag.strip_prefix("v.") {
+                    Some(classed) => classed.split_once(' ').map(|(_, name)| name),
+                    None => tag.strip_prefix("v "),
+                };
+                if let Some(name) = voice {
+                    speaker = Some(name.trim().to_string());
+                }
+                (close + 1, String::new())
+            })
+        } else if rest.starts_with('&') {
+            decode_entity(rest).map(|(decoded, len)| (len, decoded))
+        } else {
+            None
+        };
+
+        match skip {
+            Some((len, replacement)) => {
+                out.push_source(source, line_start + copied, line_start + i);
+                out.push_replacement(&replacement, line_start + i, line_start + i + len);
+                i += len;
+                copied = i;
+            }
+            None => i += rest.chars().next().map(char::len_utf8).unwrap_or(1),
+        }
+    }
+
+    out.push_source(source, line_start + copied, line_start + line.len());
+    speaker
+}
+
+/// Parsed cues plus the transcript text and each cue's range within it
+struct Transcript {
+    cues: Vec<SubtitleCue>,
+    mapped: MappedText,
+    ranges: Vec<(usize, usize)>,
+}
+
+/// Parse SRT or WebVTT; header, NOTE, STYLE and REGION blocks are skipped
+fn parse_transcript(source: &str) -> Transcript {
+    let mut transcript = Transcript { cues: Vec::new(), mapped: MappedText::default(), ranges: Vec::new() };
+
+    // Collect blank-line separated blocks of (line start, line)
+    let mut blocks: Vec<Vec<(usize, &str)>> = vec![Vec::new()];
+    let mut line_start = 0;
+    for raw_line in source.split_inclusive('\n') {
+        let line = raw_line.trim_end_matches(['\n', '\r']);
+        if line.trim().is_empty() {
+            blocks.push(Vec::new());
+        } else if let Some(block) = blocks.last_mut() {
+            let content = line.trim_start_matches('\u{feff}');
+            block.push((line_start + line.len() - content.len(), content));
+        }
+        line_start += raw_line.len();
+    }
+
+    for block in blocks.iter().filter(|b| !b.is_empty()) {
+        let first = block[0].1;
+        if ["WEBVTT", "NOTE", "STYLE", "REGION"].iter().any(|keyword| first.starts_with(keyword)) {
+            continue;
+        }
+        let Some(timing_index) = block.iter().take(2).position(|(_, line)| line.contains("-->")) else {
+            continue;
+        };
+        let Some((start_ms, end_ms)) = parse_timing(block[timing_index].1) else {
+            continue;
+        };
+
+        if !transcript.ranges.is_empty() {
+            let separator = block[0].0;
+            transcript.mapped.push_replacement("\n", separator, separator);
+        }
+        let out_start = transcript.mapped.text.len();
+        let mut speaker = None;
+        for (n, &(line_start, line)) in block[timing_index + 1..].iter().enumerate() {
+            if n > 0 {
+                transcript.mapped.push_replacement("\n", line_start, line_start);
+            }
+            speaker = push_cue_line(&mut transcript.mapped, source, line_start, line).or(speaker);
+        }
+        let out_end = transcript.mapped.text.len();
+
+        transcript.cues.push(SubtitleCue {
+            index: transcript.cues.len(),
+            identifier: (timing_index == 1).then(|| block[0].1.trim().to_string()),
+            start_ms,
+            end_ms,
+            speaker,
+            text: transcript.mapped.text[out_start..out_end].to_string(),
+        });
+        transcript.ranges.push((out_start, out_end));
+    }
+
+    transcript
+}
+
+/// Analyze subtitle cues as one transcript, tagging matches with cue times
+pub fn analyze_subtitles(source: &str) -> SubtitleAnalysis {
+    let transcript = parse_transcript(source);
+    // Cue containing (or, for separators, preceding) a transcript offset
+    let cue_at = |out: usize| transcript.ranges.partition_point(|&(start, _)| start <= out).saturating_sub(1);
+
+    let mut matches = match_patterns(&transcript.mapped.text);
+    let mut cue_spans = Vec::with_capacity(matches.len());
+    for m in &mut matches {
+        let out_end = m.position + m.match_text.len();
+        cue_spans.push((cue_at(m.position), cue_at(out_end.saturating_sub(1))));
+        m.position = transcript.mapped.source_range(m.position, out_end).0;
+    }
+
+    let result = build_processing_result(&matches, tokenize(&transcript.mapped.text).len());
+    let patterns = result
+        .patterns
+        .into_iter()
+        .zip(cue_spans)
+        .map(|(pattern, (first, last))| TimedPatternMatch {
+            pattern,
+            cue_index: first,
+            start_ms: transcript.cues[first].start_ms,
+            end_ms: transcript.cues[last].end_ms,
+        })
+        .collect();
+
+    let utterances: Vec<Utterance> = transcript
+        .cues
+        .iter()
+        .filter_map(|cue| {
+            Some(Utterance {
+                speaker: cue.speaker.as_deref()?,
+                text: &cue.text,
+                words: tokenize(&cue.text).len(),
+                start_ms: Some(cue.start_ms as i64),
+                end_ms: Some(cue.end_ms as i64),
+            })
+        })
+        .collect();
+    let turn_taking = turn_statistics(&utterances);
+
+    SubtitleAnalysis {
+        detected: result.detected,
+        confidence: result.confidence,
+        score: result.score,
+        cues: transcript.cues,
+        patterns,
+        turn_taking,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srt_matches_carry_cue_times() {
+        let source = "1\r\n00:00:01,000 --> 00:00:03,500\r\nHello there\r\n\r\n2\r\n00:00:04,000 --> 00:00:06,000\r\n<i>It's all your fault</i>\r\n";
+        let analysis = analyze_subtitles(source);
+        assert_eq!(analysis.cues.len(), 2);
+        assert_eq!(analysis.cues[1].identifier.as_deref(), Some("2"));
+        assert_eq!(analysis.cues[1].text, "It's all your fault");
+
+        let timed = analysis.patterns.iter().find(|p| p.pattern.pattern_type == "displacement").unwrap();
+        assert_eq!(timed.cue_index, 1);
+        assert_eq!((timed.start_ms, timed.end_ms), (4000, 6000));
+        assert_eq!(&source[timed.pattern.position..timed.pattern.position + 4], "It's");
+    }
+
+    #[test]
+    fn test_vtt_voices_and_header() {
+        let source = "WEBVTT\n\nNOTE recorded call\n\n00:01.000 --> 00:02.500 align:start\n<v Alice>You&apos;re so selfish</v>\n";
+        let analysis = analyze_subtitles(source);
+        assert_eq!(analysis.cues.len(), 1);
+        assert_eq!(analysis.cues[0].speaker.as_deref(), Some("Alice"));
+        assert_eq!((analysis.cues[0].start_ms, analysis.cues[0].end_ms), (1000, 2500));
+        assert!(analysis.detected);
+        assert_eq!(analysis.turn_taking[0].turns, 1);
+        assert!(analyze_subtitles("1\n00:00:01,000 --> 00:00:02,000\nHi\n").turn_taking.is_empty());
+    }
+}
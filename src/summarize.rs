@@ -0,0 +1,125 @@
+//! Extractive summarization
+//! TextRank over sentences: sentences sharing content words endorse each other,
+//! and the highest-ranked ones are kept in their original order
+
+use std::collections::BTreeSet;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::offsets::sentence_ranges;
+use super::tokenize::content_words;
+
+/// PageRank damping factor
+const DAMPING: f64 = 0.85;
+
+/// Iteration cap and convergence threshold for the ranking
+const MAX_ITERATIONS: usize = 50;
+const CONVERGENCE: f64 = 1e-6;
+
+/// Sentence selected for the summary
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SummarySentence {
+    pub text: String,
+    pub position: usize,
+    pub end: usize,
+    pub score: f64,
+}
+
+/// Extractive summary of a text
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    /// Selected sentences joined in original order
+    pub summary: String,
+    pub sentences: Vec<SummarySentence>,
+}
+
+/// TextRank similarity: shared words normalized by the log of sentence lengths
+fn similarity(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let shared = a.intersection(b).count() as f64;
+    let norm = (a.len() as f64).ln() + (b.len() as f64).ln();
+    if shared == 0.0 || norm <= 0.0 {
+        0.0
+    } else {
+        shared / norm
+    }
+}
+
+/// Rank sentences with weighted PageRank over the similarity graph
+fn rank(words: &[BTreeSet<String>]) -> Vec<f64> {
+    let n = words.len();
+    let weights: Vec<Vec<f64>> =
+        (0..n).map(|i| (0..n).map(|j| if i == j { 0.0 } else { similarity(&words[i], &words[j]) }).collect()).collect();
+    let out_totals: Vec<f64> = weights.iter().map(|row| row.iter().sum()).collect();
+
+    let mut scores = vec![1.0; n];
+    for _ in 0..MAX_ITERATIONS {
+        let next: Vec<f64> = (0..n)
+            .map(|i| {
+                let incoming: f64 = (0..n)
+                    .filter(|&j| out_totals[j] > 0.0)
+                    .map(|j| weights[j][i] / out_totals[j] * scores[j])
+                    .sum();
+                (1.0 - DAMPING) + DAMPING * incoming
+            })
+            .collect();
+        let delta: f64 = next.iter().zip(&scores).map(|(a, b)| (a - b).abs()).sum();
+        scores = next;
+        if delta < CONVERGENCE {
+            break;
+        }
+    }
+    scores
+}
+
+/// Summarize text down to at most `max_sentences` sentences
+pub fn summarize_text(text: &str, max_sentences: usize) -> Summary {
+    let ranges = sentence_ranges(text);
+    let words: Vec<BTreeSet<String>> = ranges.iter().map(|&(s, e)| content_words(&text[s..e]).into_iter().collect()).collect();
+    let scores = rank(&words);
+
+    // Highest scores win; earlier sentences break ties
+    let mut order: Vec<usize> = (0..ranges.len()).collect();
+    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+    let mut selected: Vec<usize> = order.into_iter().take(max_sentences).collect();
+    selected.sort_unstable();
+
+    let sentences: Vec<SummarySentence> = selected
+        .into_iter()
+        .map(|i| {
+            let (position, end) = ranges[i];
+            SummarySentence { text: text[position..end].to_string(), position, end, score: scores[i] }
+        })
+        .collect();
+    let summary = sentences.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" ");
+
+    Summary { summary, sentences }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_central_sentences_in_order() {
+        let text = "Work has been stressful because my manager keeps moving deadlines. \
+                    I had pasta for lunch. \
+                    My manager moved another deadline today and work piled up. \
+                    The weather was nice. \
+                    Deadlines at work make me anxious about my manager.";
+        let summary = summarize_text(text, 2);
+        assert_eq!(summary.sentences.len(), 2);
+        assert!(summary.sentences.iter().all(|s| s.text.contains("manager")));
+        assert!(summary.sentences[0].position < summary.sentences[1].position);
+        assert_eq!(&text[summary.sentences[0].position..summary.sentences[0].end], summary.sentences[0].text);
+    }
+
+    #[test]
+    fn test_short_text_is_returned_whole() {
+        let summary = summarize_text("Just one sentence.", 3);
+        assert_eq!(summary.summary, "Just one sentence.");
+        assert!(summarize_text("", 3).sentences.is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+//! Temporal orientation
+//! Past / present / future focus from verb tense and temporal adverbs
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::tokenize::tokenize;
+
+/// Past-tense auxiliaries, irregular verbs and past adverbs
+const PAST_WORDS: &[&str] = &[
+    "ago", "became", "began", "bought", "brought", "came", "did", "didn't", "earlier", "felt", "forgot", "formerly",
+    "found", "gave", "got", "had", "hadn't", "heard", "kept", "knew", "last", "left", "lost", "made", "meant", "met",
+    "previously", "ran", "said", "sat", "saw", "spoke", "stood", "thought", "told", "took", "understood", "was",
+    "wasn't", "went", "were", "weren't", "wrote", "yesterday",
+];
+
+/// Present-tense auxiliaries, common present verbs and present adverbs
+const PRESENT_WORDS: &[&str] = &[
+    "am", "are", "aren't", "currently", "do", "does", "doesn't", "don't", "feel", "feels", "i'm", "is", "isn't",
+    "it's", "know", "nowadays", "now", "presently", "that's", "they're", "think", "today", "want", "wants", "we're",
+    "you're",
+];
+
+/// Future auxiliaries and adverbs
+const FUTURE_WORDS: &[&str] = &[
+    "eventually", "gonna", "later", "next", "shall", "someday", "soon", "tomorrow", "upcoming", "will", "won't",
+];
+
+/// Words ending in "-ed" that are not past-tense verbs
+const NOT_PAST_ED: &[&str] = &["hundred", "kindred", "naked", "ragged", "rugged", "sacred", "wicked"];
+
+/// Share of temporal cues per orientation
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TemporalOrientation {
+    /// Shares of cues (sum to 1, or all 0 when there are no cues)
+    pub past: f64,
+    pub present: f64,
+    pub future: f64,
+    /// `past`, `present` or `future` when one orientation has the most cues
+    pub dominant: Option<String>,
+    /// Number of tense and temporal-adverb cues found
+    pub cues: usize,
+}
+
+/// Regular past tense ("yelled", "ignored"), excluding "-eed" words and common adjectives
+fn is_regular_past(word: &str) -> bool {
+    word.len() >= 5 && word.ends_with("ed") && !word.ends_with("eed") && !NOT_PAST_ED.contains(&word)
+}
+
+/// Measure the past / present / future focus of a text
+pub fn temporal_orientation(text: &str) -> TemporalOrientation {
+    let words: Vec<String> = tokenize(text).iter().map(|t| t.lower().replace('\u{2019}', "'")).collect();
+    let mut counts = [0usize; 3];
+
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i].as_str();
+        let next = words.get(i + 1).map(String::as_str);
+        match (word, next) {
+            // "going to" and "used to" are read as one cue, not as present / past verbs
+            ("going", Some("to")) => {
+                counts[2] += 1;
+                i += 1;
+            }
+            ("used", Some("to")) => {
+                counts[0] += 1;
+                i += 1;
+            }
+            _ if word.ends_with("'ll") || FUTURE_WORDS.contains(&word) => counts[2] += 1,
+            _ if PAST_WORDS.contains(&word) || is_regular_past(word) => counts[0] += 1,
+            _ if PRESENT_WORDS.contains(&word) => counts[1] += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let cues: usize = counts.iter().sum();
+    let share = |count: usize| if cues == 0 { 0.0 } else { count as f64 / cues as f64 };
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let dominant = match counts.iter().filter(|c| **c == max).count() {
+        1 if max > 0 => ["past", "present", "future"].iter().zip(counts).find(|(_, c)| *c == max).map(|(n, _)| n.to_string()),
+        _ => None,
+    };
+
+    TemporalOrientation { past: share(counts[0]), present: share(counts[1]), future: share(counts[2]), dominant, cues }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dominant_orientation() {
+        let past = temporal_orientation("Yesterday my boss yelled at me and I felt humiliated. It was awful.");
+        assert_eq!(past.dominant.as_deref(), Some("past"));
+
+        let future = temporal_orientation("Tomorrow I'm going to talk to her, and I'll ask for help soon.");
+        assert_eq!(future.dominant.as_deref(), Some("future"));
+        assert!((future.past + future.present + future.future - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_no_cues() {
+        let result = temporal_orientation("Blue sky, green grass.");
+        assert_eq!(result.cues, 0);
+        assert_eq!(result.dominant, None);
+        assert_eq!(result.past, 0.0);
+    }
+}
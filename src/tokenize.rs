@@ -0,0 +1,87 @@
+//! Shared word tokenizer
+//! One definition of "word" (letters, digits and inner apostrophes) for the text metrics
+
+/// Common function words ignored by content-based metrics
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "again", "all", "am", "an", "and", "any", "are", "as", "at", "be", "because", "been",
+    "before", "being", "but", "by", "can", "could", "did", "do", "does", "doing", "don't", "for", "from", "had",
+    "has", "have", "having", "he", "her", "here", "hers", "him", "his", "how", "i", "i'm", "i've", "if", "in",
+    "into", "is", "it", "it's", "its", "just", "me", "more", "my", "myself", "no", "not", "now", "of", "off", "on",
+    "once", "only", "or", "other", "our", "out", "over", "own", "really", "same", "she", "should", "so", "some",
+    "such", "than", "that", "that's", "the", "their", "them", "then", "there", "these", "they", "this", "those",
+    "through", "to", "too", "under", "until", "up", "very", "was", "we", "were", "what", "when", "where", "which",
+    "while", "who", "why", "will", "with", "would", "you", "you're", "your", "yours",
+];
+
+/// Word with its byte range in the source text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token<'_> {
+    pub fn lower(&self) -> String {
+        self.text.to_lowercase()
+    }
+}
+
+/// Split text into words; apostrophes count only between letters ("don't", not "'quoted'")
+pub fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let inner_apostrophe = matches!(c, '\'' | '\u{2019}')
+            && start.is_some()
+            && chars.peek().is_some_and(|(_, next)| next.is_alphanumeric());
+        match (start, c.is_alphanumeric() || inner_apostrophe) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                tokens.push(Token { text: &text[s..i], start: s, end: i });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(Token { text: &text[s..], start: s, end: text.len() });
+    }
+
+    tokens
+}
+
+/// Whether a lowercase word is a stopword
+pub fn is_stopword(word: &str) -> bool {
+    STOPWORDS.binary_search(&word.replace('\u{2019}', "'").as_str()).is_ok()
+}
+
+/// Lowercase content words (stopwords and single characters removed)
+pub fn content_words(text: &str) -> Vec<String> {
+    tokenize(text)
+        .iter()
+        .map(Token::lower)
+        .filter(|w| w.chars().count() > 1 && !is_stopword(w))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_keeps_inner_apostrophes() {
+        let text = "Don't say 'never' again, café!";
+        let words: Vec<&str> = tokenize(text).iter().map(|t| t.text).collect();
+        assert_eq!(words, vec!["Don't", "say", "never", "again", "café"]);
+        assert_eq!(&text[tokenize(text)[4].start..tokenize(text)[4].end], "café");
+    }
+
+    #[test]
+    fn test_stopwords_are_sorted_and_filtered() {
+        assert!(STOPWORDS.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(content_words("I really hate that my boss ignores me"), vec!["hate", "boss", "ignores"]);
+    }
+}
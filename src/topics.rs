@@ -0,0 +1,85 @@
+//! Lightweight topic detection
+//! Keyword-cluster tagger for life domains (work, family, health, money, relationship, ...)
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::tokenize::tokenize;
+
+/// Keyword clusters per topic; a trailing `*` matches any word with that prefix
+const TOPIC_KEYWORDS: &[(&str, &[&str])] = &[
+    ("work", &["work*", "job*", "boss*", "office", "coworker*", "colleague*", "manager*", "meeting*", "deadline*", "career*", "promotion", "fired", "hired", "interview*", "project*", "shift*", "salary"]),
+    ("family", &["mom", "mother*", "dad", "father*", "parent*", "sister*", "brother*", "sibling*", "family", "families", "son", "daughter*", "kid", "kids", "child*", "grandma", "grandpa", "grandparent*", "aunt*", "uncle*", "cousin*", "in-law*"]),
+    ("health", &["health*", "sick*", "ill", "illness*", "doctor*", "hospital*", "therap*", "medic*", "pain*", "sleep*", "tired", "exhaust*", "anxiety", "depress*", "exercise*", "gym", "diet*", "symptom*", "diagnos*", "headache*"]),
+    ("money", &["money", "debt*", "rent", "bill", "bills", "pay", "paid", "paycheck*", "budget*", "loan*", "mortgage*", "bank*", "savings", "afford*", "expensive", "broke", "cash", "credit"]),
+    ("relationship", &["boyfriend*", "girlfriend*", "partner*", "husband*", "wife", "wives", "spouse*", "marriage*", "married", "dating", "date", "relationship*", "breakup*", "divorce*", "ex", "romantic*", "love"]),
+    ("friendship", &["friend*", "bestie", "buddy", "buddies", "roommate*", "neighbor*", "hang", "hanging"]),
+    ("school", &["school*", "class", "classes", "teacher*", "homework", "exam*", "test", "grade*", "college*", "university", "professor*", "study*", "studying", "semester*"]),
+];
+
+/// Topic with its relevance for one text
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicScore {
+    pub topic: String,
+    /// Share of keyword hits belonging to this topic (scores sum to 1)
+    pub score: f64,
+    pub hits: usize,
+    /// Distinct keywords that matched
+    pub keywords: Vec<String>,
+}
+
+fn keyword_matches(keyword: &str, word: &str) -> bool {
+    match keyword.strip_suffix('*') {
+        Some(prefix) => word.starts_with(prefix),
+        None => word == keyword,
+    }
+}
+
+/// Rank the topics a text touches, strongest first; topics with no hits are omitted
+pub fn detect_topics(text: &str) -> Vec<TopicScore> {
+    let mut hits: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+
+    for token in tokenize(text) {
+        let word = token.lower();
+        for (topic, keywords) in TOPIC_KEYWORDS {
+            if keywords.iter().any(|k| keyword_matches(k, &word)) {
+                hits.entry(topic).or_default().push(word.clone());
+            }
+        }
+    }
+
+    let total: usize = hits.values().map(Vec::len).sum();
+    let mut topics: Vec<TopicScore> = hits
+        .into_iter()
+        .map(|(topic, mut words)| {
+            let count = words.len();
+            words.sort();
+            words.dedup();
+            TopicScore { topic: topic.to_string(), score: count as f64 / total as f64, hits: count, keywords: words }
+        })
+        .collect();
+    topics.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.topic.cmp(&b.topic)));
+    topics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranks_topics_by_hits() {
+        let topics = detect_topics("My boss moved the deadline again and I can't pay rent. Work is exhausting.");
+        let names: Vec<&str> = topics.iter().map(|t| t.topic.as_str()).collect();
+        assert_eq!(names, vec!["work", "money", "health"]);
+        assert!(topics[0].keywords.contains(&"deadline".to_string()));
+        assert!((topics.iter().map(|t| t.score).sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_no_topics() {
+        assert!(detect_topics("The sky is blue.").is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+//! Toxicity taxonomy mapping
+//! Maps internal pattern types onto the standard moderation categories
+//! (toxicity, severe_toxicity, insult, threat, identity_attack)
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::pattern_matching::{match_patterns, PatternMatch};
+use super::scoring::calculate_text_score;
+use super::DETECTION_THRESHOLD;
+
+/// Standard toxicity categories, in taxonomy order
+pub const TOXICITY_CATEGORIES: &[&str] = &["toxicity", "severe_toxicity", "insult", "threat", "identity_attack"];
+
+const INSULT_TYPES: &[&str] = &["character_judgment", "insult", "visceral_judgment", "sanity_attack", "negging"];
+const THREAT_TYPES: &[&str] = &["retaliation", "extreme_aggression", "destructive_intent", "targeted_aggression"];
+const IDENTITY_ATTACK_TYPES: &[&str] = &["dehumanization", "objectification", "dog_whistling"];
+
+/// Self-directed coping patterns: distress signals, not toxicity toward others
+const SELF_DIRECTED_TYPES: &[&str] = &[
+    "reassurance_seeking",
+    "self_victimization",
+    "self_devaluation",
+    "hopelessness",
+    "escapism",
+    "substance_use",
+    "termination_thinking",
+    "catastrophizing",
+    "external_locus_of_control",
+    "emotional_barrier",
+    "perspecticide",
+    "future_loss",
+];
+
+/// Minimum weight for a high-severity insult/threat/identity match to count as severe
+const SEVERE_MIN_WEIGHT: f64 = 0.9;
+
+/// Matched span with the categories it contributes to
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ToxicitySpan {
+    pub start: usize,
+    pub end: usize,
+    pub pattern_type: String,
+    pub categories: Vec<String>,
+    pub score: f64,
+}
+
+/// Per-category scores in the standard taxonomy
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ToxicityResult {
+    /// Score (0 - 1) for every category, keyed by category name
+    pub scores: BTreeMap<String, f64>,
+    /// Categories whose score is above the detection threshold
+    pub flagged: Vec<String>,
+    pub spans: Vec<ToxicitySpan>,
+}
+
+/// Taxonomy categories a match maps onto
+fn categories_for(m: &PatternMatch) -> Vec<&'static str> {
+    let pattern_type = m.pattern_type.as_str();
+    if SELF_DIRECTED_TYPES.contains(&pattern_type) {
+        return Vec::new();
+    }
+
+    let mut categories = vec!["toxicity"];
+    let specific = if INSULT_TYPES.contains(&pattern_type) {
+        Some("insult")
+    } else if THREAT_TYPES.contains(&pattern_type) {
+        Some("threat")
+    } else if IDENTITY_ATTACK_TYPES.contains(&pattern_type) {
+        Some("identity_attack")
+    } else {
+        None
+    };
+    if let Some(category) = specific {
+        if m.severity == "high" && m.weight >= SEVERE_MIN_WEIGHT {
+            categories.push("severe_toxicity");
+        }
+        categories.push(category);
+    }
+    categories
+}
+
+/// Score text in the standard toxicity taxonomy
+pub fn classify_toxicity(text: &str) -> ToxicityResult {
+    let mut by_category: BTreeMap<&str, Vec<PatternMatch>> = TOXICITY_CATEGORIES.iter().map(|c| (*c, Vec::new())).collect();
+    let mut spans = Vec::new();
+
+    for m in match_patterns(text) {
+        let categories = categories_for(&m);
+        if categories.is_empty() {
+            continue;
+        }
+        for category in &categories {
+            by_category.entry(category).or_default().push(m.clone());
+        }
+        spans.push(ToxicitySpan {
+            start: m.position,
+            end: m.position + m.match_text.len(),
+            pattern_type: m.pattern_type,
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+            score: m.weight,
+        });
+    }
+    spans.sort_by_key(|s| s.start);
+
+    let scores: BTreeMap<String, f64> =
+        by_category.iter().map(|(category, matches)| (category.to_string(), calculate_text_score(matches))).collect();
+    let flagged = TOXICITY_CATEGORIES
+        .iter()
+        .filter(|c| scores[**c] > DETECTION_THRESHOLD)
+        .map(|c| c.to_string())
+        .collect();
+
+    ToxicityResult { scores, flagged, spans }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insult_and_threat_mapping() {
+        let result = classify_toxicity("You're so pathetic. I will make him pay.");
+        assert_eq!(result.scores.len(), TOXICITY_CATEGORIES.len());
+        assert!(result.scores["insult"] > 0.0);
+        assert!(result.scores["threat"] > 0.0);
+        assert_eq!(result.scores["identity_attack"], 0.0);
+        assert!(result.flagged.contains(&"severe_toxicity".to_string()));
+    }
+
+    #[test]
+    fn test_self_directed_patterns_are_not_toxic() {
+        let result = classify_toxicity("Why does this always happen to me?");
+        assert!(result.spans.is_empty());
+        assert_eq!(result.scores["toxicity"], 0.0);
+    }
+}
@@ -0,0 +1,104 @@
+//! Within-text emotional trajectory
+//! Splits a long text into paragraphs (or runs of N sentences) and scores each one, so a UI can
+//! draw where in an entry things escalate. The whole text is analyzed once and each match counted
+//! in the segment it starts in, so context handling is the same as for the full result
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::analyze;
+use super::digest::mood;
+use super::lexicon::Lexicon;
+use super::offsets::{paragraph_ranges, sentence_ranges};
+use super::scoring::score_weighted_types;
+
+/// Score and mood of one segment
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TrajectoryPoint {
+    pub index: usize,
+    /// Byte range of the segment
+    pub start: usize,
+    pub end: usize,
+    pub score: f64,
+    /// Balance of positive and negative emotion words, -1 - 1
+    pub mood: f64,
+    pub pattern_count: usize,
+}
+
+/// Per-segment series for a text
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Trajectory {
+    /// "paragraph" or "sentences"
+    pub unit: String,
+    pub points: Vec<TrajectoryPoint>,
+    /// Index of the highest-scoring segment, when any segment has matches
+    pub peak: Option<usize>,
+}
+
+/// Segment ranges: paragraphs, or groups of `sentences_per_point` sentences when given (non-zero)
+fn segments(text: &str, sentences_per_point: Option<usize>) -> Vec<(usize, usize)> {
+    match sentences_per_point.filter(|&n| n > 0) {
+        Some(n) => sentence_ranges(text).chunks(n).map(|chunk| (chunk[0].0, chunk[chunk.len() - 1].1)).collect(),
+        None => paragraph_ranges(text),
+    }
+}
+
+/// Score and mood per paragraph, or per `sentences_per_point` sentences
+pub fn text_trajectory(text: &str, sentences_per_point: Option<usize>) -> Trajectory {
+    let result = analyze(text);
+    let lexicon = Lexicon::builtin();
+
+    let points: Vec<TrajectoryPoint> = segments(text, sentences_per_point)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start, end))| {
+            let patterns: Vec<_> = result.patterns.iter().filter(|p| p.position >= start && p.position < end).collect();
+            TrajectoryPoint {
+                index,
+                start,
+                end,
+                score: score_weighted_types(patterns.iter().map(|p| (p.pattern_type.as_str(), p.weight))),
+                mood: mood(&lexicon.count(&text[start..end])),
+                pattern_count: patterns.len(),
+            }
+        })
+        .collect();
+    let peak = points.iter().filter(|p| p.pattern_count > 0).max_by(|a, b| a.score.total_cmp(&b.score)).map(|p| p.index);
+
+    Trajectory {
+        unit: if sentences_per_point.is_some_and(|n| n > 0) { "sentences" } else { "paragraph" }.to_string(),
+        points,
+        peak,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paragraph_trajectory_finds_escalation() {
+        let text = "Lovely morning, I'm happy and calm.\n\nLunch was fine.\n\nThen he said I'm a failure. You're an idiot, he yelled. I'm so hurt.";
+        let trajectory = text_trajectory(text, None);
+        assert_eq!(trajectory.unit, "paragraph");
+        assert_eq!(trajectory.points.len(), 3);
+        assert!(trajectory.points[0].mood > 0.0 && trajectory.points[2].mood < 0.0);
+        assert_eq!(trajectory.points[1].pattern_count, 0);
+        assert_eq!(trajectory.peak, Some(2));
+        assert!(trajectory.points[2].score > 0.0);
+        assert_eq!(&text[trajectory.points[1].start..trajectory.points[1].end], "Lunch was fine.");
+    }
+
+    #[test]
+    fn test_sentence_groups() {
+        let text = "One. Two. Three. You're an idiot.";
+        let trajectory = text_trajectory(text, Some(2));
+        assert_eq!(trajectory.unit, "sentences");
+        let segments: Vec<&str> = trajectory.points.iter().map(|p| &text[p.start..p.end]).collect();
+        assert_eq!(segments, vec!["One. Two.", "Three. You're an idiot."]);
+        assert_eq!(trajectory.peak, Some(1));
+        assert_eq!(text_trajectory("Calm text.", None).peak, None);
+    }
+}
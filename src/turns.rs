@@ -0,0 +1,205 @@
+//! Turn-taking and dominance statistics
+//! Who holds the floor in a conversation: turns, share of words, interruptions and how quickly each
+//! speaker answers. Shared by chat exports (instant messages) and subtitle transcripts (timed cues)
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A reply this soon after an unfinished message counts as cutting the other speaker off
+const INTERRUPTION_WINDOW_MS: i64 = 60_000;
+/// Messages from one speaker that make a burst when they fall within `BURST_WINDOW_MS`
+const BURST_MIN_MESSAGES: usize = 5;
+const BURST_WINDOW_MS: i64 = 120_000;
+
+/// One message or cue, in conversation order
+#[derive(Debug, Clone)]
+pub struct Utterance<'a> {
+    pub speaker: &'a str,
+    pub text: &'a str,
+    pub words: usize,
+    /// Milliseconds on a monotonic clock, when known
+    pub start_ms: Option<i64>,
+    /// Equal to `start_ms` for instant messages
+    pub end_ms: Option<i64>,
+}
+
+/// Turn-taking statistics for one speaker
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnStats {
+    pub speaker: String,
+    /// Runs of consecutive messages by the speaker
+    pub turns: usize,
+    pub turn_share: f64,
+    pub word_count: usize,
+    /// Fraction of all words in the conversation, 0 - 1
+    pub word_share: f64,
+    pub mean_words_per_turn: f64,
+    /// Turns started over the previous speaker (overlapping cues) or right after an unfinished
+    /// message ("wait, I was-", "you never...")
+    pub interruptions: usize,
+    /// Turns with a measurable delay after the previous speaker's turn
+    pub responses: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean_response_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub median_response_ms: Option<i64>,
+}
+
+/// Whether a message stops mid-sentence
+fn unfinished(text: &str) -> bool {
+    let text = text.trim_end();
+    text.ends_with(['-', '\u{2014}', ',', '\u{2026}']) || text.ends_with("...")
+}
+
+/// Per-speaker statistics, speakers in order of first appearance
+pub fn turn_statistics(utterances: &[Utterance]) -> Vec<TurnStats> {
+    let mut stats: Vec<TurnStats> = Vec::new();
+    let mut latencies: Vec<Vec<i64>> = Vec::new();
+    let total_words: usize = utterances.iter().map(|u| u.words).sum();
+    let mut total_turns = 0;
+
+    for (i, utterance) in utterances.iter().enumerate() {
+        let slot = match stats.iter().position(|s| s.speaker == utterance.speaker) {
+            Some(slot) => slot,
+            None => {
+                stats.push(TurnStats {
+                    speaker: utterance.speaker.to_string(),
+                    turns: 0,
+                    turn_share: 0.0,
+                    word_count: 0,
+                    word_share: 0.0,
+                    mean_words_per_turn: 0.0,
+                    interruptions: 0,
+                    responses: 0,
+                    mean_response_ms: None,
+                    median_response_ms: None,
+                });
+                latencies.push(Vec::new());
+                stats.len() - 1
+            }
+        };
+        stats[slot].word_count += utterance.words;
+
+        let previous = i.checked_sub(1).map(|p| &utterances[p]);
+        if previous.is_some_and(|p| p.speaker == utterance.speaker) {
+            continue;
+        }
+        stats[slot].turns += 1;
+        total_turns += 1;
+
+        let Some(previous) = previous else { continue };
+        let (Some(start), Some(previous_start), Some(previous_end)) = (utterance.start_ms, previous.start_ms, previous.end_ms) else {
+            continue;
+        };
+        let overlapping = start < previous_end;
+        let cut_off = previous_end == previous_start && start - previous_end <= INTERRUPTION_WINDOW_MS && unfinished(previous.text);
+        if overlapping || cut_off {
+            stats[slot].interruptions += 1;
+        }
+        latencies[slot].push((start - previous_end).max(0));
+    }
+
+    for (speaker, mut latencies) in stats.iter_mut().zip(latencies) {
+        speaker.turn_share = if total_turns > 0 { speaker.turns as f64 / total_turns as f64 } else { 0.0 };
+        speaker.word_share = if total_words > 0 { speaker.word_count as f64 / total_words as f64 } else { 0.0 };
+        speaker.mean_words_per_turn = if speaker.turns > 0 { speaker.word_count as f64 / speaker.turns as f64 } else { 0.0 };
+        speaker.responses = latencies.len();
+        if !latencies.is_empty() {
+            latencies.sort_unstable();
+            speaker.mean_response_ms = Some(latencies.iter().sum::<i64>() as f64 / latencies.len() as f64);
+            speaker.median_response_ms = Some(latencies[latencies.len() / 2]);
+        }
+    }
+
+    stats
+}
+
+/// Bursts of at least `BURST_MIN_MESSAGES` timed messages from one speaker within
+/// `BURST_WINDOW_MS`, overlapping windows merged, as (speaker, indices of the speaker's messages),
+/// in order of the first message
+pub fn message_bursts<'a>(utterances: &[Utterance<'a>]) -> Vec<(&'a str, Vec<usize>)> {
+    let mut speakers: Vec<&str> = utterances.iter().map(|u| u.speaker).collect();
+    speakers.sort_unstable();
+    speakers.dedup();
+
+    let mut bursts: Vec<(&str, Vec<usize>)> = Vec::new();
+    for speaker in speakers {
+        let timed: Vec<(usize, i64)> = utterances
+            .iter()
+            .enumerate()
+            .filter(|(_, u)| u.speaker == speaker)
+            .filter_map(|(i, u)| Some((i, u.start_ms?)))
+            .collect();
+        // Last window end (in `timed`) folded into the current burst
+        let mut current: Option<(usize, usize)> = None;
+        for (first, window) in timed.windows(BURST_MIN_MESSAGES).enumerate() {
+            if window[BURST_MIN_MESSAGES - 1].1 - window[0].1 > BURST_WINDOW_MS {
+                continue;
+            }
+            let last = first + BURST_MIN_MESSAGES - 1;
+            current = match current {
+                Some((start, end)) if first <= end => Some((start, last)),
+                Some((start, end)) => {
+                    bursts.push((speaker, timed[start..=end].iter().map(|&(i, _)| i).collect()));
+                    Some((first, last))
+                }
+                None => Some((first, last)),
+            };
+        }
+        if let Some((start, end)) = current {
+            bursts.push((speaker, timed[start..=end].iter().map(|&(i, _)| i).collect()));
+        }
+    }
+    bursts.sort_by_key(|(_, indices)| indices[0]);
+    bursts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utterance<'a>(speaker: &'a str, text: &'a str, start_ms: i64, end_ms: i64) -> Utterance<'a> {
+        Utterance { speaker, text, words: text.split_whitespace().count(), start_ms: Some(start_ms), end_ms: Some(end_ms) }
+    }
+
+    #[test]
+    fn test_turns_shares_and_latencies() {
+        let utterances = [
+            utterance("Alice", "Where were you last night", 0, 0),
+            utterance("Alice", "Answer me.", 10_000, 10_000),
+            utterance("Bob", "At work.", 70_000, 70_000),
+            utterance("Alice", "Sure you were.", 80_000, 80_000),
+        ];
+        let stats = turn_statistics(&utterances);
+        assert_eq!((stats[0].speaker.as_str(), stats[0].turns, stats[0].word_count), ("Alice", 2, 10));
+        assert_eq!(stats[1].turns, 1);
+        assert!((stats[0].word_share - 10.0 / 12.0).abs() < 1e-9);
+        assert_eq!(stats[1].median_response_ms, Some(60_000));
+        assert_eq!(stats[0].mean_response_ms, Some(10_000.0));
+        assert_eq!(stats[0].interruptions + stats[1].interruptions, 0);
+    }
+
+    #[test]
+    fn test_interruptions_from_overlap_and_unfinished_messages() {
+        let cues = [utterance("Alice", "I was trying to say", 0, 3_000), utterance("Bob", "No, listen", 2_000, 4_000)];
+        assert_eq!(turn_statistics(&cues)[1].interruptions, 1);
+
+        let messages = [utterance("Alice", "I was trying to-", 0, 0), utterance("Bob", "Stop", 5_000, 5_000)];
+        assert_eq!(turn_statistics(&messages)[1].interruptions, 1);
+        let untimed = [Utterance { start_ms: None, end_ms: None, ..messages[0].clone() }, messages[1].clone()];
+        assert_eq!(turn_statistics(&untimed)[1].responses, 0);
+    }
+
+    #[test]
+    fn test_bursts_of_messages_from_one_speaker() {
+        let mut utterances: Vec<Utterance> = (0..6).map(|i| utterance("Alice", "answer me", i * 10_000, i * 10_000)).collect();
+        utterances.insert(3, utterance("Bob", "busy", 25_000, 25_000));
+        utterances.push(utterance("Alice", "hello?", 3_600_000, 3_600_000));
+        let bursts = message_bursts(&utterances);
+        assert_eq!(bursts, vec![("Alice", vec![0, 1, 2, 4, 5, 6])]);
+
+        let spread: Vec<Utterance> = (0..6).map(|i| utterance("Alice", "hi", i * 60_000, i * 60_000)).collect();
+        assert!(message_bursts(&spread).is_empty());
+    }
+}
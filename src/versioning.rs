@@ -0,0 +1,340 @@
+//! Output schema versioning
+//! Results carry a `schemaVersion` and callers can request an older shape, so the JSON-string
+//! contract can gain or rename fields without breaking existing consumers. Every result type's
+//! fields are registered below under the version that added them; an older version keeps only the
+//! fields it had
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::entity_extraction::EntityExtractionResult;
+use super::TextProcessingResult;
+
+/// Output schema versions this build can produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SchemaVersion {
+    /// Original shape
+    V1,
+    /// Adds stable `id`s, match confidence, quotes and suggestions, and the positive, empathy and
+    /// tone scores
+    V2,
+    /// Adds match scope and people, sentence scores, and entity confidences, groups, contacts,
+    /// aliases, pronoun evidence, relationship changes and conflicts
+    V3,
+}
+
+/// Every version this build can produce, oldest first
+pub const SUPPORTED_VERSIONS: &[SchemaVersion] = &[SchemaVersion::V1, SchemaVersion::V2, SchemaVersion::V3];
+
+/// Fields of one result type by the version that added them, and the result types nested in them
+pub struct Shape {
+    fields: &'static [(SchemaVersion, &'static [&'static str])],
+    /// Fields holding another result type, or a list of them
+    nested: &'static [(&'static str, &'static Shape)],
+}
+
+impl Shape {
+    fn has_field(&self, key: &str, version: SchemaVersion) -> bool {
+        self.fields.iter().any(|&(added, keys)| added <= version && keys.contains(&key))
+    }
+}
+
+const PATTERN_MATCH_RESULT: Shape = Shape {
+    fields: &[
+        (SchemaVersion::V1, &["patternType", "matchText", "position", "severity", "weight"]),
+        (SchemaVersion::V2, &["id", "confidence", "quoted", "quoteIndex", "suggestion"]),
+        (SchemaVersion::V3, &["scope", "person", "entityId", "entityRole"]),
+    ],
+    nested: &[],
+};
+
+const SENTENCE_SCORE: Shape = Shape {
+    fields: &[(SchemaVersion::V3, &["start", "end", "score", "detected", "patternIndices"])],
+    nested: &[],
+};
+
+/// Shape of `TextProcessingResult`
+pub const TEXT_PROCESSING_RESULT: Shape = Shape {
+    fields: &[
+        (SchemaVersion::V1, &["detected", "confidence", "patterns", "score"]),
+        (SchemaVersion::V2, &["positivePatterns", "positiveScore", "empathyScore", "toneIntensity", "percentile"]),
+        (SchemaVersion::V3, &["sentences"]),
+    ],
+    nested: &[
+        ("patterns", &PATTERN_MATCH_RESULT),
+        ("positivePatterns", &PATTERN_MATCH_RESULT),
+        ("sentences", &SENTENCE_SCORE),
+    ],
+};
+
+const PRONOUN_EVIDENCE: Shape = Shape {
+    fields: &[(SchemaVersion::V3, &["counts", "bestGuess", "share"])],
+    nested: &[],
+};
+
+const EXTRACTED_ENTITY: Shape = Shape {
+    fields: &[
+        (
+            SchemaVersion::V1,
+            &["name", "relationshipHint", "relationshipContext", "pronouns", "mentionContext", "sentiment", "confidence", "position"],
+        ),
+        (SchemaVersion::V2, &["id"]),
+        (
+            SchemaVersion::V3,
+            &[
+                "pronounEvidence",
+                "nameConfidence",
+                "relationshipConfidence",
+                "groupType",
+                "members",
+                "knownContact",
+                "contactId",
+                "contactMatchScore",
+                "canonicalName",
+                "aliases",
+            ],
+        ),
+    ],
+    nested: &[("pronounEvidence", &PRONOUN_EVIDENCE)],
+};
+
+const RELATIONSHIP_CHANGE: Shape = Shape {
+    fields: &[(
+        SchemaVersion::V3,
+        &["kind", "entityId", "name", "previousRelationship", "newRelationship", "trigger", "position"],
+    )],
+    nested: &[],
+};
+
+const CONFLICT_PARTY: Shape = Shape {
+    fields: &[(SchemaVersion::V3, &["text", "entityId", "relationship"])],
+    nested: &[],
+};
+
+const ENTITY_CONFLICT: Shape = Shape {
+    fields: &[(SchemaVersion::V3, &["kind", "parties", "trigger", "position"])],
+    nested: &[("parties", &CONFLICT_PARTY)],
+};
+
+/// Shape of `EntityExtractionResult`
+pub const ENTITY_EXTRACTION_RESULT: Shape = Shape {
+    fields: &[
+        (SchemaVersion::V1, &["entities", "relationshipCount", "processingTimeUs"]),
+        (SchemaVersion::V3, &["likelyNew", "relationshipChanges", "conflicts"]),
+    ],
+    nested: &[
+        ("entities", &EXTRACTED_ENTITY),
+        ("relationshipChanges", &RELATIONSHIP_CHANGE),
+        ("conflicts", &ENTITY_CONFLICT),
+    ],
+};
+
+/// Result types that can be serialized in any supported schema version
+pub trait Versioned: Serialize {
+    const SHAPE: &'static Shape;
+}
+
+impl Versioned for TextProcessingResult {
+    const SHAPE: &'static Shape = &TEXT_PROCESSING_RESULT;
+}
+
+impl Versioned for EntityExtractionResult {
+    const SHAPE: &'static Shape = &ENTITY_EXTRACTION_RESULT;
+}
+
+impl SchemaVersion {
+    pub const CURRENT: SchemaVersion = SchemaVersion::V3;
+
+    pub fn number(self) -> u32 {
+        match self {
+            SchemaVersion::V1 => 1,
+            SchemaVersion::V2 => 2,
+            SchemaVersion::V3 => 3,
+        }
+    }
+
+    pub fn from_number(number: u32) -> Option<Self> {
+        SUPPORTED_VERSIONS.iter().copied().find(|v| v.number() == number)
+    }
+
+    /// Parse a version name ("1", "v2", ...); the current version when absent or empty
+    pub fn parse(name: Option<&str>) -> Result<Self, String> {
+        match name.map(str::trim) {
+            None | Some("") => Ok(SchemaVersion::CURRENT),
+            Some(name) => name
+                .trim_start_matches(['v', 'V'])
+                .parse()
+                .ok()
+                .and_then(SchemaVersion::from_number)
+                .ok_or_else(|| format!("unsupported schema version '{}'", name)),
+        }
+    }
+}
+
+/// Pick the newest version both sides support from the versions a consumer accepts
+pub fn negotiate(accepted: &[u32]) -> Result<SchemaVersion, String> {
+    accepted
+        .iter()
+        .filter_map(|&number| SchemaVersion::from_number(number))
+        .max()
+        .ok_or_else(|| {
+            let supported: Vec<String> = SUPPORTED_VERSIONS.iter().map(|v| v.number().to_string()).collect();
+            format!("no supported schema version in {:?} (supported: {})", accepted, supported.join(", "))
+        })
+}
+
+/// Keep only the fields `version` had, here and in the result types nested in the value
+fn downgrade(value: &mut Value, shape: &Shape, version: SchemaVersion) {
+    match value {
+        Value::Object(object) => {
+            object.retain(|key, _| shape.has_field(key, version));
+            for &(field, nested) in shape.nested {
+                if let Some(child) = object.get_mut(field) {
+                    downgrade(child, nested, version);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| downgrade(item, shape, version)),
+        _ => {}
+    }
+}
+
+/// Shape an already-serialized result of the given type for `version` and stamp `schemaVersion`
+/// on it; a list of results has each one shaped and stamped
+pub fn apply_version(mut value: Value, shape: &Shape, version: SchemaVersion) -> Value {
+    let results = match &mut value {
+        Value::Array(items) => items.iter_mut().collect(),
+        result => vec![result],
+    };
+    for result in results {
+        downgrade(result, shape, version);
+        if let Value::Object(object) = result {
+            object.insert("schemaVersion".to_string(), Value::from(version.number()));
+        }
+    }
+    value
+}
+
+/// Serialize a result in the requested schema version
+pub fn to_versioned_json<T: Versioned>(value: &T, version: SchemaVersion) -> Result<String, String> {
+    let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    serde_json::to_string(&apply_version(value, T::SHAPE, version)).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use schemars::schema_for;
+
+    use super::*;
+    use super::super::entity_extraction::extract_entities;
+    use super::super::{analyze, analyze_with_entities};
+
+    fn versioned(value: &impl Versioned, version: SchemaVersion) -> Value {
+        serde_json::from_str(&to_versioned_json(value, version).unwrap()).unwrap()
+    }
+
+    fn keys(value: &Value) -> BTreeSet<&str> {
+        value.as_object().unwrap().keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn test_parse_and_negotiate() {
+        assert_eq!(SchemaVersion::parse(Some("v1")), Ok(SchemaVersion::V1));
+        assert_eq!(SchemaVersion::parse(None), Ok(SchemaVersion::CURRENT));
+        assert!(SchemaVersion::parse(Some("7")).is_err());
+        assert_eq!(negotiate(&[1, 2, 3]), Ok(SchemaVersion::V3));
+        assert_eq!(negotiate(&[1, 2, 9]), Ok(SchemaVersion::V2));
+        assert_eq!(negotiate(&[1]), Ok(SchemaVersion::V1));
+        assert!(negotiate(&[9]).is_err());
+    }
+
+    #[test]
+    fn test_v1_output_keeps_only_baseline_fields() {
+        let result = analyze("You're so selfish");
+        let v3 = versioned(&result, SchemaVersion::V3);
+        let v1 = versioned(&result, SchemaVersion::V1);
+        assert_eq!(v3["schemaVersion"], 3);
+        assert_eq!(v1["schemaVersion"], 1);
+        assert!(v3["patterns"][0]["id"].is_string());
+        assert_eq!(keys(&v1), BTreeSet::from(["detected", "confidence", "patterns", "score", "schemaVersion"]));
+        assert_eq!(keys(&v1["patterns"][0]), BTreeSet::from(["patternType", "matchText", "position", "severity", "weight"]));
+        assert_eq!(v1["patterns"][0]["patternType"], v3["patterns"][0]["patternType"]);
+
+        let entities = versioned(&extract_entities("My sister Amy called. She was upset."), SchemaVersion::V1);
+        assert_eq!(keys(&entities), BTreeSet::from(["entities", "relationshipCount", "processingTimeUs", "schemaVersion"]));
+        assert!(entities["entities"][0].get("id").is_none());
+        assert_eq!(entities["entities"][0]["pronouns"], "she/her");
+    }
+
+    #[test]
+    fn test_v2_output_is_stable() {
+        let text = "My sister Amy said you're so selfish and it's all your fault.";
+        let v3 = versioned(&analyze_with_entities(text), SchemaVersion::V3);
+        assert!(v3["patterns"][0]["entityId"].is_string());
+        let v2 = versioned(&analyze_with_entities(text), SchemaVersion::V2);
+        assert!(v2["positivePatterns"].is_array());
+        assert!(v2.get("sentences").is_none());
+        let pattern = &v2["patterns"][0];
+        assert!(pattern["id"].is_string());
+        assert!(["scope", "person", "entityId", "entityRole"].iter().all(|key| pattern.get(key).is_none()));
+
+        let entities = versioned(&extract_entities(text), SchemaVersion::V2);
+        assert_eq!(keys(&entities), BTreeSet::from(["entities", "relationshipCount", "processingTimeUs", "schemaVersion"]));
+        assert!(entities["entities"][0]["id"].is_string());
+        assert!(entities["entities"][0].get("canonicalName").is_none());
+    }
+
+    /// Definition a property schema refers to, directly or through `anyOf` / `items`
+    fn reference(schema: &Value) -> Option<&str> {
+        match schema {
+            Value::Object(object) => object
+                .get("$ref")
+                .and_then(Value::as_str)
+                .map(|r| r.trim_start_matches("#/definitions/"))
+                .or_else(|| object.values().find_map(reference)),
+            Value::Array(items) => items.iter().find_map(reference),
+            _ => None,
+        }
+    }
+
+    fn assert_registered(name: &str, schema: &Value, definitions: &Value, shape: &Shape) {
+        let properties = schema["properties"].as_object().unwrap();
+        for (field, property) in properties {
+            assert!(shape.has_field(field, SchemaVersion::CURRENT), "{}.{} is not assigned a version", name, field);
+            let nested = shape.nested.iter().find(|&&(f, _)| f == field).map(|&(_, nested)| nested);
+            match reference(property).filter(|r| definitions[*r]["properties"].is_object()) {
+                Some(r) => {
+                    let nested = nested.unwrap_or_else(|| panic!("{}.{} holds {} but has no nested shape", name, field, r));
+                    assert_registered(r, &definitions[r], definitions, nested);
+                }
+                None => assert!(nested.is_none(), "{}.{} holds no result type", name, field),
+            }
+        }
+        for &(_, keys) in shape.fields {
+            assert!(keys.iter().all(|key| properties.contains_key(*key)), "{} registers a field it doesn't have", name);
+        }
+    }
+
+    #[test]
+    fn test_every_result_field_is_assigned_a_version() {
+        let roots = [
+            ("TextProcessingResult", schema_for!(TextProcessingResult), TextProcessingResult::SHAPE),
+            ("EntityExtractionResult", schema_for!(EntityExtractionResult), EntityExtractionResult::SHAPE),
+        ];
+        for (name, schema, shape) in roots {
+            let schema = serde_json::to_value(&schema).unwrap();
+            assert_registered(name, &schema, &schema["definitions"], shape);
+        }
+    }
+
+    #[test]
+    fn test_results_and_lists_of_results_are_shaped() {
+        let value = serde_json::json!({"detected": true, "score": 1.0, "patterns": [{"patternType": "x", "quoted": true}]});
+        let v1 = apply_version(value, &TEXT_PROCESSING_RESULT, SchemaVersion::V1);
+        assert_eq!(v1["patterns"][0].get("quoted"), None);
+        let list = apply_version(serde_json::json!([{"detected": false, "sentences": []}]), &TEXT_PROCESSING_RESULT, SchemaVersion::V2);
+        assert_eq!(list, serde_json::json!([{"detected": false, "schemaVersion": 2}]));
+    }
+}
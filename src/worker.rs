@@ -0,0 +1,200 @@
+//! Web Worker integration helpers
+//! postMessage-friendly request/response envelopes so the module can run in a dedicated worker
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::batch::BatchOutput;
+use super::entity_extraction::EntityExtractionResult;
+use super::versioning::{apply_version, SchemaVersion, Shape, Versioned};
+use super::TextProcessingResult;
+
+/// Request envelope posted to the worker
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerRequest {
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    /// Output schema version to shape the result for; the current version when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
+}
+
+/// Error details for a failed request
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerError {
+    pub code: String,
+    pub message: String,
+}
+
+/// Response envelope posted back to the caller
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerResponse {
+    pub id: Value,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<WorkerError>,
+}
+
+impl WorkerResponse {
+    fn success(id: Value, result: Value) -> Self {
+        WorkerResponse { id, ok: true, result: Some(result), error: None }
+    }
+
+    fn failure(id: Value, code: &str, message: String) -> Self {
+        WorkerResponse {
+            id,
+            ok: false,
+            result: None,
+            error: Some(WorkerError { code: code.to_string(), message }),
+        }
+    }
+}
+
+/// Read the required `text` parameter from a request
+fn text_param(params: &Value) -> Result<&str, String> {
+    params
+        .get("text")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "missing string parameter 'text'".to_string())
+}
+
+/// Parse a JSON string produced by one of the exported functions back into a value
+fn parse_output(json: String) -> Value {
+    serde_json::from_str(&json).unwrap_or(Value::Null)
+}
+
+/// Dispatch a request to the matching analysis function
+fn dispatch(method: &str, params: &Value) -> Result<Value, (&'static str, String)> {
+    match method {
+        "detectHighEntropyPatterns" => text_param(params)
+            .map(|text| parse_output(super::detect_high_entropy_patterns(text)))
+            .map_err(|e| ("invalid_params", e)),
+        "extractKeywords" => text_param(params)
+            .map(|text| parse_output(super::extract_keywords(text)))
+            .map_err(|e| ("invalid_params", e)),
+        "extractPeopleEntities" => text_param(params)
+            .map(|text| parse_output(super::extract_people_entities(text)))
+            .map_err(|e| ("invalid_params", e)),
+        "analyzeBatch" => {
+            let texts = params.get("texts").map(Value::to_string).unwrap_or_default();
+            let output = params.get("output").and_then(Value::as_str).map(str::to_string);
+            let is_ndjson = BatchOutput::parse(output.as_deref()) == Ok(BatchOutput::Ndjson);
+            match super::analyze_batch(&texts, output) {
+                // NDJSON stays a string so the host can write it out directly
+                Ok(ndjson) if is_ndjson => Ok(Value::String(ndjson)),
+                Ok(json) => Ok(parse_output(json)),
+                Err(e) => Err(("invalid_params", e)),
+            }
+        }
+        "getMemoryStats" => Ok(parse_output(super::get_memory_stats())),
+        "setPronounInference" => match params.get("enabled").and_then(Value::as_bool) {
+            Some(enabled) => {
+                super::set_pronoun_inference(enabled);
+                Ok(Value::Null)
+            }
+            None => Err(("invalid_params", "missing boolean parameter 'enabled'".to_string())),
+        },
+        _ => Err(("unknown_method", format!("unknown method '{}'", method))),
+    }
+}
+
+/// Result type a method returns, when it has a versioned shape
+fn result_shape(method: &str) -> Option<&'static Shape> {
+    match method {
+        "detectHighEntropyPatterns" | "analyzeBatch" => Some(TextProcessingResult::SHAPE),
+        "extractPeopleEntities" => Some(EntityExtractionResult::SHAPE),
+        _ => None,
+    }
+}
+
+/// Handle a single worker request envelope
+pub fn handle_request(message: &str) -> WorkerResponse {
+    let request: WorkerRequest = match serde_json::from_str(message) {
+        Ok(request) => request,
+        Err(e) => return WorkerResponse::failure(Value::Null, "invalid_request", e.to_string()),
+    };
+
+    let version = match request.schema_version.map(|n| SchemaVersion::from_number(n).ok_or(n)) {
+        Some(Err(n)) => {
+            return WorkerResponse::failure(request.id, "unsupported_schema_version", format!("unsupported schema version '{}'", n))
+        }
+        Some(Ok(version)) => version,
+        None => SchemaVersion::CURRENT,
+    };
+
+    match dispatch(&request.method, &request.params) {
+        Ok(result) => WorkerResponse::success(request.id, match result_shape(&request.method) {
+            Some(shape) => apply_version(result, shape, version),
+            None => result,
+        }),
+        Err((code, message)) => WorkerResponse::failure(request.id, code, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_request_echoes_id() {
+        let response = handle_request(r#"{"id":7,"method":"extractKeywords","params":{"text":"You are lazy"}}"#);
+        assert!(response.ok);
+        assert_eq!(response.id, Value::from(7));
+        assert!(response.result.unwrap().as_array().is_some());
+
+        let batch = handle_request(r#"{"id":2,"method":"analyzeBatch","params":{"texts":["a","b"],"output":"ndjson"}}"#);
+        assert_eq!(batch.result.unwrap().as_str().unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_handle_request_errors() {
+        let unknown = handle_request(r#"{"id":"a","method":"nope"}"#);
+        assert!(!unknown.ok);
+        assert_eq!(unknown.error.unwrap().code, "unknown_method");
+
+        let malformed = handle_request("not json");
+        assert_eq!(malformed.id, Value::Null);
+        assert_eq!(malformed.error.unwrap().code, "invalid_request");
+
+        let missing = handle_request(r#"{"id":1,"method":"detectHighEntropyPatterns","params":{}}"#);
+        assert_eq!(missing.error.unwrap().code, "invalid_params");
+
+        let unsupported = handle_request(r#"{"id":3,"method":"getMemoryStats","schemaVersion":9}"#);
+        assert_eq!(unsupported.error.unwrap().code, "unsupported_schema_version");
+    }
+
+    #[test]
+    fn test_pronoun_inference_can_be_turned_off() {
+        let extract = r#"{"id":5,"method":"extractPeopleEntities","params":{"text":"My sister Amy called. She was upset."}}"#;
+        {
+            let _off = super::super::entity_extraction::pronoun_switch::inference_off();
+            assert!(handle_request(r#"{"id":6,"method":"setPronounInference","params":{"enabled":true}}"#).error.is_none());
+            assert_eq!(handle_request(extract).result.unwrap()["entities"][0]["pronouns"], "she/her");
+            assert!(handle_request(r#"{"id":7,"method":"setPronounInference","params":{"enabled":false}}"#).error.is_none());
+            assert!(handle_request(extract).result.unwrap()["entities"][0].get("pronouns").is_none());
+        }
+        assert_eq!(handle_request(extract).result.unwrap()["entities"][0]["pronouns"], "she/her");
+        assert!(handle_request(r#"{"id":8,"method":"setPronounInference","params":{}}"#).error.is_some());
+    }
+
+    #[test]
+    fn test_handle_request_applies_schema_version() {
+        let request = r#"{"id":4,"method":"detectHighEntropyPatterns","params":{"text":"You're so selfish"},"schemaVersion":1}"#;
+        let result = handle_request(request).result.unwrap();
+        assert_eq!(result["schemaVersion"], 1);
+        assert!(result["patterns"][0].get("id").is_none());
+
+        // Other results are returned as produced, whatever keys they carry
+        let stats = handle_request(r#"{"id":5,"method":"getMemoryStats","schemaVersion":1}"#).result.unwrap();
+        assert!(stats.get("schemaVersion").is_none());
+        assert!(stats["stores"].as_array().is_some_and(|stores| stores.iter().all(|s| s["approxBytes"].is_number())));
+    }
+}